@@ -0,0 +1,257 @@
+//! Typed access to the filesystem operations, for using the crate as a library.
+//!
+//! [`FilesystemService::api`] runs the same code as the MCP tools, with the same
+//! path validation, deny patterns and configured limits, but returns the data
+//! instead of formatted text. Errors are the [`ToolError`]s the tools would return.
+//! Calls made here bypass the MCP layer: they are not subject to
+//! `--max-concurrent-ops`, do not appear in usage stats or history, and are not
+//! cut to `--max-output-bytes`.
+//!
+//! ```
+//! use ironbeard_mcp_filesystem::api::ReadRange;
+//! use ironbeard_mcp_filesystem::{Config, FilesystemService, ToolError};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), ToolError> {
+//! let dir = tempfile::tempdir().unwrap();
+//! std::fs::write(dir.path().join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+//! std::fs::create_dir(dir.path().join("src")).unwrap();
+//!
+//! let config = Config::builder().allowed_directory(dir.path()).build().unwrap();
+//! let service = FilesystemService::new(config);
+//! let api = service.api();
+//!
+//! let listing = api.list_directory(dir.path(), None, 0, None).await?;
+//! let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+//! assert_eq!(names, ["src", "notes.txt"]);
+//!
+//! let notes = dir.path().join("notes.txt");
+//! let read = api.read_file(&notes, Some(ReadRange { offset: 1, limit: Some(1) })).await?;
+//! assert_eq!(read.lines, ["two"]);
+//! assert_eq!(read.total_lines, Some(3));
+//!
+//! let found = api.search_files(dir.path(), &["*.txt"], None).await?;
+//! assert_eq!(found.files.len(), 1);
+//!
+//! let info = api.file_info(&notes, true).await?;
+//! assert_eq!((info.file_type.as_str(), info.size), ("file", 14));
+//! assert!(info.sha256.is_some());
+//!
+//! // Paths outside the allowed directories are refused as they are over MCP
+//! assert!(api.read_file("/etc/passwd", None).await.is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::FilesystemService;
+use crate::error::ToolError;
+use crate::tools::search::SearchExclusions;
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
+
+pub use crate::tools::info::FileInfo;
+pub use crate::tools::list::{DirectoryListing, ListingEntry, ListingTotals};
+pub use crate::tools::read::{ReadRange, ReadResult};
+pub use crate::tools::search::{SearchMatch, SearchResults};
+
+/// Typed filesystem operations on a [`FilesystemService`], from [`FilesystemService::api`].
+#[derive(Clone, Copy)]
+pub struct Api<'a> {
+    service: &'a FilesystemService,
+}
+
+impl FilesystemService {
+    /// Typed access to the operations behind the read-only tools.
+    pub fn api(&self) -> Api<'_> {
+        Api { service: self }
+    }
+}
+
+/// Path as the tools receive it, for validation and error messages.
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn extension_arg(extensions: Option<&[&str]>) -> Option<Vec<String>> {
+    extensions.map(|exts| exts.iter().map(|e| e.to_string()).collect())
+}
+
+impl Api<'_> {
+    /// Reads a text file, or only `range` of its lines. Like read_file, whole-file
+    /// reads are refused above the max read size; ranged reads are not.
+    pub async fn read_file(
+        &self,
+        path: impl AsRef<Path>,
+        range: Option<ReadRange>,
+    ) -> Result<ReadResult, ToolError> {
+        self.service
+            .read_file_lines(&path_arg(path.as_ref()), range, false)
+            .await
+    }
+
+    /// Lists a directory, directories first. `extensions` overrides the
+    /// configured `--only-extensions` filter; `Some(&[])` shows all files.
+    /// Returns the page of at most `limit` entries (capped at
+    /// `--max-dir-entries`) starting at the 0-based `offset`; `truncated` and
+    /// `total_entries` tell whether more remain.
+    pub async fn list_directory(
+        &self,
+        path: impl AsRef<Path>,
+        extensions: Option<&[&str]>,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<DirectoryListing, ToolError> {
+        let extensions = extension_arg(extensions);
+        self.service
+            .list_directory_report(
+                &path_arg(path.as_ref()),
+                extensions.as_deref(),
+                offset,
+                limit,
+            )
+            .await
+            .map(|(_, listing)| listing)
+    }
+
+    /// Finds files under `root` matching any of the glob `patterns`, returning at
+    /// most `max_results` (default 50, max 200). The configured extension filter
+    /// applies, and paths ignored by `.gitignore` files in the tree are skipped.
+    pub async fn search_files(
+        &self,
+        root: impl AsRef<Path>,
+        patterns: &[&str],
+        max_results: Option<u32>,
+    ) -> Result<SearchResults, ToolError> {
+        self.service
+            .search_files_report(
+                &path_arg(root.as_ref()),
+                patterns.iter().map(|p| p.to_string()).collect(),
+                max_results,
+                None,
+                SearchExclusions {
+                    gitignored: true,
+                    globs: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+            .await
+            .map(|(_, results)| results)
+    }
+
+    /// Metadata of a file or directory, with the SHA-256 of a file's contents
+    /// when `include_checksum` is set.
+    pub async fn file_info(
+        &self,
+        path: impl AsRef<Path>,
+        include_checksum: bool,
+    ) -> Result<FileInfo, ToolError> {
+        self.service
+            .get_file_info_report(&path_arg(path.as_ref()), include_checksum)
+            .await
+            .map(|(_, info)| info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, FilesystemService};
+    use tempfile::TempDir;
+
+    fn make_service(max_read_size: usize) -> (TempDir, FilesystemService) {
+        let dir = TempDir::new().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            max_read_size,
+            ..Default::default()
+        });
+        (dir, service)
+    }
+
+    #[tokio::test]
+    async fn read_file_matches_tool_text() {
+        let (dir, service) = make_service(1024);
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "alpha\r\nbeta\ngamma").unwrap();
+        let read = service.api().read_file(&path, None).await.unwrap();
+        assert_eq!(read.lines, ["alpha", "beta", "gamma"]);
+        assert_eq!((read.offset, read.total_lines, read.size), (0, Some(3), 17));
+        assert!(read.to_text().starts_with("File: "));
+        assert!(
+            read.to_text()
+                .ends_with("(Lines 1-3 of 3 total, 17 B)\n\nalpha\nbeta\ngamma")
+        );
+    }
+
+    #[tokio::test]
+    async fn ranged_read_of_large_file_leaves_total_unknown() {
+        let (dir, service) = make_service(16);
+        let path = dir.path().join("big.txt");
+        std::fs::write(&path, "line\n".repeat(100)).unwrap();
+        assert!(service.api().read_file(&path, None).await.is_err());
+        let range = super::ReadRange {
+            offset: 10,
+            limit: Some(2),
+        };
+        let read = service.api().read_file(&path, Some(range)).await.unwrap();
+        assert_eq!((read.offset, read.lines.len()), (10, 2));
+        assert_eq!(read.total_lines, None);
+        assert!(
+            read.to_text()
+                .contains("(Lines 11-12 of more than 12 total")
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_file_has_zero_total_lines() {
+        let (dir, service) = make_service(1024);
+        let path = dir.path().join("empty.txt");
+        std::fs::write(&path, "").unwrap();
+        let read = service.api().read_file(&path, None).await.unwrap();
+        assert!(read.lines.is_empty());
+        assert_eq!(read.total_lines, Some(0));
+        assert!(read.to_text().ends_with("(0 B)\n\n(empty file)"));
+    }
+
+    #[tokio::test]
+    async fn list_directory_extension_override() {
+        let (dir, service) = make_service(1024);
+        std::fs::write(dir.path().join("a.rs"), "").unwrap();
+        std::fs::write(dir.path().join("b.md"), "").unwrap();
+        let listing = service
+            .api()
+            .list_directory(dir.path(), Some(&["rs"]), 0, None)
+            .await
+            .unwrap();
+        let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.rs"]);
+        assert_eq!(listing.extensions, ["rs"]);
+    }
+
+    #[tokio::test]
+    async fn list_directory_pages_with_offset_and_limit() {
+        let (dir, service) = make_service(1024);
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let listing = service
+            .api()
+            .list_directory(dir.path(), None, 1, Some(1))
+            .await
+            .unwrap();
+        let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["b.txt"]);
+        assert_eq!((listing.offset, listing.total_entries), (1, 3));
+        assert!(listing.truncated);
+    }
+
+    #[tokio::test]
+    async fn search_files_requires_a_pattern() {
+        let (dir, service) = make_service(1024);
+        let err = service
+            .api()
+            .search_files(dir.path(), &[], None)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid pattern"), "{err}");
+    }
+}
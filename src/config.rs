@@ -1,15 +1,126 @@
-use clap::Parser;
-use std::path::PathBuf;
+use crate::security::build_deny_set;
+use crate::tools::util::normalize_extensions;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Default maximum file size for read operations (10 MB).
+pub const DEFAULT_MAX_READ_SIZE: usize = 10_485_760;
+
+/// Default maximum directory traversal depth.
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Default number of entries `list_directory` shows before truncating.
+pub const DEFAULT_MAX_DIR_ENTRIES: usize = 1000;
+
+/// Default number of entries `directory_tree` prints before truncating.
+pub const DEFAULT_MAX_TREE_ENTRIES: usize = 1000;
+
+/// Default cap on the size of a single tool response (256 KB).
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 262_144;
+
+/// Default number of tool calls allowed to run at the same time.
+pub const DEFAULT_MAX_CONCURRENT_OPS: usize = 8;
+
+/// Default number of entries a recursive delete may remove without `confirm: true`.
+pub const DEFAULT_DELETE_CONFIRM_THRESHOLD: usize = 100;
+
+/// Default number of entries above which a recursive delete is refused outright.
+pub const DEFAULT_MAX_RECURSIVE_DELETE_ENTRIES: usize = 10_000;
+
+/// Default number of files `delete_matching` removes in one call.
+pub const DEFAULT_MAX_DELETE_MATCHING: usize = 500;
+
+/// Default number of entries `count_entries` visits before reporting lower bounds.
+pub const DEFAULT_MAX_COUNT_ENTRIES: u64 = 100_000;
+
+/// Default number of data rows `preview_table` scans before reporting a lower bound.
+pub const DEFAULT_MAX_TABLE_ROWS: u64 = 100_000;
+
+/// Default number of manifest entries `verify_checksums` checks in one call.
+pub const DEFAULT_MAX_CHECKSUM_ENTRIES: usize = 1000;
+
+/// Default number of files `snapshot_directory` hashes before stopping.
+pub const DEFAULT_MAX_SNAPSHOT_FILES: usize = 10_000;
+
+/// Default number of files `normalize_line_endings` converts in one call.
+pub const DEFAULT_MAX_NORMALIZE_FILES: usize = 500;
+
+/// Default largest file a tool may write in one go (50 MB).
+pub const DEFAULT_MAX_WRITE_SIZE: u64 = 52_428_800;
+
+/// Default largest archive `list_archive` will open (256 MB).
+pub const DEFAULT_MAX_ARCHIVE_SIZE: u64 = 268_435_456;
+
+/// Default cap on the total size of files `create_archive` packs (1 GB).
+pub const DEFAULT_MAX_ARCHIVE_INPUT_SIZE: u64 = 1_073_741_824;
+
+/// Default largest file `get_file_info` hashes when asked for a checksum (4 GB).
+pub const DEFAULT_MAX_HASH_SIZE: u64 = 4_294_967_296;
+
+/// Default number of entries `list_archive` shows before truncating.
+pub const DEFAULT_MAX_ARCHIVE_ENTRIES: usize = 1000;
+
+/// Glob patterns covered by `--deny-preset secrets`.
+pub const SECRETS_PRESET: &[&str] = &[
+    ".env*", "*.pem", "*.key", "id_rsa*", "*.p12", ".aws/**", ".ssh/**",
+];
+
+/// Named sets of deny patterns selectable with `--deny-preset`.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DenyPreset {
+    /// Credentials and key material: .env files, private keys, cloud and SSH config.
+    Secrets,
+}
+
+impl DenyPreset {
+    /// The glob patterns this preset expands to.
+    pub fn patterns(self) -> &'static [&'static str] {
+        match self {
+            DenyPreset::Secrets => SECRETS_PRESET,
+        }
+    }
+}
 
 /// A secure filesystem MCP server with read-only and write-gated operations
+///
+/// Parsed from the command line by the binary. Library users should construct
+/// it with [`Config::builder`] rather than a struct literal.
 #[derive(Parser, Debug, Clone)]
+#[non_exhaustive]
 #[command(name = "ironbeard-mcp-filesystem")]
 #[command(about = "A secure filesystem MCP server")]
 pub struct Config {
     /// Directories to allow access to (must exist)
-    #[arg(required = true)]
+    #[arg(required_unless_present_any = ["dirs_from", "config_file"])]
     pub allowed_directories: Vec<PathBuf>,
 
+    /// Read settings from a TOML file whose keys are the long flag names
+    /// (e.g. `allow-write = true`, `allowed-directories = ["src"]`). Flags given
+    /// on the command line take precedence; directories and deny patterns from
+    /// both are combined. Relative paths resolve against the file's directory.
+    #[arg(long = "config", value_name = "FILE")]
+    pub config_file: Option<PathBuf>,
+
+    /// Read additional allowed directories from a file, one per line.
+    /// Blank lines and lines starting with '#' are ignored; relative paths
+    /// are resolved against the file's directory.
+    #[arg(long, value_name = "FILE")]
+    pub dirs_from: Option<PathBuf>,
+
+    /// Also allow the workspace roots the client reports (MCP roots), and follow
+    /// its updates to them. Directories given on the command line always stay allowed.
+    #[arg(long, default_value_t = false)]
+    pub accept_client_roots: bool,
+
+    /// With --accept-client-roots, ignore client roots that are not inside this directory
+    #[arg(long, value_name = "DIR", requires = "accept_client_roots")]
+    pub root_allowlist_prefix: Option<PathBuf>,
+
     /// Enable write operations (create, edit, move, delete)
     #[arg(long, default_value_t = false)]
     pub allow_write: bool,
@@ -18,46 +129,945 @@ pub struct Config {
     #[arg(long, default_value_t = false)]
     pub allow_destructive: bool,
 
+    /// Keep this directory read-only even with --allow-write; it must exist (repeatable)
+    #[arg(long = "read-only-directories", value_name = "DIR")]
+    pub read_only_directories: Vec<PathBuf>,
+
     /// Maximum file size for read operations in bytes
-    #[arg(long, default_value_t = 10_485_760)]
+    #[arg(long, default_value_t = DEFAULT_MAX_READ_SIZE)]
     pub max_read_size: usize,
 
     /// Maximum directory traversal depth
-    #[arg(long, default_value_t = 10)]
+    #[arg(long, default_value_t = DEFAULT_MAX_DEPTH)]
     pub max_depth: usize,
+
+    /// Show at most this many entries per list_directory call
+    #[arg(long, default_value_t = DEFAULT_MAX_DIR_ENTRIES)]
+    pub max_dir_entries: usize,
+
+    /// Print at most this many entries per directory_tree call
+    #[arg(long, default_value_t = DEFAULT_MAX_TREE_ENTRIES)]
+    pub max_tree_entries: usize,
+
+    /// Maximum size of a tool response in bytes; longer output is truncated with a note
+    #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_BYTES)]
+    pub max_output_bytes: usize,
+
+    /// Recursive deletes of more entries than this require `confirm: true`
+    #[arg(long, default_value_t = DEFAULT_DELETE_CONFIRM_THRESHOLD)]
+    pub delete_confirm_threshold: usize,
+
+    /// Refuse recursive deletes of directories holding more entries than this,
+    /// even with `confirm: true`; nothing is deleted
+    #[arg(long, default_value_t = DEFAULT_MAX_RECURSIVE_DELETE_ENTRIES)]
+    pub max_recursive_delete_entries: usize,
+
+    /// Delete at most this many files per delete_matching call
+    #[arg(long, default_value_t = DEFAULT_MAX_DELETE_MATCHING)]
+    pub max_delete_matching: usize,
+
+    /// Stop count_entries after this many entries and report the counts as lower
+    /// bounds; also bounds each side of compare_directories and each watch_changes scan
+    #[arg(long, default_value_t = DEFAULT_MAX_COUNT_ENTRIES)]
+    pub max_count_entries: u64,
+
+    /// Stop preview_table after scanning this many data rows and report the row count as a lower bound
+    #[arg(long, default_value_t = DEFAULT_MAX_TABLE_ROWS)]
+    pub max_table_rows: u64,
+
+    /// Stop verify_checksums after checking this many manifest entries
+    #[arg(long, default_value_t = DEFAULT_MAX_CHECKSUM_ENTRIES)]
+    pub max_checksum_entries: usize,
+
+    /// Stop snapshot_directory after this many files and mark the manifest incomplete
+    #[arg(long, default_value_t = DEFAULT_MAX_SNAPSHOT_FILES)]
+    pub max_snapshot_files: usize,
+
+    /// Convert at most this many files per normalize_line_endings call
+    #[arg(long, default_value_t = DEFAULT_MAX_NORMALIZE_FILES)]
+    pub max_normalize_files: usize,
+
+    /// Largest file extract_from_archive, decompress_file, and compress_file
+    /// will write, and largest size truncate_file will extend to, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_WRITE_SIZE)]
+    pub max_write_size: u64,
+
+    /// Largest archive file list_archive will open, in bytes (independent of --max-read-size)
+    #[arg(long, default_value_t = DEFAULT_MAX_ARCHIVE_SIZE)]
+    pub max_archive_size: u64,
+
+    /// Largest total size of the files create_archive will pack, in bytes
+    #[arg(long, default_value_t = DEFAULT_MAX_ARCHIVE_INPUT_SIZE)]
+    pub max_archive_input_size: u64,
+
+    /// Largest file get_file_info hashes for include_checksum, in bytes; larger files report the hash as skipped
+    #[arg(long, default_value_t = DEFAULT_MAX_HASH_SIZE)]
+    pub max_hash_size: u64,
+
+    /// Maximum number of entries list_archive shows; the rest are counted but not listed
+    #[arg(long, default_value_t = DEFAULT_MAX_ARCHIVE_ENTRIES)]
+    pub max_archive_entries: usize,
+
+    /// Move deleted files and directories into this directory instead of removing them.
+    /// Created if missing; must be outside the allowed directories.
+    #[arg(long, value_name = "DIR")]
+    pub trash_dir: Option<PathBuf>,
+
+    /// Refuse deletes outright unless --trash-dir is set, so nothing is ever hard-deleted
+    #[arg(long, default_value_t = false)]
+    pub require_trash: bool,
+
+    /// Make destructive tools return a single-use confirmation token first and act
+    /// only when called again with it
+    #[arg(long, default_value_t = false)]
+    pub confirm_destructive: bool,
+
+    /// Remember the last N tool calls and expose them via list_operation_history
+    #[arg(long, value_name = "N")]
+    pub history_size: Option<usize>,
+
+    /// Log tool calls that take at least this many milliseconds at WARN instead of INFO
+    #[arg(long, value_name = "MS")]
+    pub log_slow_ops_ms: Option<u64>,
+
+    /// Maximum number of tool calls executing at once; extra calls wait for a slot
+    #[arg(long, default_value_t = DEFAULT_MAX_CONCURRENT_OPS)]
+    pub max_concurrent_ops: usize,
+
+    /// Only show files with these extensions in search_files, list_directory and
+    /// directory_tree (comma-separated, e.g. "rs,toml,md"). An output filter, not
+    /// an access control: other files can still be read directly.
+    #[arg(long, value_delimiter = ',')]
+    pub only_extensions: Vec<String>,
+
+    /// Deny access to paths matching this glob, even inside allowed directories (repeatable)
+    #[arg(long = "deny", visible_aliases = ["deny-pattern", "deny-patterns"], value_name = "GLOB")]
+    pub deny: Vec<String>,
+
+    /// Add a built-in set of deny patterns (repeatable)
+    #[arg(long, value_enum)]
+    pub deny_preset: Vec<DenyPreset>,
+
+    /// Deny the usual secrets (.env files, *.pem, *.key, .ssh/ and more);
+    /// the same as --deny-preset secrets
+    #[arg(long)]
+    pub deny_defaults: bool,
+
+    /// Ids of the arguments given on the command line, recorded by
+    /// [`Config::try_parse_args_from`] so a `--config` file cannot override
+    /// them. `None` when unknown, as for a builder or a plain `Config::parse`.
+    #[arg(skip)]
+    pub(crate) cli_flags: Option<HashSet<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            allowed_directories: Vec::new(),
+            config_file: None,
+            dirs_from: None,
+            accept_client_roots: false,
+            root_allowlist_prefix: None,
+            allow_write: false,
+            allow_destructive: false,
+            read_only_directories: Vec::new(),
+            max_read_size: DEFAULT_MAX_READ_SIZE,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_dir_entries: DEFAULT_MAX_DIR_ENTRIES,
+            max_tree_entries: DEFAULT_MAX_TREE_ENTRIES,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            delete_confirm_threshold: DEFAULT_DELETE_CONFIRM_THRESHOLD,
+            max_recursive_delete_entries: DEFAULT_MAX_RECURSIVE_DELETE_ENTRIES,
+            max_delete_matching: DEFAULT_MAX_DELETE_MATCHING,
+            max_count_entries: DEFAULT_MAX_COUNT_ENTRIES,
+            max_table_rows: DEFAULT_MAX_TABLE_ROWS,
+            max_checksum_entries: DEFAULT_MAX_CHECKSUM_ENTRIES,
+            max_snapshot_files: DEFAULT_MAX_SNAPSHOT_FILES,
+            max_normalize_files: DEFAULT_MAX_NORMALIZE_FILES,
+            max_write_size: DEFAULT_MAX_WRITE_SIZE,
+            max_archive_size: DEFAULT_MAX_ARCHIVE_SIZE,
+            max_archive_input_size: DEFAULT_MAX_ARCHIVE_INPUT_SIZE,
+            max_hash_size: DEFAULT_MAX_HASH_SIZE,
+            max_archive_entries: DEFAULT_MAX_ARCHIVE_ENTRIES,
+            trash_dir: None,
+            require_trash: false,
+            confirm_destructive: false,
+            history_size: None,
+            log_slow_ops_ms: None,
+            max_concurrent_ops: DEFAULT_MAX_CONCURRENT_OPS,
+            only_extensions: Vec::new(),
+            deny: Vec::new(),
+            deny_preset: Vec::new(),
+            deny_defaults: false,
+            cli_flags: None,
+        }
+    }
 }
 
 impl Config {
+    /// Parses the process arguments like [`Parser::parse`], exiting with
+    /// usage on error, and records which flags were given so they take
+    /// precedence over a `--config` file even when set to their default.
+    pub fn parse_args() -> Self {
+        Self::try_parse_args_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Like [`Config::parse_args`], for the given arguments.
+    pub fn try_parse_args_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Self::command().try_get_matches_from(args)?;
+        let mut config = Self::from_arg_matches(&matches)?;
+        config.cli_flags = Some(
+            matches
+                .ids()
+                .filter(|id| {
+                    matches!(
+                        matches.value_source(id.as_str()),
+                        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                    )
+                })
+                .map(|id| id.to_string())
+                .collect(),
+        );
+        Ok(config)
+    }
+
+    /// Starts building a [`Config`] for embedding the server in another program.
+    ///
+    /// Defaults match the CLI defaults. [`ConfigBuilder::build`] runs the same
+    /// validation and canonicalization as [`Config::validate`].
+    ///
+    /// ```
+    /// use ironbeard_mcp_filesystem::{Config, FilesystemService};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let config = Config::builder()
+    ///     .allowed_directory(dir.path())
+    ///     .allow_write(true)
+    ///     .max_depth(4)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let service = FilesystemService::new(config);
+    /// assert!(service.config.allow_write);
+    /// assert_eq!(service.config.allowed_directories[0], dir.path().canonicalize().unwrap());
+    /// ```
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
     /// Validates and canonicalizes all allowed directories.
-    /// Returns a descriptive error string if any directory is invalid.
-    pub fn validate(mut self) -> Result<Self, String> {
+    /// Collects every problem found into a [`ConfigValidationError`] rather than
+    /// stopping at the first one.
+    pub fn validate(mut self) -> Result<Self, ConfigValidationError> {
+        let mut report = ConfigValidationError::default();
+        if let Some(file) = self.config_file.take() {
+            match read_config_file(&file) {
+                Ok(settings) => self.apply_file(settings),
+                Err(message) => report.error("config_file", file.display(), message),
+            }
+        }
         if self.allow_destructive {
             self.allow_write = true;
         }
-        let mut canonicalized = Vec::with_capacity(self.allowed_directories.len());
-        for dir in &self.allowed_directories {
-            let canon = dir
-                .canonicalize()
-                .map_err(|e| format!("Failed to resolve directory '{}': {}", dir.display(), e))?;
-            if !canon.is_dir() {
-                return Err(format!("'{}' is not a directory", dir.display()));
+        if self.max_concurrent_ops == 0 {
+            report.error(
+                "max_concurrent_ops",
+                "0",
+                "--max-concurrent-ops must be at least 1",
+            );
+        }
+        if self.max_count_entries == 0 {
+            report.error(
+                "max_count_entries",
+                "0",
+                "--max-count-entries must be at least 1",
+            );
+        }
+        if self.max_dir_entries == 0 {
+            report.error(
+                "max_dir_entries",
+                "0",
+                "--max-dir-entries must be at least 1",
+            );
+        }
+        if self.max_tree_entries == 0 {
+            report.error(
+                "max_tree_entries",
+                "0",
+                "--max-tree-entries must be at least 1",
+            );
+        }
+        if self.max_table_rows == 0 {
+            report.error("max_table_rows", "0", "--max-table-rows must be at least 1");
+        }
+        if self.max_checksum_entries == 0 {
+            report.error(
+                "max_checksum_entries",
+                "0",
+                "--max-checksum-entries must be at least 1",
+            );
+        }
+        if self.max_snapshot_files == 0 {
+            report.error(
+                "max_snapshot_files",
+                "0",
+                "--max-snapshot-files must be at least 1",
+            );
+        }
+        if self.max_recursive_delete_entries == 0 {
+            report.error(
+                "max_recursive_delete_entries",
+                "0",
+                "--max-recursive-delete-entries must be at least 1",
+            );
+        }
+        if self.max_delete_matching == 0 {
+            report.error(
+                "max_delete_matching",
+                "0",
+                "--max-delete-matching must be at least 1",
+            );
+        }
+        if self.max_normalize_files == 0 {
+            report.error(
+                "max_normalize_files",
+                "0",
+                "--max-normalize-files must be at least 1",
+            );
+        }
+        if self.max_archive_entries == 0 {
+            report.error(
+                "max_archive_entries",
+                "0",
+                "--max-archive-entries must be at least 1",
+            );
+        }
+        if self.history_size == Some(0) {
+            report.error("history_size", "0", "--history-size must be at least 1");
+        }
+        // Each entry carries an optional "file:line: " prefix for error messages
+        let mut entries: Vec<(PathBuf, String)> = self
+            .allowed_directories
+            .iter()
+            .map(|d| (d.clone(), String::new()))
+            .collect();
+        if let Some(file) = self.dirs_from.take() {
+            match read_dirs_file(&file) {
+                Ok(listed) => entries.extend(listed),
+                Err(message) => report.error("dirs_from", file.display(), message),
+            }
+        }
+
+        let mut canonicalized = Vec::with_capacity(entries.len());
+        for (dir, location) in &entries {
+            match dir.canonicalize() {
+                Ok(canon) if canon.is_dir() => canonicalized.push(canon),
+                Ok(_) => report.error(
+                    "allowed_directories",
+                    dir.display(),
+                    format!("{location}'{}' is not a directory", dir.display()),
+                ),
+                Err(e) => report.error(
+                    "allowed_directories",
+                    dir.display(),
+                    format!(
+                        "{location}Failed to resolve directory '{}': {}",
+                        dir.display(),
+                        e
+                    ),
+                ),
+            }
+        }
+        self.allowed_directories = merge_overlapping(canonicalized, &mut report.warnings);
+        if let Some(prefix) = self.root_allowlist_prefix.take() {
+            match prefix.canonicalize() {
+                Ok(canon) if canon.is_dir() => self.root_allowlist_prefix = Some(canon),
+                Ok(_) => report.error(
+                    "root_allowlist_prefix",
+                    prefix.display(),
+                    format!("'{}' is not a directory", prefix.display()),
+                ),
+                Err(e) => report.error(
+                    "root_allowlist_prefix",
+                    prefix.display(),
+                    format!("Failed to resolve directory '{}': {}", prefix.display(), e),
+                ),
+            }
+        }
+        let mut read_only = Vec::with_capacity(self.read_only_directories.len());
+        for dir in &self.read_only_directories {
+            match dir.canonicalize() {
+                Ok(canon) if canon.is_dir() => read_only.push(canon),
+                Ok(_) => report.error(
+                    "read_only_directories",
+                    dir.display(),
+                    format!("'{}' is not a directory", dir.display()),
+                ),
+                Err(e) => report.error(
+                    "read_only_directories",
+                    dir.display(),
+                    format!("Failed to resolve directory '{}': {}", dir.display(), e),
+                ),
+            }
+        }
+        self.read_only_directories = read_only;
+        if let Some(trash) = self.trash_dir.take() {
+            match std::fs::create_dir_all(&trash).and_then(|()| trash.canonicalize()) {
+                Ok(canon)
+                    if self
+                        .allowed_directories
+                        .iter()
+                        .any(|d| canon.starts_with(d)) =>
+                {
+                    report.error(
+                        "trash_dir",
+                        trash.display(),
+                        format!(
+                            "Trash directory '{}' must be outside the allowed directories",
+                            trash.display()
+                        ),
+                    )
+                }
+                Ok(canon) => self.trash_dir = Some(canon),
+                Err(e) => report.error(
+                    "trash_dir",
+                    trash.display(),
+                    format!(
+                        "Failed to create trash directory '{}': {}",
+                        trash.display(),
+                        e
+                    ),
+                ),
+            }
+        }
+        self.only_extensions = normalize_extensions(&self.only_extensions);
+
+        // Expand presets into the explicit list so `deny` is the effective set
+        if std::mem::take(&mut self.deny_defaults) {
+            self.deny_preset.push(DenyPreset::Secrets);
+        }
+        for preset in std::mem::take(&mut self.deny_preset) {
+            for pattern in preset.patterns() {
+                if !self.deny.iter().any(|p| p == pattern) {
+                    self.deny.push(pattern.to_string());
+                }
+            }
+        }
+        for pattern in &self.deny {
+            if let Err(e) = build_deny_set(std::slice::from_ref(pattern)) {
+                report.error("deny", pattern, e.to_string());
+            }
+        }
+
+        if report.errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(report)
+        }
+    }
+
+    /// Fills in the settings of a `--config` file. A field keeps its current
+    /// value if it was given on the command line, so flags win even when set
+    /// to their default; when that is unknown, a value other than the default
+    /// counts as given. Directories and deny patterns are appended.
+    fn apply_file(&mut self, file: ConfigFile) {
+        let defaults = Config::default();
+        let cli_flags = self.cli_flags.clone();
+        let given = |id: &str, at_default: bool| match &cli_flags {
+            Some(ids) => ids.contains(id),
+            None => !at_default,
+        };
+        /// Takes the file's value for `field` unless the command line set it.
+        macro_rules! fill {
+            ($($field:ident),+ $(,)?) => {$(
+                if let Some(value) = file.$field
+                    && !given(stringify!($field), self.$field == defaults.$field)
+                {
+                    self.$field = value;
+                }
+            )+};
+        }
+        self.allowed_directories.extend(file.allowed_directories);
+        self.dirs_from = self.dirs_from.take().or(file.dirs_from);
+        self.root_allowlist_prefix = self
+            .root_allowlist_prefix
+            .take()
+            .or(file.root_allowlist_prefix);
+        self.read_only_directories
+            .extend(file.read_only_directories);
+        self.trash_dir = self.trash_dir.take().or(file.trash_dir);
+        self.history_size = self.history_size.or(file.history_size);
+        self.log_slow_ops_ms = self.log_slow_ops_ms.or(file.log_slow_ops_ms);
+        self.deny.extend(file.deny);
+        self.deny_preset.extend(file.deny_preset);
+        fill!(
+            accept_client_roots,
+            allow_write,
+            allow_destructive,
+            max_read_size,
+            max_depth,
+            max_dir_entries,
+            max_tree_entries,
+            max_output_bytes,
+            delete_confirm_threshold,
+            max_recursive_delete_entries,
+            max_delete_matching,
+            max_count_entries,
+            max_table_rows,
+            max_checksum_entries,
+            max_snapshot_files,
+            max_normalize_files,
+            max_write_size,
+            max_archive_size,
+            max_archive_input_size,
+            max_hash_size,
+            max_archive_entries,
+            require_trash,
+            confirm_destructive,
+            max_concurrent_ops,
+            only_extensions,
+            deny_defaults,
+        );
+    }
+}
+
+/// The contents of a `--config` file. Keys are the long flag names.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    allowed_directories: Vec<PathBuf>,
+    dirs_from: Option<PathBuf>,
+    accept_client_roots: Option<bool>,
+    root_allowlist_prefix: Option<PathBuf>,
+    allow_write: Option<bool>,
+    allow_destructive: Option<bool>,
+    read_only_directories: Vec<PathBuf>,
+    max_read_size: Option<usize>,
+    max_depth: Option<usize>,
+    max_dir_entries: Option<usize>,
+    max_tree_entries: Option<usize>,
+    max_output_bytes: Option<usize>,
+    delete_confirm_threshold: Option<usize>,
+    max_recursive_delete_entries: Option<usize>,
+    max_delete_matching: Option<usize>,
+    max_count_entries: Option<u64>,
+    max_table_rows: Option<u64>,
+    max_checksum_entries: Option<usize>,
+    max_snapshot_files: Option<usize>,
+    max_normalize_files: Option<usize>,
+    max_write_size: Option<u64>,
+    max_archive_size: Option<u64>,
+    max_archive_input_size: Option<u64>,
+    max_hash_size: Option<u64>,
+    max_archive_entries: Option<usize>,
+    trash_dir: Option<PathBuf>,
+    require_trash: Option<bool>,
+    confirm_destructive: Option<bool>,
+    history_size: Option<usize>,
+    log_slow_ops_ms: Option<u64>,
+    max_concurrent_ops: Option<usize>,
+    only_extensions: Option<Vec<String>>,
+    #[serde(alias = "deny-patterns")]
+    deny: Vec<String>,
+    deny_preset: Vec<DenyPreset>,
+    deny_defaults: Option<bool>,
+}
+
+/// A single invalid configuration value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigError {
+    /// Name of the offending `Config` field, e.g. `allowed_directories`.
+    pub field: String,
+    /// The rejected value as given.
+    pub value: String,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+/// Everything wrong with a [`Config`], returned by [`Config::validate`].
+///
+/// `Display` gives a multi-line report for humans; `Serialize` gives the same
+/// data as JSON for tooling.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigValidationError {
+    pub errors: Vec<ConfigError>,
+    pub warnings: Vec<String>,
+}
+
+impl ConfigValidationError {
+    fn error(&mut self, field: &str, value: impl std::fmt::Display, message: impl Into<String>) {
+        self.errors.push(ConfigError {
+            field: field.to_string(),
+            value: value.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.errors.len();
+        write!(
+            f,
+            "Invalid configuration ({count} error{})",
+            if count == 1 { "" } else { "s" }
+        )?;
+        for error in &self.errors {
+            write!(f, "\n  - {}: {}", error.field, error.message)?;
+        }
+        for warning in &self.warnings {
+            write!(f, "\n  warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigValidationError {}
+
+/// Builder for [`Config`]. Created with [`Config::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Adds a directory the server may access.
+    pub fn allowed_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.allowed_directories.push(dir.into());
+        self
+    }
+
+    /// Adds several directories the server may access.
+    pub fn allowed_directories<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.config
+            .allowed_directories
+            .extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Reads settings from a TOML file (see `--config`). Values set on the
+    /// builder take precedence over the file.
+    pub fn config_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.config.config_file = Some(file.into());
+        self
+    }
+
+    /// Reads additional allowed directories from a file (see `--dirs-from`).
+    pub fn dirs_from(mut self, file: impl Into<PathBuf>) -> Self {
+        self.config.dirs_from = Some(file.into());
+        self
+    }
+
+    /// Allows the workspace roots reported by the client.
+    pub fn accept_client_roots(mut self, accept: bool) -> Self {
+        self.config.accept_client_roots = accept;
+        self
+    }
+
+    /// Ignores client roots outside `dir`.
+    pub fn root_allowlist_prefix(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.root_allowlist_prefix = Some(dir.into());
+        self
+    }
+
+    /// Enables write tools.
+    pub fn allow_write(mut self, allow: bool) -> Self {
+        self.config.allow_write = allow;
+        self
+    }
+
+    /// Enables destructive tools. Implies `allow_write` once built.
+    pub fn allow_destructive(mut self, allow: bool) -> Self {
+        self.config.allow_destructive = allow;
+        self
+    }
+
+    /// Keeps a directory read-only even when write tools are enabled.
+    pub fn read_only_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.read_only_directories.push(dir.into());
+        self
+    }
+
+    /// Sets the maximum file size for read operations, in bytes.
+    pub fn max_read_size(mut self, bytes: usize) -> Self {
+        self.config.max_read_size = bytes;
+        self
+    }
+
+    /// Sets the maximum directory traversal depth.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.config.max_depth = depth;
+        self
+    }
+
+    /// Sets how many entries `list_directory` shows before truncating.
+    pub fn max_dir_entries(mut self, entries: usize) -> Self {
+        self.config.max_dir_entries = entries;
+        self
+    }
+
+    /// Sets how many entries `directory_tree` prints before truncating.
+    pub fn max_tree_entries(mut self, entries: usize) -> Self {
+        self.config.max_tree_entries = entries;
+        self
+    }
+
+    /// Sets the maximum size of a single tool response, in bytes.
+    pub fn max_output_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_output_bytes = bytes;
+        self
+    }
+
+    /// Sets how many entries a recursive delete may remove without confirmation.
+    pub fn delete_confirm_threshold(mut self, entries: usize) -> Self {
+        self.config.delete_confirm_threshold = entries;
+        self
+    }
+
+    /// Sets how many entries a recursive delete may remove at most.
+    pub fn max_recursive_delete_entries(mut self, entries: usize) -> Self {
+        self.config.max_recursive_delete_entries = entries;
+        self
+    }
+
+    /// Sets how many files `delete_matching` removes in one call.
+    pub fn max_delete_matching(mut self, files: usize) -> Self {
+        self.config.max_delete_matching = files;
+        self
+    }
+
+    /// Sets how many entries `count_entries` visits before stopping.
+    pub fn max_count_entries(mut self, entries: u64) -> Self {
+        self.config.max_count_entries = entries;
+        self
+    }
+
+    /// Sets how many data rows `preview_table` scans before stopping.
+    pub fn max_table_rows(mut self, rows: u64) -> Self {
+        self.config.max_table_rows = rows;
+        self
+    }
+
+    /// Sets how many manifest entries `verify_checksums` checks in one call.
+    pub fn max_checksum_entries(mut self, entries: usize) -> Self {
+        self.config.max_checksum_entries = entries;
+        self
+    }
+
+    /// Sets how many files `snapshot_directory` hashes before stopping.
+    pub fn max_snapshot_files(mut self, files: usize) -> Self {
+        self.config.max_snapshot_files = files;
+        self
+    }
+
+    /// Sets how many files `normalize_line_endings` converts in one call.
+    pub fn max_normalize_files(mut self, files: usize) -> Self {
+        self.config.max_normalize_files = files;
+        self
+    }
+
+    /// Sets the largest file `extract_from_archive`, `decompress_file`, and
+    /// `compress_file` will write, and the largest size `truncate_file` will
+    /// extend a file to, in bytes.
+    pub fn max_write_size(mut self, bytes: u64) -> Self {
+        self.config.max_write_size = bytes;
+        self
+    }
+
+    /// Sets the largest archive `list_archive` will open, in bytes.
+    pub fn max_archive_size(mut self, bytes: u64) -> Self {
+        self.config.max_archive_size = bytes;
+        self
+    }
+
+    /// Sets the largest total size of the files `create_archive` will pack, in bytes.
+    pub fn max_archive_input_size(mut self, bytes: u64) -> Self {
+        self.config.max_archive_input_size = bytes;
+        self
+    }
+
+    /// Sets the largest file `get_file_info` hashes, in bytes.
+    pub fn max_hash_size(mut self, bytes: u64) -> Self {
+        self.config.max_hash_size = bytes;
+        self
+    }
+
+    /// Sets how many entries `list_archive` shows before truncating.
+    pub fn max_archive_entries(mut self, entries: usize) -> Self {
+        self.config.max_archive_entries = entries;
+        self
+    }
+
+    /// Moves deleted paths into `dir` instead of removing them.
+    pub fn trash_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.trash_dir = Some(dir.into());
+        self
+    }
+
+    /// Refuses deletes unless a trash directory is configured.
+    pub fn require_trash(mut self, require: bool) -> Self {
+        self.config.require_trash = require;
+        self
+    }
+
+    /// Requires a confirmation-token handshake before destructive operations.
+    pub fn confirm_destructive(mut self, confirm: bool) -> Self {
+        self.config.confirm_destructive = confirm;
+        self
+    }
+
+    /// Keeps a history of the last `size` tool calls.
+    pub fn history_size(mut self, size: usize) -> Self {
+        self.config.history_size = Some(size);
+        self
+    }
+
+    /// Logs tool calls taking at least `ms` milliseconds at WARN.
+    pub fn log_slow_ops_ms(mut self, ms: u64) -> Self {
+        self.config.log_slow_ops_ms = Some(ms);
+        self
+    }
+
+    /// Sets how many tool calls may execute at once.
+    pub fn max_concurrent_ops(mut self, limit: usize) -> Self {
+        self.config.max_concurrent_ops = limit;
+        self
+    }
+
+    /// Sets the default extension filter for listing and search tools.
+    pub fn only_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.only_extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Adds a deny glob pattern.
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.config.deny.push(pattern.into());
+        self
+    }
+
+    /// Adds a built-in set of deny patterns.
+    pub fn deny_preset(mut self, preset: DenyPreset) -> Self {
+        self.config.deny_preset.push(preset);
+        self
+    }
+
+    /// Adds the built-in secrets deny patterns, like `--deny-defaults`.
+    pub fn deny_defaults(mut self) -> Self {
+        self.config.deny_defaults = true;
+        self
+    }
+
+    /// Validates and returns the finished [`Config`].
+    pub fn build(self) -> Result<Config, ConfigValidationError> {
+        self.config.validate()
+    }
+}
+
+/// Reads a `--config` file, resolving its relative paths against the file's
+/// directory.
+fn read_config_file(file: &Path) -> Result<ConfigFile, String> {
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read config file '{}': {}", file.display(), e))?;
+    let mut settings: ConfigFile = toml::from_str(&content)
+        .map_err(|e| format!("Invalid config file '{}': {}", file.display(), e))?;
+    let base = file.parent().unwrap_or_else(|| Path::new(""));
+    for dir in settings
+        .allowed_directories
+        .iter_mut()
+        .chain(&mut settings.read_only_directories)
+    {
+        *dir = base.join(&*dir);
+    }
+    for path in [
+        &mut settings.dirs_from,
+        &mut settings.root_allowlist_prefix,
+        &mut settings.trash_dir,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *path = base.join(&*path);
+    }
+    Ok(settings)
+}
+
+/// Reads a `--dirs-from` file into (path, "file:line: ") pairs.
+fn read_dirs_file(file: &std::path::Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read directory list '{}': {}", file.display(), e))?;
+    let base = file.parent().unwrap_or_else(|| std::path::Path::new(""));
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let location = format!("{}:{}: ", file.display(), index + 1);
+            Some((base.join(line), location))
+        })
+        .collect())
+}
+
+/// Removes duplicate roots and folds nested roots into their ancestor.
+/// Keeps the first-seen order of the surviving roots. Expects canonical paths.
+/// Each merge is logged and recorded in `warnings`.
+pub(crate) fn merge_overlapping(dirs: Vec<PathBuf>, warnings: &mut Vec<String>) -> Vec<PathBuf> {
+    let mut merged: Vec<PathBuf> = Vec::with_capacity(dirs.len());
+    for dir in dirs {
+        if let Some(ancestor) = merged.iter().find(|kept| dir.starts_with(kept)) {
+            if *ancestor != dir {
+                let note = format!(
+                    "Allowed directory {} is inside {}; using the ancestor",
+                    dir.display(),
+                    ancestor.display()
+                );
+                info!("{note}");
+                warnings.push(note);
+            }
+            continue;
+        }
+        if let Some(pos) = merged.iter().position(|kept| kept.starts_with(&dir)) {
+            for nested in merged.iter().filter(|kept| kept.starts_with(&dir)) {
+                let note = format!(
+                    "Allowed directory {} is inside {}; using the ancestor",
+                    nested.display(),
+                    dir.display()
+                );
+                info!("{note}");
+                warnings.push(note);
             }
-            canonicalized.push(canon);
+            merged.retain(|kept| !kept.starts_with(&dir));
+            merged.insert(pos.min(merged.len()), dir);
+            continue;
         }
-        self.allowed_directories = canonicalized;
-        Ok(self)
+        merged.push(dir);
     }
+    merged
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::Parser;
     use tempfile::TempDir;
 
     /// Helper to parse Config from an argument list (mimicking CLI invocation).
     fn parse(args: &[&str]) -> Result<Config, clap::Error> {
-        Config::try_parse_from(args)
+        Config::try_parse_args_from(args)
     }
 
     #[test]
@@ -108,10 +1118,54 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let result = config.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Failed to resolve"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to resolve")
+        );
+    }
+
+    #[test]
+    fn validate_reports_bad_directory_as_structured_error() {
+        let bad = PathBuf::from("/definitely/does/not/exist/abc123");
+        let config = Config {
+            allowed_directories: vec![bad.clone()],
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors.len(), 1);
+        assert_eq!(err.errors[0].field, "allowed_directories");
+        assert_eq!(err.errors[0].value, bad.display().to_string());
+
+        let json: serde_json::Value = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["errors"][0]["field"], "allowed_directories");
+        assert!(json["warnings"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn validate_collects_all_errors_into_display() {
+        let config = Config {
+            allowed_directories: vec![PathBuf::from("/definitely/does/not/exist/abc123")],
+            max_concurrent_ops: 0,
+            deny: vec!["[unclosed".into()],
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors.len(), 3);
+        let report = err.to_string();
+        assert!(report.starts_with("Invalid configuration (3 errors)"));
+        for error in &err.errors {
+            assert!(
+                report.contains(&error.message),
+                "missing: {}",
+                error.message
+            );
+        }
     }
 
     #[test]
@@ -125,10 +1179,11 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let result = config.validate();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not a directory"));
+        assert!(result.unwrap_err().to_string().contains("not a directory"));
     }
 
     #[test]
@@ -141,11 +1196,736 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let validated = config.validate().unwrap();
         assert_eq!(validated.allowed_directories[0], expected);
     }
 
+    #[test]
+    fn parses_only_extensions_list() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str, "--only-extensions", "rs,toml,md"]).unwrap();
+        assert_eq!(config.only_extensions, vec!["rs", "toml", "md"]);
+    }
+
+    #[test]
+    fn validate_normalizes_only_extensions() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            only_extensions: vec![".RS".into(), " toml ".into(), "".into(), "rs".into()],
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert_eq!(validated.only_extensions, vec!["rs", "toml"]);
+    }
+
+    #[test]
+    fn parses_repeatable_deny_flags() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--deny",
+            "*.secret",
+            "--deny",
+            "private/**",
+            "--deny-preset",
+            "secrets",
+        ])
+        .unwrap();
+        assert_eq!(config.deny, vec!["*.secret", "private/**"]);
+        assert_eq!(config.deny_preset, vec![DenyPreset::Secrets]);
+    }
+
+    #[test]
+    fn validate_expands_deny_preset() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            deny: vec!["*.pem".into()],
+            deny_preset: vec![DenyPreset::Secrets],
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert!(validated.deny_preset.is_empty());
+        assert_eq!(validated.deny.len(), SECRETS_PRESET.len());
+        assert_eq!(validated.deny[0], "*.pem");
+        assert!(validated.deny.iter().any(|p| p == ".ssh/**"));
+    }
+
+    #[test]
+    fn deny_pattern_alias_and_deny_defaults() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--deny-pattern",
+            "*.secret",
+            "--deny-patterns",
+            "**/secrets/**",
+            "--deny-defaults",
+        ])
+        .unwrap();
+        assert_eq!(config.deny, vec!["*.secret", "**/secrets/**"]);
+        assert!(config.deny_defaults);
+
+        let validated = config.validate().unwrap();
+        assert!(!validated.deny_defaults);
+        assert_eq!(validated.deny[0], "*.secret");
+        for pattern in SECRETS_PRESET {
+            assert!(validated.deny.iter().any(|p| p == pattern), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn secrets_preset_patterns_are_valid_globs() {
+        for pattern in DenyPreset::Secrets.patterns() {
+            assert!(
+                globset::Glob::new(pattern).is_ok(),
+                "invalid preset glob: {pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_rejects_invalid_deny_pattern() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            deny: vec!["[unclosed".into()],
+            ..Default::default()
+        };
+        let result = config.validate();
+        assert!(result.unwrap_err().to_string().contains("Invalid pattern"));
+    }
+
+    #[test]
+    fn validate_deduplicates_identical_directories() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf(), dir.path().join(".")],
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert_eq!(
+            validated.allowed_directories,
+            vec![dir.path().canonicalize().unwrap()]
+        );
+    }
+
+    #[test]
+    fn validate_merges_nested_directories_into_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("projects").join("foo");
+        std::fs::create_dir_all(&nested).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        for order in [
+            vec![dir.path().to_path_buf(), nested.clone()],
+            vec![nested.clone(), dir.path().to_path_buf()],
+        ] {
+            let config = Config {
+                allowed_directories: order,
+                ..Default::default()
+            };
+            let validated = config.validate().unwrap();
+            assert_eq!(validated.allowed_directories, vec![root.clone()]);
+        }
+    }
+
+    #[test]
+    fn validate_keeps_disjoint_directories() {
+        let dir1 = TempDir::new().unwrap();
+        let dir2 = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir1.path().to_path_buf(), dir2.path().to_path_buf()],
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert_eq!(
+            validated.allowed_directories,
+            vec![
+                dir1.path().canonicalize().unwrap(),
+                dir2.path().canonicalize().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_does_not_merge_sibling_with_shared_prefix() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("proj");
+        let b = dir.path().join("proj-extra");
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+        let config = Config {
+            allowed_directories: vec![a, b],
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert_eq!(validated.allowed_directories.len(), 2);
+    }
+
+    #[test]
+    fn dirs_from_satisfies_required_directories() {
+        let config = parse(&["ironbeard", "--dirs-from", "roots.txt"]).unwrap();
+        assert!(config.allowed_directories.is_empty());
+        assert_eq!(config.dirs_from, Some(PathBuf::from("roots.txt")));
+    }
+
+    #[test]
+    fn validate_reads_dirs_from_file_skipping_comments_and_blanks() {
+        let positional = TempDir::new().unwrap();
+        let listed = TempDir::new().unwrap();
+        let list_dir = TempDir::new().unwrap();
+        std::fs::create_dir(list_dir.path().join("relative")).unwrap();
+        let list_file = list_dir.path().join("roots.txt");
+        std::fs::write(
+            &list_file,
+            format!(
+                "# project roots\n\n  {}  \n   # indented comment\nrelative\n",
+                listed.path().display()
+            ),
+        )
+        .unwrap();
+
+        let config = Config {
+            allowed_directories: vec![positional.path().to_path_buf()],
+            dirs_from: Some(list_file),
+            ..Default::default()
+        };
+        let validated = config.validate().unwrap();
+        assert_eq!(
+            validated.allowed_directories,
+            vec![
+                positional.path().canonicalize().unwrap(),
+                listed.path().canonicalize().unwrap(),
+                list_dir.path().join("relative").canonicalize().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_dirs_from_error_names_file_and_line() {
+        let list_dir = TempDir::new().unwrap();
+        let list_file = list_dir.path().join("roots.txt");
+        std::fs::write(&list_file, "# comment\n/definitely/does/not/exist/abc123\n").unwrap();
+
+        let config = Config {
+            dirs_from: Some(list_file.clone()),
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains(&format!("{}:2:", list_file.display())));
+        assert!(err.contains("Failed to resolve"));
+    }
+
+    #[test]
+    fn validate_dirs_from_missing_file() {
+        let config = Config {
+            dirs_from: Some(PathBuf::from("/definitely/does/not/exist/roots.txt")),
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Failed to read directory list"));
+        assert!(err.contains("roots.txt"));
+    }
+
+    #[test]
+    fn builder_defaults_match_cli_defaults() {
+        let dir = TempDir::new().unwrap();
+        let built = Config::builder()
+            .allowed_directory(dir.path())
+            .build()
+            .unwrap();
+        let parsed = parse(&["ironbeard", dir.path().to_str().unwrap()])
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert_eq!(built.allowed_directories, parsed.allowed_directories);
+        assert_eq!(built.allow_write, parsed.allow_write);
+        assert_eq!(built.allow_destructive, parsed.allow_destructive);
+        assert_eq!(built.max_read_size, parsed.max_read_size);
+        assert_eq!(built.max_depth, parsed.max_depth);
+    }
+
+    #[test]
+    fn builder_applies_setters_and_validates() {
+        let dir = TempDir::new().unwrap();
+        let config = Config::builder()
+            .allowed_directories([dir.path()])
+            .allow_destructive(true)
+            .max_read_size(2048)
+            .only_extensions([".RS"])
+            .deny("*.key")
+            .build()
+            .unwrap();
+        assert!(config.allow_write);
+        assert_eq!(config.max_read_size, 2048);
+        assert_eq!(config.only_extensions, vec!["rs"]);
+        assert_eq!(config.deny, vec!["*.key"]);
+        assert_eq!(
+            config.allowed_directories,
+            vec![dir.path().canonicalize().unwrap()]
+        );
+    }
+
+    #[test]
+    fn builder_build_reports_invalid_directory() {
+        let result = Config::builder()
+            .allowed_directory("/definitely/does/not/exist/abc123")
+            .build();
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to resolve")
+        );
+    }
+
+    #[test]
+    fn parses_max_concurrent_ops() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_concurrent_ops, DEFAULT_MAX_CONCURRENT_OPS);
+        let config = parse(&["ironbeard", dir_str, "--max-concurrent-ops", "2"]).unwrap();
+        assert_eq!(config.max_concurrent_ops, 2);
+    }
+
+    #[test]
+    fn parses_max_output_bytes() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_output_bytes, DEFAULT_MAX_OUTPUT_BYTES);
+        let config = parse(&["ironbeard", dir_str, "--max-output-bytes", "4096"]).unwrap();
+        assert_eq!(config.max_output_bytes, 4096);
+    }
+
+    #[test]
+    fn parses_max_count_entries() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_count_entries, DEFAULT_MAX_COUNT_ENTRIES);
+        let config = parse(&["ironbeard", dir_str, "--max-count-entries", "50"]).unwrap();
+        assert_eq!(config.max_count_entries, 50);
+    }
+
+    #[test]
+    fn parses_max_dir_and_tree_entries() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_dir_entries, DEFAULT_MAX_DIR_ENTRIES);
+        assert_eq!(config.max_tree_entries, DEFAULT_MAX_TREE_ENTRIES);
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--max-dir-entries",
+            "20",
+            "--max-tree-entries",
+            "30",
+        ])
+        .unwrap();
+        assert_eq!(config.max_dir_entries, 20);
+        assert_eq!(config.max_tree_entries, 30);
+
+        let config = parse(&["ironbeard", dir_str, "--max-tree-entries", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_tree_entries");
+    }
+
+    #[test]
+    fn parses_max_table_rows() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_table_rows, DEFAULT_MAX_TABLE_ROWS);
+        let config = parse(&["ironbeard", dir_str, "--max-table-rows", "25"]).unwrap();
+        assert_eq!(config.max_table_rows, 25);
+
+        let config = parse(&["ironbeard", dir_str, "--max-table-rows", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_table_rows");
+    }
+
+    #[test]
+    fn parses_max_checksum_entries() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_checksum_entries, DEFAULT_MAX_CHECKSUM_ENTRIES);
+        let config = parse(&["ironbeard", dir_str, "--max-checksum-entries", "3"]).unwrap();
+        assert_eq!(config.max_checksum_entries, 3);
+
+        let config = parse(&["ironbeard", dir_str, "--max-checksum-entries", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_checksum_entries");
+    }
+
+    #[test]
+    fn parses_max_snapshot_files() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_snapshot_files, DEFAULT_MAX_SNAPSHOT_FILES);
+        let config = parse(&["ironbeard", dir_str, "--max-snapshot-files", "7"]).unwrap();
+        assert_eq!(config.max_snapshot_files, 7);
+
+        let config = parse(&["ironbeard", dir_str, "--max-snapshot-files", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_snapshot_files");
+    }
+
+    #[test]
+    fn validate_reads_config_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let file = dir.path().join("ironbeard.toml");
+        std::fs::write(
+            &file,
+            "allowed-directories = [\"src\"]\n\
+             allow-write = true\n\
+             max-depth = 4\n\
+             deny-preset = [\"secrets\"]\n",
+        )
+        .unwrap();
+
+        let config = parse(&["ironbeard", "--config", file.to_str().unwrap()])
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert_eq!(
+            config.allowed_directories,
+            vec![dir.path().join("src").canonicalize().unwrap()]
+        );
+        assert!(config.allow_write);
+        assert_eq!(config.max_depth, 4);
+        assert!(config.deny.iter().any(|p| p == "*.pem"));
+        assert_eq!(config.max_read_size, DEFAULT_MAX_READ_SIZE);
+    }
+
+    #[test]
+    fn command_line_overrides_config_file() {
+        let dir = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        let file = dir.path().join("ironbeard.toml");
+        std::fs::write(
+            &file,
+            "allowed-directories = [\".\"]\nmax-depth = 4\nmax-read-size = 2048\ndeny = [\"*.key\"]\n",
+        )
+        .unwrap();
+
+        let config = parse(&[
+            "ironbeard",
+            other.path().to_str().unwrap(),
+            "--config",
+            file.to_str().unwrap(),
+            "--max-depth",
+            "2",
+            "--deny",
+            "*.log",
+        ])
+        .unwrap()
+        .validate()
+        .unwrap();
+        assert_eq!(config.max_depth, 2);
+        assert_eq!(config.max_read_size, 2048);
+        assert_eq!(config.deny, vec!["*.log", "*.key"]);
+        assert_eq!(
+            config.allowed_directories,
+            vec![
+                other.path().canonicalize().unwrap(),
+                dir.path().canonicalize().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn command_line_default_value_overrides_config_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("ironbeard.toml");
+        std::fs::write(&file, "allowed-directories = [\".\"]\nmax-depth = 4\n").unwrap();
+        let config_arg = file.to_str().unwrap();
+
+        // --max-depth 10 is the built-in default but was given explicitly
+        let depth = DEFAULT_MAX_DEPTH.to_string();
+        let config = parse(&["ironbeard", "--config", config_arg, "--max-depth", &depth])
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert_eq!(config.max_depth, DEFAULT_MAX_DEPTH);
+
+        let config = parse(&["ironbeard", "--config", config_arg])
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert_eq!(config.max_depth, 4);
+    }
+
+    #[test]
+    fn validate_rejects_invalid_config_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("bad.toml");
+        std::fs::write(&file, "max-depth = \"deep\"\n").unwrap();
+        let err = Config::builder()
+            .allowed_directory(dir.path())
+            .config_file(&file)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.errors[0].field, "config_file");
+        assert!(
+            err.errors[0].message.starts_with("Invalid config file"),
+            "{err}"
+        );
+
+        std::fs::write(&file, "max-dpeth = 3\n").unwrap();
+        let err = Config::builder()
+            .allowed_directory(dir.path())
+            .config_file(&file)
+            .build()
+            .unwrap_err();
+        assert!(err.errors[0].message.contains("unknown field"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_missing_config_file() {
+        let dir = TempDir::new().unwrap();
+        let err = Config::builder()
+            .allowed_directory(dir.path())
+            .config_file(dir.path().join("missing.toml"))
+            .build()
+            .unwrap_err();
+        assert_eq!(err.errors[0].field, "config_file");
+        assert!(
+            err.errors[0]
+                .message
+                .starts_with("Failed to read config file"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_and_canonicalizes_read_only_directories() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        let docs = dir.path().join("docs");
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--allow-write",
+            "--read-only-directories",
+            docs.to_str().unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(config.read_only_directories, vec![docs.clone()]);
+        let validated = config.validate().unwrap();
+        assert_eq!(
+            validated.read_only_directories,
+            vec![docs.canonicalize().unwrap()]
+        );
+
+        let missing = dir.path().join("missing");
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--read-only-directories",
+            missing.to_str().unwrap(),
+        ])
+        .unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "read_only_directories");
+    }
+
+    #[test]
+    fn parses_client_roots_flags() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert!(!config.accept_client_roots);
+        assert_eq!(config.root_allowlist_prefix, None);
+
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--accept-client-roots",
+            "--root-allowlist-prefix",
+            dir_str,
+        ])
+        .unwrap()
+        .validate()
+        .unwrap();
+        assert!(config.accept_client_roots);
+        assert_eq!(
+            config.root_allowlist_prefix,
+            Some(dir.path().canonicalize().unwrap())
+        );
+
+        // The prefix only means something when client roots are accepted
+        assert!(parse(&["ironbeard", dir_str, "--root-allowlist-prefix", dir_str]).is_err());
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--accept-client-roots",
+            "--root-allowlist-prefix",
+            "/definitely/does/not/exist/abc123",
+        ])
+        .unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "root_allowlist_prefix");
+    }
+
+    #[test]
+    fn parses_max_recursive_delete_entries() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(
+            config.max_recursive_delete_entries,
+            DEFAULT_MAX_RECURSIVE_DELETE_ENTRIES
+        );
+        let config =
+            parse(&["ironbeard", dir_str, "--max-recursive-delete-entries", "50"]).unwrap();
+        assert_eq!(config.max_recursive_delete_entries, 50);
+
+        let config = parse(&["ironbeard", dir_str, "--max-recursive-delete-entries", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_recursive_delete_entries");
+    }
+
+    #[test]
+    fn parses_max_delete_matching() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_delete_matching, DEFAULT_MAX_DELETE_MATCHING);
+        let config = parse(&["ironbeard", dir_str, "--max-delete-matching", "20"]).unwrap();
+        assert_eq!(config.max_delete_matching, 20);
+
+        let config = parse(&["ironbeard", dir_str, "--max-delete-matching", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_delete_matching");
+    }
+
+    #[test]
+    fn parses_max_normalize_files() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_normalize_files, DEFAULT_MAX_NORMALIZE_FILES);
+        let config = parse(&["ironbeard", dir_str, "--max-normalize-files", "3"]).unwrap();
+        assert_eq!(config.max_normalize_files, 3);
+
+        let config = parse(&["ironbeard", dir_str, "--max-normalize-files", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_normalize_files");
+    }
+
+    #[test]
+    fn parses_max_hash_size() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_hash_size, DEFAULT_MAX_HASH_SIZE);
+        let config = parse(&["ironbeard", dir_str, "--max-hash-size", "1024"]).unwrap();
+        assert_eq!(config.max_hash_size, 1024);
+    }
+
+    #[test]
+    fn parses_archive_limits() {
+        let dir = TempDir::new().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let config = parse(&["ironbeard", dir_str]).unwrap();
+        assert_eq!(config.max_archive_size, DEFAULT_MAX_ARCHIVE_SIZE);
+        assert_eq!(config.max_archive_entries, DEFAULT_MAX_ARCHIVE_ENTRIES);
+        assert_eq!(config.max_write_size, DEFAULT_MAX_WRITE_SIZE);
+        assert_eq!(
+            config.max_archive_input_size,
+            DEFAULT_MAX_ARCHIVE_INPUT_SIZE
+        );
+        let config = parse(&[
+            "ironbeard",
+            dir_str,
+            "--max-archive-size",
+            "1024",
+            "--max-archive-entries",
+            "5",
+            "--max-write-size",
+            "2048",
+            "--max-archive-input-size",
+            "4096",
+        ])
+        .unwrap();
+        assert_eq!(config.max_archive_input_size, 4096);
+        assert_eq!(config.max_write_size, 2048);
+        assert_eq!(config.max_archive_size, 1024);
+        assert_eq!(config.max_archive_entries, 5);
+
+        let config = parse(&["ironbeard", dir_str, "--max-archive-entries", "0"]).unwrap();
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.errors[0].field, "max_archive_entries");
+    }
+
+    #[test]
+    fn validate_creates_and_canonicalizes_trash_dir() {
+        let dir = TempDir::new().unwrap();
+        let trash_parent = TempDir::new().unwrap();
+        let trash = trash_parent.path().join("trash");
+        let config = Config::builder()
+            .allowed_directory(dir.path())
+            .trash_dir(&trash)
+            .build()
+            .unwrap();
+        assert!(trash.is_dir());
+        assert_eq!(config.trash_dir, Some(trash.canonicalize().unwrap()));
+    }
+
+    #[test]
+    fn validate_rejects_trash_dir_inside_allowed_directory() {
+        let dir = TempDir::new().unwrap();
+        let err = Config::builder()
+            .allowed_directory(dir.path())
+            .trash_dir(dir.path().join(".trash"))
+            .build()
+            .unwrap_err();
+        assert_eq!(err.errors[0].field, "trash_dir");
+        assert!(err.to_string().contains("outside the allowed directories"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_history_size() {
+        let dir = TempDir::new().unwrap();
+        let err = Config::builder()
+            .allowed_directory(dir.path())
+            .history_size(0)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.errors[0].field, "history_size");
+    }
+
+    #[test]
+    fn validate_rejects_zero_concurrent_ops() {
+        let dir = TempDir::new().unwrap();
+        let result = Config::builder()
+            .allowed_directory(dir.path())
+            .max_concurrent_ops(0)
+            .build();
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("max-concurrent-ops")
+        );
+    }
+
     #[test]
     fn parses_allow_destructive_flag() {
         let dir = TempDir::new().unwrap();
@@ -172,6 +1952,7 @@ mod tests {
             allow_destructive: true,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let validated = config.validate().unwrap();
         assert!(validated.allow_write);
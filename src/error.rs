@@ -1,4 +1,5 @@
-use rmcp::model::{ErrorCode, ErrorData};
+use rmcp::model::{Content, ErrorCode, ErrorData, IntoContents, Meta, RawContent, RawTextContent};
+use serde_json::{Value, json};
 use std::borrow::Cow;
 use thiserror::Error;
 
@@ -7,6 +8,12 @@ pub enum FsError {
     #[error("Access denied: {path}")]
     PathDenied { path: String },
 
+    #[error("Access denied: {path} matched deny pattern '{pattern}'")]
+    DeniedByPattern { path: String, pattern: String },
+
+    #[error("Read-only: {path} is in a read-only directory")]
+    ReadOnly { path: String },
+
     #[error("Not found: {path}")]
     NotFound { path: String },
 
@@ -29,15 +36,81 @@ pub enum FsError {
     PatternError(String),
 
     #[error("Edit failed on {path}: {reason}")]
-    EditFailed { path: String, reason: String },
+    EditFailed {
+        path: String,
+        reason: String,
+        /// Occurrences of the text to replace: 0 if missing, more than 1 if ambiguous
+        match_count: usize,
+    },
+
+    #[error("Operation cancelled by the client")]
+    Cancelled,
+
+    #[error("Server is shutting down; no new operations are accepted")]
+    ShuttingDown,
+}
+
+impl FsError {
+    /// Stable machine-readable name of the variant, e.g. `file_too_large`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FsError::PathDenied { .. } => "path_denied",
+            FsError::DeniedByPattern { .. } => "denied_by_pattern",
+            FsError::ReadOnly { .. } => "read_only",
+            FsError::NotFound { .. } => "not_found",
+            FsError::NotAFile { .. } => "not_a_file",
+            FsError::NotADirectory { .. } => "not_a_directory",
+            FsError::FileTooLarge { .. } => "file_too_large",
+            FsError::BinaryFile { .. } => "binary_file",
+            FsError::IoError(_) => "io",
+            FsError::PatternError(_) => "invalid_pattern",
+            FsError::EditFailed { .. } => "edit_failed",
+            FsError::Cancelled => "cancelled",
+            FsError::ShuttingDown => "shutting_down",
+        }
+    }
+
+    /// JSON object with the `kind` and the variant's fields, sent to clients
+    /// alongside the message so they can react without parsing it.
+    pub fn details(&self) -> Value {
+        let mut details = match self {
+            FsError::PathDenied { path }
+            | FsError::ReadOnly { path }
+            | FsError::NotFound { path }
+            | FsError::NotAFile { path }
+            | FsError::NotADirectory { path }
+            | FsError::BinaryFile { path } => json!({ "path": path }),
+            FsError::DeniedByPattern { path, pattern } => {
+                json!({ "path": path, "pattern": pattern })
+            }
+            FsError::FileTooLarge { path, size, max } => {
+                json!({ "path": path, "size": size, "max": max })
+            }
+            FsError::IoError(e) => json!({ "io_kind": e.kind().to_string() }),
+            FsError::PatternError(reason) => json!({ "reason": reason }),
+            FsError::EditFailed {
+                path,
+                reason,
+                match_count,
+            } => json!({ "path": path, "reason": reason, "match_count": match_count }),
+            FsError::Cancelled | FsError::ShuttingDown => json!({}),
+        };
+        details["kind"] = json!(self.kind());
+        details
+    }
 }
 
 impl From<FsError> for ErrorData {
     fn from(err: FsError) -> Self {
         let code = match &err {
             FsError::NotFound { .. } => ErrorCode::RESOURCE_NOT_FOUND,
-            FsError::IoError(_) | FsError::EditFailed { .. } => ErrorCode::INTERNAL_ERROR,
+            FsError::IoError(_)
+            | FsError::EditFailed { .. }
+            | FsError::Cancelled
+            | FsError::ShuttingDown => ErrorCode::INTERNAL_ERROR,
             FsError::PathDenied { .. }
+            | FsError::DeniedByPattern { .. }
+            | FsError::ReadOnly { .. }
             | FsError::NotAFile { .. }
             | FsError::NotADirectory { .. }
             | FsError::FileTooLarge { .. }
@@ -47,11 +120,87 @@ impl From<FsError> for ErrorData {
         ErrorData {
             code,
             message: Cow::Owned(err.to_string()),
-            data: None,
+            data: Some(err.details()),
+        }
+    }
+}
+
+/// Error returned by a tool: the message shown to the client and, when the
+/// failure has a known cause, its machine-readable details.
+///
+/// The client receives an `isError` result whose text block is the message,
+/// with the details under that block's `_meta.error`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolError {
+    pub message: String,
+    /// An [`FsError::details`] object, or `None` for one-off failures
+    pub details: Option<Value>,
+}
+
+impl ToolError {
+    /// An I/O failure on `path`, with the message from [`io_error_message`].
+    pub fn io(err: std::io::Error, path: &str) -> Self {
+        let details = json!({
+            "kind": "io",
+            "io_kind": err.kind().to_string(),
+            "path": path,
+        });
+        Self {
+            message: io_error_message(err, path),
+            details: Some(details),
+        }
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<FsError> for ToolError {
+    fn from(err: FsError) -> Self {
+        Self {
+            message: err.to_string(),
+            details: Some(err.details()),
         }
     }
 }
 
+impl From<String> for ToolError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            details: None,
+        }
+    }
+}
+
+impl From<&str> for ToolError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl IntoContents for ToolError {
+    fn into_contents(self) -> Vec<Content> {
+        let meta = self.details.map(|details| {
+            let mut meta = Meta::new();
+            meta.0.insert("error".to_string(), details);
+            meta
+        });
+        vec![Content::new(
+            RawContent::Text(RawTextContent {
+                text: self.message,
+                meta,
+            }),
+            None,
+        )]
+    }
+}
+
 /// Converts an I/O error to a user-friendly message string.
 /// Distinguishes OS-level "Permission denied" from our security PathDenied.
 pub fn io_error_message(err: std::io::Error, path: &str) -> String {
@@ -87,12 +236,66 @@ mod tests {
 
     #[test]
     fn io_error_maps_to_internal_error() {
-        let io_err = std::io::Error::new(std::io::ErrorKind::Other, "disk failure");
+        let io_err = std::io::Error::other("disk failure");
         let err: FsError = io_err.into();
         let data: ErrorData = err.into();
         assert_eq!(data.code, ErrorCode::INTERNAL_ERROR);
     }
 
+    #[test]
+    fn error_data_carries_details() {
+        let err = FsError::FileTooLarge {
+            path: "/big".into(),
+            size: 20,
+            max: 10,
+        };
+        let message = err.to_string();
+        let data: ErrorData = err.into();
+        assert_eq!(data.message, message);
+        assert_eq!(
+            data.data,
+            Some(json!({ "kind": "file_too_large", "path": "/big", "size": 20, "max": 10 }))
+        );
+    }
+
+    #[test]
+    fn tool_error_puts_details_in_meta() {
+        let err = FsError::EditFailed {
+            path: "/a.txt".into(),
+            reason: "ambiguous".into(),
+            match_count: 2,
+        };
+        let message = err.to_string();
+        let contents = ToolError::from(err).into_contents();
+        assert_eq!(contents.len(), 1);
+        let text = contents[0].as_text().unwrap();
+        assert_eq!(text.text, message);
+        let details = &text.meta.as_ref().unwrap().0["error"];
+        assert_eq!(details["kind"], "edit_failed");
+        assert_eq!(details["match_count"], 2);
+    }
+
+    #[test]
+    fn plain_tool_error_has_no_meta() {
+        let contents = ToolError::from("something broke").into_contents();
+        let text = contents[0].as_text().unwrap();
+        assert_eq!(text.text, "something broke");
+        assert!(text.meta.is_none());
+    }
+
+    #[test]
+    fn tool_error_io_keeps_message() {
+        let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let tool_err = ToolError::io(err, "/p");
+        assert_eq!(
+            tool_err.message,
+            "Permission denied by operating system: /p"
+        );
+        let details = tool_err.details.unwrap();
+        assert_eq!(details["kind"], "io");
+        assert_eq!(details["path"], "/p");
+    }
+
     #[test]
     fn io_error_message_permission_denied() {
         let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
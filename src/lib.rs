@@ -1,11 +1,15 @@
+pub mod api;
 pub mod config;
 pub mod error;
+pub mod logging;
+pub mod roots;
 pub mod security;
 pub mod server;
 pub mod service;
+pub mod stats;
 pub mod tools;
 
-pub use config::Config;
-pub use error::FsError;
+pub use config::{Config, ConfigBuilder, ConfigError, ConfigValidationError};
+pub use error::{FsError, ToolError};
 pub use security::SecurityContext;
 pub use service::FilesystemService;
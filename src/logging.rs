@@ -0,0 +1,118 @@
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::{Peer, RoleServer};
+use std::sync::{Mutex, OnceLock};
+
+/// Level used until the client sends `logging/setLevel`.
+const DEFAULT_CLIENT_LEVEL: LoggingLevel = LoggingLevel::Info;
+
+/// Position of a level in the MCP severity order, lowest first.
+fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+/// Sends user-relevant events to the client as `notifications/message`.
+///
+/// Every event is also written to the operator log through `tracing`; only the
+/// client copy is filtered by the level set with `logging/setLevel`.
+pub struct ClientLogger {
+    min_level: Mutex<LoggingLevel>,
+    peer: OnceLock<Peer<RoleServer>>,
+}
+
+impl Default for ClientLogger {
+    fn default() -> Self {
+        Self {
+            min_level: Mutex::new(DEFAULT_CLIENT_LEVEL),
+            peer: OnceLock::new(),
+        }
+    }
+}
+
+impl ClientLogger {
+    /// Applies a `logging/setLevel` request.
+    pub fn set_level(&self, level: LoggingLevel) {
+        *self.min_level.lock().expect("log level lock poisoned") = level;
+    }
+
+    /// Remembers the client connection. Later calls are ignored.
+    pub fn attach(&self, peer: &Peer<RoleServer>) {
+        let _ = self.peer.set(peer.clone());
+    }
+
+    /// Writes `message` to the operator log and returns the client notification,
+    /// or `None` if `level` is below the client's threshold.
+    pub fn message(
+        &self,
+        level: LoggingLevel,
+        logger: &str,
+        message: &str,
+    ) -> Option<LoggingMessageNotificationParam> {
+        match severity(level) {
+            0 => tracing::debug!(target: "client", logger, "{message}"),
+            1 | 2 => tracing::info!(target: "client", logger, "{message}"),
+            3 => tracing::warn!(target: "client", logger, "{message}"),
+            _ => tracing::error!(target: "client", logger, "{message}"),
+        }
+        let min_level = *self.min_level.lock().expect("log level lock poisoned");
+        (severity(level) >= severity(min_level)).then(|| LoggingMessageNotificationParam {
+            level,
+            logger: Some(logger.to_string()),
+            data: serde_json::Value::String(message.to_string()),
+        })
+    }
+
+    /// Logs `message` and, when a client is connected and listening at this
+    /// level, sends it as a notification without waiting for delivery.
+    pub fn log(&self, level: LoggingLevel, logger: &str, message: &str) {
+        let Some(param) = self.message(level, logger, message) else {
+            return;
+        };
+        let (Some(peer), Ok(runtime)) = (
+            self.peer.get().cloned(),
+            tokio::runtime::Handle::try_current(),
+        ) else {
+            return;
+        };
+        runtime.spawn(async move {
+            let _ = peer.notify_logging_message(param).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_level_forwards_warnings_and_drops_debug() {
+        let logger = ClientLogger::default();
+        let param = logger
+            .message(LoggingLevel::Warning, "output", "Output truncated")
+            .unwrap();
+        assert_eq!(param.level, LoggingLevel::Warning);
+        assert_eq!(param.logger.as_deref(), Some("output"));
+        assert_eq!(param.data, serde_json::json!("Output truncated"));
+        assert!(logger.message(LoggingLevel::Debug, "walk", "x").is_none());
+    }
+
+    #[test]
+    fn set_level_raises_threshold() {
+        let logger = ClientLogger::default();
+        logger.set_level(LoggingLevel::Error);
+        assert!(logger.message(LoggingLevel::Warning, "walk", "x").is_none());
+        assert!(
+            logger
+                .message(LoggingLevel::Critical, "walk", "x")
+                .is_some()
+        );
+    }
+}
@@ -1,7 +1,36 @@
-use clap::Parser;
 use ironbeard_mcp_filesystem::{Config, FilesystemService};
 use rmcp::ServiceExt;
-use tracing::info;
+use rmcp::service::QuitReason;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long a shutdown signal waits for running tool calls to finish.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Time given to the last responses to reach stdout before the transport closes.
+const SHUTDOWN_FLUSH_DELAY: Duration = Duration::from_millis(100);
+
+/// Waits for SIGINT (Ctrl-C) or, on Unix, SIGTERM. Returns the signal name and
+/// the conventional exit code for it (128 + signal number).
+async fn shutdown_signal() -> (&'static str, i32) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut terminate =
+            signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => ("SIGINT", 130),
+            _ = terminate.recv() => ("SIGTERM", 143),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        ("Ctrl-C", 130)
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -11,8 +40,15 @@ async fn main() -> anyhow::Result<()> {
         .with_max_level(tracing::Level::INFO)
         .init();
 
-    let config = Config::parse().validate().unwrap_or_else(|e| {
-        eprintln!("Configuration error: {e}");
+    let config = Config::parse_args().validate().unwrap_or_else(|e| {
+        if std::env::var("IRONBEARD_JSON_ERRORS").is_ok_and(|v| v == "1") {
+            eprintln!(
+                "{}",
+                serde_json::to_string(&e).unwrap_or_else(|_| e.to_string())
+            );
+        } else {
+            eprintln!("{e}");
+        }
         std::process::exit(1);
     });
 
@@ -41,19 +77,78 @@ async fn main() -> anyhow::Result<()> {
         "Max read size: {} bytes, Max depth: {}",
         config.max_read_size, config.max_depth
     );
+    info!(
+        "Max output: {} bytes, Max concurrent operations: {}",
+        config.max_output_bytes, config.max_concurrent_ops
+    );
+    match &config.trash_dir {
+        Some(trash) => info!("Deletes move to trash: {}", trash.display()),
+        None if config.require_trash => {
+            info!("Deletes disabled: --require-trash without --trash-dir")
+        }
+        None => {}
+    }
+    if config.confirm_destructive {
+        info!("Destructive operations require a confirmation token");
+    }
+    if !config.deny.is_empty() {
+        info!("Deny patterns: {:?}", config.deny);
+    }
+    if !config.only_extensions.is_empty() {
+        info!(
+            "Output filtered to extensions: {:?}",
+            config.only_extensions
+        );
+    }
 
-    let service = FilesystemService::new(config);
-    let server = service
-        .serve((tokio::io::stdin(), tokio::io::stdout()))
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to start MCP server: {e}"))?;
+    let service = Arc::new(FilesystemService::new(config));
+    // Listen before the handshake so an early signal does not kill the process outright
+    let mut signal = tokio::spawn(shutdown_signal());
+    let server = tokio::select! {
+        server = service.clone().serve((tokio::io::stdin(), tokio::io::stdout())) => {
+            server.map_err(|e| anyhow::anyhow!("Failed to start MCP server: {e}"))?
+        }
+        received = &mut signal => {
+            let (signal, exit_code) = received?;
+            info!("{signal} received before initialization; exiting");
+            std::process::exit(exit_code);
+        }
+    };
 
     info!("MCP server running on stdio");
 
-    server
+    // On a signal, stop taking tool calls, let running ones finish, then close
+    // the transport between messages so the client sees a clean end of stream.
+    let cancel = server.cancellation_token();
+    let shutdown = tokio::spawn(async move {
+        let (signal, exit_code) = signal.await.unwrap_or(("shutdown", 1));
+        info!(
+            "{signal} received; refusing new tool calls and waiting up to {}s for {} running",
+            SHUTDOWN_GRACE.as_secs(),
+            service.concurrency().0
+        );
+        if !service.drain(SHUTDOWN_GRACE).await {
+            warn!(
+                "Shutting down with {} tool calls still running",
+                service.concurrency().0
+            );
+        }
+        tokio::time::sleep(SHUTDOWN_FLUSH_DELAY).await;
+        cancel.cancel();
+        exit_code
+    });
+
+    let reason = server
         .waiting()
         .await
         .map_err(|e| anyhow::anyhow!("Server error: {e}"))?;
 
+    if matches!(reason, QuitReason::Cancelled) {
+        let exit_code = shutdown.await.unwrap_or(1);
+        info!("Shutdown complete");
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        std::process::exit(exit_code);
+    }
     Ok(())
 }
@@ -0,0 +1,199 @@
+//! MCP client roots. With `--accept-client-roots`, the workspace folders a
+//! client reports through `roots/list` are allowed alongside the directories
+//! given on the command line, and the set is recomputed whenever the client
+//! sends `notifications/roots/list_changed`.
+
+use crate::config::{Config, merge_overlapping};
+use crate::security::SecurityContext;
+use rmcp::model::Root;
+use rmcp::{Peer, RoleServer};
+use std::path::{Path, PathBuf};
+
+/// The local path named by a `file://` URI, percent-decoded. Returns `None`
+/// for other schemes, for URIs naming a remote host, and for malformed
+/// escapes.
+pub fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !rest.starts_with('/') {
+        return None;
+    }
+    bytes_to_path(percent_decode(rest)?)
+}
+
+/// Decodes `%XX` escapes into the bytes they stand for.
+fn percent_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            out.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            out.push(b);
+        }
+    }
+    Some(out)
+}
+
+/// On Unix a path is any sequence of bytes.
+#[cfg(unix)]
+fn bytes_to_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+/// Elsewhere the path must be UTF-8, and `/C:/dir` names `C:/dir`.
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    let path = String::from_utf8(bytes).ok()?;
+    match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+/// The canonical directory a client root names, or why it is not allowed.
+/// A root must be an existing local directory, inside `prefix` when one is
+/// set, and must not contain `trash_dir`.
+fn resolve_root(
+    root: &Root,
+    prefix: Option<&Path>,
+    trash_dir: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let path = file_uri_to_path(&root.uri).ok_or("not a local file:// URI")?;
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("cannot be resolved: {e}"))?;
+    if !canonical.is_dir() {
+        return Err("not a directory".to_string());
+    }
+    if let Some(prefix) = prefix
+        && !canonical.starts_with(prefix)
+    {
+        return Err(format!(
+            "outside --root-allowlist-prefix {}",
+            prefix.display()
+        ));
+    }
+    if let Some(trash) = trash_dir
+        && trash.starts_with(&canonical)
+    {
+        return Err(format!("contains the trash directory {}", trash.display()));
+    }
+    Ok(canonical)
+}
+
+/// Asks the client for its roots and makes the allowed directories of
+/// `security` those from the command line plus every acceptable root.
+/// Rejected roots are logged and left out. Does nothing unless
+/// `--accept-client-roots` is set and the client declared the roots
+/// capability.
+pub(crate) async fn refresh_client_roots(
+    config: &Config,
+    security: &SecurityContext,
+    peer: &Peer<RoleServer>,
+) {
+    if !config.accept_client_roots {
+        return;
+    }
+    let supports_roots = peer
+        .peer_info()
+        .is_some_and(|info| info.capabilities.roots.is_some());
+    if !supports_roots {
+        return;
+    }
+    let roots = match peer.list_roots().await {
+        Ok(result) => result.roots,
+        Err(e) => {
+            tracing::warn!("Could not list client roots: {e}");
+            return;
+        }
+    };
+
+    let mut dirs = config.allowed_directories.clone();
+    for root in &roots {
+        match resolve_root(
+            root,
+            config.root_allowlist_prefix.as_deref(),
+            config.trash_dir.as_deref(),
+        ) {
+            Ok(dir) => dirs.push(dir),
+            Err(reason) => tracing::warn!("Ignoring client root {}: {reason}", root.uri),
+        }
+    }
+    let dirs = merge_overlapping(dirs, &mut Vec::new());
+    tracing::info!("Allowed directories: {:?}", dirs);
+    security.set_allowed_dirs(dirs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn root(uri: &str) -> Root {
+        Root {
+            uri: uri.to_string(),
+            name: None,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uris_decode_to_paths() {
+        assert_eq!(
+            file_uri_to_path("file:///home/me/My%20Project"),
+            Some(PathBuf::from("/home/me/My Project"))
+        );
+        assert_eq!(
+            file_uri_to_path("file://localhost/srv"),
+            Some(PathBuf::from("/srv"))
+        );
+        assert_eq!(file_uri_to_path("file://server/share"), None);
+        assert_eq!(file_uri_to_path("https://example.com/"), None);
+        assert_eq!(file_uri_to_path("file:///bad%2"), None);
+    }
+
+    #[test]
+    fn resolve_root_checks_directory_prefix_and_trash() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let project = canon.join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(canon.join("file.txt"), "x").unwrap();
+        let uri = |path: &Path| format!("file://{}", path.display()).replace(' ', "%20");
+
+        assert_eq!(
+            resolve_root(&root(&uri(&project)), None, None),
+            Ok(project.clone())
+        );
+        assert_eq!(
+            resolve_root(&root(&uri(&project)), Some(&canon), None),
+            Ok(project.clone())
+        );
+        assert_eq!(
+            resolve_root(&root(&uri(&canon.join("file.txt"))), None, None),
+            Err("not a directory".to_string())
+        );
+        assert!(
+            resolve_root(&root(&uri(&canon.join("missing"))), None, None)
+                .unwrap_err()
+                .starts_with("cannot be resolved")
+        );
+        assert!(
+            resolve_root(&root(&uri(&canon)), Some(&project), None)
+                .unwrap_err()
+                .starts_with("outside --root-allowlist-prefix")
+        );
+        assert!(
+            resolve_root(&root(&uri(&canon)), None, Some(&project.join(".trash")))
+                .unwrap_err()
+                .starts_with("contains the trash directory")
+        );
+        assert_eq!(
+            resolve_root(&root("https://example.com/"), None, None),
+            Err("not a local file:// URI".to_string())
+        );
+    }
+}
@@ -1,46 +1,391 @@
 use crate::error::FsError;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Compiles deny patterns into a single matcher.
+/// Fails on the first pattern that is not a valid glob.
+pub fn build_deny_set(patterns: &[String]) -> Result<GlobSet, FsError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| FsError::PatternError(format!("deny pattern '{pattern}': {e}")))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| FsError::PatternError(e.to_string()))
+}
+
+/// Strips the Windows extended-length prefix so `\\?\C:\dir` compares equal to
+/// `C:\dir` and `\\?\UNC\server\share` to `\\server\share`.
+#[cfg(windows)]
+pub(crate) fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let Some(s) = path.as_os_str().to_str() else {
+        return path.to_path_buf();
+    };
+    if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{rest}"))
+    } else if let Some(rest) = s.strip_prefix(r"\\?\") {
+        PathBuf::from(rest)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// No-op outside Windows, where paths have no verbatim form.
+#[cfg(not(windows))]
+pub(crate) fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// The raw bytes named by a `{hex:…}` path component, when they are not valid
+/// UTF-8. A component whose bytes are valid UTF-8 names a file literally called
+/// `{hex:…}`, since such a name never needs escaping.
+#[cfg(unix)]
+fn hex_component(name: &std::ffi::OsStr) -> Option<Vec<u8>> {
+    let hex = name.to_str()?.strip_prefix("{hex:")?.strip_suffix('}')?;
+    if hex.is_empty() || hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    std::str::from_utf8(&bytes).is_err().then_some(bytes)
+}
+
+/// Replaces `{hex:…}` components, as shown by `display_path` for names that
+/// are not valid UTF-8, with the exact bytes they encode.
+#[cfg(unix)]
+pub(crate) fn decode_hex_components(path: &Path) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Component;
+    path.components()
+        .map(|component| match component {
+            Component::Normal(name) => match hex_component(name) {
+                Some(bytes) => OsStr::from_bytes(&bytes).to_os_string(),
+                None => name.to_os_string(),
+            },
+            other => other.as_os_str().to_os_string(),
+        })
+        .collect()
+}
+
+/// Outside Unix, names are not arbitrary bytes and `{hex:…}` stays literal.
+#[cfg(not(unix))]
+pub(crate) fn decode_hex_components(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// The NFC and NFD spellings of `name` that differ from it.
+#[cfg(any(target_os = "macos", test))]
+fn normalization_variants(name: &str) -> Vec<String> {
+    use unicode_normalization::UnicodeNormalization;
+    let mut variants: Vec<String> = Vec::new();
+    for form in [name.nfc().collect::<String>(), name.nfd().collect()] {
+        if form != name && !variants.contains(&form) {
+            variants.push(form);
+        }
+    }
+    variants
+}
+
+/// True if both names are equal once brought to NFC. Names that are not
+/// valid UTF-8 must match byte for byte.
+#[cfg(any(target_os = "macos", test))]
+fn names_match_normalized(a: &std::ffi::OsStr, b: &std::ffi::OsStr) -> bool {
+    use unicode_normalization::UnicodeNormalization;
+    match (a.to_str(), b.to_str()) {
+        (Some(a), Some(b)) => a.nfc().eq(b.nfc()),
+        _ => a == b,
+    }
+}
+
+/// `path` relative to `root`, comparing components regardless of whether
+/// they are spelled in NFC or NFD.
+#[cfg(any(target_os = "macos", test))]
+fn strip_prefix_normalized(path: &Path, root: &Path) -> Option<PathBuf> {
+    let mut rest = path.components();
+    for expected in root.components() {
+        let actual = rest.next()?;
+        if !names_match_normalized(actual.as_os_str(), expected.as_os_str()) {
+            return None;
+        }
+    }
+    Some(rest.as_path().to_path_buf())
+}
+
+/// Respells every component of `path` that does not exist on disk as its NFC
+/// or NFD variant when that one does. Returns `None` if nothing was respelled.
+#[cfg(any(target_os = "macos", test))]
+fn resolve_normalized(path: &Path) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+    let mut respelled = false;
+    for component in path.components() {
+        let exact = resolved.join(component);
+        if let std::path::Component::Normal(name) = component
+            && exact.symlink_metadata().is_err()
+            && let Some(name) = name.to_str()
+            && let Some(found) = normalization_variants(name)
+                .into_iter()
+                .map(|variant| resolved.join(variant))
+                .find(|candidate| candidate.symlink_metadata().is_ok())
+        {
+            resolved = found;
+            respelled = true;
+        } else {
+            resolved = exact;
+        }
+    }
+    respelled.then_some(resolved)
+}
+
+/// `path` relative to `root`. On macOS, where the filesystem stores names
+/// decomposed, NFC and NFD spellings of a component compare equal.
+#[cfg(target_os = "macos")]
+fn strip_root(path: &Path, root: &Path) -> Option<PathBuf> {
+    strip_prefix_normalized(path, root)
+}
+
+/// `path` relative to `root`, compared component by component.
+#[cfg(not(target_os = "macos"))]
+fn strip_root(path: &Path, root: &Path) -> Option<PathBuf> {
+    path.strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+/// Canonicalizes `path`, or its parent plus the file name when `path` does not
+/// exist yet.
+fn canonicalize_lenient(path: &Path) -> Result<PathBuf, FsError> {
+    match path.canonicalize() {
+        Ok(p) => Ok(p),
+        Err(_) => {
+            // Path doesn't exist yet — canonicalize parent + append filename
+            let parent = path.parent().ok_or_else(|| FsError::PathDenied {
+                path: path.display().to_string(),
+            })?;
+            let file_name = path.file_name().ok_or_else(|| FsError::PathDenied {
+                path: path.display().to_string(),
+            })?;
+            let canonical_parent = parent.canonicalize().map_err(|_| FsError::NotFound {
+                path: parent.display().to_string(),
+            })?;
+            Ok(canonical_parent.join(file_name))
+        }
+    }
+}
+
+/// Retries a lookup that failed with `err` using the NFC or NFD spelling of
+/// each missing component. Returns the canonical path and the respelled input.
+#[cfg(target_os = "macos")]
+fn canonicalize_normalized(path: &Path, err: FsError) -> Result<(PathBuf, PathBuf), FsError> {
+    if let Some(resolved) = resolve_normalized(path)
+        && let Ok(canonical) = canonicalize_lenient(&resolved)
+    {
+        return Ok((canonical, resolved));
+    }
+    let has_variants = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .any(|name| !normalization_variants(name).is_empty());
+    match err {
+        FsError::NotFound { path } if has_variants => Err(FsError::NotFound {
+            path: format!("{path} (no Unicode NFC or NFD spelling of it exists either)"),
+        }),
+        other => Err(other),
+    }
+}
+
+/// Outside macOS, names are compared byte for byte and a failed lookup stands.
+#[cfg(not(target_os = "macos"))]
+fn canonicalize_normalized(_path: &Path, err: FsError) -> Result<(PathBuf, PathBuf), FsError> {
+    Err(err)
+}
 
+#[derive(Clone)]
 pub struct SecurityContext {
-    allowed_dirs: Vec<PathBuf>,
+    /// Shared by every clone, so a change of roots at runtime (client roots
+    /// with `--accept-client-roots`) applies to work already in flight
+    allowed_dirs: Arc<RwLock<Vec<PathBuf>>>,
+    deny_set: GlobSet,
+    /// The patterns `deny_set` was built from, in the same order
+    deny_patterns: Arc<[String]>,
+    /// Directories write tools may not change, even with `--allow-write`
+    read_only_dirs: Arc<[PathBuf]>,
 }
 
 impl SecurityContext {
     /// Creates a new SecurityContext. All directories must already be canonicalized.
     pub fn new(allowed_dirs: Vec<PathBuf>) -> Self {
-        Self { allowed_dirs }
+        Self {
+            allowed_dirs: Arc::new(RwLock::new(allowed_dirs)),
+            deny_set: GlobSet::empty(),
+            deny_patterns: Arc::from([]),
+            read_only_dirs: Arc::from([]),
+        }
+    }
+
+    /// Creates a SecurityContext that additionally rejects paths matching any
+    /// of `patterns`. Fails on the first pattern that is not a valid glob.
+    pub fn with_deny_patterns(
+        allowed_dirs: Vec<PathBuf>,
+        patterns: &[String],
+    ) -> Result<Self, FsError> {
+        Ok(Self {
+            allowed_dirs: Arc::new(RwLock::new(allowed_dirs)),
+            deny_set: build_deny_set(patterns)?,
+            deny_patterns: Arc::from(patterns),
+            read_only_dirs: Arc::from([]),
+        })
+    }
+
+    /// Makes `dirs` and everything below them read-only for write tools.
+    /// All directories must already be canonicalized.
+    pub fn with_read_only_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.read_only_dirs = Arc::from(dirs);
+        self
+    }
+
+    /// The allowed directories as of now.
+    pub fn allowed_dirs(&self) -> Vec<PathBuf> {
+        self.allowed_dirs
+            .read()
+            .expect("allowed directories lock poisoned")
+            .clone()
+    }
+
+    /// Replaces the allowed directories for this context and all its clones.
+    /// All directories must already be canonicalized.
+    pub fn set_allowed_dirs(&self, dirs: Vec<PathBuf>) {
+        *self
+            .allowed_dirs
+            .write()
+            .expect("allowed directories lock poisoned") = dirs;
+    }
+
+    /// Returns the allowed root containing `canonical` and the path relative to it.
+    /// Comparison ignores Windows `\\?\` prefixes on either side.
+    pub(crate) fn relative_to_allowed(&self, canonical: &Path) -> Option<PathBuf> {
+        self.split_allowed(canonical).map(|(_, relative)| relative)
+    }
+
+    /// Like [`Self::relative_to_allowed`], but also returns the matching root.
+    /// On macOS the comparison is insensitive to Unicode normalization.
+    pub(crate) fn split_allowed(&self, canonical: &Path) -> Option<(PathBuf, PathBuf)> {
+        let candidate = strip_verbatim_prefix(canonical);
+        let dirs = self
+            .allowed_dirs
+            .read()
+            .expect("allowed directories lock poisoned");
+        dirs.iter().find_map(|dir| {
+            strip_root(&candidate, &strip_verbatim_prefix(dir))
+                .map(|relative| (dir.clone(), relative))
+        })
+    }
+
+    /// Returns true if `canonical` is one of the allowed roots itself.
+    pub fn is_allowed_root(&self, canonical: &Path) -> bool {
+        let candidate = strip_verbatim_prefix(canonical);
+        let dirs = self
+            .allowed_dirs
+            .read()
+            .expect("allowed directories lock poisoned");
+        dirs.iter().any(|dir| {
+            strip_root(&candidate, &strip_verbatim_prefix(dir))
+                .is_some_and(|relative| relative.as_os_str().is_empty())
+        })
+    }
+
+    /// Returns true if `canonical` is a read-only directory or lies below one.
+    pub(crate) fn is_read_only(&self, canonical: &Path) -> bool {
+        let candidate = strip_verbatim_prefix(canonical);
+        self.read_only_dirs
+            .iter()
+            .any(|dir| strip_root(&candidate, &strip_verbatim_prefix(dir)).is_some())
+    }
+
+    /// Fails with [`FsError::ReadOnly`] if a write to `canonical` would change
+    /// a read-only directory: when it is in one, or, since removing or moving
+    /// a directory takes its contents along, when one lies below it.
+    pub fn ensure_writable(&self, canonical: &Path, path: &Path) -> Result<(), FsError> {
+        let candidate = strip_verbatim_prefix(canonical);
+        let contains_read_only = self
+            .read_only_dirs
+            .iter()
+            .any(|dir| strip_root(&strip_verbatim_prefix(dir), &candidate).is_some());
+        if self.is_read_only(canonical) || contains_read_only {
+            return Err(FsError::ReadOnly {
+                path: path.display().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::validate_path`], for a path a tool is about to create,
+    /// change or remove: also refused when [`Self::ensure_writable`] fails.
+    pub fn validate_writable_path(&self, path: &Path) -> Result<PathBuf, FsError> {
+        let canonical = self.validate_path(path)?;
+        self.ensure_writable(&canonical, path)?;
+        Ok(canonical)
+    }
+
+    /// Checks a canonical path against the deny set.
+    ///
+    /// Patterns are matched against every trailing run of components below the
+    /// allowed root, so `*.pem` and `.ssh/**` apply at any depth.
+    pub(crate) fn is_denied(&self, canonical: &Path) -> bool {
+        self.denied_by(canonical).is_some()
+    }
+
+    /// The first deny pattern matching `canonical`, if any.
+    pub(crate) fn denied_by(&self, canonical: &Path) -> Option<&str> {
+        if self.deny_set.is_empty() {
+            return None;
+        }
+        let relative = self.relative_to_allowed(canonical)?;
+        let components: Vec<_> = relative.components().collect();
+        (0..components.len()).find_map(|start| {
+            let suffix: PathBuf = components[start..].iter().collect();
+            let index = self.deny_set.matches(&suffix).into_iter().min()?;
+            Some(self.deny_patterns[index].as_str())
+        })
     }
 
     /// Canonicalizes the input path and checks it falls within an allowed directory.
     /// Works for both existing and not-yet-existing paths (canonicalizes parent for new files).
+    /// `{hex:…}` components are decoded first, so names that are not valid UTF-8
+    /// can be reached. On macOS a failed lookup is retried with the NFC or NFD
+    /// spelling of each missing component.
     pub fn validate_path(&self, path: &Path) -> Result<PathBuf, FsError> {
-        let canonical = match path.canonicalize() {
-            Ok(p) => p,
-            Err(_) => {
-                // Path doesn't exist yet — canonicalize parent + append filename
-                let parent = path.parent().ok_or_else(|| FsError::PathDenied {
-                    path: path.display().to_string(),
-                })?;
-                let file_name = path.file_name().ok_or_else(|| FsError::PathDenied {
-                    path: path.display().to_string(),
-                })?;
-                let canonical_parent = parent.canonicalize().map_err(|_| FsError::NotFound {
-                    path: parent.display().to_string(),
-                })?;
-                canonical_parent.join(file_name)
+        let path = &decode_hex_components(path);
+        let (canonical, respelled) = match canonicalize_lenient(path) {
+            Ok(p) => (p, None),
+            Err(e) => {
+                let (canonical, resolved) = canonicalize_normalized(path, e)?;
+                (canonical, Some(resolved))
             }
         };
 
-        if self
-            .allowed_dirs
-            .iter()
-            .any(|dir| canonical.starts_with(dir))
-        {
+        let allowed = self.relative_to_allowed(&canonical).is_some();
+        if allowed && let Some(pattern) = self.denied_by(&canonical) {
+            return Err(FsError::DeniedByPattern {
+                path: path.display().to_string(),
+                pattern: pattern.to_string(),
+            });
+        }
+        if allowed {
             Ok(canonical)
         } else {
-            Err(FsError::PathDenied {
-                path: path.display().to_string(),
-            })
+            let path = match respelled {
+                Some(resolved) => format!(
+                    "{} (matched {} by Unicode normalization)",
+                    path.display(),
+                    resolved.display()
+                ),
+                None => path.display().to_string(),
+            };
+            Err(FsError::PathDenied { path })
         }
     }
 
@@ -74,6 +419,7 @@ impl SecurityContext {
     /// it, validates it's within allowed directories, and rejects `.` or `..` in
     /// the non-existent tail segments.
     pub fn validate_creatable_path(&self, path: &Path) -> Result<PathBuf, FsError> {
+        let path = &decode_hex_components(path);
         // Reject . or .. in any component up-front (before OS normalizes them away)
         for component in path.components() {
             match component {
@@ -116,11 +462,7 @@ impl SecurityContext {
         })?;
 
         // Validate the existing ancestor is within allowed dirs
-        if !self
-            .allowed_dirs
-            .iter()
-            .any(|dir| canonical_base.starts_with(dir))
-        {
+        if self.relative_to_allowed(&canonical_base).is_none() {
             return Err(FsError::PathDenied {
                 path: path.display().to_string(),
             });
@@ -132,6 +474,13 @@ impl SecurityContext {
             result = result.join(seg);
         }
 
+        if let Some(pattern) = self.denied_by(&result) {
+            return Err(FsError::DeniedByPattern {
+                path: path.display().to_string(),
+                pattern: pattern.to_string(),
+            });
+        }
+
         Ok(result)
     }
 
@@ -169,6 +518,35 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn is_allowed_root_matches_only_roots() {
+        let (dir, ctx) = setup();
+        let canon = dir.path().canonicalize().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        assert!(ctx.is_allowed_root(&canon));
+        assert!(!ctx.is_allowed_root(&canon.join("sub")));
+    }
+
+    #[test]
+    fn set_allowed_dirs_applies_to_clones() {
+        let (dir, ctx) = setup();
+        let other = TempDir::new().unwrap();
+        let other_canon = other.path().canonicalize().unwrap();
+        fs::write(other.path().join("a.txt"), "a").unwrap();
+        let clone = ctx.clone();
+        assert!(clone.validate_path(&other.path().join("a.txt")).is_err());
+
+        ctx.set_allowed_dirs(vec![
+            dir.path().canonicalize().unwrap(),
+            other_canon.clone(),
+        ]);
+        assert_eq!(
+            clone.validate_path(&other.path().join("a.txt")).unwrap(),
+            other_canon.join("a.txt")
+        );
+        assert_eq!(clone.allowed_dirs().len(), 2);
+    }
+
     #[test]
     fn denies_path_outside_allowed_dir() {
         let (_dir, ctx) = setup();
@@ -322,6 +700,258 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn setup_with_deny(patterns: &[&str]) -> (TempDir, SecurityContext) {
+        let dir = TempDir::new().unwrap();
+        let canonical = dir.path().canonicalize().unwrap();
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        let ctx = SecurityContext::with_deny_patterns(vec![canonical], &patterns).unwrap();
+        (dir, ctx)
+    }
+
+    #[test]
+    fn deny_pattern_blocks_matching_file_at_any_depth() {
+        let (dir, ctx) = setup_with_deny(&["*.pem", ".env*"]);
+        let sub = dir.path().join("certs");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("server.pem"), "key").unwrap();
+        fs::write(dir.path().join(".env.local"), "SECRET=1").unwrap();
+        fs::write(dir.path().join("readme.txt"), "hi").unwrap();
+
+        assert!(matches!(
+            ctx.validate_path(&sub.join("server.pem")),
+            Err(FsError::DeniedByPattern { pattern, .. }) if pattern == "*.pem"
+        ));
+        assert!(matches!(
+            ctx.validate_path(&dir.path().join(".env.local")),
+            Err(FsError::DeniedByPattern { pattern, .. }) if pattern == ".env*"
+        ));
+        assert!(ctx.validate_path(&dir.path().join("readme.txt")).is_ok());
+    }
+
+    #[test]
+    fn deny_pattern_blocks_directory_contents() {
+        let (dir, ctx) = setup_with_deny(&[".ssh/**"]);
+        let ssh = dir.path().join("home").join(".ssh");
+        fs::create_dir_all(&ssh).unwrap();
+        fs::write(ssh.join("config"), "Host *").unwrap();
+
+        assert!(matches!(
+            ctx.validate_path(&ssh.join("config")),
+            Err(FsError::DeniedByPattern { .. })
+        ));
+        assert!(matches!(
+            ctx.validate_creatable_path(&ssh.join("new").join("dir")),
+            Err(FsError::DeniedByPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn deny_pattern_blocks_new_files() {
+        let (dir, ctx) = setup_with_deny(&["*.key"]);
+        let result = ctx.validate_path(&dir.path().join("new.key"));
+        assert!(matches!(result, Err(FsError::DeniedByPattern { .. })));
+    }
+
+    #[test]
+    fn read_only_dirs_refuse_writes_inside_and_above() {
+        let dir = TempDir::new().unwrap();
+        let canonical = dir.path().canonicalize().unwrap();
+        let docs = canonical.join("docs");
+        fs::create_dir_all(docs.join("api")).unwrap();
+        fs::create_dir(canonical.join("src")).unwrap();
+        let ctx =
+            SecurityContext::new(vec![canonical.clone()]).with_read_only_dirs(vec![docs.clone()]);
+
+        assert!(matches!(
+            ctx.validate_writable_path(&docs.join("api").join("new.md")),
+            Err(FsError::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            ctx.validate_writable_path(&docs),
+            Err(FsError::ReadOnly { .. })
+        ));
+        // Removing the parent would remove the read-only directory with it
+        assert!(matches!(
+            ctx.ensure_writable(&canonical, &canonical),
+            Err(FsError::ReadOnly { .. })
+        ));
+        assert!(
+            ctx.validate_writable_path(&canonical.join("src").join("main.rs"))
+                .is_ok()
+        );
+        // A sibling sharing the name as a prefix is not affected
+        assert!(
+            ctx.validate_writable_path(&canonical.join("docs-old"))
+                .is_ok()
+        );
+        // Reads are unaffected
+        assert!(ctx.validate_path(&docs.join("readme.md")).is_ok());
+    }
+
+    #[test]
+    fn deny_error_names_the_first_matching_pattern() {
+        let (dir, ctx) = setup_with_deny(&["secrets/**", "*.key"]);
+        fs::create_dir(dir.path().join("secrets")).unwrap();
+        let err = ctx
+            .validate_path(&dir.path().join("secrets").join("api.key"))
+            .unwrap_err();
+        assert!(
+            err.to_string().starts_with("Access denied: ")
+                && err
+                    .to_string()
+                    .ends_with("matched deny pattern 'secrets/**'"),
+            "{err}"
+        );
+        assert_eq!(err.details()["pattern"], "secrets/**");
+        // Outside every allowed directory there is no pattern to name
+        assert!(matches!(
+            ctx.validate_path(Path::new("/etc/ssl/private.key")),
+            Err(FsError::PathDenied { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn decodes_hex_components_that_are_not_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let decoded = decode_hex_components(Path::new("/data/{hex:ff61}/{hex:61}/{hex:f}/x"));
+        let expected = Path::new("/data")
+            .join(std::ffi::OsStr::from_bytes(b"\xffa"))
+            .join("{hex:61}/{hex:f}/x");
+        assert_eq!(decoded, expected);
+
+        let (dir, ctx) = setup();
+        let name = std::ffi::OsStr::from_bytes(b"r\xe9sum\xe9.txt");
+        if fs::write(dir.path().join(name), "cv").is_err() {
+            // The filesystem only accepts UTF-8 names
+            return;
+        }
+        let escaped = dir.path().join("{hex:72e973756de92e747874}");
+        let canonical = ctx.validate_file(&escaped).unwrap();
+        assert_eq!(canonical.file_name(), Some(name));
+        assert_eq!(fs::read_to_string(canonical).unwrap(), "cv");
+    }
+
+    #[test]
+    fn normalization_variants_cover_both_forms() {
+        let nfc = "caf\u{e9}";
+        let nfd = "cafe\u{301}";
+        assert_eq!(normalization_variants(nfc), vec![nfd.to_string()]);
+        assert_eq!(normalization_variants(nfd), vec![nfc.to_string()]);
+        assert!(normalization_variants("plain.txt").is_empty());
+    }
+
+    #[test]
+    fn normalized_matcher_ignores_form_but_not_content() {
+        let nfc = std::ffi::OsStr::new("r\u{e9}sum\u{e9}");
+        let nfd = std::ffi::OsStr::new("re\u{301}sume\u{301}");
+        assert!(names_match_normalized(nfc, nfd));
+        assert!(!names_match_normalized(nfc, std::ffi::OsStr::new("resume")));
+
+        let root = Path::new("/data/Caf\u{e9}");
+        let relative = strip_prefix_normalized(Path::new("/data/Cafe\u{301}/menu.txt"), root);
+        assert_eq!(relative, Some(PathBuf::from("menu.txt")));
+        assert_eq!(
+            strip_prefix_normalized(Path::new("/data/Cafe/menu.txt"), root),
+            None
+        );
+    }
+
+    // macOS folds the two forms during lookup itself, so the exact spelling
+    // never goes missing there.
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn resolve_normalized_respells_missing_components() {
+        let dir = TempDir::new().unwrap();
+        let decomposed = dir.path().join("Cafe\u{301}");
+        fs::create_dir(&decomposed).unwrap();
+        fs::write(decomposed.join("menu.txt"), "soup").unwrap();
+
+        let typed = dir.path().join("Caf\u{e9}").join("menu.txt");
+        assert_eq!(
+            resolve_normalized(&typed),
+            Some(decomposed.join("menu.txt"))
+        );
+        assert_eq!(resolve_normalized(&decomposed.join("menu.txt")), None);
+        assert_eq!(resolve_normalized(&dir.path().join("missing")), None);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn accepts_either_normalization_form_on_macos() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("Cafe\u{301}");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("Men\u{fc}.txt"), "soup").unwrap();
+        let ctx = SecurityContext::new(vec![root.canonicalize().unwrap()]);
+
+        let composed = root.with_file_name("Caf\u{e9}");
+        let canonical = ctx
+            .validate_file(&composed.join("Menu\u{308}.txt"))
+            .unwrap();
+        assert_eq!(fs::read_to_string(&canonical).unwrap(), "soup");
+        assert!(ctx.is_allowed_root(&composed));
+        assert!(ctx.validate_path(&composed.join("new.txt")).is_ok());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn missing_path_error_mentions_normalization_on_macos() {
+        let (dir, ctx) = setup();
+        let err = ctx
+            .validate_path(&dir.path().join("Caf\u{e9}").join("x.txt"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unicode NFC or NFD"), "{err}");
+    }
+
+    #[test]
+    fn build_deny_set_rejects_invalid_glob() {
+        let result = build_deny_set(&["[bad".to_string()]);
+        assert!(matches!(result, Err(FsError::PatternError(_))));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn strips_extended_length_prefix() {
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\C:\temp")),
+            PathBuf::from(r"C:\temp")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\UNC\server\share\file")),
+            PathBuf::from(r"\\server\share\file")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"C:\temp")),
+            PathBuf::from(r"C:\temp")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn extended_prefix_path_matches_plain_allowed_dir() {
+        let ctx = SecurityContext::new(vec![PathBuf::from(r"C:\temp")]);
+        assert!(
+            ctx.relative_to_allowed(Path::new(r"\\?\C:\temp\file.txt"))
+                .is_some()
+        );
+        assert!(ctx.relative_to_allowed(Path::new(r"\\?\C:\temp")).is_some());
+        assert!(
+            ctx.relative_to_allowed(Path::new(r"\\?\C:\other\file.txt"))
+                .is_none()
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn plain_path_matches_extended_prefix_allowed_dir() {
+        let ctx = SecurityContext::new(vec![PathBuf::from(r"\\?\C:\temp")]);
+        assert!(
+            ctx.relative_to_allowed(Path::new(r"C:\temp\file.txt"))
+                .is_some()
+        );
+    }
+
     #[test]
     fn trailing_slash_normalized() {
         let (dir, ctx) = setup();
@@ -1,14 +1,430 @@
 use crate::FilesystemService;
-use rmcp::ServerHandler;
-use rmcp::model::{Implementation, ProtocolVersion, ServerCapabilities, ServerInfo};
+use crate::error::FsError;
+use crate::roots::refresh_client_roots;
+use crate::stats;
+use crate::tools::util::{format_size, truncate_output};
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, Implementation, InitializeRequestParams,
+    InitializeResult, ListToolsResult, LoggingLevel, PaginatedRequestParams, ProtocolVersion,
+    RawContent, ServerCapabilities, ServerInfo, SetLevelRequestParams, Tool,
+};
+use rmcp::service::{NotificationContext, RequestContext};
+use rmcp::{ErrorData, RoleServer, ServerHandler};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+
+/// Newest protocol revision the server implements. Structured tool output
+/// needs 2025-06-18; older clients are served a compatible subset.
+pub const LATEST_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V_2025_06_18;
+
+/// Picks the protocol version for a session: the client's if it is not newer
+/// than ours, else ours. rmcp applies the same rule to the initialize response.
+pub(crate) fn negotiate_protocol_version(requested: &ProtocolVersion) -> ProtocolVersion {
+    if *requested < LATEST_PROTOCOL_VERSION {
+        requested.clone()
+    } else {
+        LATEST_PROTOCOL_VERSION
+    }
+}
+
+/// Removes tool definition fields that `version` does not know about:
+/// output schemas before 2025-06-18 and annotations before 2025-03-26.
+fn tool_for_version(mut tool: Tool, version: &ProtocolVersion) -> Tool {
+    if *version < ProtocolVersion::V_2025_06_18 {
+        tool.output_schema = None;
+        tool.title = None;
+    }
+    if *version < ProtocolVersion::V_2025_03_26 {
+        tool.annotations = None;
+    }
+    tool
+}
+
+/// Source of the `id` on each tool call's tracing span, unique per process.
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How a tool call ended, for its closing log event: `ok`, `tool_error` for a
+/// result flagged as an error, or `error` for a protocol-level failure.
+fn call_outcome(result: &Result<CallToolResult, ErrorData>) -> &'static str {
+    match result {
+        Ok(result) if result.is_error == Some(true) => "tool_error",
+        Ok(_) => "ok",
+        Err(_) => "error",
+    }
+}
+
+/// Allowed directories named in the instructions before the rest are summarized.
+const INSTRUCTION_DIR_LIMIT: usize = 5;
+
+/// A single poll of a tool call longer than this holds up other calls on the
+/// same runtime thread and is logged.
+const SLOW_POLL_THRESHOLD: Duration = Duration::from_millis(250);
+
+/// Drives `fut`, calling `on_slow` with the duration of any single poll that
+/// exceeds [`SLOW_POLL_THRESHOLD`], i.e. blocking work done on the runtime.
+pub(crate) async fn watch_slow_polls<F: Future>(fut: F, on_slow: impl Fn(Duration)) -> F::Output {
+    let mut fut = std::pin::pin!(fut);
+    std::future::poll_fn(|cx| {
+        let started = Instant::now();
+        let poll = fut.as_mut().poll(cx);
+        let busy = started.elapsed();
+        if busy > SLOW_POLL_THRESHOLD {
+            on_slow(busy);
+        }
+        poll
+    })
+    .await
+}
+
+/// Suggests how to retrieve the rest of a truncated response from `tool`.
+fn truncation_hint(tool: &str) -> &'static str {
+    match tool {
+        "read_file" => "use offset/limit to read the rest",
+        "read_multiple_files" => "read files individually with read_file offset/limit",
+        "list_directory" => "use offset/limit to page through the rest",
+        "directory_tree" | "search_files" => "narrow the path, pattern, depth, or extensions",
+        "edit_file" => "the edit was applied; use read_file to review the result",
+        _ => "use offset/limit or narrower queries",
+    }
+}
+
+/// Applies the `--max-output-bytes` cap to every text block of a tool result.
+/// Returns true if anything was cut.
+pub(crate) fn cap_tool_output(result: &mut CallToolResult, tool: &str, max_bytes: usize) -> bool {
+    let mut truncated = false;
+    for content in &mut result.content {
+        if let RawContent::Text(text) = &mut content.raw {
+            truncated |= truncate_output(&mut text.text, max_bytes, truncation_hint(tool));
+        }
+    }
+    truncated
+}
+
+/// For a listing tool called with `format: "json"`, the array of its result
+/// that [`cap_json_listing`] shortens instead of cutting the text. `None` for
+/// every other call, including tools whose own `format` argument means
+/// something else, such as validate_syntax.
+fn json_listing_key(tool: &str, arguments: &rmcp::model::JsonObject) -> Option<&'static str> {
+    if arguments.get("format").and_then(|f| f.as_str()) != Some("json") {
+        return None;
+    }
+    match tool {
+        "list_directory" | "directory_tree" => Some("entries"),
+        "search_files" => Some("files"),
+        _ => None,
+    }
+}
+
+/// Applies the `--max-output-bytes` cap to a JSON listing by dropping entries
+/// from the end of its `key` array and setting `truncated`, so the text block
+/// still parses. Returns true if anything was dropped.
+pub(crate) fn cap_json_listing(result: &mut CallToolResult, key: &str, max_bytes: usize) -> bool {
+    let Some(text) = result.content.iter_mut().find_map(|c| match &mut c.raw {
+        RawContent::Text(text) => Some(text),
+        _ => None,
+    }) else {
+        return false;
+    };
+    if text.text.len() <= max_bytes {
+        return false;
+    }
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&text.text) else {
+        return false;
+    };
+    let Some(entries) = value.get_mut(key).and_then(|v| v.as_array_mut()) else {
+        return false;
+    };
+    // Estimate from each entry's size first, then confirm on the real output
+    let mut len = text.text.len();
+    while len > max_bytes {
+        let Some(entry) = entries.pop() else { break };
+        len = len.saturating_sub(entry.to_string().len() + 1);
+    }
+    value["truncated"] = serde_json::Value::Bool(true);
+    let mut json = value.to_string();
+    while json.len() > max_bytes {
+        let popped = value
+            .get_mut(key)
+            .and_then(|v| v.as_array_mut())
+            .and_then(|entries| entries.pop());
+        if popped.is_none() {
+            break;
+        }
+        json = value.to_string();
+    }
+    text.text = json;
+    if let Some(structured) = &mut result.structured_content {
+        *structured = value;
+    }
+    true
+}
+
+/// Picks the path a tool call operates on from its arguments, for the history.
+fn operation_path(arguments: &rmcp::model::JsonObject) -> Option<String> {
+    ["path", "source", "input_path"]
+        .iter()
+        .find_map(|key| arguments.get(*key)?.as_str().map(str::to_string))
+        .or_else(|| {
+            let paths: Vec<&str> = arguments
+                .get("paths")?
+                .as_array()?
+                .iter()
+                .filter_map(|p| p.as_str())
+                .collect();
+            (!paths.is_empty()).then(|| paths.join(", "))
+        })
+}
+
+impl FilesystemService {
+    /// Protocol version of the current session; the latest before initialization.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+            .get()
+            .cloned()
+            .unwrap_or(LATEST_PROTOCOL_VERSION)
+    }
+
+    /// Settles the session's protocol version from the client's request.
+    /// Only the first negotiation counts.
+    pub(crate) fn negotiate(&self, requested: &ProtocolVersion) -> ProtocolVersion {
+        self.protocol_version
+            .get_or_init(|| negotiate_protocol_version(requested))
+            .clone()
+    }
+
+    /// Emits the closing event of a tool call, at WARN when it took at least
+    /// `--log-slow-ops-ms`.
+    fn log_call_finished(&self, result: &Result<CallToolResult, ErrorData>, elapsed: Duration) {
+        let outcome = call_outcome(result);
+        let duration_ms = elapsed.as_millis() as u64;
+        let slow = self
+            .config
+            .log_slow_ops_ms
+            .is_some_and(|threshold| duration_ms >= threshold);
+        if slow {
+            tracing::warn!(duration_ms, outcome, "slow tool call finished");
+        } else {
+            tracing::info!(duration_ms, outcome, "tool call finished");
+        }
+    }
+
+    /// Builds the `instructions` string sent at initialization: the allowed
+    /// directories, which tool tiers are enabled, and the active limits.
+    pub fn instructions(&self) -> String {
+        let dirs = &self.config.allowed_directories;
+        let shown: Vec<String> = dirs
+            .iter()
+            .take(INSTRUCTION_DIR_LIMIT)
+            .map(|d| d.display().to_string())
+            .collect();
+        let mut parts = vec![
+            "Secure filesystem access server.".to_string(),
+            if dirs.len() > INSTRUCTION_DIR_LIMIT {
+                format!(
+                    "Allowed directories ({}): {}, and {} more (see list_allowed_directories).",
+                    dirs.len(),
+                    shown.join(", "),
+                    dirs.len() - INSTRUCTION_DIR_LIMIT
+                )
+            } else {
+                format!("Allowed directories: {}.", shown.join(", "))
+            },
+        ];
+        if self.config.allow_write {
+            parts.push("Write operations: enabled.".to_string());
+        } else {
+            parts.push("Read-only mode: no tools modify files.".to_string());
+        }
+        if self.config.allow_destructive {
+            parts.push("Destructive operations: enabled.".to_string());
+        }
+        parts.push(format!(
+            "Max file read size: {}.",
+            format_size(self.config.max_read_size as u64)
+        ));
+        parts.push(format!("Max traversal depth: {}.", self.config.max_depth));
+        parts.push(
+            "For large files, call read_file with offset and limit to page through lines."
+                .to_string(),
+        );
+        parts.push(
+            "Before directory_tree or search_files on a directory that may be large, call count_entries to gauge its size."
+                .to_string(),
+        );
+        parts.push(
+            "Names that are not valid UTF-8 are listed with \"(non-UTF-8 name, hex: ...)\"; to pass one to a tool, write that path component as {hex:...} with the same bytes."
+                .to_string(),
+        );
+        parts.join(" ")
+    }
+}
 
-#[rmcp::tool_handler]
 impl ServerHandler for FilesystemService {
+    /// Dispatches a tool call once a concurrency slot is available, counts it in
+    /// the usage stats, records it in the operation history, and caps the size
+    /// of its output, shortening JSON listings by entries. A call cancelled by
+    /// the client returns a cancellation error right away.
+    ///
+    /// Everything the call logs, including its blocking sections, falls under a
+    /// `tool_call` span with a per-process id, the tool name and its path
+    /// argument; the span ends with an event giving the duration and outcome.
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.clone();
+        let path = request.arguments.as_ref().and_then(operation_path);
+        let json_listing = request
+            .arguments
+            .as_ref()
+            .and_then(|args| json_listing_key(&tool, args));
+        let span = tracing::info_span!(
+            "tool_call",
+            id = NEXT_CALL_ID.fetch_add(1, Ordering::Relaxed),
+            tool = %tool,
+            path = path.as_deref(),
+            bytes_read = Empty,
+            bytes_written = Empty,
+        );
+        let call = async {
+            let started = Instant::now();
+            tracing::debug!("tool call started");
+            self.client_log.attach(&context.peer);
+            let ct = context.ct.clone();
+            let succeeded = |result: &Result<CallToolResult, ErrorData>| {
+                result.as_ref().is_ok_and(|r| r.is_error != Some(true))
+            };
+            let result = self
+                .op_limiter
+                .run(async {
+                    let tcc = ToolCallContext::new(self, request, context);
+                    let call = async {
+                        // Blocking walks notice the token on their own; this returns at once
+                        let result = tokio::select! {
+                            result = self.tool_router.call(tcc) => result,
+                            () = ct.cancelled() => Err(FsError::Cancelled.into()),
+                        };
+                        let (read, written) = stats::call_io();
+                        let span = tracing::Span::current();
+                        span.record("bytes_read", read);
+                        span.record("bytes_written", written);
+                        result
+                    };
+                    let call = watch_slow_polls(call, |busy| {
+                        tracing::warn!(
+                            "{tool} held a runtime thread for {} ms without yielding",
+                            busy.as_millis()
+                        );
+                    });
+                    self.stats.instrument(&tool, call, succeeded).await
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.into()));
+            let success = succeeded(&result);
+            let elapsed = started.elapsed();
+            self.log_call_finished(&result, elapsed);
+            self.record_operation(&tool, path, success, elapsed).await;
+            let mut result = result?;
+            // Clients before 2025-06-18 only read the content blocks
+            if self.protocol_version() < ProtocolVersion::V_2025_06_18 {
+                result.structured_content = None;
+            }
+            let max_bytes = self.config.max_output_bytes;
+            let capped = match json_listing {
+                Some(key) => cap_json_listing(&mut result, key, max_bytes),
+                None => cap_tool_output(&mut result, &tool, max_bytes),
+            };
+            if capped {
+                self.client_log.log(
+                    LoggingLevel::Warning,
+                    "output",
+                    &format!(
+                        "{tool} output truncated at {} (--max-output-bytes)",
+                        format_size(self.config.max_output_bytes as u64)
+                    ),
+                );
+            }
+            Ok(result)
+        };
+        // Called through the trait: ToolStats has its own `instrument`
+        tracing::Instrument::instrument(call, span).await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let version = self.protocol_version();
+        Ok(ListToolsResult {
+            tools: self
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|tool| tool_for_version(tool, &version))
+                .collect(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.client_log.set_level(request.level);
+        Ok(())
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Tool> {
+        let tool = self.tool_router.get(name).cloned()?;
+        Some(tool_for_version(tool, &self.protocol_version()))
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        self.negotiate(&request.protocol_version);
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
+
+    /// Picks up the client's roots once the session is up, when
+    /// `--accept-client-roots` is set.
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        tracing::info!("client initialized");
+        self.client_log.attach(&context.peer);
+        if self.config.accept_client_roots {
+            // rmcp handles this notification before it starts reading
+            // messages, so the roots/list response can only arrive once
+            // this handler has returned
+            let (config, security) = (self.config.clone(), self.security.clone());
+            tokio::spawn(async move {
+                refresh_client_roots(&config, &security, &context.peer).await;
+            });
+        }
+    }
+
+    /// Recomputes the allowed directories from the client's new roots.
+    async fn on_roots_list_changed(&self, context: NotificationContext<RoleServer>) {
+        refresh_client_roots(&self.config, &self.security, &context.peer).await;
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            protocol_version: ProtocolVersion::V_2024_11_05,
+            protocol_version: self.protocol_version(),
             capabilities: ServerCapabilities {
                 tools: Some(Default::default()),
+                logging: Some(Default::default()),
                 ..Default::default()
             },
             server_info: Implementation {
@@ -16,28 +432,35 @@ impl ServerHandler for FilesystemService {
                 version: env!("CARGO_PKG_VERSION").to_string(),
                 ..Default::default()
             },
-            instructions: Some(
-                "Secure filesystem access server. Use list_allowed_directories to see available paths.".to_string(),
-            ),
+            instructions: Some(self.instructions()),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::FilesystemService;
     use rmcp::ServerHandler;
     use tempfile::TempDir;
 
     fn make_service() -> (TempDir, FilesystemService) {
+        make_service_with(false, false)
+    }
+
+    fn make_service_with(
+        allow_write: bool,
+        allow_destructive: bool,
+    ) -> (TempDir, FilesystemService) {
         let dir = TempDir::new().unwrap();
         let canon = dir.path().canonicalize().unwrap();
         let config = crate::Config {
             allowed_directories: vec![canon],
-            allow_write: false,
-            allow_destructive: false,
+            allow_write,
+            allow_destructive,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         (dir, FilesystemService::new(config))
     }
@@ -58,10 +481,381 @@ mod tests {
         assert!(info.capabilities.prompts.is_none());
     }
 
+    #[test]
+    fn server_info_has_logging_capability() {
+        let (_dir, service) = make_service();
+        assert!(service.get_info().capabilities.logging.is_some());
+    }
+
+    #[test]
+    fn structured_tools_advertise_output_schemas() {
+        let (_dir, service) = make_service();
+        for name in ["search_files", "list_directory", "get_file_info"] {
+            let tool = service.get_tool(name).unwrap();
+            let schema = tool.output_schema.expect(name);
+            assert_eq!(schema["type"], "object", "{name}");
+        }
+        assert!(
+            service
+                .get_tool("read_file")
+                .unwrap()
+                .output_schema
+                .is_none()
+        );
+    }
+
+    /// Expected (read_only, destructive, idempotent) hints for every tool.
+    const ANNOTATION_MATRIX: &[(&str, bool, bool, bool)] = &[
+        ("list_allowed_directories", true, false, true),
+        ("list_directory", true, false, true),
+        ("read_file", true, false, true),
+        ("read_multiple_files", true, false, true),
+        ("read_file_as_hex", true, false, true),
+        ("read_file_base64", true, false, true),
+        ("get_file_info", true, false, true),
+        ("directory_tree", true, false, true),
+        ("search_files", true, false, true),
+        ("search_file_contents", true, false, true),
+        ("grep_file", true, false, true),
+        ("list_operation_history", true, false, true),
+        ("list_trash", true, false, true),
+        ("server_stats", true, false, true),
+        ("health_check", true, false, true),
+        ("list_archive", true, false, true),
+        ("count_entries", true, false, true),
+        ("code_stats", true, false, true),
+        ("size_by_extension", true, false, true),
+        ("count", true, false, true),
+        ("identify_file", true, false, true),
+        ("get_file_checksum", true, false, true),
+        ("image_info", true, false, true),
+        ("preview_table", true, false, true),
+        ("validate_syntax", true, false, true),
+        ("outline_markdown", true, false, true),
+        ("outline_code", true, false, true),
+        ("verify_checksums", true, false, true),
+        ("snapshot_directory", true, false, true),
+        ("compare_directories", true, false, true),
+        ("diff_files", true, false, true),
+        ("watch_changes", true, false, false),
+        ("edit_file", false, false, false),
+        ("write_file", false, true, true),
+        ("write_file_base64", false, true, true),
+        ("append_file", false, false, false),
+        ("touch_file", false, false, false),
+        ("create_directory", false, false, true),
+        ("create_hardlink", false, false, false),
+        ("copy_file", false, false, false),
+        ("copy_directory", false, false, false),
+        ("truncate_file", false, true, true),
+        ("normalize_line_endings", false, false, true),
+        ("compress_file", false, true, true),
+        ("decompress_file", false, true, true),
+        ("extract_from_archive", false, false, false),
+        ("create_archive", false, true, false),
+        ("restore_from_trash", false, false, false),
+        ("delete_file", false, true, false),
+        ("move_file", false, true, false),
+        ("delete_directory", false, true, false),
+        ("delete_directory_recursive", false, true, false),
+        ("delete_matching", false, true, false),
+    ];
+
+    #[test]
+    fn every_tool_is_fully_annotated() {
+        let dir = TempDir::new().unwrap();
+        let service = FilesystemService::new(crate::Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            allow_write: true,
+            allow_destructive: true,
+            trash_dir: Some(dir.path().join(".trash")),
+            history_size: Some(10),
+            ..Default::default()
+        });
+        let tools = service.tool_router.list_all();
+        assert_eq!(tools.len(), ANNOTATION_MATRIX.len());
+        for tool in tools {
+            let name = tool.name.as_ref();
+            let &(_, read_only, destructive, idempotent) = ANNOTATION_MATRIX
+                .iter()
+                .find(|(n, ..)| *n == name)
+                .unwrap_or_else(|| panic!("{name} missing from the annotation matrix"));
+            let ann = tool.annotations.as_ref().expect(name);
+            assert!(
+                ann.title.as_deref().is_some_and(|t| !t.is_empty()),
+                "{name}"
+            );
+            assert_eq!(ann.read_only_hint, Some(read_only), "{name}");
+            assert_eq!(ann.destructive_hint, Some(destructive), "{name}");
+            assert_eq!(ann.idempotent_hint, Some(idempotent), "{name}");
+            // Every tool works on the local, sandboxed filesystem only
+            assert_eq!(ann.open_world_hint, Some(false), "{name}");
+        }
+    }
+
+    #[test]
+    fn get_info_advertises_latest_protocol_before_negotiation() {
+        let (_dir, service) = make_service();
+        assert_eq!(service.get_info().protocol_version, LATEST_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn get_info_reports_older_client_version() {
+        let (_dir, service) = make_service();
+        let agreed = service.negotiate(&ProtocolVersion::V_2024_11_05);
+        assert_eq!(agreed, ProtocolVersion::V_2024_11_05);
+        let info = service.get_info();
+        assert_eq!(info.protocol_version, ProtocolVersion::V_2024_11_05);
+        assert!(info.capabilities.tools.is_some());
+        assert!(info.capabilities.logging.is_some());
+        // Later requests cannot change the agreed version
+        service.negotiate(&ProtocolVersion::V_2025_06_18);
+        assert_eq!(service.protocol_version(), ProtocolVersion::V_2024_11_05);
+    }
+
+    #[test]
+    fn get_info_caps_newer_client_at_latest() {
+        let (_dir, service) = make_service();
+        let future: ProtocolVersion =
+            serde_json::from_value(serde_json::json!("2099-01-01")).unwrap();
+        assert_eq!(service.negotiate(&future), LATEST_PROTOCOL_VERSION);
+        assert_eq!(service.get_info().protocol_version, LATEST_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn tool_definitions_follow_negotiated_version() {
+        let (_dir, service) = make_service();
+        let tool = service.tool_router.get("list_directory").unwrap().clone();
+
+        let current = tool_for_version(tool.clone(), &ProtocolVersion::V_2025_06_18);
+        assert!(current.output_schema.is_some());
+        assert!(current.annotations.is_some());
+
+        let march = tool_for_version(tool.clone(), &ProtocolVersion::V_2025_03_26);
+        assert!(march.output_schema.is_none());
+        assert!(march.annotations.is_some());
+
+        let original = tool_for_version(tool, &ProtocolVersion::V_2024_11_05);
+        assert!(original.output_schema.is_none());
+        assert!(original.annotations.is_none());
+        assert_eq!(original.name, "list_directory");
+
+        service.negotiate(&ProtocolVersion::V_2024_11_05);
+        assert!(
+            service
+                .get_tool("list_directory")
+                .unwrap()
+                .output_schema
+                .is_none()
+        );
+    }
+
     #[test]
     fn server_info_version_matches_cargo() {
         let (_dir, service) = make_service();
         let info = service.get_info();
         assert_eq!(info.server_info.version, env!("CARGO_PKG_VERSION"));
     }
+
+    #[test]
+    fn instructions_read_only_has_no_write_mention() {
+        let (_dir, service) = make_service();
+        let instructions = service.get_info().instructions.unwrap();
+        assert!(!instructions.is_empty());
+        assert!(!instructions.contains("Write operations"));
+        assert!(!instructions.contains("Destructive operations"));
+        assert!(instructions.contains("Max file read size: 10.0 MB"));
+        assert!(instructions.contains("Max traversal depth: 10"));
+        assert!(instructions.contains("Read-only mode"));
+        assert!(instructions.contains("offset and limit"));
+    }
+
+    #[test]
+    fn instructions_list_allowed_directories() {
+        let (dir, service) = make_service();
+        let canon = dir.path().canonicalize().unwrap();
+        let instructions = service.instructions();
+        assert!(
+            instructions.contains(&format!("Allowed directories: {}.", canon.display())),
+            "{instructions}"
+        );
+        assert!(!instructions.contains("more (see list_allowed_directories)"));
+    }
+
+    #[test]
+    fn instructions_summarize_many_allowed_directories() {
+        let dirs: Vec<TempDir> = (0..8).map(|_| TempDir::new().unwrap()).collect();
+        let service = FilesystemService::new(crate::Config {
+            allowed_directories: dirs.iter().map(|d| d.path().to_path_buf()).collect(),
+            ..Default::default()
+        });
+        let instructions = service.instructions();
+        assert!(instructions.contains("Allowed directories (8): "));
+        assert!(instructions.contains("and 3 more (see list_allowed_directories)."));
+        assert!(instructions.contains(&dirs[4].path().display().to_string()));
+        assert!(!instructions.contains(&dirs[5].path().display().to_string()));
+    }
+
+    #[test]
+    fn instructions_mention_enabled_tiers() {
+        let (_dir, service) = make_service_with(true, false);
+        let instructions = service.get_info().instructions.unwrap();
+        assert!(instructions.contains("Write operations: enabled"));
+        assert!(!instructions.contains("Destructive operations"));
+
+        let (_dir, service) = make_service_with(true, true);
+        let instructions = service.get_info().instructions.unwrap();
+        assert!(instructions.contains("Destructive operations: enabled"));
+    }
+
+    fn text_result(text: &str) -> CallToolResult {
+        CallToolResult::success(vec![rmcp::model::Content::text(text)])
+    }
+
+    fn first_text(result: &CallToolResult) -> &str {
+        match &result.content[0].raw {
+            RawContent::Text(t) => &t.text,
+            _ => panic!("expected text content"),
+        }
+    }
+
+    #[test]
+    fn call_outcome_distinguishes_tool_and_protocol_errors() {
+        assert_eq!(call_outcome(&Ok(text_result("done"))), "ok");
+        let failed = CallToolResult::error(vec![rmcp::model::Content::text("Not found")]);
+        assert_eq!(call_outcome(&Ok(failed)), "tool_error");
+        assert_eq!(call_outcome(&Err(FsError::Cancelled.into())), "error");
+    }
+
+    #[test]
+    fn cap_tool_output_read_family_suggests_offset_limit() {
+        let mut result = text_result(&"line\n".repeat(100));
+        assert!(cap_tool_output(&mut result, "read_file", 64));
+        let text = first_text(&result);
+        assert!(text.len() < 200);
+        assert!(text.contains("output truncated at 64 B, use offset/limit"));
+    }
+
+    #[test]
+    fn cap_tool_output_listing_family_hints() {
+        let mut result = text_result(&"[FILE] x.txt\n".repeat(100));
+        cap_tool_output(&mut result, "list_directory", 64);
+        assert!(first_text(&result).contains("use offset/limit to page"));
+
+        for tool in ["directory_tree", "search_files"] {
+            let mut result = text_result(&"[FILE] x.txt\n".repeat(100));
+            cap_tool_output(&mut result, tool, 64);
+            assert!(first_text(&result).contains("narrow the path"));
+        }
+    }
+
+    #[test]
+    fn cap_tool_output_write_family_and_default() {
+        let mut result = text_result(&"+added line\n".repeat(100));
+        cap_tool_output(&mut result, "edit_file", 64);
+        assert!(first_text(&result).contains("the edit was applied"));
+
+        let mut result = text_result(&"x".repeat(100));
+        cap_tool_output(&mut result, "get_file_info", 64);
+        assert!(first_text(&result).contains("narrower queries"));
+    }
+
+    #[test]
+    fn cap_tool_output_keeps_small_output_and_utf8() {
+        let mut result = text_result("small");
+        assert!(!cap_tool_output(&mut result, "read_file", 64));
+        assert_eq!(first_text(&result), "small");
+
+        let mut result = text_result(&"\u{1F980}".repeat(10));
+        cap_tool_output(&mut result, "read_file", 9);
+        assert!(first_text(&result).starts_with("\u{1F980}\u{1F980}\n"));
+    }
+
+    #[tokio::test]
+    async fn watch_slow_polls_reports_blocking_polls_only() {
+        let slow = std::cell::Cell::new(Vec::new());
+        let record = |busy: Duration| {
+            let mut seen = slow.take();
+            seen.push(busy);
+            slow.set(seen);
+        };
+
+        let output = watch_slow_polls(
+            async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                1
+            },
+            record,
+        )
+        .await;
+        assert_eq!(output, 1);
+        assert!(slow.take().is_empty(), "awaiting is not blocking");
+
+        watch_slow_polls(
+            async { std::thread::sleep(SLOW_POLL_THRESHOLD + Duration::from_millis(50)) },
+            record,
+        )
+        .await;
+        let seen = slow.take();
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0] > SLOW_POLL_THRESHOLD);
+    }
+
+    #[test]
+    fn json_listing_key_covers_listing_tools_only() {
+        let json = serde_json::json!({"format": "json"});
+        let json = json.as_object().unwrap();
+        assert_eq!(json_listing_key("list_directory", json), Some("entries"));
+        assert_eq!(json_listing_key("directory_tree", json), Some("entries"));
+        assert_eq!(json_listing_key("search_files", json), Some("files"));
+        assert_eq!(json_listing_key("validate_syntax", json), None);
+        let text = serde_json::json!({"format": "text"});
+        assert_eq!(
+            json_listing_key("list_directory", text.as_object().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn cap_json_listing_drops_entries_and_stays_valid() {
+        let entries: Vec<_> = (0..100)
+            .map(|i| serde_json::json!({"name": format!("file_{i:03}.txt"), "kind": "file"}))
+            .collect();
+        let listing = serde_json::json!({"path": "/a", "entries": entries, "truncated": false});
+        let mut result = text_result(&listing.to_string());
+        result.structured_content = Some(listing);
+
+        assert!(cap_json_listing(&mut result, "entries", 512));
+        let text = first_text(&result);
+        assert!(text.len() <= 512, "{}", text.len());
+        let parsed: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(parsed["truncated"], true);
+        let kept = parsed["entries"].as_array().unwrap();
+        assert!(!kept.is_empty() && kept.len() < 100);
+        assert_eq!(kept[0]["name"], "file_000.txt");
+        assert_eq!(result.structured_content, Some(parsed));
+
+        let mut small = text_result(r#"{"entries":[],"truncated":false}"#);
+        assert!(!cap_json_listing(&mut small, "entries", 512));
+    }
+
+    #[test]
+    fn operation_path_prefers_single_path_arguments() {
+        let args = |value: serde_json::Value| value.as_object().unwrap().clone();
+        assert_eq!(
+            operation_path(&args(serde_json::json!({"path": "/a", "pattern": "*"}))),
+            Some("/a".to_string())
+        );
+        assert_eq!(
+            operation_path(&args(
+                serde_json::json!({"source": "/a", "destination": "/b"})
+            )),
+            Some("/a".to_string())
+        );
+        assert_eq!(
+            operation_path(&args(serde_json::json!({"paths": ["/a", "/b"]}))),
+            Some("/a, /b".to_string())
+        );
+        assert_eq!(operation_path(&args(serde_json::json!({}))), None);
+    }
 }
@@ -1,30 +1,667 @@
 use crate::config::Config;
+use crate::error::FsError;
+use crate::logging::ClientLogger;
 use crate::security::SecurityContext;
+use crate::stats::ToolStats;
+use crate::tools::util::to_hex;
 use rmcp::handler::server::router::tool::ToolRouter;
+use rmcp::model::ProtocolVersion;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{Mutex, Notify, Semaphore};
+use tracing::debug;
+
+/// Caps how many tool calls execute at once and tracks current and peak load.
+///
+/// Calls beyond the limit wait for a slot rather than failing.
+pub struct OpLimiter {
+    semaphore: Semaphore,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+    /// Set by [`OpLimiter::drain`]; no new operations start afterwards
+    closed: AtomicBool,
+    /// Signalled whenever the last in-flight operation finishes
+    idle: Notify,
+}
+
+/// Decrements the in-flight count even if the guarded future is dropped early.
+struct InFlightGuard<'a>(&'a OpLimiter);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl OpLimiter {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            semaphore: Semaphore::new(max_concurrent),
+            in_flight: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            idle: Notify::new(),
+        }
+    }
+
+    /// Runs `fut` once a slot is free, or fails with [`FsError::ShuttingDown`]
+    /// once [`OpLimiter::drain`] has been called.
+    pub async fn run<F: Future>(&self, fut: F) -> Result<F::Output, FsError> {
+        if self.is_closed() {
+            return Err(FsError::ShuttingDown);
+        }
+        let _permit = match self.semaphore.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                debug!(
+                    "Concurrency limit reached ({} in flight); waiting for a slot",
+                    self.in_flight()
+                );
+                self.semaphore
+                    .acquire()
+                    .await
+                    .expect("operation semaphore is never closed")
+            }
+        };
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        let _guard = InFlightGuard(self);
+        // Checked after counting ourselves in, so drain either sees this
+        // operation or this operation sees the flag
+        if self.is_closed() {
+            return Err(FsError::ShuttingDown);
+        }
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        Ok(fut.await)
+    }
+
+    /// Stops new operations from starting and waits up to `grace` for the
+    /// running ones to finish. Returns false if some were still running.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        self.closed.store(true, Ordering::SeqCst);
+        let idle = async {
+            loop {
+                let notified = self.idle.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(grace, idle).await.is_ok()
+    }
+
+    /// Whether [`OpLimiter::drain`] has been called.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Number of operations currently executing.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Highest number of operations that have executed at the same time.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+/// How long a `--confirm-destructive` token stays valid.
+pub const CONFIRMATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Outstanding confirmation tokens for `--confirm-destructive`.
+///
+/// Each token is random, bound to one operation description (tool plus canonical
+/// paths), expires after a TTL, and is consumed by the first attempt to use it.
+pub struct ConfirmationStore {
+    ttl: Duration,
+    pending: std::sync::Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl ConfirmationStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            pending: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Issues a fresh token for `operation`.
+    pub fn issue(&self, operation: &str) -> Result<String, String> {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes)
+            .map_err(|e| format!("Failed to generate confirmation token: {e}"))?;
+        let token = to_hex(&bytes);
+        let now = Instant::now();
+        let mut pending = self.pending.lock().expect("confirmation lock poisoned");
+        pending.retain(|_, (_, expires)| *expires > now);
+        pending.insert(token.clone(), (operation.to_string(), now + self.ttl));
+        Ok(token)
+    }
+
+    /// Consumes `token`, succeeding only if it was issued for exactly `operation`
+    /// and has not expired. The token is gone afterwards either way.
+    pub fn redeem(&self, token: &str, operation: &str) -> Result<(), String> {
+        self.lookup(token, operation, true)
+    }
+
+    /// Like [`redeem`](Self::redeem) but leaves the token in place, for dry runs.
+    pub fn check(&self, token: &str, operation: &str) -> Result<(), String> {
+        self.lookup(token, operation, false)
+    }
+
+    fn lookup(&self, token: &str, operation: &str, consume: bool) -> Result<(), String> {
+        let mut pending = self.pending.lock().expect("confirmation lock poisoned");
+        let entry = if consume {
+            pending.remove(token.trim())
+        } else {
+            pending.get(token.trim()).cloned()
+        };
+        match entry {
+            None => Err(
+                "Unknown or already used confirmation token. Call again without confirm_token to get a new one."
+                    .to_string(),
+            ),
+            Some((_, expires)) if expires <= Instant::now() => Err(
+                "Confirmation token expired. Call again without confirm_token to get a new one."
+                    .to_string(),
+            ),
+            Some((issued_for, _)) if issued_for != operation => Err(
+                "Confirmation token was issued for a different operation or paths. Call again without confirm_token to get a new one."
+                    .to_string(),
+            ),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// One completed tool call, kept when `--history-size` is set.
+#[derive(Debug, Clone)]
+pub struct OperationRecord {
+    pub timestamp: SystemTime,
+    pub tool: String,
+    /// The main path argument, if the tool takes one
+    pub path: Option<String>,
+    pub success: bool,
+    pub duration_ms: u64,
+}
 
 pub struct FilesystemService {
     pub config: Config,
     pub security: SecurityContext,
     pub(crate) tool_router: ToolRouter<FilesystemService>,
+    pub(crate) op_limiter: OpLimiter,
+    /// Last `history_size` tool calls, oldest first; `None` when disabled,
+    /// including a `history_size` of `Some(0)`
+    pub operation_history: Option<Arc<Mutex<VecDeque<OperationRecord>>>>,
+    pub(crate) confirmations: ConfirmationStore,
+    /// Forwards notable events to the client as `notifications/message`
+    pub client_log: ClientLogger,
+    /// Protocol version agreed with the client at initialization
+    pub(crate) protocol_version: OnceLock<ProtocolVersion>,
+    /// Per-tool usage counters reported by `server_stats`
+    pub stats: ToolStats,
 }
 
 impl FilesystemService {
+    /// Creates the service from a validated [`Config`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.deny` contains an invalid glob; [`Config::validate`] rejects those.
     pub fn new(config: Config) -> Self {
-        let security = SecurityContext::new(config.allowed_directories.clone());
+        Self::with_extra_router(config, ToolRouter::new())
+    }
+
+    /// Creates the service with caller-supplied tools served alongside the
+    /// built-in ones.
+    ///
+    /// Extra tools are listed and dispatched like built-in tools, so they share
+    /// the concurrency limit, usage stats, history and `--max-output-bytes` cap.
+    /// They are not gated by `--allow-write` or `--allow-destructive`; enforce
+    /// any such policy in the tool itself, e.g. through [`FilesystemService::security`].
+    ///
+    /// ```
+    /// use ironbeard_mcp_filesystem::{Config, FilesystemService};
+    /// use rmcp::ServiceExt;
+    /// use rmcp::handler::server::router::tool::{ToolRoute, ToolRouter};
+    /// use rmcp::handler::server::tool::ToolCallContext;
+    /// use rmcp::model::{CallToolResult, Content, Tool, object};
+    /// use serde_json::{Value, json};
+    /// use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let hello = ToolRoute::new_dyn(
+    ///     Tool::new("hello", "Greets the caller", object(json!({ "type": "object" }))),
+    ///     |_: ToolCallContext<'_, FilesystemService>| {
+    ///         Box::pin(async { Ok(CallToolResult::success(vec![Content::text("Hello!")])) })
+    ///     },
+    /// );
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let config = Config::builder().allowed_directory(dir.path()).build().unwrap();
+    /// let service = FilesystemService::with_extra_router(config, ToolRouter::new().with_route(hello));
+    /// assert!(service.tool_router().has_route("hello"));
+    ///
+    /// // Serve over an in-memory pipe and call the tool as a client would
+    /// let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    /// let (server_read, server_write) = tokio::io::split(server_io);
+    /// tokio::spawn(async move {
+    ///     let server = service.serve((server_read, server_write)).await.unwrap();
+    ///     server.waiting().await.unwrap();
+    /// });
+    /// let (client_read, mut client_write) = tokio::io::split(client_io);
+    /// let mut responses = BufReader::new(client_read).lines();
+    /// for message in [
+    ///     json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+    ///         "protocolVersion": "2025-06-18", "capabilities": {},
+    ///         "clientInfo": { "name": "doc", "version": "0" } } }),
+    ///     json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+    ///     json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+    ///         "params": { "name": "hello", "arguments": {} } }),
+    /// ] {
+    ///     client_write.write_all(format!("{message}\n").as_bytes()).await.unwrap();
+    /// }
+    /// let response = loop {
+    ///     let line = responses.next_line().await.unwrap().unwrap();
+    ///     let message: Value = serde_json::from_str(&line).unwrap();
+    ///     if message["id"] == 2 {
+    ///         break message;
+    ///     }
+    /// };
+    /// assert_eq!(response["result"]["content"][0]["text"], "Hello!");
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.deny` contains an invalid glob, or if an extra tool has
+    /// the same name as a built-in tool enabled by `config`.
+    pub fn with_extra_router(config: Config, extra: ToolRouter<FilesystemService>) -> Self {
+        let security =
+            SecurityContext::with_deny_patterns(config.allowed_directories.clone(), &config.deny)
+                .expect("deny patterns are checked by Config::validate")
+                .with_read_only_dirs(config.read_only_directories.clone());
         let mut tool_router = Self::list_tools_router()
             + Self::read_tools_router()
             + Self::info_tools_router()
-            + Self::search_tools_router();
+            + Self::search_tools_router()
+            + Self::stats_tools_router()
+            + Self::health_tools_router()
+            + Self::archive_tools_router()
+            + Self::count_tools_router()
+            + Self::image_tools_router()
+            + Self::table_tools_router()
+            + Self::syntax_tools_router()
+            + Self::markdown_tools_router()
+            + Self::code_tools_router()
+            + Self::checksum_tools_router()
+            + Self::compare_tools_router()
+            + Self::watch_tools_router();
         if config.allow_write {
             tool_router += Self::write_tools_router();
+            tool_router += Self::compress_tools_router();
+            tool_router += Self::archive_write_tools_router();
+            tool_router += Self::line_ending_tools_router();
+            if config.trash_dir.is_some() {
+                tool_router += Self::trash_tools_router();
+            }
         }
         if config.allow_destructive {
             tool_router += Self::destructive_tools_router();
         }
+        // Some(0) only gets past an unvalidated Config; treat it as disabled
+        let operation_history = config.history_size.filter(|&size| size > 0).map(|size| {
+            tool_router += Self::history_tools_router();
+            Arc::new(Mutex::new(VecDeque::with_capacity(size)))
+        });
+        for tool in extra.list_all() {
+            assert!(
+                !tool_router.has_route(&tool.name),
+                "extra tool `{}` clashes with a built-in tool",
+                tool.name
+            );
+        }
+        tool_router.merge(extra);
+        let op_limiter = OpLimiter::new(config.max_concurrent_ops);
+        let stats = ToolStats::new(tool_router.list_all().into_iter().map(|t| t.name.into()));
         Self {
             config,
             security,
             tool_router,
+            op_limiter,
+            operation_history,
+            confirmations: ConfirmationStore::new(CONFIRMATION_TTL),
+            client_log: ClientLogger::default(),
+            protocol_version: OnceLock::new(),
+            stats,
         }
     }
+
+    /// Every tool this service serves, built-in and extra.
+    pub fn tool_router(&self) -> &ToolRouter<FilesystemService> {
+        &self.tool_router
+    }
+
+    /// Appends a finished tool call to the history, dropping the oldest entry
+    /// once `history_size` is reached. Does nothing when history is disabled.
+    pub async fn record_operation(
+        &self,
+        tool: &str,
+        path: Option<String>,
+        success: bool,
+        duration: Duration,
+    ) {
+        let (Some(history), Some(size)) = (&self.operation_history, self.config.history_size)
+        else {
+            return;
+        };
+        let mut history = history.lock().await;
+        while history.len() >= size {
+            history.pop_front();
+        }
+        history.push_back(OperationRecord {
+            timestamp: SystemTime::now(),
+            tool: tool.to_string(),
+            path,
+            success,
+            duration_ms: duration.as_millis() as u64,
+        });
+    }
+
+    /// Refuses new tool calls and waits up to `grace` for running ones to
+    /// finish, for a clean shutdown. Returns false if the grace period ran out.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        self.op_limiter.drain(grace).await
+    }
+
+    /// Current and peak number of tool calls executing concurrently.
+    pub fn concurrency(&self) -> (usize, usize) {
+        (self.op_limiter.in_flight(), self.op_limiter.peak())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn op_limiter_caps_concurrent_operations() {
+        let limiter = Arc::new(OpLimiter::new(3));
+        let gate = Arc::new(Semaphore::new(0));
+
+        let mut handles = Vec::new();
+        for _ in 0..20 {
+            let limiter = limiter.clone();
+            let gate = gate.clone();
+            handles.push(tokio::spawn(async move {
+                limiter
+                    .run(async {
+                        gate.acquire().await.unwrap().forget();
+                    })
+                    .await
+                    .unwrap();
+            }));
+        }
+
+        while limiter.in_flight() < 3 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(limiter.in_flight(), 3);
+
+        gate.add_permits(20);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(limiter.peak(), 3);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn op_limiter_releases_slot_when_future_dropped() {
+        let limiter = OpLimiter::new(1);
+        let gate = Semaphore::new(0);
+        let mut pending = Box::pin(limiter.run(gate.acquire()));
+        let still_pending = std::future::poll_fn(|cx| {
+            std::task::Poll::Ready(pending.as_mut().poll(cx).is_pending())
+        })
+        .await;
+        assert!(still_pending);
+        assert_eq!(limiter.in_flight(), 1);
+        drop(pending);
+        assert_eq!(limiter.in_flight(), 0);
+        limiter.run(async {}).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn drain_waits_for_in_flight_and_refuses_new_operations() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let service = Arc::new(FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            ..Default::default()
+        }));
+        let (finish, finished) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn({
+            let service = service.clone();
+            async move { service.op_limiter.run(finished).await }
+        });
+        while service.concurrency().0 == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let drain = tokio::spawn({
+            let service = service.clone();
+            async move { service.drain(Duration::from_secs(5)).await }
+        });
+        while !service.op_limiter.is_closed() {
+            tokio::task::yield_now().await;
+        }
+        let refused = service.op_limiter.run(async {}).await;
+        assert!(matches!(refused, Err(FsError::ShuttingDown)));
+        assert!(!drain.is_finished());
+
+        finish.send(()).unwrap();
+        assert!(running.await.unwrap().is_ok());
+        assert!(drain.await.unwrap());
+        assert_eq!(service.concurrency().0, 0);
+    }
+
+    #[tokio::test]
+    async fn drain_gives_up_after_grace_period() {
+        let limiter = Arc::new(OpLimiter::new(1));
+        let stuck = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.run(std::future::pending::<()>()).await }
+        });
+        while limiter.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!limiter.drain(Duration::from_millis(20)).await);
+        assert_eq!(limiter.in_flight(), 1);
+        stuck.abort();
+    }
+
+    #[tokio::test]
+    async fn drain_returns_at_once_when_idle() {
+        let limiter = OpLimiter::new(2);
+        assert!(limiter.drain(Duration::ZERO).await);
+    }
+
+    #[test]
+    fn confirmation_token_is_single_use() {
+        let store = ConfirmationStore::new(CONFIRMATION_TTL);
+        let token = store.issue("delete_file\n/a").unwrap();
+        assert_eq!(token.len(), 32);
+        store.check(&token, "delete_file\n/a").unwrap();
+        store.redeem(&token, "delete_file\n/a").unwrap();
+        let err = store.redeem(&token, "delete_file\n/a").unwrap_err();
+        assert!(err.contains("already used"), "{err}");
+    }
+
+    #[test]
+    fn confirmation_token_expires() {
+        let store = ConfirmationStore::new(Duration::ZERO);
+        let token = store.issue("delete_file\n/a").unwrap();
+        let err = store.redeem(&token, "delete_file\n/a").unwrap_err();
+        assert!(err.contains("expired"), "{err}");
+    }
+
+    #[test]
+    fn confirmation_token_bound_to_operation() {
+        let store = ConfirmationStore::new(CONFIRMATION_TTL);
+        let token = store.issue("delete_file\n/a").unwrap();
+        let err = store.redeem(&token, "delete_file\n/b").unwrap_err();
+        assert!(err.contains("different operation"), "{err}");
+        // A mismatched attempt burns the token
+        assert!(store.redeem(&token, "delete_file\n/a").is_err());
+    }
+
+    fn history_service(size: usize) -> FilesystemService {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            history_size: Some(size),
+            ..Default::default()
+        };
+        FilesystemService::new(config)
+    }
+
+    async fn history_of(service: &FilesystemService) -> Vec<OperationRecord> {
+        let history = service.operation_history.as_ref().unwrap().lock().await;
+        history.iter().cloned().collect()
+    }
+
+    #[tokio::test]
+    async fn record_operation_keeps_only_last_history_size() {
+        let service = history_service(3);
+        for i in 0..5 {
+            service
+                .record_operation(&format!("tool{i}"), None, true, Duration::ZERO)
+                .await;
+        }
+        let tools: Vec<String> = history_of(&service)
+            .await
+            .into_iter()
+            .map(|r| r.tool)
+            .collect();
+        assert_eq!(tools, vec!["tool2", "tool3", "tool4"]);
+    }
+
+    #[tokio::test]
+    async fn record_operation_records_failures() {
+        let service = history_service(3);
+        service
+            .record_operation(
+                "read_file",
+                Some("/nope".into()),
+                false,
+                Duration::from_millis(7),
+            )
+            .await;
+        let history = history_of(&service).await;
+        assert!(!history[0].success);
+        assert_eq!(history[0].path.as_deref(), Some("/nope"));
+        assert_eq!(history[0].duration_ms, 7);
+    }
+
+    #[tokio::test]
+    async fn record_operation_noop_when_disabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            ..Default::default()
+        });
+        service
+            .record_operation("read_file", None, true, Duration::ZERO)
+            .await;
+        assert!(service.operation_history.is_none());
+        let names: Vec<_> = service
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert!(!names.iter().any(|n| n == "list_operation_history"));
+    }
+
+    #[tokio::test]
+    async fn zero_history_size_disables_history() {
+        let service = history_service(0);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            service.record_operation("read_file", None, true, Duration::ZERO),
+        )
+        .await
+        .expect("record_operation must not hang");
+        assert!(service.operation_history.is_none());
+        assert!(!service.tool_router.has_route("list_operation_history"));
+    }
+
+    fn extra_tool(name: &'static str) -> ToolRouter<FilesystemService> {
+        use rmcp::handler::server::router::tool::ToolRoute;
+        use rmcp::handler::server::tool::ToolCallContext;
+        use rmcp::model::{CallToolResult, Tool, object};
+        let route = ToolRoute::new_dyn(
+            Tool::new(
+                name,
+                "Test tool",
+                object(serde_json::json!({ "type": "object" })),
+            ),
+            |_: ToolCallContext<'_, FilesystemService>| {
+                Box::pin(async { Ok(CallToolResult::success(Vec::new())) })
+            },
+        );
+        ToolRouter::new().with_route(route)
+    }
+
+    #[test]
+    fn extra_tools_are_routed_and_counted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().to_path_buf()],
+            ..Default::default()
+        };
+        let builtin = FilesystemService::new(config.clone())
+            .tool_router()
+            .list_all()
+            .len();
+        let service = FilesystemService::with_extra_router(config, extra_tool("ping"));
+        assert!(service.tool_router().has_route("ping"));
+        assert!(service.tool_router().has_route("read_file"));
+        assert_eq!(service.tool_router().list_all().len(), builtin + 1);
+        assert!(service.stats.usage("ping").is_some());
+    }
+
+    #[test]
+    fn extra_tool_may_reuse_name_of_disabled_builtin() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let service = FilesystemService::with_extra_router(
+            Config {
+                allowed_directories: vec![dir.path().to_path_buf()],
+                ..Default::default()
+            },
+            extra_tool("write_file"),
+        );
+        assert!(service.tool_router().has_route("write_file"));
+    }
+
+    #[test]
+    #[should_panic(expected = "clashes with a built-in tool")]
+    fn extra_tool_clashing_with_builtin_panics() {
+        let dir = tempfile::TempDir::new().unwrap();
+        FilesystemService::with_extra_router(
+            Config {
+                allowed_directories: vec![dir.path().to_path_buf()],
+                ..Default::default()
+            },
+            extra_tool("read_file"),
+        );
+    }
 }
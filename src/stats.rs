@@ -0,0 +1,280 @@
+use crate::tools::util::format_size;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    /// Byte counters of the tool call running on this task
+    static CALL_IO: Arc<CallIo>;
+}
+
+#[derive(Default)]
+struct CallIo {
+    read: AtomicU64,
+    written: AtomicU64,
+}
+
+/// Adds `bytes` of file data read to the current tool call's counters.
+/// Does nothing outside [`ToolStats::instrument`], e.g. in unit tests.
+pub fn record_read(bytes: u64) {
+    let _ = CALL_IO.try_with(|io| io.read.fetch_add(bytes, Ordering::Relaxed));
+}
+
+/// Adds `bytes` of file data written to the current tool call's counters.
+pub fn record_written(bytes: u64) {
+    let _ = CALL_IO.try_with(|io| io.written.fetch_add(bytes, Ordering::Relaxed));
+}
+
+/// Bytes read and written so far by the current tool call; zeros outside one.
+pub fn call_io() -> (u64, u64) {
+    CALL_IO
+        .try_with(|io| {
+            (
+                io.read.load(Ordering::Relaxed),
+                io.written.load(Ordering::Relaxed),
+            )
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+struct ToolCounters {
+    invocations: AtomicU64,
+    successes: AtomicU64,
+    errors: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    duration_us: AtomicU64,
+}
+
+/// Point-in-time copy of one tool's counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolUsage {
+    pub invocations: u64,
+    pub successes: u64,
+    pub errors: u64,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub duration: Duration,
+}
+
+/// Per-tool usage counters since the server started.
+///
+/// The tool set is fixed at construction, so updates are lock-free.
+pub struct ToolStats {
+    started: Instant,
+    tools: BTreeMap<String, ToolCounters>,
+}
+
+impl ToolStats {
+    pub fn new(tools: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            started: Instant::now(),
+            tools: tools
+                .into_iter()
+                .map(|name| (name, ToolCounters::default()))
+                .collect(),
+        }
+    }
+
+    /// Time since the server started.
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Runs one call of `tool`, counting it as a success if `succeeded` says so.
+    /// A call dropped before it finishes is counted as an error.
+    pub async fn instrument<F: Future>(
+        &self,
+        tool: &str,
+        call: F,
+        succeeded: impl FnOnce(&F::Output) -> bool,
+    ) -> F::Output {
+        let Some(counters) = self.tools.get(tool) else {
+            return call.await;
+        };
+        counters.invocations.fetch_add(1, Ordering::Relaxed);
+        let io = Arc::new(CallIo::default());
+        let mut record = Record {
+            counters,
+            io: io.clone(),
+            started: Instant::now(),
+            success: false,
+        };
+        let output = CALL_IO.scope(io, call).await;
+        record.success = succeeded(&output);
+        output
+    }
+
+    /// Counters for `tool`, or `None` if it is not registered.
+    pub fn usage(&self, tool: &str) -> Option<ToolUsage> {
+        let c = self.tools.get(tool)?;
+        Some(ToolUsage {
+            invocations: c.invocations.load(Ordering::Relaxed),
+            successes: c.successes.load(Ordering::Relaxed),
+            errors: c.errors.load(Ordering::Relaxed),
+            bytes_read: c.bytes_read.load(Ordering::Relaxed),
+            bytes_written: c.bytes_written.load(Ordering::Relaxed),
+            duration: Duration::from_micros(c.duration_us.load(Ordering::Relaxed)),
+        })
+    }
+
+    /// Formats a table of every tool called so far.
+    pub fn report(&self) -> String {
+        let used: Vec<(&str, ToolUsage)> = self
+            .tools
+            .keys()
+            .filter_map(|name| Some((name.as_str(), self.usage(name)?)))
+            .filter(|(_, usage)| usage.invocations > 0)
+            .collect();
+        if used.is_empty() {
+            return "(no tool calls yet)".to_string();
+        }
+        let width = used
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let mut output = format!(
+            "{:<width$}  {:>6}  {:>6}  {:>6}  {:>10}  {:>10}  {:>10}",
+            "Tool", "Calls", "OK", "Errors", "Read", "Written", "Time"
+        );
+        let mut total = ToolUsage::default();
+        for (name, usage) in &used {
+            output.push('\n');
+            output.push_str(&usage_row(name, usage, width));
+            total.invocations += usage.invocations;
+            total.successes += usage.successes;
+            total.errors += usage.errors;
+            total.bytes_read += usage.bytes_read;
+            total.bytes_written += usage.bytes_written;
+            total.duration += usage.duration;
+        }
+        output.push('\n');
+        output.push_str(&usage_row("Total", &total, width));
+        output
+    }
+}
+
+fn usage_row(name: &str, usage: &ToolUsage, width: usize) -> String {
+    format!(
+        "{name:<width$}  {:>6}  {:>6}  {:>6}  {:>10}  {:>10}  {:>10}",
+        usage.invocations,
+        usage.successes,
+        usage.errors,
+        format_size(usage.bytes_read),
+        format_size(usage.bytes_written),
+        format!("{} ms", usage.duration.as_millis()),
+    )
+}
+
+/// Folds one call into its tool's counters when the call ends or is dropped.
+struct Record<'a> {
+    counters: &'a ToolCounters,
+    io: Arc<CallIo>,
+    started: Instant,
+    success: bool,
+}
+
+impl Drop for Record<'_> {
+    fn drop(&mut self) {
+        let c = self.counters;
+        if self.success {
+            c.successes.fetch_add(1, Ordering::Relaxed);
+        } else {
+            c.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        c.bytes_read
+            .fetch_add(self.io.read.load(Ordering::Relaxed), Ordering::Relaxed);
+        c.bytes_written
+            .fetch_add(self.io.written.load(Ordering::Relaxed), Ordering::Relaxed);
+        c.duration_us
+            .fetch_add(self.started.elapsed().as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> ToolStats {
+        ToolStats::new(["read_file".to_string(), "write_file".to_string()])
+    }
+
+    #[tokio::test]
+    async fn instrument_counts_outcomes_and_bytes() {
+        let stats = stats();
+        let ok = stats
+            .instrument(
+                "read_file",
+                async {
+                    record_read(100);
+                    Ok::<_, ()>(())
+                },
+                Result::is_ok,
+            )
+            .await;
+        assert!(ok.is_ok());
+        stats
+            .instrument("read_file", async { Err::<(), _>(()) }, Result::is_ok)
+            .await
+            .unwrap_err();
+        stats
+            .instrument("write_file", async { record_written(7) }, |_| true)
+            .await;
+
+        let io = stats
+            .instrument(
+                "read_file",
+                async {
+                    record_read(3);
+                    record_read(4);
+                    call_io()
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(io, (7, 0));
+        assert_eq!(call_io(), (0, 0));
+
+        let read = stats.usage("read_file").unwrap();
+        assert_eq!((read.invocations, read.successes, read.errors), (3, 2, 1));
+        assert_eq!((read.bytes_read, read.bytes_written), (107, 0));
+        assert_eq!(stats.usage("write_file").unwrap().bytes_written, 7);
+    }
+
+    #[tokio::test]
+    async fn dropped_call_counts_as_error() {
+        let stats = stats();
+        let call = stats.instrument("read_file", std::future::pending::<()>(), |_| true);
+        let _ = tokio::time::timeout(Duration::from_millis(10), call).await;
+        let usage = stats.usage("read_file").unwrap();
+        assert_eq!((usage.invocations, usage.errors), (1, 1));
+    }
+
+    #[test]
+    fn record_outside_a_call_is_ignored() {
+        record_read(5);
+        record_written(5);
+    }
+
+    #[tokio::test]
+    async fn report_lists_only_called_tools_with_total() {
+        let stats = stats();
+        assert_eq!(stats.report(), "(no tool calls yet)");
+        stats
+            .instrument("write_file", async { record_written(2048) }, |_| true)
+            .await;
+        let report = stats.report();
+        assert!(!report.contains("read_file"));
+        let row = report
+            .lines()
+            .find(|l| l.starts_with("write_file"))
+            .unwrap();
+        assert!(row.contains("2.0 KB"), "{row}");
+        assert!(report.lines().last().unwrap().starts_with("Total"));
+    }
+}
@@ -0,0 +1,1219 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use crate::tools::search::{Traversal, find_matching_files};
+use globset::Glob;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use super::util::{format_size, spawn_blocking};
+
+/// Offset of the `ustar` magic in a tar header block.
+const USTAR_MAGIC_OFFSET: usize = 257;
+
+/// Parameters for the extract_from_archive tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ExtractFromArchiveParams {
+    /// Absolute path to a .zip, .tar, or .tar.gz archive
+    archive_path: String,
+    /// Entry name exactly as shown by list_archive, e.g. "src/main.rs"
+    entry: String,
+    /// Directory to extract into; the entry's path inside the archive is kept
+    /// below it. Required unless to_response is true.
+    destination: Option<String>,
+    /// Return the entry's text in the response instead of writing it to disk
+    /// (UTF-8 only, up to --max-read-size; default: false)
+    to_response: Option<bool>,
+}
+
+/// Parameters for the create_archive tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CreateArchiveParams {
+    /// Absolute path to the directory to archive
+    source: String,
+    /// Absolute path for the new .zip file; must not be inside source
+    destination: String,
+    /// Only include files whose path relative to source matches this glob,
+    /// e.g. "**/*.rs" (default: every file)
+    include: Option<String>,
+    /// Replace destination if it already exists (default: false)
+    overwrite: Option<bool>,
+}
+
+/// Parameters for the list_archive tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ListArchiveParams {
+    /// Absolute path to a .zip, .tar, or .tar.gz archive
+    path: String,
+}
+
+/// Archive formats recognized by list_archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Identifies the format from the first bytes of the file, ignoring the extension.
+    fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Some(ArchiveFormat::Zip)
+        } else if header.starts_with(&[0x1f, 0x8b]) {
+            Some(ArchiveFormat::TarGz)
+        } else if header.get(USTAR_MAGIC_OFFSET..USTAR_MAGIC_OFFSET + 5) == Some(b"ustar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+/// One file or directory inside an archive.
+#[derive(Debug)]
+struct ArchiveEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    /// Stored size and compression method; zip only, tar entries are not
+    /// compressed individually
+    compressed: Option<(u64, String)>,
+}
+
+/// The first `max_entries` entries of an archive plus totals over all of them.
+#[derive(Debug)]
+struct ArchiveListing {
+    entries: Vec<ArchiveEntry>,
+    total_entries: usize,
+    files: usize,
+    dirs: usize,
+    total_size: u64,
+}
+
+impl ArchiveListing {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            total_entries: 0,
+            files: 0,
+            dirs: 0,
+            total_size: 0,
+        }
+    }
+
+    fn push(&mut self, entry: ArchiveEntry, max_entries: usize) {
+        self.total_entries += 1;
+        if entry.is_dir {
+            self.dirs += 1;
+        } else {
+            self.files += 1;
+            self.total_size += entry.size;
+        }
+        if self.entries.len() < max_entries {
+            self.entries.push(entry);
+        }
+    }
+}
+
+/// Reads the zip central directory without decompressing anything.
+/// Fails on the first encrypted entry.
+fn list_zip<R: Read + Seek>(
+    reader: R,
+    max_entries: usize,
+    path: &str,
+) -> Result<ArchiveListing, ToolError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| ToolError::from(format!("Invalid zip archive {path}: {e}")))?;
+    let mut listing = ArchiveListing::new();
+    for index in 0..archive.len() {
+        let file = archive
+            .by_index_raw(index)
+            .map_err(|e| ToolError::from(format!("Invalid zip archive {path}: {e}")))?;
+        if file.encrypted() {
+            return Err(format!(
+                "Encrypted zip archive: {path} ({} is password-protected). Encrypted archives cannot be listed.",
+                file.name()
+            )
+            .into());
+        }
+        let entry = ArchiveEntry {
+            name: file.name().to_string(),
+            is_dir: file.is_dir(),
+            size: file.size(),
+            compressed: Some((
+                file.compressed_size(),
+                format!("{:?}", file.compression()).to_lowercase(),
+            )),
+        };
+        listing.push(entry, max_entries);
+    }
+    Ok(listing)
+}
+
+/// Walks the tar headers, skipping over entry data.
+fn list_tar<R: Read>(
+    reader: R,
+    max_entries: usize,
+    path: &str,
+) -> Result<ArchiveListing, ToolError> {
+    let invalid = |e: std::io::Error| ToolError::from(format!("Invalid tar archive {path}: {e}"));
+    let mut archive = tar::Archive::new(reader);
+    let mut listing = ArchiveListing::new();
+    for entry in archive.entries().map_err(invalid)? {
+        let entry = entry.map_err(invalid)?;
+        let header = entry.header();
+        let entry = ArchiveEntry {
+            name: entry
+                .path()
+                .map_err(invalid)?
+                .to_string_lossy()
+                .into_owned(),
+            is_dir: header.entry_type().is_dir(),
+            size: header.size().map_err(invalid)?,
+            compressed: None,
+        };
+        listing.push(entry, max_entries);
+    }
+    Ok(listing)
+}
+
+/// Opens `file` and detects its format from the first 512 bytes.
+fn open_archive(
+    file: &Path,
+    path: &str,
+) -> Result<(ArchiveFormat, BufReader<std::fs::File>), ToolError> {
+    let mut reader = std::fs::File::open(file).map_err(|e| ToolError::io(e, path))?;
+    let mut header = Vec::with_capacity(512);
+    (&mut reader)
+        .take(512)
+        .read_to_end(&mut header)
+        .map_err(|e| ToolError::io(e, path))?;
+    reader.rewind().map_err(|e| ToolError::io(e, path))?;
+    let format = ArchiveFormat::detect(&header).ok_or_else(|| {
+        ToolError::from(format!(
+            "Unsupported archive format: {path}. list_archive supports zip, tar, and tar.gz."
+        ))
+    })?;
+    Ok((format, BufReader::new(reader)))
+}
+
+/// Opens `file`, detects its format, and lists it.
+fn list_archive_sync(
+    file: &Path,
+    max_entries: usize,
+    path: &str,
+) -> Result<(ArchiveFormat, ArchiveListing), ToolError> {
+    let (format, reader) = open_archive(file, path)?;
+    let listing = match format {
+        ArchiveFormat::Zip => list_zip(reader, max_entries, path)?,
+        ArchiveFormat::Tar => list_tar(reader, max_entries, path)?,
+        ArchiveFormat::TarGz => list_tar(flate2::read::GzDecoder::new(reader), max_entries, path)?,
+    };
+    Ok((format, listing))
+}
+
+/// Turns an entry name into a path relative to the destination directory,
+/// rejecting absolute paths and `..` so the entry cannot escape it (zip-slip).
+fn entry_target(entry: &str) -> Result<PathBuf, ToolError> {
+    let mut target = PathBuf::new();
+    for component in Path::new(entry).components() {
+        match component {
+            Component::Normal(part) => target.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(format!(
+                    "Unsafe entry path in archive: {entry}. It would be written outside the destination directory."
+                )
+                .into());
+            }
+        }
+    }
+    if target.as_os_str().is_empty() {
+        return Err(format!("Invalid entry name: {entry:?}").into());
+    }
+    Ok(target)
+}
+
+/// Reads at most `max_size` bytes from `reader`, failing with
+/// [`FsError::FileTooLarge`] if there is more. Guards against archives whose
+/// headers understate an entry's size.
+fn read_capped(
+    reader: impl Read,
+    max_size: u64,
+    entry: &str,
+    path: &str,
+) -> Result<Vec<u8>, ToolError> {
+    let mut data = Vec::new();
+    reader
+        .take(max_size + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| ToolError::from(format!("Failed to read {entry} from {path}: {e}")))?;
+    if data.len() as u64 > max_size {
+        return Err(FsError::FileTooLarge {
+            path: entry.to_string(),
+            size: data.len() as u64,
+            max: max_size,
+        }
+        .into());
+    }
+    Ok(data)
+}
+
+fn entry_not_found(entry: &str, path: &str) -> ToolError {
+    format!("Entry not found in archive {path}: {entry}. Use list_archive to see entry names.")
+        .into()
+}
+
+fn entry_is_directory(entry: &str) -> ToolError {
+    format!("Entry is a directory: {entry}. Only single files can be extracted.").into()
+}
+
+fn entry_too_large(entry: &str, size: u64, max_size: u64) -> ToolError {
+    FsError::FileTooLarge {
+        path: entry.to_string(),
+        size,
+        max: max_size,
+    }
+    .into()
+}
+
+/// Decompresses one zip entry.
+fn read_zip_entry<R: Read + Seek>(
+    reader: R,
+    entry: &str,
+    max_size: u64,
+    path: &str,
+) -> Result<Vec<u8>, ToolError> {
+    let invalid =
+        |e: zip::result::ZipError| ToolError::from(format!("Invalid zip archive {path}: {e}"));
+    let mut archive = zip::ZipArchive::new(reader).map_err(invalid)?;
+    let index = archive
+        .index_for_name(entry)
+        .ok_or_else(|| entry_not_found(entry, path))?;
+    {
+        let raw = archive.by_index_raw(index).map_err(invalid)?;
+        if raw.encrypted() {
+            return Err(format!(
+                "Encrypted zip archive: {path} ({entry} is password-protected). Encrypted entries cannot be extracted."
+            )
+            .into());
+        }
+        if raw.is_dir() {
+            return Err(entry_is_directory(entry));
+        }
+        if raw.size() > max_size {
+            return Err(entry_too_large(entry, raw.size(), max_size));
+        }
+    }
+    let file = archive.by_index(index).map_err(invalid)?;
+    read_capped(file, max_size, entry, path)
+}
+
+/// Scans a tar stream for `entry` and reads it.
+fn read_tar_entry<R: Read>(
+    reader: R,
+    entry: &str,
+    max_size: u64,
+    path: &str,
+) -> Result<Vec<u8>, ToolError> {
+    let invalid = |e: std::io::Error| ToolError::from(format!("Invalid tar archive {path}: {e}"));
+    let mut archive = tar::Archive::new(reader);
+    for candidate in archive.entries().map_err(invalid)? {
+        let candidate = candidate.map_err(invalid)?;
+        if candidate.path().map_err(invalid)?.to_string_lossy() != entry {
+            continue;
+        }
+        let header = candidate.header();
+        if header.entry_type().is_dir() {
+            return Err(entry_is_directory(entry));
+        }
+        let size = header.size().map_err(invalid)?;
+        if size > max_size {
+            return Err(entry_too_large(entry, size, max_size));
+        }
+        return read_capped(candidate, max_size, entry, path);
+    }
+    Err(entry_not_found(entry, path))
+}
+
+/// Opens `file` and reads `entry` out of it, up to `max_size` bytes.
+fn read_entry_sync(
+    file: &Path,
+    entry: &str,
+    max_size: u64,
+    path: &str,
+) -> Result<Vec<u8>, ToolError> {
+    let (format, reader) = open_archive(file, path)?;
+    match format {
+        ArchiveFormat::Zip => read_zip_entry(reader, entry, max_size, path),
+        ArchiveFormat::Tar => read_tar_entry(reader, entry, max_size, path),
+        ArchiveFormat::TarGz => {
+            read_tar_entry(flate2::read::GzDecoder::new(reader), entry, max_size, path)
+        }
+    }
+}
+
+/// Writes `data` to a new file at `target`, creating missing parent directories.
+fn write_new_file(target: &Path, data: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(target)?
+        .write_all(data)
+}
+
+/// Streams `files` into a zip at `destination`, naming each entry by its path
+/// relative to `source`. Writes to a temporary sibling first and renames it into
+/// place, so a failure never leaves a half-written archive at `destination`.
+/// Returns the final archive size.
+fn write_zip_sync(
+    source: &Path,
+    files: &[(PathBuf, u64)],
+    destination: &Path,
+) -> std::io::Result<u64> {
+    let file_name = destination
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let partial = destination.with_file_name(format!(".{file_name}.partial"));
+    let result = (|| {
+        let mut zip = zip::ZipWriter::new(BufWriter::new(std::fs::File::create(&partial)?));
+        for (path, size) in files {
+            let relative = path.strip_prefix(source).unwrap_or(path);
+            let name = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .large_file(*size >= u32::MAX as u64);
+            zip.start_file(name, options)
+                .map_err(std::io::Error::other)?;
+            std::io::copy(&mut std::fs::File::open(path)?, &mut zip)?;
+        }
+        zip.finish()
+            .map_err(std::io::Error::other)?
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&partial, destination)?;
+        Ok(std::fs::metadata(destination)?.len())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+impl FilesystemService {
+    /// Validates an archive path and checks it against `--max-archive-size`.
+    /// Returns the canonical path and the archive's size.
+    async fn validate_archive(&self, requested: &str) -> Result<(PathBuf, u64), ToolError> {
+        let file = self.security.validate_file(Path::new(requested))?;
+        let archive_size = tokio::fs::metadata(&file)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?
+            .len();
+        let max_size = self.config.max_archive_size;
+        if archive_size > max_size {
+            return Err(FsError::FileTooLarge {
+                path: requested.to_string(),
+                size: archive_size,
+                max: max_size,
+            }
+            .into());
+        }
+        Ok((file, archive_size))
+    }
+}
+
+/// Renders a listing: header, one line per entry, totals, and a truncation note.
+fn format_listing(
+    path: &str,
+    format: ArchiveFormat,
+    archive_size: u64,
+    listing: &ArchiveListing,
+) -> String {
+    let mut out = format!(
+        "Archive: {path} ({}, {} entries, {} on disk)\n",
+        format.name(),
+        listing.total_entries,
+        format_size(archive_size)
+    );
+    for entry in &listing.entries {
+        out.push('\n');
+        if entry.is_dir {
+            out.push_str(&format!("[DIR]  {}", entry.name));
+            continue;
+        }
+        out.push_str(&format!(
+            "[FILE] {} ({}",
+            entry.name,
+            format_size(entry.size)
+        ));
+        if let Some((compressed, method)) = &entry.compressed {
+            out.push_str(&format!(", {} {method}", format_size(*compressed)));
+        }
+        out.push(')');
+    }
+    if listing.entries.is_empty() {
+        out.push_str("\n(empty archive)");
+    }
+    out.push_str(&format!(
+        "\n\nTotal: {} files, {} directories, {} uncompressed",
+        listing.files,
+        listing.dirs,
+        format_size(listing.total_size)
+    ));
+    if listing.entries.len() < listing.total_entries {
+        out.push_str(&format!(
+            "\n(Showing first {} of {} entries; raise --max-archive-entries to list more)",
+            listing.entries.len(),
+            listing.total_entries
+        ));
+    }
+    out
+}
+
+#[rmcp::tool_router(router = "archive_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Lists the entries of a zip, tar, or tar.gz archive without extracting it.
+    #[rmcp::tool(
+        name = "list_archive",
+        description = "Lists the entries of a zip, tar, or tar.gz archive without extracting it. The format is detected from the file contents, not the extension. Each entry shows its name and uncompressed size; zip entries also show their compressed size and method. Output is capped at --max-archive-entries entries with a note when truncated. Encrypted zips are refused.",
+        annotations(
+            title = "List Archive",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn list_archive(
+        &self,
+        Parameters(params): Parameters<ListArchiveParams>,
+    ) -> Result<String, ToolError> {
+        let (file, archive_size) = self.validate_archive(&params.path).await?;
+
+        let max_entries = self.config.max_archive_entries;
+        let path = params.path.clone();
+        let (format, listing) =
+            spawn_blocking(move || list_archive_sync(&file, max_entries, &path))
+                .await
+                .map_err(|e| e.to_string())??;
+        // Zip listings only read the central directory; tar listings read it all
+        if format != ArchiveFormat::Zip {
+            stats::record_read(archive_size);
+        }
+        Ok(format_listing(&params.path, format, archive_size, &listing))
+    }
+}
+
+#[rmcp::tool_router(router = "archive_write_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Extracts one entry of a zip, tar, or tar.gz archive.
+    #[rmcp::tool(
+        name = "extract_from_archive",
+        description = "Extracts a single entry from a zip, tar, or tar.gz archive into a destination directory, keeping the entry's path inside the archive below it. Never overwrites an existing file. Entries whose path would leave the destination (\"../\", absolute paths) are refused, as are entries larger than --max-write-size. With to_response: true, returns a small UTF-8 entry's text instead of writing it.",
+        annotations(
+            title = "Extract From Archive",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn extract_from_archive(
+        &self,
+        Parameters(params): Parameters<ExtractFromArchiveParams>,
+    ) -> Result<String, ToolError> {
+        let (file, _) = self.validate_archive(&params.archive_path).await?;
+        let entry = params.entry.clone();
+        let path = params.archive_path.clone();
+
+        if params.to_response.unwrap_or(false) {
+            let max_size = self.config.max_read_size as u64;
+            let data = spawn_blocking(move || read_entry_sync(&file, &entry, max_size, &path))
+                .await
+                .map_err(|e| e.to_string())??;
+            stats::record_read(data.len() as u64);
+            let size = data.len() as u64;
+            let text = String::from_utf8(data).map_err(|_| {
+                ToolError::from(format!(
+                    "Entry is not UTF-8 text: {}. Extract it to a destination instead.",
+                    params.entry
+                ))
+            })?;
+            return Ok(format!(
+                "{} from {} ({}):\n\n{text}",
+                params.entry,
+                params.archive_path,
+                format_size(size)
+            ));
+        }
+
+        let Some(destination) = params.destination.as_deref() else {
+            return Err("destination is required unless to_response is true".into());
+        };
+        let relative = entry_target(&params.entry)?;
+        let dest = self
+            .security
+            .validate_creatable_path(Path::new(destination))?;
+        let target = self
+            .security
+            .validate_creatable_path(&dest.join(&relative))?;
+        self.security
+            .ensure_writable(&target, Path::new(destination))?;
+        // validate_creatable_path resolves symlinked ancestors, so this also
+        // catches a directory inside the destination that links elsewhere
+        if !target.starts_with(&dest) {
+            return Err(format!(
+                "Unsafe entry path in archive: {}. It would be written outside the destination directory.",
+                params.entry
+            )
+            .into());
+        }
+        if target.exists() {
+            return Err(format!("Destination exists: {}", target.display()).into());
+        }
+
+        let max_size = self.config.max_write_size;
+        let target_clone = target.clone();
+        let size = spawn_blocking(move || {
+            let data = read_entry_sync(&file, &entry, max_size, &path)?;
+            write_new_file(&target_clone, &data)
+                .map_err(|e| ToolError::io(e, &target_clone.display().to_string()))?;
+            Ok::<_, ToolError>(data.len() as u64)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(size);
+        stats::record_written(size);
+        Ok(format!(
+            "Extracted {} from {} to {} ({})",
+            params.entry,
+            params.archive_path,
+            target.display(),
+            format_size(size)
+        ))
+    }
+
+    /// Packs a directory tree into a new zip archive.
+    #[rmcp::tool(
+        name = "create_archive",
+        description = "Packs the files under a directory into a new deflate-compressed .zip archive, walking up to --max-depth levels. Entries are named by their path relative to source; symlinks, empty directories, and denied paths are left out. Use include to keep only files matching a glob. Refuses an existing destination unless overwrite is true, a destination inside source, and inputs totalling more than --max-archive-input-size. Reports the file count and archive size.",
+        annotations(
+            title = "Create Archive",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn create_archive(
+        &self,
+        Parameters(params): Parameters<CreateArchiveParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let source = self
+            .security
+            .validate_directory(Path::new(&params.source))?;
+        let destination = self
+            .security
+            .validate_creatable_path(Path::new(&params.destination))?;
+        self.security
+            .ensure_writable(&destination, Path::new(&params.destination))?;
+        let is_zip = destination
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if !is_zip {
+            return Err(format!("destination must end in .zip: {}", params.destination).into());
+        }
+        if destination.starts_with(&source) {
+            return Err(format!(
+                "Destination {} is inside the source directory {}; choose a path outside it.",
+                params.destination, params.source
+            )
+            .into());
+        }
+        if destination.is_dir() {
+            return Err(FsError::NotAFile {
+                path: params.destination,
+            }
+            .into());
+        }
+        if destination.exists() && !params.overwrite.unwrap_or(false) {
+            return Err(format!(
+                "Destination exists: {}. Pass overwrite: true to replace it.",
+                params.destination
+            )
+            .into());
+        }
+
+        let include = params.include.as_deref().unwrap_or("**");
+        let matcher = globset::GlobSetBuilder::new()
+            .add(Glob::new(include).map_err(|e| FsError::PatternError(e.to_string()))?)
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+        let found = find_matching_files(
+            &source,
+            &matcher,
+            &[],
+            Traversal {
+                max_depth: self.config.max_depth,
+                gitignore: false,
+                exclude: globset::GlobSet::empty(),
+                security: self.security.clone(),
+            },
+            usize::MAX,
+            |_| true,
+            &ct,
+        )
+        .await?;
+        self.log_unreadable(&source, &found.skipped);
+        if found.files.is_empty() {
+            return Err(format!("No files to archive under {}", params.source).into());
+        }
+        let input_size: u64 = found.files.iter().map(|(_, size)| size).sum();
+        let max_input = self.config.max_archive_input_size;
+        if input_size > max_input {
+            return Err(format!(
+                "Archive input too large: {} files totalling {} under {} (max {}). Narrow it with include.",
+                found.files.len(),
+                format_size(input_size),
+                params.source,
+                format_size(max_input)
+            )
+            .into());
+        }
+
+        let file_count = found.files.len();
+        let denied = found.denied;
+        let dest_clone = destination.clone();
+        let archive_size =
+            spawn_blocking(move || write_zip_sync(&source, &found.files, &dest_clone))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| ToolError::io(e, &params.destination))?;
+        stats::record_read(input_size);
+        stats::record_written(archive_size);
+
+        let mut out = format!(
+            "Created {} with {file_count} files ({} in, {} archive)",
+            destination.display(),
+            format_size(input_size),
+            format_size(archive_size)
+        );
+        if denied > 0 {
+            out.push_str(&format!("\nSkipped {denied} denied paths"));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn make_service(dir: &TempDir, config: Config) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..config
+        })
+    }
+
+    async fn list(service: &FilesystemService, path: &Path) -> Result<String, ToolError> {
+        service
+            .list_archive(Parameters(ListArchiveParams {
+                path: path.to_string_lossy().to_string(),
+            }))
+            .await
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.add_directory("docs/", options).unwrap();
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn tar_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn lists_zip_entries_with_compression_info() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bundle.zip");
+        write_zip(&path, &[("docs/a.txt", b"hello"), ("b.bin", &[0u8; 2048])]);
+        let service = make_service(&dir, Config::default());
+
+        let output = list(&service, &path).await.unwrap();
+        assert!(output.contains("(zip, 3 entries,"), "{output}");
+        assert!(output.contains("[DIR]  docs/"));
+        assert!(output.contains("[FILE] docs/a.txt (5 B, 5 B stored)"));
+        assert!(output.contains("[FILE] b.bin (2.0 KB, 2.0 KB stored)"));
+        assert!(output.ends_with("Total: 2 files, 1 directories, 2.0 KB uncompressed"));
+    }
+
+    #[tokio::test]
+    async fn lists_tar_and_tar_gz_by_magic_bytes() {
+        let dir = TempDir::new().unwrap();
+        let tar = tar_bytes(&[("src/main.rs", b"fn main() {}"), ("README", b"hi")]);
+        // Extensions are deliberately misleading: detection uses the contents
+        let plain = dir.path().join("plain.bin");
+        std::fs::write(&plain, &tar).unwrap();
+        let gz = dir.path().join("packed.zip");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+        std::fs::write(&gz, encoder.finish().unwrap()).unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let output = list(&service, &plain).await.unwrap();
+        assert!(output.contains("(tar, 2 entries,"), "{output}");
+        assert!(output.contains("[FILE] src/main.rs (12 B)"));
+        assert!(output.contains("[FILE] README (2 B)"));
+
+        let output = list(&service, &gz).await.unwrap();
+        assert!(output.contains("(tar.gz, 2 entries,"), "{output}");
+        assert!(output.contains("[FILE] src/main.rs (12 B)"));
+    }
+
+    #[tokio::test]
+    async fn caps_entries_with_truncation_note() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("many.zip");
+        let names: Vec<String> = (0..5).map(|i| format!("f{i}.txt")).collect();
+        let files: Vec<(&str, &[u8])> = names.iter().map(|n| (n.as_str(), &b"x"[..])).collect();
+        write_zip(&path, &files);
+        let service = make_service(
+            &dir,
+            Config {
+                max_archive_entries: 2,
+                ..Default::default()
+            },
+        );
+
+        let output = list(&service, &path).await.unwrap();
+        assert_eq!(output.matches("\n[").count(), 2, "{output}");
+        assert!(output.contains("Total: 5 files, 1 directories"));
+        assert!(
+            output.ends_with(
+                "(Showing first 2 of 6 entries; raise --max-archive-entries to list more)"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn refuses_encrypted_zip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secret.zip");
+        write_zip(&path, &[("secret.txt", b"hidden")]);
+        // Set the "encrypted" bit in every local and central directory header
+        let mut bytes = std::fs::read(&path).unwrap();
+        for (signature, flag_offset) in [(b"PK\x03\x04", 6), (b"PK\x01\x02", 8)] {
+            let starts: Vec<usize> = bytes
+                .windows(4)
+                .enumerate()
+                .filter(|(_, w)| w == signature)
+                .map(|(i, _)| i)
+                .collect();
+            for start in starts {
+                bytes[start + flag_offset] |= 1;
+            }
+        }
+        std::fs::write(&path, bytes).unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let err = list(&service, &path).await.unwrap_err();
+        assert!(
+            err.message.starts_with("Encrypted zip archive:"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unsupported_format() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.zip");
+        std::fs::write(&path, "just text").unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let err = list(&service, &path).await.unwrap_err();
+        assert!(err.message.starts_with("Unsupported archive format:"));
+    }
+
+    #[tokio::test]
+    async fn archive_size_ceiling_is_separate_from_max_read_size() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.tar");
+        std::fs::write(&path, tar_bytes(&[("a", &[0u8; 4096])])).unwrap();
+
+        let service = make_service(
+            &dir,
+            Config {
+                max_read_size: 16,
+                ..Default::default()
+            },
+        );
+        assert!(list(&service, &path).await.is_ok());
+
+        let service = make_service(
+            &dir,
+            Config {
+                max_archive_size: 1024,
+                ..Default::default()
+            },
+        );
+        let err = list(&service, &path).await.unwrap_err();
+        assert!(
+            err.message.starts_with("File too large:"),
+            "{}",
+            err.message
+        );
+        assert_eq!(err.details.unwrap()["max"], 1024);
+    }
+
+    async fn extract(
+        service: &FilesystemService,
+        archive: &Path,
+        entry: &str,
+        destination: Option<&Path>,
+        to_response: Option<bool>,
+    ) -> Result<String, ToolError> {
+        service
+            .extract_from_archive(Parameters(ExtractFromArchiveParams {
+                archive_path: archive.to_string_lossy().to_string(),
+                entry: entry.to_string(),
+                destination: destination.map(|d| d.to_string_lossy().to_string()),
+                to_response,
+            }))
+            .await
+    }
+
+    fn deflated_zip(path: &Path, name: &str, data: &[u8]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file(name, options).unwrap();
+        zip.write_all(data).unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn extracts_zip_entry_below_destination() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("bundle.zip");
+        deflated_zip(&archive, "src/lib.rs", b"pub fn f() {}\n");
+        let dest = dir.path().join("out");
+        let service = make_service(&dir, Config::default());
+
+        let output = extract(&service, &archive, "src/lib.rs", Some(&dest), None)
+            .await
+            .unwrap();
+        assert!(output.starts_with("Extracted src/lib.rs from"), "{output}");
+        assert!(output.ends_with("(14 B)"));
+        let written = dest.join("src").join("lib.rs");
+        assert_eq!(std::fs::read(&written).unwrap(), b"pub fn f() {}\n");
+
+        let err = extract(&service, &archive, "src/lib.rs", Some(&dest), None)
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Destination exists"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn extracts_tar_gz_entry_to_response() {
+        let dir = TempDir::new().unwrap();
+        let tar = tar_bytes(&[("notes/todo.txt", b"ship it")]);
+        let archive = dir.path().join("notes.tar.gz");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+        std::fs::write(&archive, encoder.finish().unwrap()).unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let output = extract(&service, &archive, "notes/todo.txt", None, Some(true))
+            .await
+            .unwrap();
+        assert!(output.ends_with("(7 B):\n\nship it"), "{output}");
+        assert!(!dir.path().join("notes").exists());
+
+        let err = extract(&service, &archive, "notes/missing.txt", None, Some(true))
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Entry not found in archive"));
+    }
+
+    #[tokio::test]
+    async fn rejects_zip_slip_entry() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("evil.zip");
+        deflated_zip(&archive, "../evil", b"gotcha");
+        let dest = dir.path().join("out");
+        let service = make_service(&dir, Config::default());
+
+        let err = extract(&service, &archive, "../evil", Some(&dest), None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Unsafe entry path in archive: ../evil"),
+            "{err}"
+        );
+        assert!(!dir.path().join("evil").exists());
+        assert!(!dest.exists());
+        assert!(entry_target("/etc/passwd").is_err());
+        assert_eq!(entry_target("./a/b").unwrap(), Path::new("a/b"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rejects_entry_through_symlinked_directory() {
+        let dir = TempDir::new().unwrap();
+        let outside = dir.path().join("outside");
+        let dest = dir.path().join("out");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::os::unix::fs::symlink(&outside, dest.join("link")).unwrap();
+        let archive = dir.path().join("a.zip");
+        deflated_zip(&archive, "link/file.txt", b"x");
+        let service = make_service(&dir, Config::default());
+
+        let err = extract(&service, &archive, "link/file.txt", Some(&dest), None)
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Unsafe entry path"), "{err}");
+        assert!(!outside.join("file.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_entry_larger_than_max_write_size() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("big.zip");
+        deflated_zip(&archive, "big.txt", &[b'a'; 4096]);
+        let service = make_service(
+            &dir,
+            Config {
+                max_write_size: 1024,
+                ..Default::default()
+            },
+        );
+
+        let err = extract(&service, &archive, "big.txt", Some(dir.path()), None)
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("File too large: big.txt"), "{err}");
+        assert!(!dir.path().join("big.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn requires_destination_unless_to_response() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("a.zip");
+        deflated_zip(&archive, "a.txt", b"a");
+        let service = make_service(&dir, Config::default());
+
+        let err = extract(&service, &archive, "a.txt", None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "destination is required unless to_response is true"
+        );
+    }
+
+    async fn create(
+        service: &FilesystemService,
+        source: &Path,
+        destination: &Path,
+        include: Option<&str>,
+        overwrite: Option<bool>,
+    ) -> Result<String, ToolError> {
+        service
+            .create_archive(
+                Parameters(CreateArchiveParams {
+                    source: source.to_string_lossy().to_string(),
+                    destination: destination.to_string_lossy().to_string(),
+                    include: include.map(str::to_string),
+                    overwrite,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+    }
+
+    fn zip_names(path: &Path) -> Vec<String> {
+        let archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn creates_zip_from_directory() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("site");
+        std::fs::create_dir_all(source.join("css")).unwrap();
+        std::fs::write(source.join("index.html"), "<html></html>").unwrap();
+        std::fs::write(source.join("css").join("app.css"), "body {}").unwrap();
+        std::fs::write(source.join(".env"), "SECRET=1").unwrap();
+        let archive = dir.path().join("site.zip");
+        let service = make_service(
+            &dir,
+            Config {
+                deny: vec![".env".into()],
+                ..Default::default()
+            },
+        );
+
+        let output = create(&service, &source, &archive, None, None)
+            .await
+            .unwrap();
+        assert!(output.contains("with 2 files (20 B in,"), "{output}");
+        assert!(output.ends_with("Skipped 1 denied paths"));
+        assert_eq!(zip_names(&archive), ["css/app.css", "index.html"]);
+        assert!(!dir.path().join(".site.zip.partial").exists());
+
+        // The result round-trips through extract_from_archive
+        let text = extract(&service, &archive, "css/app.css", None, Some(true))
+            .await
+            .unwrap();
+        assert!(text.ends_with("body {}"));
+    }
+
+    #[tokio::test]
+    async fn include_glob_and_max_depth_limit_entries() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("src");
+        std::fs::create_dir_all(source.join("a").join("b")).unwrap();
+        std::fs::write(source.join("main.rs"), "").unwrap();
+        std::fs::write(source.join("notes.md"), "").unwrap();
+        std::fs::write(source.join("a").join("lib.rs"), "").unwrap();
+        std::fs::write(source.join("a").join("b").join("deep.rs"), "").unwrap();
+        let archive = dir.path().join("rust.zip");
+        let service = make_service(
+            &dir,
+            Config {
+                max_depth: 1,
+                ..Default::default()
+            },
+        );
+
+        create(&service, &source, &archive, Some("**/*.rs"), None)
+            .await
+            .unwrap();
+        assert_eq!(zip_names(&archive), ["a/lib.rs", "main.rs"]);
+    }
+
+    #[tokio::test]
+    async fn refuses_existing_destination_unless_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("src");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("a.txt"), "a").unwrap();
+        let archive = dir.path().join("out.zip");
+        std::fs::write(&archive, "old").unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let err = create(&service, &source, &archive, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Destination exists"), "{err}");
+        assert_eq!(std::fs::read(&archive).unwrap(), b"old");
+
+        create(&service, &source, &archive, None, Some(true))
+            .await
+            .unwrap();
+        assert_eq!(zip_names(&archive), ["a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn refuses_destination_inside_source() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let err = create(
+            &service,
+            dir.path(),
+            &dir.path().join("self.zip"),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.message.contains("is inside the source directory"),
+            "{err}"
+        );
+        assert!(!dir.path().join("self.zip").exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_input_over_cap() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("src");
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("a.bin"), [0u8; 600]).unwrap();
+        std::fs::write(source.join("b.bin"), [0u8; 600]).unwrap();
+        let archive = dir.path().join("out.zip");
+        let service = make_service(
+            &dir,
+            Config {
+                max_archive_input_size: 1000,
+                ..Default::default()
+            },
+        );
+
+        let err = create(&service, &source, &archive, None, None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.starts_with("Archive input too large: 2 files"),
+            "{err}"
+        );
+        assert!(!archive.exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_path_outside_allowed_dirs() {
+        let dir = TempDir::new().unwrap();
+        let service = make_service(&dir, Config::default());
+        let err = list(&service, Path::new("/etc/passwd")).await.unwrap_err();
+        assert!(err.message.starts_with("Access denied"));
+    }
+}
@@ -0,0 +1,752 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use super::count::{Visit, walk_sync};
+use super::util::{format_size, is_binary, sha256_file, spawn_blocking, to_hex};
+
+/// Parameters for the verify_checksums tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct VerifyChecksumsParams {
+    /// Absolute path to a sha256sum-format manifest ("<hex>  <relative path>" per line)
+    path: String,
+}
+
+/// Parameters for the snapshot_directory tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct SnapshotDirectoryParams {
+    /// Absolute path to the directory to snapshot
+    path: String,
+    /// Glob patterns, relative to the directory, for files and directories to
+    /// leave out (e.g. "target", "**/*.log"); matching directories are not entered
+    exclude: Option<Vec<String>>,
+}
+
+/// One well-formed manifest line.
+#[derive(Debug, PartialEq)]
+struct ManifestEntry {
+    /// Expected SHA-256, lowercase hex
+    digest: String,
+    /// Expected size in bytes, from a `snapshot_directory` manifest
+    size: Option<u64>,
+    /// Path as written in the manifest, relative to its directory
+    path: String,
+}
+
+/// The outcome of checking one entry.
+#[derive(Debug, PartialEq)]
+enum EntryStatus {
+    Ok,
+    /// The file's digest differs from the manifest's
+    Failed,
+    Missing,
+    /// The path leaves the allowed directories or matches a deny pattern
+    Denied,
+    /// The file exists but could not be hashed
+    Unreadable(String),
+}
+
+impl std::fmt::Display for EntryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryStatus::Ok => f.write_str("OK"),
+            EntryStatus::Failed => f.write_str("FAILED"),
+            EntryStatus::Missing => f.write_str("MISSING"),
+            EntryStatus::Denied => f.write_str("DENIED"),
+            EntryStatus::Unreadable(reason) => write!(f, "FAILED to read ({reason})"),
+        }
+    }
+}
+
+/// Parses one line in the coreutils `sha256sum` format: 64 hex digits, a
+/// space, a space or `*` (binary mode), then the path. A leading backslash
+/// marks a path with `\\`, `\n`, or `\r` escapes. The `snapshot_directory`
+/// form, with the size in bytes and two spaces between hash and path, is
+/// accepted too.
+fn parse_manifest_line(line: &str) -> Option<ManifestEntry> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let digest = line.get(..64)?;
+    if !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let rest = line[64..].strip_prefix(' ')?;
+    let path = rest.strip_prefix([' ', '*'])?;
+    if path.is_empty() {
+        return None;
+    }
+    let (size, path) = match path.split_once("  ") {
+        Some((size, name)) if !name.is_empty() && size.bytes().all(|b| b.is_ascii_digit()) => {
+            match size.parse() {
+                Ok(size) => (Some(size), name),
+                Err(_) => (None, path),
+            }
+        }
+        _ => (None, path),
+    };
+    let path = if escaped {
+        unescape(path)?
+    } else {
+        path.to_string()
+    };
+    Some(ManifestEntry {
+        digest: digest.to_ascii_lowercase(),
+        size,
+        path,
+    })
+}
+
+/// Formats a manifest line, escaping the path as `sha256sum` does when it
+/// holds a backslash or line break.
+fn manifest_line(digest: &str, size: u64, path: &str) -> String {
+    if path.contains(['\\', '\n', '\r']) {
+        let escaped = path
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\\{digest}  {size}  {escaped}")
+    } else {
+        format!("{digest}  {size}  {path}")
+    }
+}
+
+fn unescape(path: &str) -> Option<String> {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Resolves `.` and `..` in `path` without touching the filesystem.
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Checks one entry against the file it names, relative to `manifest_dir`.
+/// The path is first checked lexically so that entries pointing outside the
+/// sandbox are denied before anything on disk is looked at, then validated
+/// again after symlinks are resolved.
+fn verify_entry(
+    entry: &ManifestEntry,
+    manifest_dir: &Path,
+    security: &SecurityContext,
+) -> (EntryStatus, u64) {
+    let joined = manifest_dir.join(&entry.path);
+    let lexical = lexical_normalize(&joined);
+    if security.relative_to_allowed(&lexical).is_none() || security.is_denied(&lexical) {
+        return (EntryStatus::Denied, 0);
+    }
+    let canonical = match security.validate_file(&joined) {
+        Ok(canonical) => canonical,
+        Err(FsError::PathDenied { .. } | FsError::DeniedByPattern { .. }) => {
+            return (EntryStatus::Denied, 0);
+        }
+        Err(FsError::NotFound { .. }) => return (EntryStatus::Missing, 0),
+        Err(FsError::NotAFile { .. }) => {
+            return (EntryStatus::Unreadable("not a regular file".to_string()), 0);
+        }
+        Err(err) => return (EntryStatus::Unreadable(err.to_string()), 0),
+    };
+    let size = std::fs::metadata(&canonical).map_or(0, |m| m.len());
+    if entry.size.is_some_and(|expected| expected != size) {
+        return (EntryStatus::Failed, 0);
+    }
+    match sha256_file(&canonical) {
+        Ok(actual) if actual == entry.digest => (EntryStatus::Ok, size),
+        Ok(_) => (EntryStatus::Failed, size),
+        Err(err) => (EntryStatus::Unreadable(err.to_string()), 0),
+    }
+}
+
+/// Verifies `entries` in order on the current thread, returning each status
+/// and the number of bytes hashed. Fails with [`FsError::Cancelled`] if `ct`
+/// fires between entries.
+fn verify_entries_sync(
+    entries: &[ManifestEntry],
+    manifest_dir: &Path,
+    security: &SecurityContext,
+    ct: &CancellationToken,
+) -> Result<(Vec<EntryStatus>, u64), FsError> {
+    let mut statuses = Vec::with_capacity(entries.len());
+    let mut hashed = 0;
+    for entry in entries {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let (status, bytes) = verify_entry(entry, manifest_dir, security);
+        hashed += bytes;
+        statuses.push(status);
+    }
+    Ok((statuses, hashed))
+}
+
+/// A content manifest of a directory built by [`snapshot_sync`].
+#[derive(Debug, Default)]
+struct Snapshot {
+    /// (relative path, sha256, size), sorted by path
+    files: Vec<(String, String, u64)>,
+    /// (relative path, reason), sorted by path
+    skipped: Vec<(String, String)>,
+    denied: u64,
+    unreadable_dirs: u64,
+    /// Whether the walk stopped at the file cap
+    truncated: bool,
+}
+
+impl Snapshot {
+    /// The manifest's file lines, each newline-terminated.
+    fn manifest(&self) -> String {
+        self.files
+            .iter()
+            .map(|(path, digest, size)| manifest_line(digest, *size, path) + "\n")
+            .collect()
+    }
+}
+
+/// `path` relative to `root` with `/` separators, as written in manifests.
+pub(crate) fn manifest_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Walks `root` up to `max_depth` levels and hashes every regular file not
+/// matching `exclude`, in sorted path order. Files over `max_size`, symlinks,
+/// and files that cannot be read are listed as skipped. Stops collecting at
+/// `max_files`, and fails with [`FsError::Cancelled`] if `ct` fires.
+fn snapshot_sync(
+    root: &Path,
+    security: &SecurityContext,
+    exclude: &GlobSet,
+    max_depth: usize,
+    max_files: usize,
+    max_size: u64,
+    ct: &CancellationToken,
+) -> Result<Snapshot, FsError> {
+    let mut snapshot = Snapshot::default();
+    let mut found: Vec<(String, PathBuf)> = Vec::new();
+    let mut denied = 0;
+    snapshot.unreadable_dirs = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            let relative = manifest_path(root, path);
+            if exclude.is_match(&relative) {
+                return Visit::Skip;
+            }
+            if file_type.is_symlink() {
+                snapshot
+                    .skipped
+                    .push((relative, "symlink, not followed".to_string()));
+            } else if file_type.is_file() {
+                if found.len() >= max_files {
+                    snapshot.truncated = true;
+                    return Visit::Stop;
+                }
+                found.push((relative, path.to_path_buf()));
+            }
+            Visit::Continue
+        },
+    )?;
+    snapshot.denied = denied;
+
+    found.sort();
+    for (relative, path) in found {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let size = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) => {
+                snapshot
+                    .skipped
+                    .push((relative, format!("unreadable: {err}")));
+                continue;
+            }
+        };
+        if size > max_size {
+            let reason = format!("over max read size, {}", format_size(size));
+            snapshot.skipped.push((relative, reason));
+            continue;
+        }
+        match sha256_file(&path) {
+            Ok(digest) => snapshot.files.push((relative, digest, size)),
+            Err(err) => snapshot
+                .skipped
+                .push((relative, format!("unreadable: {err}"))),
+        }
+    }
+    snapshot.skipped.sort();
+    Ok(snapshot)
+}
+
+/// Renders the snapshot: a `#` header with the totals and tree hash, the
+/// manifest lines, then the skipped files as `#` comments. The header comes
+/// first so it survives output truncation, and comments are ignored by
+/// verify_checksums.
+fn format_snapshot(root: &Path, snapshot: &Snapshot, max_files: usize) -> String {
+    let manifest = snapshot.manifest();
+    let total: u64 = snapshot.files.iter().map(|(_, _, size)| size).sum();
+    let mut out = format!(
+        "# Snapshot of {}\n# {} files, {}; tree sha256 {}\n",
+        root.display(),
+        snapshot.files.len(),
+        format_size(total),
+        to_hex(&Sha256::digest(manifest.as_bytes()))
+    );
+    if snapshot.truncated {
+        out.push_str(&format!(
+            "# INCOMPLETE: stopped after {max_files} files; raise --max-snapshot-files or exclude more\n"
+        ));
+    }
+    let mut notes = Vec::new();
+    if !snapshot.skipped.is_empty() {
+        notes.push(format!("{} skipped", snapshot.skipped.len()));
+    }
+    if snapshot.denied > 0 {
+        notes.push(format!("{} denied paths left out", snapshot.denied));
+    }
+    if snapshot.unreadable_dirs > 0 {
+        notes.push(format!(
+            "{} unreadable directories",
+            snapshot.unreadable_dirs
+        ));
+    }
+    if !notes.is_empty() {
+        out.push_str(&format!("# {}\n", notes.join(", ")));
+    }
+    out.push_str(&manifest);
+    for (path, reason) in &snapshot.skipped {
+        out.push_str(&format!("# skipped ({reason}): {path}\n"));
+    }
+    out.pop();
+    out
+}
+
+#[rmcp::tool_router(router = "checksum_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Checks files against a sha256sum-format manifest.
+    #[rmcp::tool(
+        name = "verify_checksums",
+        description = "Verifies files against a sha256sum-format manifest (\"<sha256 hex>  <path>\" per line, as written by `sha256sum`), resolving each path relative to the manifest's directory. Reports OK, FAILED, MISSING, or DENIED per entry and a summary. Paths outside the allowed directories are reported as DENIED and never opened. Checks at most --max-checksum-entries entries per call.",
+        annotations(
+            title = "Verify Checksums",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn verify_checksums(
+        &self,
+        Parameters(params): Parameters<VerifyChecksumsParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max = self.config.max_read_size as u64;
+        if size > max {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max,
+            }
+            .into());
+        }
+        let content = tokio::fs::read(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+        if is_binary(&content) {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        }
+
+        let text = String::from_utf8_lossy(&content);
+        let mut entries = Vec::new();
+        let mut improper = 0;
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_manifest_line(line) {
+                Some(entry) => entries.push(entry),
+                None => improper += 1,
+            }
+        }
+        if entries.is_empty() {
+            return Err(format!(
+                "No checksum lines found in {}; expected \"<sha256 hex>  <path>\" per line",
+                params.path
+            )
+            .into());
+        }
+        let total = entries.len();
+        let limit = self.config.max_checksum_entries;
+        entries.truncate(limit);
+
+        let manifest_dir = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let security = self.security.clone();
+        let (entries, (statuses, hashed)) = spawn_blocking(move || {
+            let result = verify_entries_sync(&entries, &manifest_dir, &security, &ct);
+            result.map(|verified| (entries, verified))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(hashed);
+
+        let mut out = String::new();
+        let (mut ok, mut failed, mut missing, mut denied) = (0, 0, 0, 0);
+        for (entry, status) in entries.iter().zip(&statuses) {
+            match status {
+                EntryStatus::Ok => ok += 1,
+                EntryStatus::Failed | EntryStatus::Unreadable(_) => failed += 1,
+                EntryStatus::Missing => missing += 1,
+                EntryStatus::Denied => denied += 1,
+            }
+            out.push_str(&format!("{}: {status}\n", entry.path));
+        }
+        out.push_str(&format!(
+            "\nSummary: {} checked: {ok} OK, {failed} FAILED, {missing} MISSING, {denied} DENIED",
+            statuses.len()
+        ));
+        if improper > 0 {
+            let noun = if improper == 1 { "line" } else { "lines" };
+            out.push_str(&format!("; {improper} improperly formatted {noun} skipped"));
+        }
+        if total > limit {
+            out.push_str(&format!(
+                "\n(Stopped after {limit} of {total} entries; raise --max-checksum-entries to verify the rest)"
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Builds a sorted sha256 manifest of a directory tree.
+    #[rmcp::tool(
+        name = "snapshot_directory",
+        description = "Builds a content manifest of a directory: one \"<sha256>  <size>  <relative path>\" line per file, sorted by path, under a header with the file count, total size, and a tree hash (the sha256 of the manifest lines). Identical trees give identical output, so snapshots can be diffed, or saved and checked later with verify_checksums. Walks up to --max-depth levels and at most --max-snapshot-files files. Symlinks are not followed, and files over --max-read-size are listed as skipped. Use exclude to leave out build output and similar.",
+        annotations(
+            title = "Snapshot Directory",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn snapshot_directory(
+        &self,
+        Parameters(params): Parameters<SnapshotDirectoryParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        let mut builder = GlobSetBuilder::new();
+        for pattern in params.exclude.iter().flatten() {
+            let glob = Glob::new(pattern)
+                .map_err(|e| FsError::PatternError(format!("exclude pattern '{pattern}': {e}")))?;
+            builder.add(glob);
+        }
+        let exclude = builder
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let max_files = self.config.max_snapshot_files;
+        let max_size = self.config.max_read_size as u64;
+        let walk_root = root.clone();
+        let snapshot = spawn_blocking(move || {
+            snapshot_sync(
+                &walk_root, &security, &exclude, max_depth, max_files, max_size, &ct,
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(snapshot.files.iter().map(|(_, _, size)| size).sum());
+        Ok(format_snapshot(&root, &snapshot, max_files))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use crate::tools::util::to_hex;
+    use sha2::{Digest, Sha256};
+    use tempfile::TempDir;
+
+    fn digest(data: &[u8]) -> String {
+        to_hex(&Sha256::digest(data))
+    }
+
+    #[test]
+    fn parses_coreutils_lines() {
+        let hex = "a".repeat(64);
+        let cases = [
+            (format!("{hex}  dist/app.tar.gz"), Some("dist/app.tar.gz")),
+            (format!("{hex} *bin/tool"), Some("bin/tool")),
+            (format!("{}  upper.txt", "A".repeat(64)), Some("upper.txt")),
+            (format!("\\{hex}  a\\\\b\\nc"), Some("a\\b\nc")),
+            (format!("{hex}  12  sized.txt"), Some("sized.txt")),
+            (format!("{hex}  2024  notes.txt"), Some("notes.txt")),
+            (format!("{hex}  "), None),
+            (format!("{hex}x file"), None),
+            (format!("{}  short", "a".repeat(63)), None),
+            ("SHA256 (file) = abc".to_string(), None),
+        ];
+        for (line, expected) in cases {
+            let parsed = parse_manifest_line(&line);
+            assert_eq!(
+                parsed.as_ref().map(|e| e.path.as_str()),
+                expected,
+                "{line:?}"
+            );
+            if let Some(entry) = parsed {
+                assert_eq!(entry.digest, hex);
+            }
+        }
+    }
+
+    #[test]
+    fn manifest_lines_round_trip() {
+        let hex = "b".repeat(64);
+        for path in ["plain/file.txt", "back\\slash", "new\nline"] {
+            let entry = parse_manifest_line(&manifest_line(&hex, 42, path)).unwrap();
+            assert_eq!(entry.path, path);
+            assert_eq!(entry.size, Some(42));
+            assert_eq!(entry.digest, hex);
+        }
+        let entry = parse_manifest_line(&format!("{hex}  plain.txt")).unwrap();
+        assert_eq!(entry.size, None);
+    }
+
+    #[test]
+    fn lexical_normalize_resolves_dots() {
+        assert_eq!(
+            lexical_normalize(Path::new("/a/b/./../c/../../d")),
+            PathBuf::from("/d")
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_checksums_reports_each_entry() {
+        let sandbox = TempDir::new().unwrap();
+        let root = sandbox.path().canonicalize().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        let outside_rel = format!(
+            "../{}/secret.txt",
+            outside.path().file_name().unwrap().to_string_lossy()
+        );
+
+        std::fs::create_dir(root.join("dist")).unwrap();
+        std::fs::write(root.join("dist/good.bin"), "good").unwrap();
+        std::fs::write(root.join("dist/bad.bin"), "corrupted").unwrap();
+        let manifest = root.join("SHA256SUMS");
+        std::fs::write(
+            &manifest,
+            format!(
+                "{}  dist/good.bin\n{} *dist/bad.bin\n{}  dist/gone.bin\n{}  {outside_rel}\nnot a checksum line\n",
+                digest(b"good"),
+                digest(b"original"),
+                digest(b"gone"),
+                digest(b"secret"),
+            ),
+        )
+        .unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![root.clone()],
+            ..Default::default()
+        });
+
+        let output = service
+            .verify_checksums(
+                Parameters(VerifyChecksumsParams {
+                    path: manifest.to_string_lossy().to_string(),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "dist/good.bin: OK\ndist/bad.bin: FAILED\ndist/gone.bin: MISSING\n{outside_rel}: DENIED\n\n\
+                 Summary: 4 checked: 1 OK, 1 FAILED, 1 MISSING, 1 DENIED; 1 improperly formatted line skipped"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_checksums_stops_at_entry_cap() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a"), "a").unwrap();
+        let manifest = root.join("sums.txt");
+        std::fs::write(&manifest, format!("{0}  a\n{0}  a\n{0}  a\n", digest(b"a"))).unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![root],
+            max_checksum_entries: 2,
+            ..Default::default()
+        });
+
+        let output = service
+            .verify_checksums(
+                Parameters(VerifyChecksumsParams {
+                    path: manifest.to_string_lossy().to_string(),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(
+            output.ends_with(
+                "Summary: 2 checked: 2 OK, 0 FAILED, 0 MISSING, 0 DENIED\n\
+                 (Stopped after 2 of 3 entries; raise --max-checksum-entries to verify the rest)"
+            ),
+            "{output}"
+        );
+    }
+
+    fn snapshot_service(
+        root: &Path,
+        max_read_size: usize,
+        max_snapshot_files: usize,
+    ) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![root.to_path_buf()],
+            max_read_size,
+            max_snapshot_files,
+            ..Default::default()
+        })
+    }
+
+    async fn snapshot(service: &FilesystemService, root: &Path, exclude: &[&str]) -> String {
+        service
+            .snapshot_directory(
+                Parameters(SnapshotDirectoryParams {
+                    path: root.to_string_lossy().to_string(),
+                    exclude: Some(exclude.iter().map(|p| p.to_string()).collect()),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn snapshot_directory_is_sorted_and_verifiable() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join("src/nested/b.rs"), "b").unwrap();
+        std::fs::write(root.join("src/a.rs"), "aa").unwrap();
+        std::fs::write(root.join("README"), "readme").unwrap();
+        std::fs::write(root.join("target/out.bin"), "build output").unwrap();
+        std::fs::write(root.join("big.dat"), "x".repeat(100)).unwrap();
+        let service = snapshot_service(&root, 64, 100);
+
+        let output = snapshot(&service, &root, &["target"]).await;
+        let manifest = format!(
+            "{}  6  README\n{}  2  src/a.rs\n{}  1  src/nested/b.rs\n",
+            digest(b"readme"),
+            digest(b"aa"),
+            digest(b"b")
+        );
+        assert_eq!(
+            output,
+            format!(
+                "# Snapshot of {}\n# 3 files, 9 B; tree sha256 {}\n# 1 skipped\n{manifest}\
+                 # skipped (over max read size, 100 B): big.dat",
+                root.display(),
+                digest(manifest.as_bytes())
+            )
+        );
+        // Deterministic: a second run gives the same output
+        assert_eq!(snapshot(&service, &root, &["target"]).await, output);
+
+        // The output is itself a manifest verify_checksums accepts
+        std::fs::write(root.join("snapshot.sha256"), &output).unwrap();
+        std::fs::write(root.join("src/a.rs"), "changed").unwrap();
+        let verified = snapshot_service(&root, 1024, 100)
+            .verify_checksums(
+                Parameters(VerifyChecksumsParams {
+                    path: root.join("snapshot.sha256").to_string_lossy().to_string(),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            verified,
+            "README: OK\nsrc/a.rs: FAILED\nsrc/nested/b.rs: OK\n\n\
+             Summary: 3 checked: 2 OK, 1 FAILED, 0 MISSING, 0 DENIED"
+        );
+    }
+
+    #[tokio::test]
+    async fn snapshot_directory_stops_at_file_cap() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(root.join(name), name).unwrap();
+        }
+        let service = snapshot_service(&root, 1024, 2);
+
+        let output = snapshot(&service, &root, &[]).await;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(
+            lines[1].starts_with("# 2 files, 2 B; tree sha256 "),
+            "{output}"
+        );
+        assert_eq!(
+            lines[2],
+            "# INCOMPLETE: stopped after 2 files; raise --max-snapshot-files or exclude more"
+        );
+        assert_eq!(lines.len(), 5, "{output}");
+    }
+}
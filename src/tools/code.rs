@@ -0,0 +1,575 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::util::is_binary;
+
+/// Parameters for the outline_code tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct OutlineCodeParams {
+    /// Absolute path to the source file
+    path: String,
+}
+
+/// A language `outline_code` has heuristics for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+}
+
+/// Supported languages and their extensions, as listed in error messages.
+const SUPPORTED: &str = "Rust (.rs), Python (.py, .pyi), JavaScript/TypeScript (.js, .jsx, .mjs, .cjs, .ts, .tsx, .mts, .cts), Go (.go)";
+
+impl Language {
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+                Some(Language::JavaScript)
+            }
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Language::Rust => "Rust",
+            Language::Python => "Python",
+            Language::JavaScript => "JavaScript/TypeScript",
+            Language::Go => "Go",
+        })
+    }
+}
+
+/// An item found by [`outline`].
+#[derive(Debug, PartialEq)]
+struct Item {
+    /// 0 for top-level items, 1 for members of a class, impl, or trait
+    depth: usize,
+    signature: String,
+    /// 1-based line the item starts on
+    line: usize,
+}
+
+/// Longest signature shown before it is cut with "...".
+const MAX_SIGNATURE_CHARS: usize = 160;
+
+/// Width of a line's leading whitespace, counting a tab as four columns.
+fn indent_of(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// The leading identifier of `s`: letters, digits, `_`, and `$`.
+fn leading_word(s: &str) -> &str {
+    let end = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(s.len());
+    &s[..end]
+}
+
+/// Repeatedly strips any of `words` (followed by whitespace) from the front
+/// of `s`.
+fn strip_words<'a>(mut s: &'a str, words: &[&str]) -> &'a str {
+    loop {
+        let word = leading_word(s);
+        let rest = &s[word.len()..];
+        if word.is_empty() || !words.contains(&word) || !rest.starts_with([' ', '\t']) {
+            return s;
+        }
+        s = rest.trim_start();
+    }
+}
+
+/// The signature shown for an item: its first line, without a trailing
+/// opening brace or semicolon (or colon, for Python).
+fn signature(content: &str, language: Language) -> String {
+    let mut text = content.trim();
+    let terminators: &[char] = match language {
+        Language::Python => &[':'],
+        _ => &['{', ';'],
+    };
+    text = text.trim_end_matches(terminators).trim_end();
+    if text.chars().count() > MAX_SIGNATURE_CHARS {
+        let cut: String = text.chars().take(MAX_SIGNATURE_CHARS).collect();
+        return format!("{cut}...");
+    }
+    text.to_string()
+}
+
+/// The keyword of a Rust item line such as `pub(crate) async fn`, after
+/// visibility and qualifiers.
+fn rust_keyword(content: &str) -> Option<&str> {
+    let mut s = content;
+    if let Some(rest) = s.strip_prefix("pub") {
+        if rest.starts_with('(') {
+            s = rest[rest.find(')')? + 1..].trim_start();
+        } else if rest.starts_with([' ', '\t']) {
+            s = rest.trim_start();
+        }
+    }
+    s = strip_words(s, &["async", "const", "unsafe", "default"]);
+    if let Some(rest) = s.strip_prefix("extern") {
+        // extern "C" fn, but not extern crate
+        let rest = rest.trim_start();
+        if let Some(abi) = rest.strip_prefix('"') {
+            s = abi[abi.find('"')? + 1..].trim_start();
+        } else {
+            s = rest;
+        }
+    }
+    let word = leading_word(s);
+    match word {
+        "fn" | "struct" | "enum" | "union" | "trait" | "impl" | "mod" | "type" => Some(word),
+        "macro_rules" if s[word.len()..].starts_with('!') => Some(word),
+        _ => None,
+    }
+}
+
+/// The keyword of a JavaScript or TypeScript top-level line, `export` for
+/// exports of anything else.
+fn js_keyword(content: &str) -> Option<&str> {
+    let s = strip_words(
+        content,
+        &["export", "default", "declare", "async", "abstract"],
+    );
+    match leading_word(s) {
+        word @ ("function" | "class" | "interface" | "type" | "enum" | "namespace") => Some(word),
+        _ if leading_word(content) == "export" => Some("export"),
+        _ => None,
+    }
+}
+
+/// Whether a line inside a JavaScript class body declares a method.
+fn is_js_method(content: &str) -> bool {
+    let s = strip_words(
+        content,
+        &[
+            "public",
+            "private",
+            "protected",
+            "static",
+            "async",
+            "get",
+            "set",
+            "readonly",
+            "abstract",
+            "override",
+        ],
+    );
+    let s = s.trim_start_matches(['*', '#']);
+    let name = leading_word(s);
+    if name.is_empty()
+        || matches!(
+            name,
+            "if" | "for" | "while" | "switch" | "catch" | "return" | "function"
+        )
+    {
+        return false;
+    }
+    let rest = s[name.len()..].trim_start_matches('?');
+    rest.starts_with('(') || rest.starts_with('<')
+}
+
+/// Open class, impl, or trait whose members are being collected.
+struct Container {
+    /// Indent of its members, set by the first line inside it
+    member_indent: Option<usize>,
+}
+
+/// Extracts the top-level items of `text`, and the members of classes,
+/// impls, and traits one level down. This is a line-based approximation, not
+/// a parser: items are recognized by leading keywords on unindented lines,
+/// block comments and Python triple-quoted strings are skipped, and a
+/// container ends at the next unindented line (in Python) or closing brace.
+fn outline(text: &str, language: Language) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut container: Option<Container> = None;
+    let mut in_block_comment = false;
+    let mut in_docstring = false;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let content = line.trim();
+        if language == Language::Python {
+            let quotes = content.matches("\"\"\"").count() + content.matches("'''").count();
+            if in_docstring {
+                in_docstring = quotes % 2 == 0;
+                continue;
+            }
+            in_docstring = quotes % 2 == 1;
+        } else if in_block_comment || content.starts_with("/*") {
+            in_block_comment = !content.contains("*/");
+            continue;
+        }
+        let comment = if language == Language::Python {
+            "#"
+        } else {
+            "//"
+        };
+        if content.is_empty() || content.starts_with(comment) {
+            continue;
+        }
+
+        let indent = indent_of(line);
+        if indent > 0 {
+            let Some(open) = &mut container else {
+                continue;
+            };
+            let member_indent = *open.member_indent.get_or_insert(indent);
+            let is_member = indent == member_indent
+                && match language {
+                    Language::Rust => rust_keyword(content).is_some(),
+                    Language::Python => {
+                        matches!(
+                            leading_word(strip_words(content, &["async"])),
+                            "def" | "class"
+                        )
+                    }
+                    Language::JavaScript => is_js_method(content),
+                    Language::Go => false,
+                };
+            if is_member {
+                items.push(Item {
+                    depth: 1,
+                    signature: signature(content, language),
+                    line: number,
+                });
+            }
+            continue;
+        }
+
+        // Brace languages close a container with an unindented brace; lines
+        // such as a where clause or a lone { leave it open
+        if language == Language::Python || content.starts_with('}') {
+            container = None;
+        }
+        let keyword = match language {
+            Language::Rust => rust_keyword(content),
+            Language::Python => match leading_word(strip_words(content, &["async"])) {
+                word @ ("def" | "class") => Some(word),
+                _ => None,
+            },
+            Language::JavaScript => js_keyword(content),
+            Language::Go => match leading_word(content) {
+                word @ ("func" | "type") => Some(word),
+                _ => None,
+            },
+        };
+        let Some(keyword) = keyword else {
+            continue;
+        };
+        items.push(Item {
+            depth: 0,
+            signature: signature(content, language),
+            line: number,
+        });
+        let opens_block = match language {
+            Language::Python => true,
+            _ => !content.ends_with(';') && !content.ends_with('}'),
+        };
+        let is_container = match language {
+            Language::Rust => matches!(keyword, "impl" | "trait" | "mod"),
+            Language::Python => keyword == "class",
+            Language::JavaScript => keyword == "class",
+            Language::Go => false,
+        };
+        container = (is_container && opens_block).then_some(Container {
+            member_indent: None,
+        });
+    }
+    items
+}
+
+/// Renders the items as a list indented by depth, under a note that the
+/// outline is approximate.
+fn format_outline(items: &[Item], language: Language) -> String {
+    let mut out =
+        format!("Language: {language} (approximate outline from line-based heuristics)\n\n");
+    if items.is_empty() {
+        out.push_str("Items: none");
+        return out;
+    }
+    out.push_str(&format!("Items ({}):\n", items.len()));
+    for item in items {
+        out.push_str(&format!(
+            "{}- {} (line {})\n",
+            "  ".repeat(item.depth),
+            item.signature,
+            item.line
+        ));
+    }
+    out.pop();
+    out
+}
+
+#[rmcp::tool_router(router = "code_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Lists the top-level items of a source file with their line numbers.
+    #[rmcp::tool(
+        name = "outline_code",
+        description = "Outlines a Rust, Python, JavaScript/TypeScript, or Go source file (chosen by extension): the first line of each top-level item (Rust fn/struct/enum/trait/impl/mod, Python def/class, JS/TS function/class/export, Go func/type) with its line number, and methods of impls, traits, and classes indented one level. Approximate: it matches keywords line by line rather than parsing, so unusual formatting can hide or add items. Use read_file with offset = line - 1 to jump to an item.",
+        annotations(
+            title = "Outline Code",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn outline_code(
+        &self,
+        Parameters(params): Parameters<OutlineCodeParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let language = Language::from_extension(&canonical).ok_or_else(|| {
+            format!(
+                "Unsupported language for {}: outline_code supports {SUPPORTED}",
+                params.path
+            )
+        })?;
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max = self.config.max_read_size as u64;
+        if size > max {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max,
+            }
+            .into());
+        }
+        let content = tokio::fs::read(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+        if is_binary(&content) {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        }
+        let text = String::from_utf8_lossy(&content);
+        Ok(format_outline(&outline(&text, language), language))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn items(text: &str, language: Language) -> Vec<(usize, String, usize)> {
+        outline(text, language)
+            .into_iter()
+            .map(|item| (item.depth, item.signature, item.line))
+            .collect()
+    }
+
+    fn item(depth: usize, signature: &str, line: usize) -> (usize, String, usize) {
+        (depth, signature.to_string(), line)
+    }
+
+    #[test]
+    fn outlines_rust() {
+        let text = "\
+use std::fmt;
+
+/// Docs
+#[derive(Debug)]
+pub struct Point { x: i32 }
+
+pub(crate) async fn run(a: u8) -> u8 {
+    fn helper() {}
+    a
+}
+
+impl<T: Clone> Wrapper<T>
+where
+    T: Send,
+{
+    pub fn new() -> Self {
+        todo!()
+    }
+
+    const LIMIT: usize = 3;
+}
+/*
+fn commented() {}
+*/
+mod io;
+unsafe extern \"C\" fn callback() {}
+macro_rules! square {
+    ($x:expr) => { $x * $x };
+}
+";
+        assert_eq!(
+            items(text, Language::Rust),
+            [
+                item(0, "pub struct Point { x: i32 }", 5),
+                item(0, "pub(crate) async fn run(a: u8) -> u8", 7),
+                item(0, "impl<T: Clone> Wrapper<T>", 12),
+                item(1, "pub fn new() -> Self", 16),
+                item(0, "mod io", 25),
+                item(0, "unsafe extern \"C\" fn callback() {}", 26),
+                item(0, "macro_rules! square", 27),
+            ]
+        );
+    }
+
+    #[test]
+    fn outlines_python() {
+        let text = "\
+import os
+
+\"\"\"
+def not_real():
+\"\"\"
+
+@decorator
+class Shape(Base):
+    \"\"\"A shape.
+
+    def also_not_real(self):
+    \"\"\"
+    sides = 0
+
+    def area(self):
+        def inner():
+            pass
+
+    async def load(self):
+        pass
+
+async def main():
+    pass
+";
+        assert_eq!(
+            items(text, Language::Python),
+            [
+                item(0, "class Shape(Base)", 8),
+                item(1, "def area(self)", 15),
+                item(1, "async def load(self)", 19),
+                item(0, "async def main()", 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn outlines_javascript_and_typescript() {
+        let text = "\
+import { a } from './a';
+
+export default class Store extends Base {
+  #items = [];
+  constructor(items) {
+    if (items) {
+      this.load(items);
+    }
+  }
+  static async *stream() {}
+  get size() {
+    return 0;
+  }
+}
+
+function helper(x) {
+  return x;
+}
+export const LIMIT = 3;
+export { helper };
+interface Options {
+  verbose?: boolean;
+}
+const local = () => 1;
+";
+        assert_eq!(
+            items(text, Language::JavaScript),
+            [
+                item(0, "export default class Store extends Base", 3),
+                item(1, "constructor(items)", 5),
+                item(1, "static async *stream() {}", 10),
+                item(1, "get size()", 11),
+                item(0, "function helper(x)", 16),
+                item(0, "export const LIMIT = 3", 19),
+                item(0, "export { helper }", 20),
+                item(0, "interface Options", 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn outlines_go() {
+        let text = "\
+package main
+
+type Server struct {
+\tAddr string
+}
+
+func (s *Server) Start() error {
+\treturn nil
+}
+
+// func commented() {}
+func main() {
+}
+";
+        assert_eq!(
+            items(text, Language::Go),
+            [
+                item(0, "type Server struct", 3),
+                item(0, "func (s *Server) Start() error", 7),
+                item(0, "func main()", 12),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn outline_code_mirrors_markdown_outline() {
+        let dir = TempDir::new().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+        let outline = |path: &Path| {
+            service.outline_code(Parameters(OutlineCodeParams {
+                path: path.to_string_lossy().to_string(),
+            }))
+        };
+
+        let file = dir.path().join("app.py");
+        std::fs::write(&file, "class App:\n    def run(self):\n        pass\n").unwrap();
+        assert_eq!(
+            outline(&file).await.unwrap(),
+            "Language: Python (approximate outline from line-based heuristics)\n\n\
+             Items (2):\n- class App (line 1)\n  - def run(self) (line 2)"
+        );
+
+        let empty = dir.path().join("empty.go");
+        std::fs::write(&empty, "package empty\n").unwrap();
+        assert!(outline(&empty).await.unwrap().ends_with("\n\nItems: none"));
+
+        let unknown = dir.path().join("build.zig");
+        std::fs::write(&unknown, "pub fn main() void {}\n").unwrap();
+        let err = outline(&unknown).await.unwrap_err();
+        assert!(
+            err.message.starts_with("Unsupported language for ")
+                && err.message.ends_with(SUPPORTED),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,688 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio_util::sync::CancellationToken;
+
+use super::count::{Visit, walk_sync};
+use super::util::{format_size, is_binary, sha256_file, spawn_blocking};
+
+/// Most paths listed per group; the rest are counted.
+const MAX_LISTED_PER_GROUP: usize = 100;
+
+/// Parameters for the compare_directories tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CompareDirectoriesParams {
+    /// Absolute path to the first directory (A)
+    path_a: String,
+    /// Absolute path to the second directory (B)
+    path_b: String,
+    /// Hash same-size files to compare their contents instead of their
+    /// modification times (default: false, metadata only)
+    content: Option<bool>,
+}
+
+/// Parameters for the diff_files tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct DiffFilesParams {
+    /// Absolute path to the first file (A, shown as ---)
+    path_a: String,
+    /// Absolute path to the second file (B, shown as +++)
+    path_b: String,
+    /// Unchanged lines shown around each change (default: 3)
+    context: Option<usize>,
+}
+
+/// Reads a text file for diff_files, refusing files over `max_size` bytes
+/// and binary files.
+fn read_text_sync(path: &Path, requested: &str, max_size: u64) -> Result<String, ToolError> {
+    let size = std::fs::metadata(path)
+        .map_err(|e| ToolError::io(e, requested))?
+        .len();
+    if size > max_size {
+        return Err(FsError::FileTooLarge {
+            path: requested.to_string(),
+            size,
+            max: max_size,
+        }
+        .into());
+    }
+    let content = std::fs::read(path).map_err(|e| ToolError::io(e, requested))?;
+    if is_binary(&content) {
+        return Err(FsError::BinaryFile {
+            path: requested.to_string(),
+        }
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// What a relative path is on one side of the comparison.
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    File {
+        size: u64,
+        modified: Option<SystemTime>,
+    },
+    Dir,
+    /// A symlink and its target, never followed
+    Symlink(PathBuf),
+}
+
+impl Entry {
+    fn kind(&self) -> &'static str {
+        match self {
+            Entry::File { .. } => "file",
+            Entry::Dir => "directory",
+            Entry::Symlink(_) => "symlink",
+        }
+    }
+}
+
+/// The entries under one root, keyed by `/`-separated relative path.
+#[derive(Debug, Default)]
+struct Tree {
+    entries: BTreeMap<String, Entry>,
+    denied: u64,
+    unreadable: u64,
+    truncated: bool,
+}
+
+/// Walks `root` up to `max_depth` levels, recording every entry's type and
+/// metadata, and stops once `limit` entries have been seen.
+fn scan_tree(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    limit: u64,
+    ct: &CancellationToken,
+) -> Result<Tree, FsError> {
+    let mut tree = Tree::default();
+    let mut denied = 0;
+    tree.unreadable = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if tree.entries.len() as u64 >= limit {
+                tree.truncated = true;
+                return Visit::Stop;
+            }
+            let entry = if file_type.is_symlink() {
+                Entry::Symlink(std::fs::read_link(path).unwrap_or_default())
+            } else if file_type.is_dir() {
+                Entry::Dir
+            } else {
+                let metadata = std::fs::symlink_metadata(path).ok();
+                Entry::File {
+                    size: metadata.as_ref().map_or(0, |m| m.len()),
+                    modified: metadata.and_then(|m| m.modified().ok()),
+                }
+            };
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let key = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            tree.entries.insert(key, entry);
+            Visit::Continue
+        },
+    )?;
+    tree.denied = denied;
+    Ok(tree)
+}
+
+/// The result of comparing two trees.
+#[derive(Debug, Default, PartialEq)]
+struct Comparison {
+    /// Paths only in A; a directory stands for everything below it
+    only_a: Vec<String>,
+    only_b: Vec<String>,
+    /// (path, how it differs)
+    different: Vec<(String, String)>,
+    /// Files and symlinks found equal on both sides
+    identical: u64,
+    /// Bytes read while hashing
+    hashed: u64,
+}
+
+/// Paths in `from` missing from `other`, with a directory's descendants
+/// folded into the directory itself (shown with a trailing `/`).
+fn only_in(from: &Tree, other: &Tree) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut folded: HashSet<&str> = HashSet::new();
+    for (path, entry) in &from.entries {
+        if other.entries.contains_key(path) {
+            continue;
+        }
+        let mut ancestors =
+            std::iter::successors(path.rsplit_once('/'), |(parent, _)| parent.rsplit_once('/'));
+        if ancestors.any(|(parent, _)| folded.contains(parent)) {
+            continue;
+        }
+        if *entry == Entry::Dir {
+            folded.insert(path);
+            paths.push(format!("{path}/"));
+        } else {
+            paths.push(path.clone());
+        }
+    }
+    paths
+}
+
+/// Compares the entries both trees share: type, then size, then either the
+/// content hash (`content`) or the modification time.
+fn compare_trees(
+    root_a: &Path,
+    a: &Tree,
+    root_b: &Path,
+    b: &Tree,
+    content: bool,
+    ct: &CancellationToken,
+) -> Result<Comparison, FsError> {
+    let mut comparison = Comparison {
+        only_a: only_in(a, b),
+        only_b: only_in(b, a),
+        ..Default::default()
+    };
+    for (path, entry_a) in &a.entries {
+        let Some(entry_b) = b.entries.get(path) else {
+            continue;
+        };
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let difference = match (entry_a, entry_b) {
+            (Entry::Dir, Entry::Dir) => continue,
+            (Entry::Symlink(target_a), Entry::Symlink(target_b)) => {
+                (target_a != target_b).then(|| {
+                    format!(
+                        "symlink target {} vs {}",
+                        target_a.display(),
+                        target_b.display()
+                    )
+                })
+            }
+            (
+                Entry::File {
+                    size: size_a,
+                    modified: modified_a,
+                },
+                Entry::File {
+                    size: size_b,
+                    modified: modified_b,
+                },
+            ) => {
+                if size_a != size_b {
+                    Some(format!(
+                        "size {} vs {}",
+                        format_size(*size_a),
+                        format_size(*size_b)
+                    ))
+                } else if content {
+                    let hash_a = sha256_file(&root_a.join(path));
+                    let hash_b = sha256_file(&root_b.join(path));
+                    match (hash_a, hash_b) {
+                        (Ok(hash_a), Ok(hash_b)) => {
+                            comparison.hashed += size_a * 2;
+                            (hash_a != hash_b).then(|| "content differs".to_string())
+                        }
+                        (Err(err), _) | (_, Err(err)) => Some(format!("could not hash: {err}")),
+                    }
+                } else {
+                    match (modified_a, modified_b) {
+                        (Some(time_a), Some(time_b)) if time_a != time_b => {
+                            let newer = if time_a > time_b { "A" } else { "B" };
+                            Some(format!("modified time differs ({newer} newer)"))
+                        }
+                        _ => None,
+                    }
+                }
+            }
+            (entry_a, entry_b) => Some(format!("{} vs {}", entry_a.kind(), entry_b.kind())),
+        };
+        match difference {
+            Some(difference) => comparison.different.push((path.clone(), difference)),
+            None => comparison.identical += 1,
+        }
+    }
+    Ok(comparison)
+}
+
+/// Appends a titled group, listing at most [`MAX_LISTED_PER_GROUP`] lines.
+pub(crate) fn push_group(out: &mut String, title: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    out.push_str(&format!("\n{title} ({}):\n", lines.len()));
+    for line in lines.iter().take(MAX_LISTED_PER_GROUP) {
+        out.push_str(&format!("  {line}\n"));
+    }
+    if lines.len() > MAX_LISTED_PER_GROUP {
+        out.push_str(&format!(
+            "  ... and {} more\n",
+            lines.len() - MAX_LISTED_PER_GROUP
+        ));
+    }
+}
+
+fn format_comparison(
+    root_a: &Path,
+    root_b: &Path,
+    trees: (&Tree, &Tree),
+    comparison: &Comparison,
+    content: bool,
+    limit: u64,
+) -> String {
+    let mode = if content {
+        "size and content"
+    } else {
+        "size and modified time"
+    };
+    let mut out = format!(
+        "A: {}\nB: {}\nCompared by {mode}.\n",
+        root_a.display(),
+        root_b.display()
+    );
+    push_group(&mut out, "Only in A", &comparison.only_a);
+    push_group(&mut out, "Only in B", &comparison.only_b);
+    let different: Vec<String> = comparison
+        .different
+        .iter()
+        .map(|(path, difference)| format!("{path}: {difference}"))
+        .collect();
+    push_group(&mut out, "Different", &different);
+
+    if comparison.only_a.is_empty() && comparison.only_b.is_empty() && different.is_empty() {
+        out.push_str(&format!(
+            "\nNo differences ({} files and symlinks match).",
+            comparison.identical
+        ));
+    } else {
+        out.push_str(&format!("\nIdentical: {}", comparison.identical));
+    }
+    let (a, b) = trees;
+    let denied = a.denied + b.denied;
+    let unreadable = a.unreadable + b.unreadable;
+    if denied > 0 || unreadable > 0 {
+        out.push_str(&format!(
+            "\nSkipped: {denied} denied, {unreadable} unreadable"
+        ));
+    }
+    if a.truncated || b.truncated {
+        out.push_str(&format!(
+            "\nStopped after {limit} entries on one side; results are partial. Raise --max-count-entries or compare subdirectories."
+        ));
+    }
+    out
+}
+
+#[rmcp::tool_router(router = "compare_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Reports the differences between two directory trees.
+    #[rmcp::tool(
+        name = "compare_directories",
+        description = "Compares two directory trees up to --max-depth levels and lists, with paths relative to each root, entries only in A, only in B, and in both but different. Files differ by size, then by modification time, or by SHA-256 of same-size files with content: true (slower, but ignores timestamps). Symlinks compare by target and are not followed. Each side is walked up to --max-count-entries entries.",
+        annotations(
+            title = "Compare Directories",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn compare_directories(
+        &self,
+        Parameters(params): Parameters<CompareDirectoriesParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root_a = self
+            .security
+            .validate_directory(Path::new(&params.path_a))?;
+        let root_b = self
+            .security
+            .validate_directory(Path::new(&params.path_b))?;
+        let content = params.content.unwrap_or(false);
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let limit = self.config.max_count_entries;
+
+        let (walk_a, walk_b) = (root_a.clone(), root_b.clone());
+        let (a, b, comparison) = spawn_blocking(move || {
+            let a = scan_tree(&walk_a, &security, max_depth, limit, &ct)?;
+            let b = scan_tree(&walk_b, &security, max_depth, limit, &ct)?;
+            let comparison = compare_trees(&walk_a, &a, &walk_b, &b, content, &ct)?;
+            Ok::<_, FsError>((a, b, comparison))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(comparison.hashed);
+
+        Ok(format_comparison(
+            &root_a,
+            &root_b,
+            (&a, &b),
+            &comparison,
+            content,
+            limit,
+        ))
+    }
+
+    /// Shows a unified diff between two text files.
+    #[rmcp::tool(
+        name = "diff_files",
+        description = "Compares two text files line by line and returns a unified diff (---/+++ headers, @@ hunks) from A to B, with context lines of unchanged text around each change (default 3), after a summary of lines added and removed. Reports when the files are identical. Binary files and files over the read limit are refused.",
+        annotations(
+            title = "Diff Files",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn diff_files(
+        &self,
+        Parameters(params): Parameters<DiffFilesParams>,
+    ) -> Result<String, ToolError> {
+        let file_a = self.security.validate_file(Path::new(&params.path_a))?;
+        let file_b = self.security.validate_file(Path::new(&params.path_b))?;
+        let max_size = self.config.max_read_size as u64;
+
+        let (a, b) = {
+            let (file_a, file_b) = (file_a.clone(), file_b.clone());
+            let (path_a, path_b) = (params.path_a.clone(), params.path_b.clone());
+            spawn_blocking(move || {
+                Ok::<_, ToolError>((
+                    read_text_sync(&file_a, &path_a, max_size)?,
+                    read_text_sync(&file_b, &path_b, max_size)?,
+                ))
+            })
+            .await
+            .map_err(|e| e.to_string())??
+        };
+        stats::record_read((a.len() + b.len()) as u64);
+
+        let (name_a, name_b) = (file_a.display().to_string(), file_b.display().to_string());
+        if a == b {
+            return Ok(format!("Files are identical: {name_a} and {name_b}"));
+        }
+        let diff = TextDiff::from_lines(&a, &b);
+        let (mut added, mut removed) = (0, 0);
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => added += 1,
+                ChangeTag::Delete => removed += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        let unified = diff
+            .unified_diff()
+            .context_radius(params.context.unwrap_or(3))
+            .header(&name_a, &name_b)
+            .to_string();
+        Ok(format!(
+            "{added} line{} added, {removed} line{} removed\n\n{unified}",
+            if added == 1 { "" } else { "s" },
+            if removed == 1 { "" } else { "s" },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    /// Two trees: A has old.txt and olddir/, B has new.txt; same.txt matches,
+    /// size.txt differs in size, touched.txt only in mtime, and swapped.txt in
+    /// content but not size or mtime.
+    fn fixture() -> (TempDir, PathBuf, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (a, b) = (root.join("a"), root.join("b"));
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for side in [&a, &b] {
+            std::fs::create_dir(side).unwrap();
+            for (name, body) in [("same.txt", "same"), ("touched.txt", "touch")] {
+                std::fs::write(side.join(name), body).unwrap();
+                set_modified(&side.join(name), time);
+            }
+        }
+        std::fs::write(a.join("old.txt"), "old").unwrap();
+        std::fs::create_dir_all(a.join("olddir/deep")).unwrap();
+        std::fs::write(a.join("olddir/deep/f"), "f").unwrap();
+        std::fs::write(b.join("new.txt"), "new").unwrap();
+        std::fs::write(a.join("size.txt"), "short").unwrap();
+        std::fs::write(b.join("size.txt"), "longer").unwrap();
+        set_modified(&b.join("touched.txt"), time + Duration::from_secs(60));
+        std::fs::write(a.join("swapped.txt"), "abc").unwrap();
+        std::fs::write(b.join("swapped.txt"), "xyz").unwrap();
+        set_modified(&a.join("swapped.txt"), time);
+        set_modified(&b.join("swapped.txt"), time);
+        (dir, a, b)
+    }
+
+    fn compare(a: &Path, b: &Path, content: bool) -> Comparison {
+        let security = SecurityContext::new(vec![a.parent().unwrap().to_path_buf()]);
+        let ct = CancellationToken::new();
+        let tree_a = scan_tree(a, &security, 10, 1000, &ct).unwrap();
+        let tree_b = scan_tree(b, &security, 10, 1000, &ct).unwrap();
+        compare_trees(a, &tree_a, b, &tree_b, content, &ct).unwrap()
+    }
+
+    #[test]
+    fn metadata_comparison_uses_size_then_mtime() {
+        let (_dir, a, b) = fixture();
+        let comparison = compare(&a, &b, false);
+        assert_eq!(comparison.only_a, ["old.txt", "olddir/"]);
+        assert_eq!(comparison.only_b, ["new.txt"]);
+        assert_eq!(
+            comparison.different,
+            [
+                ("size.txt".to_string(), "size 5 B vs 6 B".to_string()),
+                (
+                    "touched.txt".to_string(),
+                    "modified time differs (B newer)".to_string()
+                ),
+            ]
+        );
+        // swapped.txt matches on size and mtime, so metadata calls it identical
+        assert_eq!(comparison.identical, 2);
+        assert_eq!(comparison.hashed, 0);
+    }
+
+    #[test]
+    fn content_comparison_hashes_same_size_files() {
+        let (_dir, a, b) = fixture();
+        let comparison = compare(&a, &b, true);
+        assert_eq!(
+            comparison.different,
+            [
+                ("size.txt".to_string(), "size 5 B vs 6 B".to_string()),
+                ("swapped.txt".to_string(), "content differs".to_string()),
+            ]
+        );
+        assert_eq!(comparison.identical, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_compare_by_target_and_type() {
+        let (_dir, a, b) = fixture();
+        std::os::unix::fs::symlink("same.txt", a.join("link")).unwrap();
+        std::os::unix::fs::symlink("other.txt", b.join("link")).unwrap();
+        std::os::unix::fs::symlink("same.txt", a.join("kind")).unwrap();
+        std::fs::write(b.join("kind"), "x").unwrap();
+        let comparison = compare(&a, &b, false);
+        assert!(comparison.different.contains(&(
+            "link".to_string(),
+            "symlink target same.txt vs other.txt".to_string()
+        )));
+        assert!(
+            comparison
+                .different
+                .contains(&("kind".to_string(), "symlink vs file".to_string()))
+        );
+    }
+
+    fn diff_params(a: &Path, b: &Path, context: Option<usize>) -> DiffFilesParams {
+        DiffFilesParams {
+            path_a: a.to_string_lossy().to_string(),
+            path_b: b.to_string_lossy().to_string(),
+            context,
+        }
+    }
+
+    fn diff_service(dir: &TempDir) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn diff_files_reports_identical_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (a, b) = (root.join("a.rs"), root.join("b.rs"));
+        std::fs::write(&a, "fn main() {}\n").unwrap();
+        std::fs::write(&b, "fn main() {}\n").unwrap();
+
+        let output = diff_service(&dir)
+            .diff_files(Parameters(diff_params(&a, &b, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("Files are identical: {} and {}", a.display(), b.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn diff_files_returns_unified_diff() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (a, b) = (root.join("old.txt"), root.join("new.txt"));
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        std::fs::write(&a, lines.join("\n") + "\n").unwrap();
+        let mut changed = lines.clone();
+        changed[4] = "line five".to_string();
+        std::fs::write(&b, changed.join("\n") + "\n").unwrap();
+        let service = diff_service(&dir);
+
+        let output = service
+            .diff_files(Parameters(diff_params(&a, &b, Some(1))))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "1 line added, 1 line removed\n\n\
+                 --- {}\n+++ {}\n@@ -4,3 +4,3 @@\n line 4\n-line 5\n+line five\n line 6\n",
+                a.display(),
+                b.display()
+            )
+        );
+
+        // The default context is three lines
+        let output = service
+            .diff_files(Parameters(diff_params(&a, &b, None)))
+            .await
+            .unwrap();
+        assert!(output.contains("@@ -2,7 +2,7 @@"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn diff_files_refuses_binary_and_denied_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let text = root.join("a.txt");
+        let binary = root.join("b.bin");
+        std::fs::write(&text, "text\n").unwrap();
+        std::fs::write(&binary, b"\x7fELF\0\0\0").unwrap();
+        let other = TempDir::new().unwrap();
+        let outside = other.path().join("secret.txt");
+        std::fs::write(&outside, "secret\n").unwrap();
+        let service = diff_service(&dir);
+
+        let err = service
+            .diff_files(Parameters(diff_params(&text, &binary, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Binary file"), "{err}");
+
+        for (a, b) in [(&outside, &text), (&text, &outside)] {
+            let err = service
+                .diff_files(Parameters(diff_params(a, b, None)))
+                .await
+                .unwrap_err();
+            assert!(err.message.contains("Access denied"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn compare_directories_groups_differences() {
+        let (dir, a, b) = fixture();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+
+        let output = service
+            .compare_directories(
+                Parameters(CompareDirectoriesParams {
+                    path_a: a.to_string_lossy().to_string(),
+                    path_b: b.to_string_lossy().to_string(),
+                    content: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let expected = "Compared by size and modified time.\n\n\
+             Only in A (2):\n  old.txt\n  olddir/\n\n\
+             Only in B (1):\n  new.txt\n\n\
+             Different (2):\n  size.txt: size 5 B vs 6 B\n  touched.txt: modified time differs (B newer)\n\n\
+             Identical: 2";
+        assert!(output.ends_with(expected), "{output}");
+
+        let output = service
+            .compare_directories(
+                Parameters(CompareDirectoriesParams {
+                    path_a: a.to_string_lossy().to_string(),
+                    path_b: a.to_string_lossy().to_string(),
+                    content: Some(true),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(
+            output.ends_with(
+                "Compared by size and content.\n\nNo differences (6 files and symlinks match)."
+            ),
+            "{output}"
+        );
+    }
+}
@@ -0,0 +1,601 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::util::{atomic_temp_path, format_size, spawn_blocking};
+
+/// Compression formats supported by compress_file and decompress_file.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum CompressFormat {
+    Gzip,
+    Zstd,
+}
+
+impl CompressFormat {
+    /// Infers the format from a file extension (`.gz`/`.gzip` or `.zst`/`.zstd`).
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "gz" | "gzip" => Some(CompressFormat::Gzip),
+            "zst" | "zstd" => Some(CompressFormat::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Parameters for the compress_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CompressFileParams {
+    /// Absolute path to the file to compress
+    input_path: String,
+    /// Absolute path for the compressed output file
+    output_path: String,
+    /// Compression format: "gzip" or "zstd" (default: inferred from output_path, else gzip)
+    #[schemars(
+        description = "Compression format: \"gzip\" or \"zstd\" (default: inferred from output_path, else gzip)"
+    )]
+    format: Option<CompressFormat>,
+    /// Replace output_path if it already exists (default: false)
+    #[schemars(description = "Replace output_path if it already exists (default: false)")]
+    overwrite: Option<bool>,
+}
+
+/// Parameters for the decompress_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct DecompressFileParams {
+    /// Absolute path to the compressed file (.gz or .zst)
+    input_path: String,
+    /// Absolute path for the decompressed output file
+    output_path: String,
+    /// Replace output_path if it already exists (default: false)
+    #[schemars(description = "Replace output_path if it already exists (default: false)")]
+    overwrite: Option<bool>,
+}
+
+/// Bytes moved per read while streaming a file through an encoder or decoder.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A file writer that fails with [`std::io::ErrorKind::FileTooLarge`] instead
+/// of writing past `max` bytes.
+struct CappedWriter {
+    file: std::fs::File,
+    written: u64,
+    max: u64,
+}
+
+impl Write for CappedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written + buf.len() as u64 > self.max {
+            return Err(std::io::ErrorKind::FileTooLarge.into());
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// One compress or decompress run: the resolved files, the paths as the
+/// caller gave them (for error messages), and the `--max-write-size` cap.
+struct Transfer {
+    input: PathBuf,
+    output: PathBuf,
+    input_path: String,
+    output_path: String,
+    max_size: u64,
+}
+
+impl Transfer {
+    fn input_error(&self, err: std::io::Error) -> ToolError {
+        ToolError::io(err, &self.input_path)
+    }
+
+    fn output_error(&self, err: std::io::Error) -> ToolError {
+        if err.kind() == std::io::ErrorKind::FileTooLarge {
+            return format!(
+                "Refusing to write {}: output would exceed --max-write-size ({})",
+                self.output_path,
+                format_size(self.max_size)
+            )
+            .into();
+        }
+        ToolError::io(err, &self.output_path)
+    }
+
+    fn open_input(&self) -> Result<std::fs::File, ToolError> {
+        std::fs::File::open(&self.input).map_err(|e| self.input_error(e))
+    }
+
+    /// Streams `reader` into `writer`, attributing read failures to the input
+    /// and write failures to the output.
+    fn pipe(&self, mut reader: impl Read, writer: &mut impl Write) -> Result<(), ToolError> {
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(self.input_error(e)),
+            };
+            writer
+                .write_all(&buf[..n])
+                .map_err(|e| self.output_error(e))?;
+        }
+    }
+
+    /// Runs `write` against a capped temporary sibling of the output, syncs it,
+    /// and renames it into place, so a failed run leaves no partial file and
+    /// an existing output untouched. Returns the bytes written.
+    fn write_output(
+        &self,
+        write: impl FnOnce(CappedWriter) -> Result<CappedWriter, ToolError>,
+    ) -> Result<u64, ToolError> {
+        let temp = atomic_temp_path(&self.output);
+        let result = (|| {
+            let file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp)
+                .map_err(|e| self.output_error(e))?;
+            let writer = write(CappedWriter {
+                file,
+                written: 0,
+                max: self.max_size,
+            })?;
+            writer.file.sync_all().map_err(|e| self.output_error(e))?;
+            std::fs::rename(&temp, &self.output).map_err(|e| self.output_error(e))?;
+            Ok(writer.written)
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        result
+    }
+}
+
+/// Streams the input through the encoder for `format` into the output.
+fn compress_sync(transfer: &Transfer, format: CompressFormat) -> Result<u64, ToolError> {
+    let reader = transfer.open_input()?;
+    transfer.write_output(|writer| match format {
+        CompressFormat::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            transfer.pipe(reader, &mut encoder)?;
+            encoder.finish().map_err(|e| transfer.output_error(e))
+        }
+        CompressFormat::Zstd => {
+            let mut encoder =
+                zstd::Encoder::new(writer, 0).map_err(|e| transfer.output_error(e))?;
+            transfer.pipe(reader, &mut encoder)?;
+            encoder.finish().map_err(|e| transfer.output_error(e))
+        }
+    })
+}
+
+/// Streams the input through the decoder for `format` into the output.
+fn decompress_sync(transfer: &Transfer, format: CompressFormat) -> Result<u64, ToolError> {
+    let reader = transfer.open_input()?;
+    transfer.write_output(|mut writer| {
+        match format {
+            CompressFormat::Gzip => {
+                transfer.pipe(flate2::read::GzDecoder::new(reader), &mut writer)?;
+            }
+            CompressFormat::Zstd => {
+                let decoder = zstd::Decoder::new(reader).map_err(|e| transfer.input_error(e))?;
+                transfer.pipe(decoder, &mut writer)?;
+            }
+        }
+        Ok(writer)
+    })
+}
+
+/// Formats the size summary shared by both tools.
+fn format_ratio(input_size: u64, output_size: u64) -> String {
+    let ratio = if input_size == 0 {
+        0.0
+    } else {
+        output_size as f64 / input_size as f64 * 100.0
+    };
+    format!(
+        "Input: {}, Output: {}, Ratio: {ratio:.1}%",
+        format_size(input_size),
+        format_size(output_size),
+    )
+}
+
+impl FilesystemService {
+    /// Resolves and checks the files of a compress or decompress call. The
+    /// output must differ from the input, and an existing output is refused
+    /// unless `overwrite` is set.
+    fn prepare_transfer(
+        &self,
+        input_path: &str,
+        output_path: &str,
+        overwrite: Option<bool>,
+    ) -> Result<Transfer, ToolError> {
+        let input = self.security.validate_file(Path::new(input_path))?;
+        let output = self
+            .security
+            .validate_writable_path(Path::new(output_path))?;
+        if input == output {
+            return Err("input_path and output_path must differ".to_string().into());
+        }
+        if output.is_dir() {
+            return Err(FsError::NotAFile {
+                path: output_path.to_string(),
+            }
+            .into());
+        }
+        if output.exists() && !overwrite.unwrap_or(false) {
+            return Err(format!(
+                "Destination exists: {output_path}. Pass overwrite: true to replace it."
+            )
+            .into());
+        }
+        Ok(Transfer {
+            input,
+            output,
+            input_path: input_path.to_string(),
+            output_path: output_path.to_string(),
+            max_size: self.config.max_write_size,
+        })
+    }
+}
+
+#[rmcp::tool_router(router = "compress_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Compresses a file with gzip or zstd.
+    #[rmcp::tool(
+        name = "compress_file",
+        description = "Compresses a file with gzip or zstd and writes the result to output_path. The format defaults to the output extension (.gz or .zst), else gzip. Refuses an existing output_path unless overwrite is true, and output over --max-write-size; a failed run leaves no partial file. Returns input size, output size, and compression ratio.",
+        annotations(
+            title = "Compress File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn compress_file(
+        &self,
+        Parameters(params): Parameters<CompressFileParams>,
+    ) -> Result<String, ToolError> {
+        let transfer =
+            self.prepare_transfer(&params.input_path, &params.output_path, params.overwrite)?;
+        let format = params
+            .format
+            .or_else(|| CompressFormat::from_extension(&transfer.output))
+            .unwrap_or(CompressFormat::Gzip);
+
+        let (input, output) = (transfer.input.clone(), transfer.output.clone());
+        let output_size = spawn_blocking(move || compress_sync(&transfer, format))
+            .await
+            .map_err(|e| e.to_string())??;
+
+        let input_size = tokio::fs::metadata(&input)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        stats::record_read(input_size);
+        stats::record_written(output_size);
+        Ok(format!(
+            "Compressed {} to {} ({:?})\n{}",
+            input.display(),
+            output.display(),
+            format,
+            format_ratio(input_size, output_size),
+        ))
+    }
+
+    /// Decompresses a gzip or zstd file, detecting the format from its extension.
+    #[rmcp::tool(
+        name = "decompress_file",
+        description = "Decompresses a .gz or .zst file to output_path. The format is detected from the input file extension. Refuses an existing output_path unless overwrite is true, and stops once the output would exceed --max-write-size; a failed run leaves no partial file. Returns input size, output size, and compression ratio.",
+        annotations(
+            title = "Decompress File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn decompress_file(
+        &self,
+        Parameters(params): Parameters<DecompressFileParams>,
+    ) -> Result<String, ToolError> {
+        let transfer =
+            self.prepare_transfer(&params.input_path, &params.output_path, params.overwrite)?;
+        let format = CompressFormat::from_extension(&transfer.input).ok_or_else(|| {
+            FsError::PatternError(format!(
+                "unsupported compression format for {} (expected .gz or .zst)",
+                params.input_path
+            ))
+            .to_string()
+        })?;
+
+        let (input, output) = (transfer.input.clone(), transfer.output.clone());
+        let output_size = spawn_blocking(move || decompress_sync(&transfer, format))
+            .await
+            .map_err(|e| e.to_string())??;
+
+        let input_size = tokio::fs::metadata(&input)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        stats::record_read(input_size);
+        stats::record_written(output_size);
+        Ok(format!(
+            "Decompressed {} to {} ({:?})\n{}",
+            input.display(),
+            output.display(),
+            format,
+            format_ratio(input_size, output_size),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn make_service(dirs: Vec<PathBuf>) -> FilesystemService {
+        let config = Config {
+            allowed_directories: dirs,
+            allow_write: true,
+            ..Default::default()
+        };
+        FilesystemService::new(config)
+    }
+
+    #[test]
+    fn compress_tools_router_contains_both() {
+        let router = FilesystemService::compress_tools_router();
+        let names: Vec<String> = router
+            .list_all()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"compress_file".to_string()));
+        assert!(names.contains(&"decompress_file".to_string()));
+    }
+
+    #[test]
+    fn compress_tools_hidden_without_write() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        assert!(service.tool_router.get("compress_file").is_none());
+        assert!(service.tool_router.get("decompress_file").is_none());
+    }
+
+    #[tokio::test]
+    async fn gzip_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let original = "the quick brown fox jumps over the lazy dog\n".repeat(200);
+        std::fs::write(dir.path().join("text.txt"), &original).unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .compress_file(Parameters(CompressFileParams {
+                input_path: dir.path().join("text.txt").to_string_lossy().to_string(),
+                output_path: dir.path().join("text.txt.gz").to_string_lossy().to_string(),
+                format: None,
+                overwrite: None,
+            }))
+            .await
+            .unwrap();
+        assert!(result.contains("Ratio:"));
+        let compressed = std::fs::metadata(dir.path().join("text.txt.gz"))
+            .unwrap()
+            .len();
+        assert!(compressed < original.len() as u64);
+
+        service
+            .decompress_file(Parameters(DecompressFileParams {
+                input_path: dir.path().join("text.txt.gz").to_string_lossy().to_string(),
+                output_path: dir
+                    .path()
+                    .join("restored.txt")
+                    .to_string_lossy()
+                    .to_string(),
+                overwrite: None,
+            }))
+            .await
+            .unwrap();
+        let restored = std::fs::read_to_string(dir.path().join("restored.txt")).unwrap();
+        assert_eq!(restored, original);
+    }
+
+    #[tokio::test]
+    async fn zstd_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let original = "zstd payload ".repeat(500);
+        std::fs::write(dir.path().join("data.txt"), &original).unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .compress_file(Parameters(CompressFileParams {
+                input_path: dir.path().join("data.txt").to_string_lossy().to_string(),
+                output_path: dir.path().join("data.bin").to_string_lossy().to_string(),
+                format: Some(CompressFormat::Zstd),
+                overwrite: None,
+            }))
+            .await
+            .unwrap();
+        assert!(result.contains("Zstd"));
+
+        std::fs::rename(dir.path().join("data.bin"), dir.path().join("data.zst")).unwrap();
+        service
+            .decompress_file(Parameters(DecompressFileParams {
+                input_path: dir.path().join("data.zst").to_string_lossy().to_string(),
+                output_path: dir.path().join("out.txt").to_string_lossy().to_string(),
+                overwrite: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+            original
+        );
+    }
+
+    #[tokio::test]
+    async fn compress_output_outside_denied() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("text.txt"), "data").unwrap();
+        let other = TempDir::new().unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .compress_file(Parameters(CompressFileParams {
+                input_path: dir.path().join("text.txt").to_string_lossy().to_string(),
+                output_path: other.path().join("out.gz").to_string_lossy().to_string(),
+                format: None,
+                overwrite: None,
+            }))
+            .await;
+        assert!(result.unwrap_err().message.contains("Access denied"));
+        assert!(!other.path().join("out.gz").exists());
+    }
+
+    #[tokio::test]
+    async fn decompress_unsupported_extension() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("archive.rar"), "data").unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .decompress_file(Parameters(DecompressFileParams {
+                input_path: dir.path().join("archive.rar").to_string_lossy().to_string(),
+                output_path: dir.path().join("out").to_string_lossy().to_string(),
+                overwrite: None,
+            }))
+            .await;
+        assert!(result.unwrap_err().message.contains("Invalid pattern"));
+    }
+
+    fn gzip(path: &Path, data: &[u8]) {
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    fn decompress_params(
+        input: &Path,
+        output: &Path,
+        overwrite: Option<bool>,
+    ) -> DecompressFileParams {
+        DecompressFileParams {
+            input_path: input.to_string_lossy().to_string(),
+            output_path: output.to_string_lossy().to_string(),
+            overwrite,
+        }
+    }
+
+    fn leftovers(dir: &Path) -> Vec<String> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with('.'))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn existing_output_needs_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let (input, output) = (canon.join("data.gz"), canon.join("data.txt"));
+        gzip(&input, b"fresh");
+        std::fs::write(&output, "keep me").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .decompress_file(Parameters(decompress_params(&input, &output, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "keep me");
+
+        let err = service
+            .compress_file(Parameters(CompressFileParams {
+                input_path: output.to_string_lossy().to_string(),
+                output_path: input.to_string_lossy().to_string(),
+                format: None,
+                overwrite: None,
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+
+        service
+            .decompress_file(Parameters(decompress_params(&input, &output, Some(true))))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "fresh");
+    }
+
+    #[tokio::test]
+    async fn decompress_stops_at_max_write_size() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let (input, output) = (canon.join("bomb.gz"), canon.join("bomb.txt"));
+        gzip(&input, &vec![0u8; 1_000_000]);
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon.clone()],
+            allow_write: true,
+            max_write_size: 4096,
+            ..Default::default()
+        });
+
+        let err = service
+            .decompress_file(Parameters(decompress_params(&input, &output, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--max-write-size (4.0 KB)"), "{err}");
+        assert!(err.message.contains(&*output.to_string_lossy()), "{err}");
+        assert!(!output.exists());
+        assert!(leftovers(&canon).is_empty(), "{:?}", leftovers(&canon));
+    }
+
+    #[tokio::test]
+    async fn corrupt_input_is_blamed_and_leaves_no_output() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let (input, output) = (canon.join("broken.gz"), canon.join("broken.txt"));
+        std::fs::write(&input, b"\x1f\x8b not really gzip").unwrap();
+        let service = make_service(vec![canon.clone()]);
+
+        let err = service
+            .decompress_file(Parameters(decompress_params(&input, &output, None)))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.details.unwrap()["path"],
+            input.to_string_lossy().as_ref()
+        );
+        assert!(!output.exists());
+        assert!(leftovers(&canon).is_empty(), "{:?}", leftovers(&canon));
+    }
+}
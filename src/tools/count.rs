@@ -0,0 +1,1099 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::FileType;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::checksum::manifest_path;
+use super::util::{BINARY_CHECK_SIZE, format_size, is_binary, spawn_blocking};
+
+/// Read buffer for line counting.
+const LINE_COUNT_CHUNK: usize = 64 * 1024;
+
+/// Parameters for the count_entries tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CountEntriesParams {
+    /// Absolute path to the directory to count
+    path: String,
+}
+
+/// Parameters for the count tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CountParams {
+    /// Absolute paths of the files to count
+    paths: Vec<String>,
+}
+
+/// Parameters for the code_stats tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CodeStatsParams {
+    /// Absolute path to the directory to summarize
+    path: String,
+    /// Show only the N extensions with the most lines (default: all); totals
+    /// still cover every file
+    top: Option<usize>,
+}
+
+/// Parameters for the size_by_extension tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct SizeByExtensionParams {
+    /// Absolute path to the directory to summarize
+    path: String,
+    /// Glob patterns, relative to the directory, for files and directories to
+    /// leave out (e.g. "node_modules", "**/*.log"); matching directories are
+    /// not entered
+    exclude: Option<Vec<String>>,
+    /// Show only the N largest extensions and roll the rest into "(other)"
+    /// (default: 20)
+    top: Option<usize>,
+}
+
+/// Extensions size_by_extension lists before rolling the rest into
+/// "(other)".
+const DEFAULT_SIZE_TOP: usize = 20;
+
+/// Totals gathered by [`count_entries_sync`].
+#[derive(Debug, Default, PartialEq)]
+struct EntryCounts {
+    files: u64,
+    dirs: u64,
+    symlinks: u64,
+    /// Directories that could not be read and were skipped
+    unreadable: u64,
+    /// Whether the walk stopped at the ceiling before visiting everything
+    truncated: bool,
+}
+
+impl EntryCounts {
+    fn total(&self) -> u64 {
+        self.files + self.dirs + self.symlinks
+    }
+}
+
+/// What [`walk_sync`] does after visiting an entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Visit {
+    Continue,
+    /// Do not descend into this directory
+    Skip,
+    /// End the walk
+    Stop,
+}
+
+/// Walks `root` on the current thread up to `max_depth` levels, calling `visit`
+/// with each entry's path and type (symlinks are reported, never followed).
+/// Denied paths are passed to `denied` instead and not entered. Returns the
+/// number of directories that could not be read, or stops early when `visit`
+/// returns [`Visit::Stop`]. Fails with [`FsError::Cancelled`] if `ct` fires
+/// between directories.
+pub(crate) fn walk_sync(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    ct: &CancellationToken,
+    mut denied: impl FnMut(&Path),
+    mut visit: impl FnMut(&Path, FileType) -> Visit,
+) -> Result<u64, FsError> {
+    let mut unreadable = 0;
+    let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = stack.pop() {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            unreadable += 1;
+            continue;
+        };
+        for entry in entries {
+            let Ok(entry) = entry else {
+                unreadable += 1;
+                break;
+            };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if security.is_denied(&path) {
+                denied(&path);
+                continue;
+            }
+            let step = visit(&path, file_type);
+            if step == Visit::Stop {
+                return Ok(unreadable);
+            }
+            if file_type.is_dir() && depth < max_depth && step == Visit::Continue {
+                stack.push((path, depth + 1));
+            }
+        }
+    }
+    Ok(unreadable)
+}
+
+/// Counts the entries below `root` up to `max_depth` levels without keeping
+/// their names. Symlinks are counted but not followed, denied paths are
+/// neither counted nor entered, and the walk stops once `limit` entries have
+/// been seen.
+fn count_entries_sync(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    limit: u64,
+    ct: &CancellationToken,
+) -> Result<EntryCounts, FsError> {
+    let mut counts = EntryCounts::default();
+    counts.unreadable = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| {},
+        |_, file_type| {
+            if file_type.is_symlink() {
+                counts.symlinks += 1;
+            } else if file_type.is_dir() {
+                counts.dirs += 1;
+            } else {
+                counts.files += 1;
+            }
+            if counts.total() >= limit {
+                counts.truncated = true;
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        },
+    )?;
+    Ok(counts)
+}
+
+/// Per-extension totals and skip reasons gathered by [`code_stats_sync`].
+#[derive(Debug, Default)]
+struct CodeStats {
+    /// Extension (lowercased, `(none)` if absent) to (files, lines)
+    by_extension: HashMap<String, (u64, u64)>,
+    binary: u64,
+    too_large: u64,
+    denied: u64,
+    unreadable: u64,
+}
+
+/// Per-extension sizes and skip reasons gathered by
+/// [`size_by_extension_sync`].
+#[derive(Debug, Default)]
+struct ExtensionSizes {
+    /// Extension (lowercased, `(none)` if absent) to (files, bytes)
+    by_extension: HashMap<String, (u64, u64)>,
+    denied: u64,
+    unreadable: u64,
+}
+
+/// The lowercased extension `path` is tallied under, `(none)` if it has none.
+fn extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+/// Counts lines in `path` by scanning for `\n` bytes, so encodings are never
+/// decoded. A final line without a trailing newline still counts. Returns
+/// `None` for binary files (a NUL in the first 8 KB).
+fn count_lines(path: &Path) -> std::io::Result<Option<u64>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = vec![0u8; LINE_COUNT_CHUNK];
+    let mut lines = 0;
+    let mut scanned = 0;
+    let mut last = b'\n';
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &buf[..n];
+        if scanned < BINARY_CHECK_SIZE && is_binary(&chunk[..n.min(BINARY_CHECK_SIZE - scanned)]) {
+            return Ok(None);
+        }
+        scanned += n;
+        lines += chunk.iter().filter(|&&b| b == b'\n').count() as u64;
+        last = chunk[n - 1];
+    }
+    if last != b'\n' {
+        lines += 1;
+    }
+    Ok(Some(lines))
+}
+
+/// `wc`-style counts for one text file.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct WordCounts {
+    lines: u64,
+    words: u64,
+    bytes: u64,
+    /// Longest line in characters, not counting its `\n` or `\r\n`
+    max_line: u64,
+}
+
+/// Result of counting one file for the count tool.
+#[derive(Debug, PartialEq)]
+enum FileCount {
+    Text(WordCounts),
+    /// Binary file: only its size is reported
+    Binary(u64),
+}
+
+/// Streams `path` once, counting lines (`\n` bytes), words (runs of
+/// non-whitespace), bytes, and the longest line in UTF-8 characters. Files that
+/// [`is_binary`] classifies as binary are not read past their first chunk.
+fn word_count_sync(path: &Path) -> std::io::Result<FileCount> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = vec![0u8; LINE_COUNT_CHUNK];
+    let mut counts = WordCounts::default();
+    let mut in_word = false;
+    let mut line_chars = 0u64;
+    let mut prev = b'\n';
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &buf[..n];
+        if counts.bytes == 0 && is_binary(chunk) {
+            return Ok(FileCount::Binary(file.metadata()?.len()));
+        }
+        counts.bytes += n as u64;
+        for &byte in chunk {
+            if byte == b'\n' {
+                counts.lines += 1;
+                let length = line_chars - u64::from(prev == b'\r');
+                counts.max_line = counts.max_line.max(length);
+                line_chars = 0;
+            } else if byte & 0xC0 != 0x80 {
+                // Count each UTF-8 character once, at its leading byte
+                line_chars += 1;
+            }
+            if byte.is_ascii_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                counts.words += 1;
+            }
+            prev = byte;
+        }
+    }
+    counts.max_line = counts.max_line.max(line_chars);
+    Ok(FileCount::Text(counts))
+}
+
+/// Walks `root` and tallies line counts by extension, skipping binaries,
+/// files over `max_size`, and files that cannot be read.
+fn code_stats_sync(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    max_size: u64,
+    ct: &CancellationToken,
+) -> Result<CodeStats, FsError> {
+    let mut stats = CodeStats::default();
+    let mut denied = 0;
+    let unreadable_dirs = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if !file_type.is_file() {
+                return Visit::Continue;
+            }
+            if std::fs::metadata(path).is_ok_and(|m| m.len() > max_size) {
+                stats.too_large += 1;
+                return Visit::Continue;
+            }
+            match count_lines(path) {
+                Ok(Some(lines)) => {
+                    let totals = stats.by_extension.entry(extension_key(path)).or_default();
+                    totals.0 += 1;
+                    totals.1 += lines;
+                }
+                Ok(None) => stats.binary += 1,
+                Err(_) => stats.unreadable += 1,
+            }
+            Visit::Continue
+        },
+    )?;
+    stats.denied = denied;
+    stats.unreadable += unreadable_dirs;
+    Ok(stats)
+}
+
+/// Walks `root` and tallies file counts and sizes by extension, leaving out
+/// paths matching `exclude`. Symlinks are not followed or counted.
+fn size_by_extension_sync(
+    root: &Path,
+    security: &SecurityContext,
+    exclude: &GlobSet,
+    max_depth: usize,
+    ct: &CancellationToken,
+) -> Result<ExtensionSizes, FsError> {
+    let mut sizes = ExtensionSizes::default();
+    let mut denied = 0;
+    let unreadable_dirs = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if exclude.is_match(manifest_path(root, path)) {
+                return Visit::Skip;
+            }
+            if !file_type.is_file() {
+                return Visit::Continue;
+            }
+            match std::fs::symlink_metadata(path) {
+                Ok(metadata) => {
+                    let totals = sizes.by_extension.entry(extension_key(path)).or_default();
+                    totals.0 += 1;
+                    totals.1 += metadata.len();
+                }
+                Err(_) => sizes.unreadable += 1,
+            }
+            Visit::Continue
+        },
+    )?;
+    sizes.denied = denied;
+    sizes.unreadable += unreadable_dirs;
+    Ok(sizes)
+}
+
+/// Renders the per-extension size table, largest first, with the rows past
+/// `top` rolled into "(other)", a total row, and a footer explaining skipped
+/// paths.
+fn format_extension_sizes(root: &Path, sizes: &ExtensionSizes, top: usize) -> String {
+    let mut rows: Vec<(&str, u64, u64)> = sizes
+        .by_extension
+        .iter()
+        .map(|(ext, (files, bytes))| (ext.as_str(), *files, *bytes))
+        .collect();
+    rows.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then(a.0.cmp(b.0)));
+    let (files, bytes) = rows
+        .iter()
+        .fold((0, 0), |(f, b), (_, files, bytes)| (f + files, b + bytes));
+    let extensions = match rows.len() {
+        1 => "1 extension".to_string(),
+        n => format!("{n} extensions"),
+    };
+    if rows.len() > top {
+        let (other_files, other_bytes) = rows
+            .drain(top..)
+            .fold((0, 0), |(f, b), (_, files, bytes)| (f + files, b + bytes));
+        rows.push(("(other)", other_files, other_bytes));
+    }
+    let share = |part: u64| {
+        if bytes == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", part as f64 * 100.0 / bytes as f64)
+        }
+    };
+
+    let mut out = format!(
+        "Size by extension for {}: {files} files, {} ({bytes} bytes) in {extensions}\n\n",
+        root.display(),
+        format_size(bytes)
+    );
+    let width = rows
+        .iter()
+        .map(|(ext, ..)| ext.len())
+        .chain(["Extension".len()])
+        .max()
+        .unwrap_or(0);
+    out.push_str(&format!(
+        "{:<width$}  {:>8}  {:>10}  {:>6}\n",
+        "Extension", "Files", "Size", "Share"
+    ));
+    for (extension, ext_files, ext_bytes) in &rows {
+        out.push_str(&format!(
+            "{extension:<width$}  {ext_files:>8}  {:>10}  {:>6}\n",
+            format_size(*ext_bytes),
+            share(*ext_bytes)
+        ));
+    }
+    out.push_str(&format!(
+        "{:<width$}  {files:>8}  {:>10}  {:>6}\n",
+        "Total",
+        format_size(bytes),
+        share(bytes)
+    ));
+
+    let mut skipped = Vec::new();
+    if sizes.denied > 0 {
+        skipped.push(format!("{} denied", sizes.denied));
+    }
+    if sizes.unreadable > 0 {
+        skipped.push(format!("{} unreadable", sizes.unreadable));
+    }
+    if skipped.is_empty() {
+        out.push_str("\nSkipped: none");
+    } else {
+        out.push_str(&format!("\nSkipped: {}", skipped.join(", ")));
+    }
+    out
+}
+
+/// Renders the per-extension table, most lines first, with totals and a
+/// footer explaining skipped files.
+fn format_code_stats(root: &Path, stats: &CodeStats, top: Option<usize>, max_size: u64) -> String {
+    let mut rows: Vec<(&String, &(u64, u64))> = stats.by_extension.iter().collect();
+    rows.sort_by(|a, b| b.1.1.cmp(&a.1.1).then(b.1.0.cmp(&a.1.0)).then(a.0.cmp(b.0)));
+    let (files, lines) = rows
+        .iter()
+        .fold((0, 0), |(f, l), (_, (files, lines))| (f + files, l + lines));
+    let shown = top.unwrap_or(rows.len()).min(rows.len());
+
+    let mut out = format!(
+        "Code stats for {} ({files} files, {} extensions)\n\n",
+        root.display(),
+        rows.len()
+    );
+    let width = rows[..shown]
+        .iter()
+        .map(|(ext, _)| ext.len())
+        .chain(["Extension".len(), "Total".len()])
+        .max()
+        .unwrap_or(0);
+    out.push_str(&format!(
+        "{:<width$}  {:>8}  {:>10}\n",
+        "Extension", "Files", "Lines"
+    ));
+    for (extension, (ext_files, ext_lines)) in &rows[..shown] {
+        out.push_str(&format!(
+            "{extension:<width$}  {ext_files:>8}  {ext_lines:>10}\n"
+        ));
+    }
+    if shown < rows.len() {
+        out.push_str(&format!(
+            "({} more extensions not shown)\n",
+            rows.len() - shown
+        ));
+    }
+    out.push_str(&format!("{:<width$}  {files:>8}  {lines:>10}\n", "Total"));
+
+    let mut skipped = Vec::new();
+    if stats.binary > 0 {
+        skipped.push(format!("{} binary", stats.binary));
+    }
+    if stats.too_large > 0 {
+        skipped.push(format!(
+            "{} over max read size ({})",
+            stats.too_large,
+            format_size(max_size)
+        ));
+    }
+    if stats.denied > 0 {
+        skipped.push(format!("{} denied", stats.denied));
+    }
+    if stats.unreadable > 0 {
+        skipped.push(format!("{} unreadable", stats.unreadable));
+    }
+    if skipped.is_empty() {
+        out.push_str("\nSkipped: none");
+    } else {
+        out.push_str(&format!("\nSkipped: {}", skipped.join(", ")));
+    }
+    out
+}
+
+/// Renders the four-line summary; counts are prefixed with `≥` when truncated.
+fn format_counts(root: &Path, counts: &EntryCounts, limit: u64, elapsed: Duration) -> String {
+    let bound = if counts.truncated { "≥" } else { "" };
+    let mut scanned = format!("Scanned {} in {} ms", root.display(), elapsed.as_millis());
+    if counts.truncated {
+        scanned.push_str(&format!(
+            " (stopped at the {limit}-entry ceiling; counts are lower bounds)"
+        ));
+    }
+    if counts.unreadable > 0 {
+        scanned.push_str(&format!(
+            " ({} unreadable directories skipped)",
+            counts.unreadable
+        ));
+    }
+    format!(
+        "Files: {bound}{}\nDirectories: {bound}{}\nSymlinks: {bound}{}\n{scanned}",
+        counts.files, counts.dirs, counts.symlinks
+    )
+}
+
+#[rmcp::tool_router(router = "count_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Counts files, directories, and symlinks under a directory.
+    #[rmcp::tool(
+        name = "count_entries",
+        description = "Counts files, directories, and symlinks under a directory, up to --max-depth levels, without listing them. Stops at --max-count-entries and reports lower bounds (\"≥N\") when it does. Symlinks are counted but not followed, and denied paths are skipped. Cheap: call it before directory_tree or search_files on a directory that may be large.",
+        annotations(
+            title = "Count Entries",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn count_entries(
+        &self,
+        Parameters(params): Parameters<CountEntriesParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let limit = self.config.max_count_entries;
+        let started = Instant::now();
+        let walk_root = root.clone();
+        let counts = spawn_blocking(move || {
+            count_entries_sync(&walk_root, &security, max_depth, limit, &ct)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(format_counts(&root, &counts, limit, started.elapsed()))
+    }
+
+    /// Counts lines, words, bytes, and max line length for one or more files.
+    #[rmcp::tool(
+        name = "count",
+        description = "Counts lines, words, bytes, and the longest line (in characters) for each file, like wc, plus a total row. Files are streamed, never loaded whole. Binary files report bytes only. Files over --max-read-size or that cannot be read get an inline error and the rest are still counted.",
+        annotations(
+            title = "Count Lines and Words",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn count(
+        &self,
+        Parameters(params): Parameters<CountParams>,
+    ) -> Result<String, ToolError> {
+        let mut out = format!(
+            "{:>10} {:>10} {:>12} {:>9}  Path\n",
+            "Lines", "Words", "Bytes", "Max line"
+        );
+        let mut total = WordCounts::default();
+        for file_path in &params.paths {
+            let result: Result<FileCount, ToolError> = async {
+                let canonical = self.security.validate_file(Path::new(file_path))?;
+                let size = tokio::fs::metadata(&canonical)
+                    .await
+                    .map_err(|e| ToolError::io(e, file_path))?
+                    .len();
+                let max_size = self.config.max_read_size as u64;
+                if size > max_size {
+                    return Err(FsError::FileTooLarge {
+                        path: file_path.clone(),
+                        size,
+                        max: max_size,
+                    }
+                    .into());
+                }
+                let path = file_path.clone();
+                spawn_blocking(move || word_count_sync(&canonical))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| ToolError::io(e, &path))
+            }
+            .await;
+
+            match result {
+                Ok(FileCount::Text(counts)) => {
+                    stats::record_read(counts.bytes);
+                    total.lines += counts.lines;
+                    total.words += counts.words;
+                    total.bytes += counts.bytes;
+                    total.max_line = total.max_line.max(counts.max_line);
+                    out.push_str(&format!(
+                        "{:>10} {:>10} {:>12} {:>9}  {file_path}\n",
+                        counts.lines, counts.words, counts.bytes, counts.max_line
+                    ));
+                }
+                Ok(FileCount::Binary(bytes)) => {
+                    total.bytes += bytes;
+                    out.push_str(&format!(
+                        "{:>10} {:>10} {bytes:>12} {:>9}  {file_path} (binary, bytes only)\n",
+                        "-", "-", "-"
+                    ));
+                }
+                Err(err) => out.push_str(&format!("{file_path}: Error: {err}\n")),
+            }
+        }
+        out.push_str(&format!(
+            "{:>10} {:>10} {:>12} {:>9}  total",
+            total.lines, total.words, total.bytes, total.max_line
+        ));
+        Ok(out)
+    }
+
+    /// Summarizes file and line counts by extension under a directory.
+    #[rmcp::tool(
+        name = "code_stats",
+        description = "Summarizes a source tree: file and line counts per file extension under a directory, up to --max-depth levels, sorted by line count with totals. Binary files, files over --max-read-size, and denied paths are skipped and counted in a footer. Use top to show only the largest extensions.",
+        annotations(
+            title = "Code Stats",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn code_stats(
+        &self,
+        Parameters(params): Parameters<CodeStatsParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let max_size = self.config.max_read_size as u64;
+        let walk_root = root.clone();
+        let stats = spawn_blocking(move || {
+            code_stats_sync(&walk_root, &security, max_depth, max_size, &ct)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(format_code_stats(&root, &stats, params.top, max_size))
+    }
+
+    /// Totals file sizes by extension under a directory.
+    #[rmcp::tool(
+        name = "size_by_extension",
+        description = "Shows which kinds of files take up space: file count and total size per file extension (lowercased, \"(none)\" for extensionless files) under a directory, up to --max-depth levels, largest first with each extension's share. Extensions past top (default 20) are rolled into an \"(other)\" row, and the header gives the grand total in bytes. Symlinks are not followed, and denied paths are skipped and counted in a footer. Use exclude to leave out directories such as node_modules.",
+        annotations(
+            title = "Size by Extension",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn size_by_extension(
+        &self,
+        Parameters(params): Parameters<SizeByExtensionParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        let mut builder = GlobSetBuilder::new();
+        for pattern in params.exclude.iter().flatten() {
+            let glob = Glob::new(pattern)
+                .map_err(|e| FsError::PatternError(format!("exclude pattern '{pattern}': {e}")))?;
+            builder.add(glob);
+        }
+        let exclude = builder
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let walk_root = root.clone();
+        let sizes = spawn_blocking(move || {
+            size_by_extension_sync(&walk_root, &security, &exclude, max_depth, &ct)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(format_extension_sizes(
+            &root,
+            &sizes,
+            params.top.unwrap_or(DEFAULT_SIZE_TOP),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn make_service(dir: &TempDir, config: Config) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..config
+        })
+    }
+
+    async fn count(service: &FilesystemService, path: &Path) -> Result<String, ToolError> {
+        service
+            .count_entries(
+                Parameters(CountEntriesParams {
+                    path: path.to_string_lossy().to_string(),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+    }
+
+    fn populate(root: &Path) {
+        std::fs::create_dir_all(root.join("a").join("b")).unwrap();
+        std::fs::write(root.join("one.txt"), "").unwrap();
+        std::fs::write(root.join("a").join("two.txt"), "").unwrap();
+        std::fs::write(root.join("a").join("b").join("three.txt"), "").unwrap();
+        std::fs::write(root.join("secret.pem"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("a"), root.join("link")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn counts_files_dirs_and_symlinks() {
+        let dir = TempDir::new().unwrap();
+        populate(dir.path());
+        let service = make_service(
+            &dir,
+            Config {
+                deny: vec!["*.pem".into()],
+                ..Default::default()
+            },
+        );
+
+        let output = count(&service, dir.path()).await.unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4, "{output}");
+        assert_eq!(lines[0], "Files: 3");
+        assert_eq!(lines[1], "Directories: 2");
+        #[cfg(unix)]
+        assert_eq!(lines[2], "Symlinks: 1");
+        assert!(lines[3].starts_with("Scanned "), "{output}");
+        assert!(lines[3].contains(" ms"));
+    }
+
+    #[tokio::test]
+    async fn respects_max_depth() {
+        let dir = TempDir::new().unwrap();
+        populate(dir.path());
+        let service = make_service(
+            &dir,
+            Config {
+                max_depth: 0,
+                ..Default::default()
+            },
+        );
+
+        let output = count(&service, dir.path()).await.unwrap();
+        assert!(output.starts_with("Files: 2\nDirectories: 1\n"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn stops_at_ceiling_with_lower_bounds() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..10 {
+            std::fs::write(dir.path().join(format!("{i}.txt")), "").unwrap();
+        }
+        let service = make_service(
+            &dir,
+            Config {
+                max_count_entries: 4,
+                ..Default::default()
+            },
+        );
+
+        let output = count(&service, dir.path()).await.unwrap();
+        assert!(
+            output.starts_with("Files: ≥4\nDirectories: ≥0\n"),
+            "{output}"
+        );
+        assert!(output.ends_with("(stopped at the 4-entry ceiling; counts are lower bounds)"));
+    }
+
+    #[test]
+    fn cancelled_walk_fails() {
+        let dir = TempDir::new().unwrap();
+        let security = SecurityContext::new(vec![dir.path().canonicalize().unwrap()]);
+        let ct = CancellationToken::new();
+        ct.cancel();
+        let result = count_entries_sync(dir.path(), &security, 10, 100, &ct);
+        assert!(matches!(result, Err(FsError::Cancelled)));
+    }
+
+    async fn code_stats(
+        service: &FilesystemService,
+        path: &Path,
+        top: Option<usize>,
+    ) -> Result<String, ToolError> {
+        service
+            .code_stats(
+                Parameters(CodeStatsParams {
+                    path: path.to_string_lossy().to_string(),
+                    top,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+    }
+
+    #[test]
+    fn count_lines_handles_missing_trailing_newline_and_binaries() {
+        let dir = TempDir::new().unwrap();
+        let cases: [(&str, &[u8], Option<u64>); 5] = [
+            ("empty", b"", Some(0)),
+            ("one", b"a\n", Some(1)),
+            ("unterminated", b"a\nb", Some(2)),
+            ("crlf", b"a\r\nb\r\n", Some(2)),
+            ("binary", b"a\0b\n", None),
+        ];
+        for (name, content, expected) in cases {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            assert_eq!(count_lines(&path).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn code_stats_aggregates_by_extension() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src").join("main.rs"), "fn main() {\n}\n").unwrap();
+        std::fs::write(dir.path().join("src").join("lib.RS"), "a\nb\nc\n").unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(dir.path().join("Makefile"), "all:\n").unwrap();
+        std::fs::write(dir.path().join("logo.png"), [0x89, 0, 1, 2]).unwrap();
+        std::fs::write(dir.path().join("huge.txt"), "x\n".repeat(64)).unwrap();
+        std::fs::write(dir.path().join("key.pem"), "secret\n").unwrap();
+        let service = make_service(
+            &dir,
+            Config {
+                max_read_size: 100,
+                deny: vec!["*.pem".into()],
+                ..Default::default()
+            },
+        );
+
+        let output = code_stats(&service, dir.path(), None).await.unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].ends_with("(4 files, 3 extensions)"), "{output}");
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            ["Extension", "Files", "Lines"]
+        );
+        assert_eq!(
+            lines[3].split_whitespace().collect::<Vec<_>>(),
+            ["rs", "2", "5"]
+        );
+        assert_eq!(
+            lines[4].split_whitespace().collect::<Vec<_>>(),
+            ["(none)", "1", "1"]
+        );
+        assert_eq!(
+            lines[5].split_whitespace().collect::<Vec<_>>(),
+            ["toml", "1", "1"]
+        );
+        assert_eq!(
+            lines[6].split_whitespace().collect::<Vec<_>>(),
+            ["Total", "4", "7"]
+        );
+        assert_eq!(
+            lines.last().unwrap(),
+            &"Skipped: 1 binary, 1 over max read size (100 B), 1 denied"
+        );
+    }
+
+    #[tokio::test]
+    async fn code_stats_top_limits_rows_but_not_totals() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "1\n2\n3\n").unwrap();
+        std::fs::write(dir.path().join("b.md"), "1\n2\n").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "1\n").unwrap();
+        let service = make_service(&dir, Config::default());
+
+        let output = code_stats(&service, dir.path(), Some(1)).await.unwrap();
+        assert!(output.contains("\nrs "), "{output}");
+        assert!(!output.contains("\nmd "));
+        assert!(output.contains("(2 more extensions not shown)"));
+        let total = output.lines().find(|l| l.starts_with("Total")).unwrap();
+        assert_eq!(
+            total.split_whitespace().collect::<Vec<_>>(),
+            ["Total", "3", "6"]
+        );
+        assert!(output.ends_with("Skipped: none"));
+    }
+
+    async fn size_by_extension(
+        service: &FilesystemService,
+        path: &Path,
+        exclude: &[&str],
+        top: Option<usize>,
+    ) -> String {
+        service
+            .size_by_extension(
+                Parameters(SizeByExtensionParams {
+                    path: path.to_string_lossy().to_string(),
+                    exclude: Some(exclude.iter().map(|p| p.to_string()).collect()),
+                    top,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn size_by_extension_sorts_by_size_and_rolls_up_other() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("assets").join("a.PNG"), "x".repeat(600)).unwrap();
+        std::fs::write(root.join("assets").join("b.png"), "x".repeat(300)).unwrap();
+        std::fs::write(root.join("main.rs"), "x".repeat(80)).unwrap();
+        std::fs::write(root.join("LICENSE"), "x".repeat(15)).unwrap();
+        std::fs::write(root.join("notes.txt"), "x".repeat(5)).unwrap();
+        std::fs::write(root.join("node_modules").join("dep.js"), "x".repeat(5000)).unwrap();
+        std::fs::write(root.join("key.pem"), "secret").unwrap();
+        let service = make_service(
+            &dir,
+            Config {
+                deny: vec!["*.pem".into()],
+                ..Default::default()
+            },
+        );
+
+        let output = size_by_extension(&service, &root, &["node_modules"], Some(2)).await;
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "Size by extension for {}: 5 files, 1000 B (1000 bytes) in 4 extensions",
+                root.display()
+            )
+        );
+        let rows: Vec<Vec<&str>> = lines[2..7]
+            .iter()
+            .map(|l| l.split_whitespace().collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                vec!["Extension", "Files", "Size", "Share"],
+                vec!["png", "2", "900", "B", "90.0%"],
+                vec!["rs", "1", "80", "B", "8.0%"],
+                vec!["(other)", "2", "20", "B", "2.0%"],
+                vec!["Total", "5", "1000", "B", "100.0%"],
+            ]
+        );
+        assert_eq!(lines.last().unwrap(), &"Skipped: 1 denied");
+
+        // Without the exclude, node_modules dominates and nothing is rolled up
+        let output = size_by_extension(&service, &root, &[], None).await;
+        assert!(output.contains("6 files, 5.9 KB (6000 bytes) in 5 extensions"));
+        assert!(
+            output.lines().nth(3).unwrap().starts_with("js "),
+            "{output}"
+        );
+        assert!(!output.contains("(other)"));
+    }
+
+    #[test]
+    fn word_count_matches_wc() {
+        let dir = TempDir::new().unwrap();
+        let cases: [(&str, &[u8], WordCounts); 4] = [
+            ("empty", b"", WordCounts::default()),
+            (
+                "plain",
+                b"hello world\nfoo\n",
+                WordCounts {
+                    lines: 2,
+                    words: 3,
+                    bytes: 16,
+                    max_line: 11,
+                },
+            ),
+            (
+                "crlf_unterminated",
+                b"a b\r\n  c\td",
+                WordCounts {
+                    lines: 1,
+                    words: 4,
+                    bytes: 10,
+                    max_line: 5,
+                },
+            ),
+            (
+                "utf8",
+                "héllo wörld\n".as_bytes(),
+                WordCounts {
+                    lines: 1,
+                    words: 2,
+                    bytes: 14,
+                    max_line: 11,
+                },
+            ),
+        ];
+        for (name, content, expected) in cases {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            assert_eq!(
+                word_count_sync(&path).unwrap(),
+                FileCount::Text(expected),
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn count_reports_rows_binary_errors_and_total() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        let bin = dir.path().join("logo.png");
+        let big = dir.path().join("big.txt");
+        std::fs::write(&a, "one two\nthree\n").unwrap();
+        std::fs::write(&b, "four\n").unwrap();
+        std::fs::write(&bin, [0x89, 0, 0, 0]).unwrap();
+        std::fs::write(&big, "x".repeat(200)).unwrap();
+        let service = make_service(
+            &dir,
+            Config {
+                max_read_size: 100,
+                ..Default::default()
+            },
+        );
+        let paths = [&a, &b, &bin, &big, &dir.path().join("missing.txt")]
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        let output = service
+            .count(Parameters(CountParams { paths }))
+            .await
+            .unwrap();
+        let rows: Vec<Vec<&str>> = output
+            .lines()
+            .map(|l| l.split_whitespace().collect())
+            .collect();
+        assert_eq!(rows.len(), 7, "{output}");
+        assert_eq!(rows[1][..4], ["2", "3", "14", "7"]);
+        assert_eq!(rows[2][..4], ["1", "1", "5", "4"]);
+        assert_eq!(rows[3][..4], ["-", "-", "4", "-"]);
+        assert!(output.contains("logo.png (binary, bytes only)"));
+        assert!(
+            output.contains("big.txt: Error: File too large"),
+            "{output}"
+        );
+        assert!(output.contains("missing.txt: Error: "));
+        assert_eq!(rows[6], ["3", "4", "23", "7", "total"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_file_path() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("f.txt");
+        std::fs::write(&file, "").unwrap();
+        let service = make_service(&dir, Config::default());
+        let err = count(&service, &file).await.unwrap_err();
+        assert!(err.message.starts_with("Not a directory"), "{err}");
+    }
+}
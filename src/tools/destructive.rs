@@ -1,13 +1,39 @@
 use crate::FilesystemService;
-use crate::error::io_error_message;
+use crate::error::{FsError, ToolError};
+use crate::service::CONFIRMATION_TTL;
+use crate::tools::search::{Traversal, find_matching_files};
+use crate::tools::util::{
+    format_date, format_size, is_binary, sha256_file, spawn_blocking, to_hex,
+};
+use globset::Glob;
 use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::LoggingLevel;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct DeleteFileParams {
     /// Absolute path to the file to delete
     path: String,
+    /// Abort unless the file is exactly this many bytes
+    #[schemars(description = "Abort unless the file is exactly this many bytes")]
+    expected_size: Option<u64>,
+    /// Abort unless the file's SHA-256 (hex) matches
+    #[schemars(description = "Abort unless the file's SHA-256 (hex) matches")]
+    expected_sha256: Option<String>,
+    /// Describe what would happen without changing anything (default: false)
+    #[schemars(
+        description = "Describe what would happen without changing anything (default: false)"
+    )]
+    dry_run: Option<bool>,
+    /// Token from a previous call, required when the server runs with --confirm-destructive
+    #[schemars(
+        description = "Token from a previous call, required when the server runs with --confirm-destructive"
+    )]
+    confirm_token: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -16,83 +42,930 @@ struct MoveFileParams {
     source: String,
     /// Absolute path to the destination
     destination: String,
+    /// Replace an existing destination file or empty directory (default: false)
+    #[schemars(
+        description = "Replace an existing destination file or empty directory (default: false)"
+    )]
+    overwrite: Option<bool>,
+    /// Create missing parent directories of the destination (default: false)
+    #[schemars(
+        description = "Create missing parent directories of the destination (default: false)"
+    )]
+    create_parents: Option<bool>,
+    /// Describe what would happen without changing anything (default: false)
+    #[schemars(
+        description = "Describe what would happen without changing anything (default: false)"
+    )]
+    dry_run: Option<bool>,
+    /// Token from a previous call, required when the server runs with --confirm-destructive
+    #[schemars(
+        description = "Token from a previous call, required when the server runs with --confirm-destructive"
+    )]
+    confirm_token: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct DeleteDirectoryParams {
-    /// Absolute path to the empty directory to delete
+    /// Absolute path to the directory to delete
+    path: String,
+    /// Delete the directory and everything inside it (default: false, empty directories only)
+    #[schemars(
+        description = "Delete the directory and everything inside it (default: false, empty directories only)"
+    )]
+    recursive: Option<bool>,
+    /// Required for recursive deletes above the server's entry threshold
+    #[schemars(
+        description = "Must be true to recursively delete more entries than the server's confirmation threshold"
+    )]
+    confirm: Option<bool>,
+    /// Describe what would happen without changing anything (default: false)
+    #[schemars(
+        description = "Describe what would happen without changing anything (default: false)"
+    )]
+    dry_run: Option<bool>,
+    /// Token from a previous call, required when the server runs with --confirm-destructive
+    #[schemars(
+        description = "Token from a previous call, required when the server runs with --confirm-destructive"
+    )]
+    confirm_token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct DeleteDirectoryRecursiveParams {
+    /// Absolute path to the directory to delete with everything inside it
+    path: String,
+    /// Must be true; without it nothing is deleted
+    #[schemars(description = "Must be true; without it nothing is deleted")]
+    confirm: Option<bool>,
+    /// Describe what would happen without changing anything (default: false)
+    #[schemars(
+        description = "Describe what would happen without changing anything (default: false)"
+    )]
+    dry_run: Option<bool>,
+    /// Token from a previous call, required when the server runs with --confirm-destructive
+    #[schemars(
+        description = "Token from a previous call, required when the server runs with --confirm-destructive"
+    )]
+    confirm_token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct DeleteMatchingParams {
+    /// Absolute path to the directory to search under
     path: String,
+    /// Glob pattern matched against paths relative to `path` (e.g. "**/*.orig")
+    pattern: String,
+    /// Set to true, together with the preview token, to actually delete
+    #[schemars(description = "Set to true, together with the preview token, to actually delete")]
+    confirm: Option<bool>,
+    /// Token returned by the preview call
+    #[schemars(description = "Token returned by the preview call")]
+    token: Option<String>,
+    /// Describe what would happen without changing anything (default: false)
+    #[schemars(
+        description = "Describe what would happen without changing anything (default: false)"
+    )]
+    dry_run: Option<bool>,
+}
+
+/// Marks a dry-run response so it can't be mistaken for a real change.
+pub(crate) fn dry_run_report(description: &str) -> String {
+    format!("DRY RUN (nothing was changed): {description}")
+}
+
+/// Identifies a set of files: a SHA-256 prefix of the sorted path list.
+fn preview_token(files: &[(PathBuf, u64)]) -> String {
+    let mut paths: Vec<String> = files.iter().map(|(p, _)| p.display().to_string()).collect();
+    paths.sort();
+    let digest = Sha256::digest(paths.join("\n").as_bytes());
+    to_hex(&digest[..8])
+}
+
+/// What a recursive delete will remove. Symlinks count as files and are never followed.
+#[derive(Debug, Default)]
+struct TreeStats {
+    files: usize,
+    dirs: usize,
+    bytes: u64,
+    /// Every entry below the root, for deny checks
+    entries: Vec<std::path::PathBuf>,
+}
+
+impl TreeStats {
+    fn total(&self) -> usize {
+        self.files + self.dirs
+    }
+}
+
+/// Walks `dir` without following symlinks, counting everything below it, and
+/// stops early once more than `limit` entries are counted. Fails with
+/// `Interrupted` once `ct` is cancelled.
+fn collect_tree_sync(
+    dir: &std::path::Path,
+    stats: &mut TreeStats,
+    limit: usize,
+    ct: &CancellationToken,
+) -> std::io::Result<()> {
+    if ct.is_cancelled() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            FsError::Cancelled,
+        ));
+    }
+    for entry in std::fs::read_dir(dir)? {
+        if stats.total() > limit {
+            break;
+        }
+        let entry = entry?;
+        let path = entry.path();
+        let meta = std::fs::symlink_metadata(&path)?;
+        if meta.is_dir() {
+            stats.dirs += 1;
+            collect_tree_sync(&path, stats, limit, ct)?;
+        } else {
+            stats.files += 1;
+            if meta.is_file() {
+                stats.bytes += meta.len();
+            }
+        }
+        stats.entries.push(path);
+    }
+    Ok(())
+}
+
+/// Largest destination file whose hash `move_file` reports before overwriting it.
+const REPLACED_HASH_LIMIT: u64 = 1024 * 1024;
+
+/// Describes a destination about to be overwritten, e.g.
+/// "replaced existing file (2.1 KB, modified 2024-10-02, sha256 ab12cd34ef567890…)".
+/// The hash is only included for small text files.
+fn describe_replaced_sync(path: &Path, meta: &std::fs::Metadata) -> String {
+    if meta.is_dir() {
+        return "replaced existing empty directory".to_string();
+    }
+    let mut details = vec![format_size(meta.len())];
+    if let Ok(modified) = meta.modified() {
+        details.push(format!("modified {}", format_date(modified)));
+    }
+    if meta.is_file()
+        && meta.len() <= REPLACED_HASH_LIMIT
+        && let Ok(content) = std::fs::read(path)
+        && !is_binary(&content)
+    {
+        let digest = to_hex(&Sha256::digest(&content));
+        details.push(format!("sha256 {}\u{2026}", &digest[..16]));
+    }
+    format!("replaced existing file ({})", details.join(", "))
+}
+
+/// Renames `src` to `dest`, failing with `AlreadyExists` if `dest` exists.
+///
+/// Uses `renameat2(RENAME_NOREPLACE)` so the check is atomic, falling back to
+/// check-then-rename on filesystems that don't support the flag.
+#[cfg(target_os = "linux")]
+fn rename_noreplace_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_src = CString::new(src.as_os_str().as_bytes())?;
+    let c_dest = CString::new(dest.as_os_str().as_bytes())?;
+    // SAFETY: both pointers come from live NUL-terminated CStrings
+    let rc = unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            c_src.as_ptr(),
+            libc::AT_FDCWD,
+            c_dest.as_ptr(),
+            libc::RENAME_NOREPLACE,
+        )
+    };
+    if rc == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EINVAL | libc::ENOSYS) => rename_check_then_move_sync(src, dest),
+        _ => Err(err),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rename_noreplace_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    rename_check_then_move_sync(src, dest)
+}
+
+/// Portable fallback for [`rename_noreplace_sync`]; racy between check and rename.
+fn rename_check_then_move_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(dest).is_ok() {
+        return Err(std::io::ErrorKind::AlreadyExists.into());
+    }
+    std::fs::rename(src, dest)
+}
+
+impl FilesystemService {
+    /// What a delete does with the current config: "delete" or "move to trash".
+    fn removal_verb(&self) -> &'static str {
+        if self.config.trash_dir.is_some() {
+            "move to trash"
+        } else {
+            "delete"
+        }
+    }
+
+    /// Refuses to move or delete an allowed root: every later path check would
+    /// fail once it is gone.
+    fn refuse_allowed_root(&self, canonical: &Path, action: &str) -> Result<(), ToolError> {
+        if self.security.is_allowed_root(canonical) {
+            return Err(format!(
+                "Refusing to {action} allowed root directory {}: the server depends on it. Operate on its contents instead.",
+                canonical.display()
+            ).into());
+        }
+        Ok(())
+    }
+
+    /// Two-phase handshake for `--confirm-destructive`.
+    ///
+    /// Without a token, issues one bound to `tool` and `binding` (canonical paths
+    /// and flags) and returns `Some(response)`; the caller must stop there. With a
+    /// token, consumes it and returns `None` so the operation can go ahead.
+    fn confirmation_gate(
+        &self,
+        tool: &str,
+        binding: &[String],
+        token: Option<&str>,
+        summary: &str,
+    ) -> Result<Option<String>, ToolError> {
+        if !self.config.confirm_destructive {
+            return Ok(None);
+        }
+        let operation = format!("{tool}\n{}", binding.join("\n"));
+        match token {
+            Some(token) => {
+                self.confirmations.redeem(token, &operation)?;
+                Ok(None)
+            }
+            None => {
+                let token = self.confirmations.issue(&operation)?;
+                Ok(Some(format!(
+                    "CONFIRMATION REQUIRED (nothing was changed): {summary}\n\nTo proceed, call {tool} again with the same arguments and confirm_token: \"{token}\" within {} minutes. The token works once.",
+                    CONFIRMATION_TTL.as_secs() / 60
+                )))
+            }
+        }
+    }
+}
+
+/// Binds a `delete_matching` confirmation token to the exact set of files.
+fn matching_binding(files: &[(PathBuf, u64)]) -> String {
+    let mut paths: Vec<String> = files.iter().map(|(p, _)| p.display().to_string()).collect();
+    paths.sort();
+    format!("delete_matching\n{}", paths.join("\n"))
 }
 
 #[rmcp::tool_router(router = "destructive_tools_router", vis = "pub(crate)")]
 impl FilesystemService {
     #[rmcp::tool(
         name = "delete_file",
-        description = "Deletes a single file. The file must exist and be a regular file (not a directory).",
-        annotations(read_only_hint = false, destructive_hint = true)
+        description = "Deletes a single file. The file must exist and be a regular file (not a directory). If the server has a trash directory configured, the file is moved there instead. Pass expected_size and/or expected_sha256 to abort if the file changed since it was read. With dry_run: true, reports what would happen without changing anything. If the server runs with --confirm-destructive, the first call returns a confirmation token instead of acting; repeat the same call with confirm_token set to it within 5 minutes to proceed.",
+        annotations(
+            title = "Delete File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
     )]
     async fn delete_file(
         &self,
         Parameters(params): Parameters<DeleteFileParams>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ToolError> {
         let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_file(path)
-            .map_err(|e| e.to_string())?;
+        let canonical = self.security.validate_file(path)?;
+        self.security.ensure_writable(&canonical, path)?;
+        self.check_delete_allowed()?;
+        // Checked right before removal so a stale read can't delete a file that
+        // has since changed.
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        if let Some(expected) = params.expected_size
+            && expected != size
+        {
+            return Err(format!(
+                "Precondition failed: {} is {size} bytes, expected {expected}",
+                canonical.display()
+            )
+            .into());
+        }
+        if let Some(expected) = &params.expected_sha256 {
+            let canonical_clone = canonical.clone();
+            let actual = spawn_blocking(move || sha256_file(&canonical_clone))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| ToolError::io(e, &params.path))?;
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                return Err(format!(
+                    "Precondition failed: {} has SHA-256 {actual}, expected {}",
+                    canonical.display(),
+                    expected.trim()
+                )
+                .into());
+            }
+        }
+        if params.dry_run.unwrap_or(false) {
+            return Ok(dry_run_report(&format!(
+                "would {} 1 file, {} ({})",
+                self.removal_verb(),
+                canonical.display(),
+                format_size(size)
+            )));
+        }
+        if let Some(response) = self.confirmation_gate(
+            "delete_file",
+            &[canonical.display().to_string()],
+            params.confirm_token.as_deref(),
+            &format!(
+                "would {} 1 file, {} ({})",
+                self.removal_verb(),
+                canonical.display(),
+                format_size(size)
+            ),
+        )? {
+            return Ok(response);
+        }
+        if let Some(trashed) = self.move_to_trash(&canonical).await? {
+            return Ok(format!(
+                "Moved file {} to trash at {}",
+                canonical.display(),
+                trashed.display()
+            ));
+        }
         tokio::fs::remove_file(&canonical)
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            .map_err(|e| ToolError::io(e, &params.path))?;
         Ok(format!("Deleted file {}", canonical.display()))
     }
 
     #[rmcp::tool(
         name = "move_file",
-        description = "Moves or renames a file or directory. Both source and destination must be within allowed directories. The source must exist.",
-        annotations(read_only_hint = false, destructive_hint = true)
+        description = "Moves or renames a file or directory. Both source and destination must be within allowed directories. The source must exist. Fails if the destination exists unless overwrite: true is passed; a non-empty destination directory is never overwritten. With create_parents: true, missing parent directories of the destination are created. With dry_run: true, reports what would happen without changing anything. If the server runs with --confirm-destructive, a call that would overwrite an existing destination returns a confirmation token instead of acting; repeat the same call with confirm_token set to it within 5 minutes to proceed.",
+        annotations(
+            title = "Move File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
     )]
     async fn move_file(
         &self,
         Parameters(params): Parameters<MoveFileParams>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ToolError> {
         let source = std::path::Path::new(&params.source);
         let destination = std::path::Path::new(&params.destination);
-        let canonical_source = self
-            .security
-            .validate_path_exists(source)
-            .map_err(|e| e.to_string())?;
-        let canonical_dest = self
-            .security
-            .validate_path(destination)
-            .map_err(|e| e.to_string())?;
+        let canonical_source = self.security.validate_path_exists(source)?;
+        self.security.ensure_writable(&canonical_source, source)?;
+        let create_parents = params.create_parents.unwrap_or(false);
+        let canonical_dest = if create_parents {
+            self.security.validate_creatable_path(destination)
+        } else {
+            self.security.validate_path(destination)
+        }?;
+        self.security
+            .ensure_writable(&canonical_dest, destination)?;
+        self.refuse_allowed_root(&canonical_source, "move")?;
+        self.refuse_allowed_root(&canonical_dest, "replace")?;
+
+        // Ancestors that don't exist yet, outermost first
+        let dry_run = params.dry_run.unwrap_or(false);
+        let mut created: Vec<PathBuf> = Vec::new();
+        if create_parents && let Some(parent) = canonical_dest.parent() {
+            for ancestor in parent.ancestors() {
+                if tokio::fs::try_exists(ancestor).await.unwrap_or(false) {
+                    break;
+                }
+                created.push(ancestor.to_path_buf());
+            }
+            created.reverse();
+            if !created.is_empty() && !dry_run {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| ToolError::io(e, &params.destination))?;
+            }
+        }
+        let created_note = if created.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " ({} directories: {})",
+                if dry_run { "would create" } else { "created" },
+                created
+                    .iter()
+                    .map(|d| d.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        let destination_exists_error = || -> ToolError {
+            format!(
+                "Destination exists: {}. Pass overwrite: true to replace it.",
+                canonical_dest.display()
+            )
+            .into()
+        };
+
+        let overwrite = params.overwrite.unwrap_or(false);
+        let existing = tokio::fs::symlink_metadata(&canonical_dest).await.ok();
+        if dry_run {
+            let outcome = match &existing {
+                None => "destination does not exist".to_string(),
+                Some(_) if !overwrite => return Err(destination_exists_error()),
+                Some(meta) if meta.is_dir() => {
+                    if std::fs::read_dir(&canonical_dest).is_ok_and(|mut d| d.next().is_some()) {
+                        return Err(format!(
+                            "Destination is a non-empty directory: {}. Refusing to overwrite it.",
+                            canonical_dest.display()
+                        )
+                        .into());
+                    }
+                    self.check_delete_allowed()?;
+                    format!("would {} the existing empty directory", self.removal_verb())
+                }
+                Some(meta) => {
+                    self.check_delete_allowed()?;
+                    format!(
+                        "would {} the existing file ({})",
+                        self.removal_verb(),
+                        format_size(meta.len())
+                    )
+                }
+            };
+            return Ok(dry_run_report(&format!(
+                "would move {} to {}, {outcome}{created_note}",
+                canonical_source.display(),
+                canonical_dest.display()
+            )));
+        }
+
+        if !overwrite {
+            let (src, dest) = (canonical_source.clone(), canonical_dest.clone());
+            return match spawn_blocking(move || rename_noreplace_sync(&src, &dest))
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                Ok(()) => Ok(format!(
+                    "Moved {} to {}{created_note}",
+                    canonical_source.display(),
+                    canonical_dest.display()
+                )),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    Err(destination_exists_error())
+                }
+                Err(e) => Err(ToolError::io(e, &params.source)),
+            };
+        }
+
+        let mut trashed = None;
+        let mut replaced_note = String::new();
+        if let Some(meta) = existing {
+            if meta.is_dir() {
+                let mut entries = tokio::fs::read_dir(&canonical_dest)
+                    .await
+                    .map_err(|e| ToolError::io(e, &params.destination))?;
+                if entries
+                    .next_entry()
+                    .await
+                    .map_err(|e| ToolError::io(e, &params.destination))?
+                    .is_some()
+                {
+                    return Err(format!(
+                        "Destination is a non-empty directory: {}. Refusing to overwrite it.",
+                        canonical_dest.display()
+                    )
+                    .into());
+                }
+            }
+            // Overwriting deletes the old destination, so it goes through the trash rules
+            self.check_delete_allowed()?;
+            let what = if meta.is_dir() {
+                "the existing empty directory".to_string()
+            } else {
+                format!("the existing file ({})", format_size(meta.len()))
+            };
+            if let Some(response) = self.confirmation_gate(
+                "move_file",
+                &[
+                    canonical_source.display().to_string(),
+                    canonical_dest.display().to_string(),
+                ],
+                params.confirm_token.as_deref(),
+                &format!(
+                    "would move {} to {}, replacing {what}",
+                    canonical_source.display(),
+                    canonical_dest.display()
+                ),
+            )? {
+                return Ok(response);
+            }
+            // Captured before the old destination disappears, for the transcript
+            let dest = canonical_dest.clone();
+            replaced_note = spawn_blocking(move || describe_replaced_sync(&dest, &meta))
+                .await
+                .map_err(|e| e.to_string())?;
+            trashed = self.move_to_trash(&canonical_dest).await?;
+        }
         tokio::fs::rename(&canonical_source, &canonical_dest)
             .await
-            .map_err(|e| io_error_message(e, &params.source))?;
-        Ok(format!(
-            "Moved {} to {}",
+            .map_err(|e| ToolError::io(e, &params.source))?;
+        let mut output = format!(
+            "Moved {} to {}{created_note}",
             canonical_source.display(),
             canonical_dest.display()
-        ))
+        );
+        if !replaced_note.is_empty() {
+            output.push_str(&format!("; {replaced_note}"));
+        }
+        if let Some(trashed) = trashed {
+            output.push_str(&format!(
+                "; previous destination moved to trash at {}",
+                trashed.display()
+            ));
+        }
+        Ok(output)
+    }
+
+    #[rmcp::tool(
+        name = "delete_matching",
+        description = "Deletes files matching a glob under a directory, in two steps. First call without confirm to get a preview listing every file that would be removed and a token. Then call again with confirm: true and that token; deletion only happens if the matching files are unchanged. Only regular files are deleted, never directories or symlinks. Denied paths are skipped, traversal stops at the server's max depth, and at most --max-delete-matching files are deleted per call. With dry_run: true, the confirming call checks the token and reports what would be deleted without changing anything. If the server runs with --confirm-destructive, the preview token is single-use and expires after 5 minutes.",
+        annotations(
+            title = "Delete Matching Files",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn delete_matching(
+        &self,
+        Parameters(params): Parameters<DeleteMatchingParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_directory(path)?;
+        let matcher = globset::GlobSetBuilder::new()
+            .add(Glob::new(&params.pattern).map_err(|e| FsError::PatternError(e.to_string()))?)
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+
+        let max_files = self.config.max_delete_matching;
+        let denied_skipped = std::sync::atomic::AtomicUsize::new(0);
+        let mut found = find_matching_files(
+            &canonical,
+            &matcher,
+            &[],
+            Traversal {
+                max_depth: self.config.max_depth,
+                gitignore: false,
+                exclude: globset::GlobSet::empty(),
+                security: self.security.clone(),
+            },
+            max_files + 1,
+            |p| {
+                let denied = self.security.is_read_only(p);
+                if denied {
+                    denied_skipped.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                !denied
+            },
+            &ct,
+        )
+        .await?;
+        self.log_unreadable(&canonical, &found.skipped);
+        let denied_skipped = denied_skipped.into_inner() + found.denied;
+        if denied_skipped > 0 {
+            self.client_log.log(
+                LoggingLevel::Info,
+                "delete_matching",
+                &format!(
+                    "Skipped {denied_skipped} path(s) covered by deny patterns or in read-only directories"
+                ),
+            );
+        }
+        let more_remain = found.files.len() > max_files;
+        found.files.truncate(max_files);
+        let files = found.files;
+
+        if files.is_empty() {
+            return Ok(format!(
+                "No files match \"{}\" in {}",
+                params.pattern,
+                canonical.display()
+            ));
+        }
+        let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+        let cap_note = if more_remain {
+            format!(" (more files match; only the first {max_files} are handled per call)")
+        } else {
+            String::new()
+        };
+
+        let confirm_mode = self.config.confirm_destructive;
+        if !params.confirm.unwrap_or(false) {
+            let token = if confirm_mode {
+                self.confirmations.issue(&matching_binding(&files))?
+            } else {
+                preview_token(&files)
+            };
+            let mut output = format!(
+                "Preview: {} file(s) matching \"{}\" in {} would be deleted ({}){cap_note}:\n\n",
+                files.len(),
+                params.pattern,
+                canonical.display(),
+                format_size(total_bytes)
+            );
+            for (file, size) in &files {
+                output.push_str(&format!("{} ({})\n", file.display(), format_size(*size)));
+            }
+            output.push_str(&format!(
+                "\nTo delete these files, call delete_matching again with confirm: true and token: \"{token}\""
+            ));
+            return Ok(output);
+        }
+
+        let dry_run = params.dry_run.unwrap_or(false);
+        let given = params.token.as_deref().unwrap_or_default();
+        if confirm_mode {
+            let binding = matching_binding(&files);
+            if dry_run {
+                self.confirmations.check(given, &binding)?;
+            } else {
+                self.confirmations.redeem(given, &binding)?;
+            }
+        } else if given != preview_token(&files) {
+            return Err(
+                "Token does not match the current set of matching files. Call delete_matching without confirm to get a fresh preview.".into(),
+            );
+        }
+        self.check_delete_allowed()?;
+        if dry_run {
+            let mut output = dry_run_report(&format!(
+                "would {} {} file(s) matching \"{}\" in {} ({}){cap_note}:\n\n",
+                self.removal_verb(),
+                files.len(),
+                params.pattern,
+                canonical.display(),
+                format_size(total_bytes)
+            ));
+            for (file, size) in &files {
+                output.push_str(&format!("{} ({})\n", file.display(), format_size(*size)));
+            }
+            return Ok(output);
+        }
+
+        let mut removed = Vec::new();
+        let mut failed = Vec::new();
+        for (file, _) in &files {
+            let outcome = match self.move_to_trash(file).await {
+                Ok(Some(_)) => Ok(()),
+                Ok(None) => tokio::fs::remove_file(file)
+                    .await
+                    .map_err(|e| ToolError::io(e, &file.display().to_string())),
+                Err(e) => Err(e),
+            };
+            match outcome {
+                Ok(()) => removed.push(file),
+                Err(e) => failed.push(format!("{}: {e}", file.display())),
+            }
+        }
+
+        let verb = if self.config.trash_dir.is_some() {
+            "Moved to trash"
+        } else {
+            "Deleted"
+        };
+        let mut output = format!(
+            "{verb} {} file(s) matching \"{}\" in {}{cap_note}:\n\n",
+            removed.len(),
+            params.pattern,
+            canonical.display()
+        );
+        for file in removed {
+            output.push_str(&format!("{}\n", file.display()));
+        }
+        if !failed.is_empty() {
+            output.push_str(&format!("\nFailed ({}):\n", failed.len()));
+            for line in failed {
+                output.push_str(&format!("{line}\n"));
+            }
+        }
+        Ok(output)
     }
 
     #[rmcp::tool(
         name = "delete_directory",
-        description = "Deletes an empty directory. The directory must exist and be empty. Does NOT recursively delete contents.",
-        annotations(read_only_hint = false, destructive_hint = true)
+        description = "Deletes a directory. By default the directory must be empty. With recursive: true, deletes the directory and all of its contents (symlinks inside are removed, never followed) and reports what was removed. Allowed root directories cannot be deleted recursively. Large trees additionally require confirm: true. If the server has a trash directory configured, the directory is moved there instead. With dry_run: true, reports what would be removed without changing anything. If the server runs with --confirm-destructive, the first call returns a confirmation token instead of acting; repeat the same call with confirm_token set to it within 5 minutes to proceed.",
+        annotations(
+            title = "Delete Directory",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
     )]
     async fn delete_directory(
         &self,
         Parameters(params): Parameters<DeleteDirectoryParams>,
-    ) -> Result<String, String> {
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
         let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_directory(path)?;
+        self.security.ensure_writable(&canonical, path)?;
+        self.refuse_allowed_root(&canonical, "delete")?;
+        self.check_delete_allowed()?;
+        let dry_run = params.dry_run.unwrap_or(false);
+        if !params.recursive.unwrap_or(false) {
+            let mut entries = tokio::fs::read_dir(&canonical)
+                .await
+                .map_err(|e| ToolError::io(e, &params.path))?;
+            if entries
+                .next_entry()
+                .await
+                .map_err(|e| ToolError::io(e, &params.path))?
+                .is_some()
+            {
+                return Err(format!(
+                    "Directory not empty: {}. Pass recursive: true to delete its contents.",
+                    canonical.display()
+                )
+                .into());
+            }
+            if dry_run {
+                return Ok(dry_run_report(&format!(
+                    "would {} empty directory {}",
+                    self.removal_verb(),
+                    canonical.display()
+                )));
+            }
+            if let Some(response) = self.confirmation_gate(
+                "delete_directory",
+                &[canonical.display().to_string()],
+                params.confirm_token.as_deref(),
+                &format!(
+                    "would {} empty directory {}",
+                    self.removal_verb(),
+                    canonical.display()
+                ),
+            )? {
+                return Ok(response);
+            }
+            if let Some(trashed) = self.move_to_trash(&canonical).await? {
+                return Ok(format!(
+                    "Moved directory {} to trash at {}",
+                    canonical.display(),
+                    trashed.display()
+                ));
+            }
+            tokio::fs::remove_dir(&canonical)
+                .await
+                .map_err(|e| ToolError::io(e, &params.path))?;
+            return Ok(format!("Deleted directory {}", canonical.display()));
+        }
+
+        let root = canonical.clone();
+        let max_entries = self.config.max_recursive_delete_entries;
+        let stats = spawn_blocking(move || {
+            let mut stats = TreeStats::default();
+            collect_tree_sync(&root, &mut stats, max_entries, &ct).map(|()| stats)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| ToolError::io(e, &params.path))?;
+        if stats.total() > max_entries {
+            return Err(format!(
+                "Refusing to delete {}: it contains more than {max_entries} entries (--max-recursive-delete-entries). Nothing was deleted; delete its subdirectories separately.",
+                canonical.display()
+            )
+            .into());
+        }
+
+        if let Some(denied) = stats.entries.iter().find(|p| self.security.is_denied(p)) {
+            return Err(format!(
+                "Refusing to delete {}: it contains denied path {}",
+                canonical.display(),
+                denied.display()
+            )
+            .into());
+        }
+        let counts = format!(
+            "{} file(s), {} subdirector{}, {}",
+            stats.files,
+            stats.dirs,
+            if stats.dirs == 1 { "y" } else { "ies" },
+            format_size(stats.bytes)
+        );
+        let threshold = self.config.delete_confirm_threshold;
+        let needs_confirm = stats.total() > threshold && !params.confirm.unwrap_or(false);
+        if dry_run {
+            let mut report = dry_run_report(&format!(
+                "would {} directory {} recursively: {counts}",
+                self.removal_verb(),
+                canonical.display()
+            ));
+            if needs_confirm {
+                report.push_str(&format!(
+                    "\n{} entries is more than the limit of {threshold}; the real call would require confirm: true.",
+                    stats.total()
+                ));
+            }
+            return Ok(report);
+        }
+        if needs_confirm {
+            return Err(format!(
+                "{} contains {} entries ({} files, {} directories, {}), more than the limit of {threshold}. Call again with confirm: true to delete it.",
+                canonical.display(),
+                stats.total(),
+                stats.files,
+                stats.dirs,
+                format_size(stats.bytes)
+            ).into());
+        }
+        if let Some(response) = self.confirmation_gate(
+            "delete_directory",
+            &[canonical.display().to_string(), "recursive".to_string()],
+            params.confirm_token.as_deref(),
+            &format!(
+                "would {} directory {} recursively: {counts}",
+                self.removal_verb(),
+                canonical.display()
+            ),
+        )? {
+            return Ok(response);
+        }
+        if let Some(trashed) = self.move_to_trash(&canonical).await? {
+            return Ok(format!(
+                "Moved directory {} to trash at {}: {counts}",
+                canonical.display(),
+                trashed.display()
+            ));
+        }
+        // remove_dir_all unlinks symlinks rather than descending into them
+        tokio::fs::remove_dir_all(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        Ok(format!(
+            "Deleted directory {} recursively: {counts}",
+            canonical.display()
+        ))
+    }
+
+    #[rmcp::tool(
+        name = "delete_directory_recursive",
+        description = "Deletes a directory and everything inside it. Requires confirm: true on every call, whatever the size of the tree; without it nothing is deleted. Otherwise behaves like delete_directory with recursive: true: symlinks inside are removed, never followed, allowed root directories and trees holding denied paths are refused, a configured trash directory receives the tree instead, and the counts of what was removed are reported. With dry_run: true, reports what would be removed without changing anything, and confirm is not needed.",
+        annotations(
+            title = "Delete Directory Recursively",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn delete_directory_recursive(
+        &self,
+        Parameters(params): Parameters<DeleteDirectoryRecursiveParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
         let canonical = self
             .security
-            .validate_directory(path)
-            .map_err(|e| e.to_string())?;
-        tokio::fs::remove_dir(&canonical)
-            .await
-            .map_err(|e| io_error_message(e, &params.path))?;
-        Ok(format!("Deleted directory {}", canonical.display()))
+            .validate_directory(std::path::Path::new(&params.path))?;
+        let confirmed = params.confirm.unwrap_or(false);
+        let dry_run = params.dry_run.unwrap_or(false);
+        if !confirmed && !dry_run {
+            return Err(format!(
+                "delete_directory_recursive removes {} and everything inside it, so it needs explicit confirmation. Nothing was deleted; call again with confirm: true.",
+                canonical.display()
+            )
+            .into());
+        }
+        // Explicit confirmation also covers --delete-confirm-threshold
+        let mut output = self
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: params.path,
+                    recursive: Some(true),
+                    confirm: Some(true),
+                    dry_run: params.dry_run,
+                    confirm_token: params.confirm_token,
+                }),
+                ct,
+            )
+            .await?;
+        if dry_run && !confirmed {
+            output.push_str("\nThe real call requires confirm: true.");
+        }
+        Ok(output)
     }
 }
 
@@ -110,16 +983,19 @@ mod tests {
             allow_destructive: true,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
 
     #[test]
-    fn destructive_tools_router_contains_all_three() {
+    fn destructive_tools_router_contains_all_five() {
         let router = FilesystemService::destructive_tools_router();
         let tools = router.list_all();
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
-        assert_eq!(tools.len(), 3);
+        assert_eq!(tools.len(), 5);
+        assert!(names.contains(&"delete_directory_recursive"));
+        assert!(names.contains(&"delete_matching"));
         assert!(names.contains(&"delete_file"));
         assert!(names.contains(&"move_file"));
         assert!(names.contains(&"delete_directory"));
@@ -132,6 +1008,8 @@ mod tests {
             let ann = tool.annotations.as_ref().unwrap();
             assert_eq!(ann.read_only_hint, Some(false));
             assert_eq!(ann.destructive_hint, Some(true));
+            // A repeat call finds nothing to delete or move and fails
+            assert_eq!(ann.idempotent_hint, Some(false));
         }
     }
 
@@ -145,6 +1023,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let service = FilesystemService::new(config);
         let tools = service.tool_router.list_all();
@@ -152,7 +1031,8 @@ mod tests {
         assert!(!names.contains(&"delete_file"));
         assert!(!names.contains(&"move_file"));
         assert!(!names.contains(&"delete_directory"));
-        assert_eq!(tools.len(), 10);
+        assert!(!names.contains(&"delete_directory_recursive"));
+        assert_eq!(tools.len(), 45);
     }
 
     #[test]
@@ -165,6 +1045,7 @@ mod tests {
             allow_destructive: true,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let service = FilesystemService::new(config);
         let tools = service.tool_router.list_all();
@@ -172,7 +1053,8 @@ mod tests {
         assert!(names.contains(&"delete_file"));
         assert!(names.contains(&"move_file"));
         assert!(names.contains(&"delete_directory"));
-        assert_eq!(tools.len(), 13);
+        assert!(names.contains(&"delete_directory_recursive"));
+        assert_eq!(tools.len(), 50);
     }
 
     #[tokio::test]
@@ -186,6 +1068,10 @@ mod tests {
         let result = service
             .delete_file(Parameters(DeleteFileParams {
                 path: file.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.unwrap().contains("Deleted file"));
@@ -200,6 +1086,10 @@ mod tests {
         let result = service
             .delete_file(Parameters(DeleteFileParams {
                 path: dir.path().join("nope.txt").to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_err());
@@ -215,6 +1105,10 @@ mod tests {
         let result = service
             .delete_file(Parameters(DeleteFileParams {
                 path: sub.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_err());
@@ -231,6 +1125,10 @@ mod tests {
         let result = service
             .delete_file(Parameters(DeleteFileParams {
                 path: outside.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_err());
@@ -249,6 +1147,10 @@ mod tests {
             .move_file(Parameters(MoveFileParams {
                 source: src.to_string_lossy().to_string(),
                 destination: dst.to_string_lossy().to_string(),
+                overwrite: None,
+                create_parents: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.unwrap().contains("Moved"));
@@ -269,6 +1171,10 @@ mod tests {
             .move_file(Parameters(MoveFileParams {
                 source: src_dir.to_string_lossy().to_string(),
                 destination: dst_dir.to_string_lossy().to_string(),
+                overwrite: None,
+                create_parents: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_ok());
@@ -288,6 +1194,10 @@ mod tests {
             .move_file(Parameters(MoveFileParams {
                 source: dir.path().join("nope.txt").to_string_lossy().to_string(),
                 destination: dir.path().join("dest.txt").to_string_lossy().to_string(),
+                overwrite: None,
+                create_parents: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_err());
@@ -305,6 +1215,10 @@ mod tests {
             .move_file(Parameters(MoveFileParams {
                 source: outside.to_string_lossy().to_string(),
                 destination: dir.path().join("stolen.txt").to_string_lossy().to_string(),
+                overwrite: None,
+                create_parents: None,
+                dry_run: None,
+                confirm_token: None,
             }))
             .await;
         assert!(result.is_err());
@@ -322,10 +1236,228 @@ mod tests {
             .move_file(Parameters(MoveFileParams {
                 source: src.to_string_lossy().to_string(),
                 destination: other.path().join("exfil.txt").to_string_lossy().to_string(),
+                overwrite: None,
+                create_parents: None,
+                dry_run: None,
+                confirm_token: None,
+            }))
+            .await;
+        assert!(result.is_err());
+        assert!(src.exists());
+    }
+
+    fn move_params(source: &Path, destination: &Path, overwrite: Option<bool>) -> MoveFileParams {
+        MoveFileParams {
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+            overwrite,
+            create_parents: None,
+            dry_run: None,
+            confirm_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn move_file_refuses_existing_destination() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("new.txt");
+        let dst = dir.path().join("existing.txt");
+        std::fs::write(&src, "new").unwrap();
+        std::fs::write(&dst, "precious").unwrap();
+        let service = make_service(vec![canon]);
+        for overwrite in [None, Some(false)] {
+            let err = service
+                .move_file(Parameters(move_params(&src, &dst, overwrite)))
+                .await
+                .unwrap_err();
+            assert!(err.message.contains("Destination exists"), "{err}");
+        }
+        assert_eq!(std::fs::read_to_string(&src).unwrap(), "new");
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "precious");
+    }
+
+    #[tokio::test]
+    async fn move_file_refuses_existing_empty_directory_without_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("src_dir");
+        let dst = dir.path().join("dst_dir");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::create_dir(&dst).unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .move_file(Parameters(move_params(&src, &dst, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+        assert!(src.exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_overwrite_replaces_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("new.txt");
+        let dst = dir.path().join("existing.txt");
+        std::fs::write(&src, "new").unwrap();
+        std::fs::write(&dst, "old").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(move_params(&src, &dst, Some(true))))
+            .await
+            .unwrap();
+        assert!(result.contains("Moved"));
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "new");
+    }
+
+    #[tokio::test]
+    async fn move_file_overwrite_refuses_nonempty_directory() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("src_dir");
+        let dst = dir.path().join("dst_dir");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::create_dir(&dst).unwrap();
+        std::fs::write(dst.join("keep.txt"), "keep").unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .move_file(Parameters(move_params(&src, &dst, Some(true))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("non-empty directory"), "{err}");
+        assert!(src.exists());
+        assert!(dst.join("keep.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_overwrite_moves_old_destination_to_trash() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let trash = TempDir::new().unwrap();
+        let src = dir.path().join("new.txt");
+        let dst = dir.path().join("existing.txt");
+        std::fs::write(&src, "new").unwrap();
+        std::fs::write(&dst, "old").unwrap();
+        let service = make_trash_service(canon, trash.path());
+        let result = service
+            .move_file(Parameters(move_params(&src, &dst, Some(true))))
+            .await
+            .unwrap();
+        assert!(
+            result.contains("previous destination moved to trash"),
+            "{result}"
+        );
+        assert!(
+            result.contains("replaced existing file (3 B, modified "),
+            "{result}"
+        );
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "new");
+        let trashed = only_trash_folder(trash.path(), dir.path()).join("existing.txt");
+        assert_eq!(std::fs::read_to_string(trashed).unwrap(), "old");
+    }
+
+    #[tokio::test]
+    async fn move_file_create_parents_creates_missing_ancestors() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("report.txt");
+        std::fs::write(&src, "data").unwrap();
+        let dst = dir.path().join("archive").join("2026").join("report.txt");
+        let service = make_service(vec![canon.clone()]);
+        let result = service
+            .move_file(Parameters(MoveFileParams {
+                create_parents: Some(true),
+                ..move_params(&src, &dst, None)
+            }))
+            .await
+            .unwrap();
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "data");
+        let archive = canon.join("archive");
+        let year = archive.join("2026");
+        assert!(
+            result.contains(&format!(
+                "created directories: {}, {}",
+                archive.display(),
+                year.display()
+            )),
+            "{result}"
+        );
+    }
+
+    #[tokio::test]
+    async fn move_file_without_create_parents_fails_for_missing_parent() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("report.txt");
+        std::fs::write(&src, "data").unwrap();
+        let dst = dir.path().join("missing").join("report.txt");
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(move_params(&src, &dst, None)))
+            .await;
+        assert!(result.is_err());
+        assert!(src.exists());
+        assert!(!dir.path().join("missing").exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_create_parents_rejects_dotdot_in_missing_tail() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("report.txt");
+        std::fs::write(&src, "data").unwrap();
+        let dst = dir
+            .path()
+            .join("new")
+            .join("..")
+            .join("..")
+            .join("escaped.txt");
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(MoveFileParams {
+                create_parents: Some(true),
+                ..move_params(&src, &dst, None)
             }))
             .await;
         assert!(result.is_err());
         assert!(src.exists());
+        assert!(!dir.path().join("new").exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_create_parents_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("report.txt");
+        std::fs::write(&src, "data").unwrap();
+        let other = TempDir::new().unwrap();
+        let dst = other.path().join("a").join("b.txt");
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(MoveFileParams {
+                create_parents: Some(true),
+                ..move_params(&src, &dst, None)
+            }))
+            .await;
+        assert!(result.is_err());
+        assert!(!other.path().join("a").exists());
+    }
+
+    #[test]
+    fn rename_check_then_move_refuses_existing() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("a");
+        let dst = dir.path().join("b");
+        std::fs::write(&src, "a").unwrap();
+        std::fs::write(&dst, "b").unwrap();
+        let err = rename_check_then_move_sync(&src, &dst).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        std::fs::remove_file(&dst).unwrap();
+        rename_check_then_move_sync(&src, &dst).unwrap();
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "a");
     }
 
     #[tokio::test]
@@ -336,9 +1468,16 @@ mod tests {
         std::fs::create_dir(&sub).unwrap();
         let service = make_service(vec![canon]);
         let result = service
-            .delete_directory(Parameters(DeleteDirectoryParams {
-                path: sub.to_string_lossy().to_string(),
-            }))
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: sub.to_string_lossy().to_string(),
+                    recursive: None,
+                    confirm: None,
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
             .await;
         assert!(result.unwrap().contains("Deleted directory"));
         assert!(!sub.exists());
@@ -353,9 +1492,16 @@ mod tests {
         std::fs::write(sub.join("file.txt"), "data").unwrap();
         let service = make_service(vec![canon]);
         let result = service
-            .delete_directory(Parameters(DeleteDirectoryParams {
-                path: sub.to_string_lossy().to_string(),
-            }))
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: sub.to_string_lossy().to_string(),
+                    recursive: None,
+                    confirm: None,
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
             .await;
         assert!(result.is_err());
         assert!(sub.exists());
@@ -369,9 +1515,16 @@ mod tests {
         std::fs::write(&file, "data").unwrap();
         let service = make_service(vec![canon]);
         let result = service
-            .delete_directory(Parameters(DeleteDirectoryParams {
-                path: file.to_string_lossy().to_string(),
-            }))
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: file.to_string_lossy().to_string(),
+                    recursive: None,
+                    confirm: None,
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
             .await;
         assert!(result.is_err());
     }
@@ -385,11 +1538,1257 @@ mod tests {
         let outside = other.path().join("secret_dir");
         std::fs::create_dir(&outside).unwrap();
         let result = service
-            .delete_directory(Parameters(DeleteDirectoryParams {
-                path: outside.to_string_lossy().to_string(),
-            }))
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: outside.to_string_lossy().to_string(),
+                    recursive: None,
+                    confirm: None,
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
             .await;
         assert!(result.is_err());
         assert!(outside.exists());
     }
+
+    fn delete_dir_params(path: &std::path::Path, confirm: Option<bool>) -> DeleteDirectoryParams {
+        DeleteDirectoryParams {
+            path: path.to_string_lossy().to_string(),
+            recursive: Some(true),
+            confirm,
+            dry_run: None,
+            confirm_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_removes_tree_and_reports_counts() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("build");
+        std::fs::create_dir_all(target.join("obj").join("deep")).unwrap();
+        std::fs::write(target.join("a.o"), "12345").unwrap();
+        std::fs::write(target.join("obj").join("b.o"), "123").unwrap();
+        std::fs::write(target.join("obj").join("deep").join("c.o"), "12").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.contains("3 file(s)"), "{result}");
+        assert!(result.contains("2 subdirectories"), "{result}");
+        assert!(result.contains("10 B"), "{result}");
+        assert!(!target.exists());
+        assert!(dir.path().exists());
+    }
+
+    fn recursive_params(
+        path: &std::path::Path,
+        confirm: Option<bool>,
+    ) -> DeleteDirectoryRecursiveParams {
+        DeleteDirectoryRecursiveParams {
+            path: path.to_string_lossy().to_string(),
+            confirm,
+            dry_run: None,
+            confirm_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_tool_requires_confirm() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("work");
+        std::fs::create_dir_all(target.join("sub")).unwrap();
+        std::fs::write(target.join("sub").join("a.txt"), "x").unwrap();
+        let service = make_service(vec![canon]);
+
+        for confirm in [None, Some(false)] {
+            let err = service
+                .delete_directory_recursive(
+                    Parameters(recursive_params(&target, confirm)),
+                    CancellationToken::new(),
+                )
+                .await
+                .unwrap_err();
+            assert!(err.message.contains("confirm: true"), "{err}");
+            assert!(err.message.contains("Nothing was deleted"), "{err}");
+            assert!(target.join("sub").join("a.txt").exists());
+        }
+
+        let report = service
+            .delete_directory_recursive(
+                Parameters(DeleteDirectoryRecursiveParams {
+                    dry_run: Some(true),
+                    ..recursive_params(&target, None)
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(report.starts_with("DRY RUN"), "{report}");
+        assert!(
+            report.ends_with("The real call requires confirm: true."),
+            "{report}"
+        );
+        assert!(target.exists());
+
+        let result = service
+            .delete_directory_recursive(
+                Parameters(recursive_params(&target, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.starts_with("Deleted directory"), "{result}");
+        assert!(result.contains("1 file(s), 1 subdirectory"), "{result}");
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_tool_refuses_bad_paths() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("file.txt");
+        std::fs::write(&file, "data").unwrap();
+        let secret = canon.join("secret");
+        std::fs::create_dir(&secret).unwrap();
+        let other = TempDir::new().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon.clone()],
+            allow_write: true,
+            allow_destructive: true,
+            deny: vec!["secret".to_string()],
+            ..Default::default()
+        });
+        let call = |path: PathBuf| {
+            let service = &service;
+            async move {
+                service
+                    .delete_directory_recursive(
+                        Parameters(recursive_params(&path, Some(true))),
+                        CancellationToken::new(),
+                    )
+                    .await
+                    .unwrap_err()
+            }
+        };
+
+        let err = call(file.clone()).await;
+        assert!(err.message.contains("Not a directory"), "{err}");
+        let err = call(secret.clone()).await;
+        assert_eq!(err.details.unwrap()["kind"], "denied_by_pattern");
+        let err = call(other.path().to_path_buf()).await;
+        assert_eq!(err.details.unwrap()["kind"], "path_denied");
+        let err = call(canon.clone()).await;
+        assert!(err.message.contains("allowed"), "{err}");
+        assert!(file.exists() && secret.exists() && other.path().exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_rejects_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("not_a_dir.txt");
+        std::fs::write(&file, "data").unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&file, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Not a directory"), "{err}");
+        assert!(file.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_false_keeps_nonempty() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("keep");
+        std::fs::create_dir(&target).unwrap();
+        std::fs::write(target.join("f.txt"), "x").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: target.to_string_lossy().to_string(),
+                    recursive: Some(false),
+                    confirm: Some(true),
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(target.join("f.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_refuses_allowed_root() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("f.txt"), "x").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(dir.path(), Some(true))),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(result.unwrap_err().message.contains("allowed root"));
+        assert!(dir.path().join("f.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_refuses_root_via_dotdot() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(
+                    &dir.path().join("sub").join(".."),
+                    Some(true),
+                )),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(result.unwrap_err().message.contains("allowed root"));
+        assert!(dir.path().join("sub").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_requires_confirm_above_threshold() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("many");
+        std::fs::create_dir(&target).unwrap();
+        for i in 0..5 {
+            std::fs::write(target.join(format!("{i}.txt")), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            delete_confirm_threshold: 3,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("5 entries"), "{err}");
+        assert!(err.message.contains("confirm: true"), "{err}");
+        assert!(target.exists());
+
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, Some(false))),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(err.is_err());
+        assert!(target.exists());
+
+        service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_dry_run_above_threshold_needs_no_confirm() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("many");
+        std::fs::create_dir(&target).unwrap();
+        for i in 0..5 {
+            std::fs::write(target.join(format!("{i}.txt")), "x").unwrap();
+        }
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            delete_confirm_threshold: 3,
+            ..Default::default()
+        });
+
+        let report = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    dry_run: Some(true),
+                    ..delete_dir_params(&target, None)
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(report.starts_with("DRY RUN"), "{report}");
+        assert!(report.contains("5 file(s)"), "{report}");
+        assert!(
+            report.contains("the real call would require confirm: true"),
+            "{report}"
+        );
+        assert_eq!(std::fs::read_dir(&target).unwrap().count(), 5);
+
+        let report = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    dry_run: Some(true),
+                    ..delete_dir_params(&target, Some(true))
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!report.contains("require confirm"), "{report}");
+        assert!(target.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_refuses_trees_over_max_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("workspace");
+        std::fs::create_dir_all(target.join("src")).unwrap();
+        for i in 0..4 {
+            std::fs::write(target.join("src").join(format!("{i}.rs")), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            delete_confirm_threshold: 2,
+            max_recursive_delete_entries: 4,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        // confirm: true does not lift the hard limit
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("more than 4 entries"), "{err}");
+        assert!(
+            err.message.contains("--max-recursive-delete-entries"),
+            "{err}"
+        );
+        assert_eq!(std::fs::read_dir(target.join("src")).unwrap().count(), 4);
+
+        // Five entries in all: src/ and its four files. One fewer fits the limit
+        std::fs::remove_file(target.join("src/0.rs")).unwrap();
+        service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_at_threshold_needs_no_confirm() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("few");
+        std::fs::create_dir(&target).unwrap();
+        for i in 0..3 {
+            std::fs::write(target.join(format!("{i}.txt")), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            delete_confirm_threshold: 3,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!target.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn delete_directory_recursive_unlinks_symlinks_without_following() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let keep = dir.path().join("keep");
+        std::fs::create_dir(&keep).unwrap();
+        std::fs::write(keep.join("precious.txt"), "data").unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("external.txt"), "data").unwrap();
+
+        let target = dir.path().join("build");
+        std::fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&keep, target.join("dir_link")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), target.join("outside_link")).unwrap();
+        std::os::unix::fs::symlink(keep.join("precious.txt"), target.join("file_link")).unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.contains("3 file(s)"), "{result}");
+        assert!(result.contains("0 subdirectories"), "{result}");
+        assert!(!target.exists());
+        assert!(keep.join("precious.txt").exists());
+        assert!(outside.path().join("external.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_refuses_tree_with_denied_path() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("app");
+        std::fs::create_dir(&target).unwrap();
+        std::fs::write(target.join(".env"), "SECRET=1").unwrap();
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            deny: vec![".env".into()],
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("denied path"), "{err}");
+        assert!(target.join(".env").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+        let other = TempDir::new().unwrap();
+        let outside = other.path().join("victim");
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(outside.join("f.txt"), "x").unwrap();
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(&outside, Some(true))),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(outside.join("f.txt").exists());
+    }
+
+    fn make_trash_service(root: PathBuf, trash: &Path) -> FilesystemService {
+        let config = Config::builder()
+            .allowed_directory(root)
+            .allow_destructive(true)
+            .trash_dir(trash)
+            .build()
+            .unwrap();
+        FilesystemService::new(config)
+    }
+
+    /// Returns where `root`'s items land in the single timestamped folder in `trash`.
+    fn only_trash_folder(trash: &Path, root: &Path) -> PathBuf {
+        let folders: Vec<_> = std::fs::read_dir(trash)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(folders.len(), 1, "{folders:?}");
+        let root = root.canonicalize().unwrap();
+        folders[0].join(crate::tools::trash::trash_root_key(&root))
+    }
+
+    #[tokio::test]
+    async fn delete_file_moves_to_trash_preserving_relative_path() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let trash = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let file = dir.path().join("src").join("old.rs");
+        std::fs::write(&file, "fn old() {}").unwrap();
+
+        let service = make_trash_service(canon, trash.path());
+        let result = service
+            .delete_file(Parameters(DeleteFileParams {
+                path: file.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
+            }))
+            .await
+            .unwrap();
+        assert!(result.contains("to trash at"), "{result}");
+        assert!(!file.exists());
+
+        let trashed = only_trash_folder(trash.path(), dir.path())
+            .join("src")
+            .join("old.rs");
+        assert_eq!(std::fs::read_to_string(trashed).unwrap(), "fn old() {}");
+    }
+
+    #[tokio::test]
+    async fn delete_directory_recursive_moves_tree_to_trash() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let trash = TempDir::new().unwrap();
+        let target = dir.path().join("build");
+        std::fs::create_dir_all(target.join("obj")).unwrap();
+        std::fs::write(target.join("obj").join("a.o"), "abc").unwrap();
+
+        let service = make_trash_service(canon, trash.path());
+        let result = service
+            .delete_directory(
+                Parameters(delete_dir_params(&target, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.contains("to trash at"), "{result}");
+        assert!(result.contains("1 file(s), 1 subdirectory"), "{result}");
+        assert!(!target.exists());
+        let trashed = only_trash_folder(trash.path(), dir.path()).join("build");
+        assert!(trashed.join("obj").join("a.o").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_directory_with_trash_still_requires_empty() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let trash = TempDir::new().unwrap();
+        let target = dir.path().join("full");
+        std::fs::create_dir(&target).unwrap();
+        std::fs::write(target.join("f.txt"), "x").unwrap();
+
+        let service = make_trash_service(canon, trash.path());
+        let result = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    path: target.to_string_lossy().to_string(),
+                    recursive: None,
+                    confirm: None,
+                    dry_run: None,
+                    confirm_token: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(result.unwrap_err().message.contains("not empty"));
+        assert!(target.join("f.txt").exists());
+        assert_eq!(std::fs::read_dir(trash.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn require_trash_without_trash_dir_refuses_deletes() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("keep.txt");
+        std::fs::write(&file, "x").unwrap();
+        let sub = dir.path().join("empty");
+        std::fs::create_dir(&sub).unwrap();
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            require_trash: true,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        let err = service
+            .delete_file(Parameters(DeleteFileParams {
+                path: file.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: None,
+                confirm_token: None,
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--require-trash"), "{err}");
+        let err = service
+            .delete_directory(
+                Parameters(delete_dir_params(&sub, Some(true))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--require-trash"), "{err}");
+        assert!(file.exists());
+        assert!(sub.exists());
+    }
+
+    fn matching_params(
+        root: &Path,
+        pattern: &str,
+        confirm: Option<bool>,
+        token: Option<String>,
+    ) -> DeleteMatchingParams {
+        DeleteMatchingParams {
+            path: root.to_string_lossy().to_string(),
+            pattern: pattern.to_string(),
+            confirm,
+            token,
+            dry_run: None,
+        }
+    }
+
+    /// Pulls the token out of a delete_matching preview.
+    fn token_from(preview: &str) -> String {
+        let start = preview.rfind("token: \"").unwrap() + "token: \"".len();
+        preview[start..preview.len() - 1].to_string()
+    }
+
+    fn make_patch_leftovers(root: &Path) {
+        std::fs::create_dir_all(root.join("src").join("deep")).unwrap();
+        std::fs::write(root.join("a.orig"), "1").unwrap();
+        std::fs::write(root.join("src").join("b.rej"), "22").unwrap();
+        std::fs::write(root.join("src").join("deep").join("c.orig"), "333").unwrap();
+        std::fs::write(root.join("src").join("keep.rs"), "fn main() {}").unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_matching_preview_lists_files_without_deleting() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        make_patch_leftovers(dir.path());
+        let service = make_service(vec![canon]);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "**/*.{orig,rej}", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(preview.starts_with("Preview: 3 file(s)"), "{preview}");
+        assert!(preview.contains("c.orig"));
+        assert!(!preview.contains("keep.rs"));
+        assert!(dir.path().join("a.orig").exists());
+        assert_eq!(token_from(&preview).len(), 16);
+    }
+
+    #[tokio::test]
+    async fn delete_matching_confirm_with_token_deletes_only_matches() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        make_patch_leftovers(dir.path());
+        let service = make_service(vec![canon]);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "**/*.{orig,rej}", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let result = service
+            .delete_matching(
+                Parameters(matching_params(
+                    dir.path(),
+                    "**/*.{orig,rej}",
+                    Some(true),
+                    Some(token_from(&preview)),
+                )),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.starts_with("Deleted 3 file(s)"), "{result}");
+        assert!(!dir.path().join("a.orig").exists());
+        assert!(!dir.path().join("src").join("b.rej").exists());
+        assert!(!dir.path().join("src").join("deep").join("c.orig").exists());
+        assert!(dir.path().join("src").join("keep.rs").exists());
+        assert!(dir.path().join("src").join("deep").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_matching_confirm_requires_matching_token() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        make_patch_leftovers(dir.path());
+        let service = make_service(vec![canon]);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "**/*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let token = token_from(&preview);
+
+        // Missing token
+        let err = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "**/*.orig", Some(true), None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Token does not match"));
+
+        // The set changed after the preview
+        std::fs::write(dir.path().join("new.orig"), "surprise").unwrap();
+        let err = service
+            .delete_matching(
+                Parameters(matching_params(
+                    dir.path(),
+                    "**/*.orig",
+                    Some(true),
+                    Some(token),
+                )),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Token does not match"));
+        assert!(dir.path().join("a.orig").exists());
+        assert!(dir.path().join("new.orig").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_matching_never_deletes_directories() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("build.orig")).unwrap();
+        std::fs::write(dir.path().join("x.orig"), "x").unwrap();
+        let service = make_service(vec![canon]);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(preview.starts_with("Preview: 1 file(s)"), "{preview}");
+        service
+            .delete_matching(
+                Parameters(matching_params(
+                    dir.path(),
+                    "*.orig",
+                    Some(true),
+                    Some(token_from(&preview)),
+                )),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(dir.path().join("build.orig").is_dir());
+    }
+
+    #[tokio::test]
+    async fn delete_matching_skips_denied_and_respects_max_depth() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(dir.path().join("a").join("b")).unwrap();
+        std::fs::write(dir.path().join("top.orig"), "x").unwrap();
+        std::fs::write(dir.path().join("secret.orig"), "x").unwrap();
+        std::fs::write(dir.path().join("a").join("b").join("deep.orig"), "x").unwrap();
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            max_depth: 1,
+            deny: vec!["secret.*".into()],
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "**/*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(preview.starts_with("Preview: 1 file(s)"), "{preview}");
+        assert!(preview.contains("top.orig"));
+        assert!(!preview.contains("secret.orig"));
+        assert!(!preview.contains("deep.orig"));
+    }
+
+    #[tokio::test]
+    async fn delete_matching_caps_files_per_call() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            allow_destructive: true,
+            max_delete_matching: 20,
+            ..Default::default()
+        });
+        let max_files = service.config.max_delete_matching;
+        for i in 0..max_files + 5 {
+            std::fs::write(dir.path().join(format!("{i}.tmp")), "").unwrap();
+        }
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "*.tmp", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(preview.starts_with(&format!("Preview: {max_files} file(s)")));
+        assert!(preview.contains("more files match"));
+        service
+            .delete_matching(
+                Parameters(matching_params(
+                    dir.path(),
+                    "*.tmp",
+                    Some(true),
+                    Some(token_from(&preview)),
+                )),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 5);
+    }
+
+    #[tokio::test]
+    async fn delete_matching_no_matches() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+        let output = service
+            .delete_matching(
+                Parameters(matching_params(dir.path(), "*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.starts_with("No files match"));
+    }
+
+    #[tokio::test]
+    async fn delete_file_dry_run_leaves_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("keep.txt");
+        std::fs::write(&file, "still here").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_file(Parameters(DeleteFileParams {
+                path: file.to_string_lossy().to_string(),
+                expected_size: None,
+                expected_sha256: None,
+                dry_run: Some(true),
+                confirm_token: None,
+            }))
+            .await
+            .unwrap();
+        assert!(result.starts_with("DRY RUN"), "{result}");
+        assert!(result.contains("would delete 1 file"), "{result}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "still here");
+    }
+
+    #[tokio::test]
+    async fn move_file_dry_run_leaves_tree() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("a.txt");
+        std::fs::write(&src, "a").unwrap();
+        let dst = dir.path().join("new/deeper/b.txt");
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(MoveFileParams {
+                create_parents: Some(true),
+                dry_run: Some(true),
+                ..move_params(&src, &dst, None)
+            }))
+            .await
+            .unwrap();
+        assert!(result.starts_with("DRY RUN"), "{result}");
+        assert!(result.contains("destination does not exist"), "{result}");
+        assert!(result.contains("would create directories"), "{result}");
+        assert!(src.exists());
+        assert!(!dir.path().join("new").exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_dry_run_reports_replacement() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("a.txt");
+        let dst = dir.path().join("b.txt");
+        std::fs::write(&src, "a").unwrap();
+        std::fs::write(&dst, "b").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .move_file(Parameters(MoveFileParams {
+                dry_run: Some(true),
+                ..move_params(&src, &dst, None)
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+
+        let result = service
+            .move_file(Parameters(MoveFileParams {
+                dry_run: Some(true),
+                ..move_params(&src, &dst, Some(true))
+            }))
+            .await
+            .unwrap();
+        assert!(
+            result.contains("would delete the existing file"),
+            "{result}"
+        );
+        assert_eq!(std::fs::read_to_string(&src).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(&dst).unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn delete_directory_dry_run_leaves_tree() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let target = dir.path().join("tree");
+        std::fs::create_dir_all(target.join("sub")).unwrap();
+        std::fs::write(target.join("sub/f.txt"), "x").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    dry_run: Some(true),
+                    ..delete_dir_params(&target, None)
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.starts_with("DRY RUN"), "{result}");
+        assert!(result.contains("1 file(s), 1 subdirectory"), "{result}");
+        assert!(target.join("sub/f.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn delete_matching_dry_run_checks_token_and_keeps_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("a.orig"), "a").unwrap();
+        let service = make_service(vec![canon.clone()]);
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(&canon, "*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let result = service
+            .delete_matching(
+                Parameters(DeleteMatchingParams {
+                    dry_run: Some(true),
+                    ..matching_params(&canon, "*.orig", Some(true), Some(token_from(&preview)))
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(result.starts_with("DRY RUN"), "{result}");
+        assert!(result.contains("would delete 1 file(s)"), "{result}");
+        assert!(dir.path().join("a.orig").exists());
+    }
+
+    fn conditional_delete(
+        file: &Path,
+        expected_size: Option<u64>,
+        expected_sha256: Option<&str>,
+    ) -> DeleteFileParams {
+        DeleteFileParams {
+            path: file.to_string_lossy().to_string(),
+            expected_size,
+            expected_sha256: expected_sha256.map(str::to_string),
+            dry_run: None,
+            confirm_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_file_preconditions_match() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("abc.txt");
+        std::fs::write(&file, "abc").unwrap();
+        let service = make_service(vec![canon]);
+        let sha = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        service
+            .delete_file(Parameters(conditional_delete(&file, Some(3), Some(sha))))
+            .await
+            .unwrap();
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_file_size_mismatch_aborts() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("abc.txt");
+        std::fs::write(&file, "abcd").unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .delete_file(Parameters(conditional_delete(&file, Some(3), None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Precondition failed"), "{err}");
+        assert!(err.message.contains("is 4 bytes, expected 3"), "{err}");
+        assert!(file.exists());
+    }
+
+    #[tokio::test]
+    async fn delete_file_hash_mismatch_aborts() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("abc.txt");
+        std::fs::write(&file, "abc").unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .delete_file(Parameters(conditional_delete(&file, None, Some("00ff"))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Precondition failed"), "{err}");
+        assert!(err.message.contains("ba7816bf8f01cfea"), "{err}");
+        assert!(file.exists());
+    }
+
+    fn make_confirming_service(dir: &Path) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.canonicalize().unwrap()],
+            allow_write: true,
+            allow_destructive: true,
+            confirm_destructive: true,
+            ..Default::default()
+        })
+    }
+
+    fn confirm_token_from(response: &str) -> String {
+        let start = response.find("confirm_token: \"").unwrap() + "confirm_token: \"".len();
+        let end = start + response[start..].find('"').unwrap();
+        response[start..end].to_string()
+    }
+
+    #[tokio::test]
+    async fn confirm_destructive_delete_file_handshake() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+        let service = make_confirming_service(dir.path());
+        let params = |token: Option<String>| DeleteFileParams {
+            path: file.to_string_lossy().to_string(),
+            expected_size: None,
+            expected_sha256: None,
+            dry_run: None,
+            confirm_token: token,
+        };
+
+        let first = service.delete_file(Parameters(params(None))).await.unwrap();
+        assert!(first.starts_with("CONFIRMATION REQUIRED"), "{first}");
+        assert!(file.exists());
+
+        let token = confirm_token_from(&first);
+        let result = service
+            .delete_file(Parameters(params(Some(token.clone()))))
+            .await
+            .unwrap();
+        assert!(result.contains("Deleted file"), "{result}");
+        assert!(!file.exists());
+
+        std::fs::write(&file, "again").unwrap();
+        let err = service
+            .delete_file(Parameters(params(Some(token))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("already used"), "{err}");
+        assert!(file.exists());
+    }
+
+    #[tokio::test]
+    async fn confirm_destructive_rejects_token_for_other_path() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        std::fs::write(&a, "a").unwrap();
+        std::fs::write(&b, "b").unwrap();
+        let service = make_confirming_service(dir.path());
+
+        let preview = service
+            .move_file(Parameters(MoveFileParams {
+                confirm_token: None,
+                ..move_params(&a, &b, Some(true))
+            }))
+            .await
+            .unwrap();
+        assert!(preview.starts_with("CONFIRMATION REQUIRED"), "{preview}");
+        assert!(preview.contains("replacing the existing file"), "{preview}");
+
+        let err = service
+            .move_file(Parameters(MoveFileParams {
+                confirm_token: Some(confirm_token_from(&preview)),
+                ..move_params(&b, &a, Some(true))
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("different operation"), "{err}");
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn confirm_destructive_delete_matching_token_is_single_use() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("a.orig"), "a").unwrap();
+        let service = make_confirming_service(dir.path());
+
+        let preview = service
+            .delete_matching(
+                Parameters(matching_params(&canon, "*.orig", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let token = token_from(&preview);
+        assert_eq!(token.len(), 32);
+        service
+            .delete_matching(
+                Parameters(matching_params(
+                    &canon,
+                    "*.orig",
+                    Some(true),
+                    Some(token.clone()),
+                )),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(!dir.path().join("a.orig").exists());
+
+        std::fs::write(dir.path().join("a.orig"), "a").unwrap();
+        let err = service
+            .delete_matching(
+                Parameters(matching_params(&canon, "*.orig", Some(true), Some(token))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("already used"), "{err}");
+        assert!(dir.path().join("a.orig").exists());
+    }
+
+    #[tokio::test]
+    async fn move_file_overwrite_reports_replaced_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("new.txt");
+        let dst = dir.path().join("existing.txt");
+        std::fs::write(&src, "new").unwrap();
+        std::fs::write(&dst, "abc").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(move_params(&src, &dst, Some(true))))
+            .await
+            .unwrap();
+        assert!(
+            result.contains("replaced existing file (3 B, modified "),
+            "{result}"
+        );
+        assert!(
+            result.contains("sha256 ba7816bf8f01cfea\u{2026}"),
+            "{result}"
+        );
+    }
+
+    #[tokio::test]
+    async fn move_file_overwrite_skips_hash_for_binary() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let src = dir.path().join("new.bin");
+        let dst = dir.path().join("existing.bin");
+        std::fs::write(&src, "new").unwrap();
+        std::fs::write(&dst, [0u8, 1, 2]).unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .move_file(Parameters(move_params(&src, &dst, Some(true))))
+            .await
+            .unwrap();
+        assert!(result.contains("replaced existing file (3 B"), "{result}");
+        assert!(!result.contains("sha256"), "{result}");
+    }
+
+    #[tokio::test]
+    async fn move_file_refuses_allowed_root() {
+        let dir = TempDir::new().unwrap();
+        let parent = dir.path().canonicalize().unwrap();
+        let root = parent.join("root");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("f.txt"), "x").unwrap();
+        std::fs::create_dir(parent.join("other")).unwrap();
+        let service = make_service(vec![root.clone(), parent.join("other")]);
+        let err = service
+            .move_file(Parameters(move_params(
+                &root,
+                &parent.join("other").join("moved"),
+                None,
+            )))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("allowed root"), "{err}");
+        assert!(root.join("f.txt").exists());
+
+        // Children of a root can still be moved
+        service
+            .move_file(Parameters(move_params(
+                &root.join("f.txt"),
+                &root.join("g.txt"),
+                None,
+            )))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_directory_refuses_empty_allowed_root() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+        let err = service
+            .delete_directory(
+                Parameters(DeleteDirectoryParams {
+                    recursive: None,
+                    ..delete_dir_params(dir.path(), None)
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("allowed root"), "{err}");
+        assert!(dir.path().exists());
+    }
 }
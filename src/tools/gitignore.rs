@@ -0,0 +1,144 @@
+//! A `.gitignore` matcher covering the rules that matter for searches: comments,
+//! negation with `!`, directory-only patterns ending in `/`, patterns anchored
+//! by a `/`, and `**`. Each file applies below the directory holding it, and a
+//! deeper file overrides a shallower one.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+
+/// How one pattern line applies once its glob matches.
+#[derive(Debug)]
+struct Rule {
+    /// `!pattern`: re-includes what an earlier pattern ignored
+    negated: bool,
+    /// `pattern/`: matches directories only
+    dir_only: bool,
+}
+
+/// The patterns of one `.gitignore` file.
+#[derive(Debug)]
+pub struct Gitignore {
+    /// Directory holding the file; patterns are relative to it
+    dir: PathBuf,
+    rules: Vec<Rule>,
+    set: GlobSet,
+}
+
+impl Gitignore {
+    /// Reads `dir/.gitignore`, or returns `None` if there is none or it holds
+    /// no patterns.
+    pub fn from_dir(dir: &Path) -> Option<Gitignore> {
+        let text = std::fs::read_to_string(dir.join(".gitignore")).ok()?;
+        let gitignore = Gitignore::parse(dir, &text);
+        (!gitignore.rules.is_empty()).then_some(gitignore)
+    }
+
+    /// Builds a matcher from the contents of a `.gitignore` in `dir`. Lines
+    /// that do not form a valid glob are skipped, as git does.
+    pub fn parse(dir: &Path, text: &str) -> Gitignore {
+        let mut rules = Vec::new();
+        let mut builder = GlobSetBuilder::new();
+        for line in text.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            if line.is_empty() {
+                continue;
+            }
+            // A slash before the end anchors the pattern to `dir`; otherwise
+            // it matches at any depth
+            let pattern = match line.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if line.contains('/') => line.to_string(),
+                None => format!("**/{line}"),
+            };
+            let Ok(glob) = GlobBuilder::new(&pattern).literal_separator(true).build() else {
+                continue;
+            };
+            builder.add(glob);
+            rules.push(Rule { negated, dir_only });
+        }
+        let set = builder.build().unwrap_or_else(|_| GlobSet::empty());
+        Gitignore {
+            dir: dir.to_path_buf(),
+            rules,
+            set,
+        }
+    }
+
+    /// `Some(true)` if the last pattern matching `path` ignores it,
+    /// `Some(false)` if it re-includes it, and `None` if none match.
+    fn decide(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.dir).ok()?;
+        self.set
+            .matches(relative)
+            .into_iter()
+            .rev()
+            .find(|&i| is_dir || !self.rules[i].dir_only)
+            .map(|i| !self.rules[i].negated)
+    }
+}
+
+/// Whether `path` is ignored by `stack`, the `.gitignore` files of the
+/// directories above it ordered from the top down.
+pub fn is_ignored(stack: &[std::sync::Arc<Gitignore>], path: &Path, is_dir: bool) -> bool {
+    stack
+        .iter()
+        .rev()
+        .find_map(|gitignore| gitignore.decide(path, is_dir))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn ignored(stack: &[Arc<Gitignore>], path: &str, is_dir: bool) -> bool {
+        is_ignored(stack, Path::new(path), is_dir)
+    }
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let root = Arc::new(Gitignore::parse(
+            Path::new("/repo"),
+            "# build output\ntarget/\n*.log\n/TODO\ndocs/*.tmp\n",
+        ));
+        let stack = [root];
+        assert!(ignored(&stack, "/repo/target", true));
+        assert!(ignored(&stack, "/repo/crates/a/target", true));
+        // Directory-only patterns leave files of that name alone
+        assert!(!ignored(&stack, "/repo/target", false));
+        assert!(ignored(&stack, "/repo/a/b/debug.log", false));
+        assert!(ignored(&stack, "/repo/TODO", false));
+        assert!(!ignored(&stack, "/repo/src/TODO", false));
+        assert!(ignored(&stack, "/repo/docs/x.tmp", false));
+        assert!(!ignored(&stack, "/repo/docs/sub/x.tmp", false));
+        assert!(!ignored(&stack, "/repo/src/main.rs", false));
+    }
+
+    #[test]
+    fn negation_and_deeper_files_override() {
+        let root = Arc::new(Gitignore::parse(
+            Path::new("/repo"),
+            "*.log\n!keep.log\n\\#literal\n",
+        ));
+        let nested = Arc::new(Gitignore::parse(Path::new("/repo/logs"), "!*.log\n"));
+        let both = [root.clone(), nested];
+        let top = [root];
+        assert!(ignored(&top, "/repo/a.log", false));
+        assert!(!ignored(&top, "/repo/keep.log", false));
+        assert!(ignored(&top, "/repo/#literal", false));
+        assert!(!ignored(&both, "/repo/logs/a.log", false));
+        assert!(ignored(&both, "/repo/a.log", false));
+    }
+}
@@ -0,0 +1,198 @@
+use super::stats::format_uptime;
+use super::util::spawn_blocking;
+use crate::FilesystemService;
+use crate::error::ToolError;
+use std::path::{Path, PathBuf};
+
+/// State of one allowed directory as seen by `health_check`.
+#[derive(Debug, PartialEq)]
+enum RootStatus {
+    Ok,
+    /// The path no longer exists, e.g. an unmounted volume
+    Missing,
+    /// The path exists but is no longer a directory
+    NotADirectory,
+    /// Listing the directory failed
+    Unreadable(String),
+    /// The path now resolves somewhere else, e.g. a remounted or replaced symlink
+    Moved(PathBuf),
+}
+
+impl RootStatus {
+    fn describe(&self) -> String {
+        match self {
+            RootStatus::Ok => "ok".to_string(),
+            RootStatus::Missing => "missing: the directory no longer exists".to_string(),
+            RootStatus::NotADirectory => "not a directory anymore".to_string(),
+            RootStatus::Unreadable(reason) => format!("unreadable: {reason}"),
+            RootStatus::Moved(now) => format!("moved: now resolves to {}", now.display()),
+        }
+    }
+}
+
+/// Checks one allowed root without recursing: it must exist, be a directory,
+/// yield its first entry, and still canonicalize to the configured path.
+fn probe_root(root: &Path) -> RootStatus {
+    let metadata = match std::fs::metadata(root) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return RootStatus::Missing,
+        Err(e) => return RootStatus::Unreadable(e.to_string()),
+    };
+    if !metadata.is_dir() {
+        return RootStatus::NotADirectory;
+    }
+    match std::fs::read_dir(root) {
+        Ok(mut entries) => {
+            if let Some(Err(e)) = entries.next() {
+                return RootStatus::Unreadable(e.to_string());
+            }
+        }
+        Err(e) => return RootStatus::Unreadable(e.to_string()),
+    }
+    match std::fs::canonicalize(root) {
+        Ok(canonical) if canonical == root => RootStatus::Ok,
+        Ok(canonical) => RootStatus::Moved(canonical),
+        Err(e) => RootStatus::Unreadable(e.to_string()),
+    }
+}
+
+#[rmcp::tool_router(router = "health_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Reports the server version and uptime, and whether each allowed
+    /// directory is still reachable.
+    #[rmcp::tool(
+        name = "health_check",
+        description = "Checks that the server is up and each allowed directory is still reachable: it must exist, be readable, and resolve to the same canonical path as at startup. Reports server version and uptime. If a directory is flagged, ask the user to remount or restore it rather than retrying; tools fail on paths under it until then.",
+        annotations(
+            title = "Health Check",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn health_check(&self) -> Result<String, ToolError> {
+        let roots = self.security.allowed_dirs();
+        let statuses = spawn_blocking(move || {
+            roots
+                .into_iter()
+                .map(|root| {
+                    let status = probe_root(&root);
+                    (root, status)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let problems = statuses
+            .iter()
+            .filter(|(_, status)| *status != RootStatus::Ok)
+            .count();
+        let mut out = format!(
+            "ironbeard-mcp-filesystem {} (uptime {})\n",
+            env!("CARGO_PKG_VERSION"),
+            format_uptime(self.stats.uptime())
+        );
+        if problems == 0 {
+            out.push_str(&format!(
+                "Status: healthy, all {} allowed directories reachable\n",
+                statuses.len()
+            ));
+        } else {
+            out.push_str(&format!(
+                "Status: PROBLEM, {problems} of {} allowed directories unreachable. \
+                 Ask the user to remount or restore them; retrying will not help.\n",
+                statuses.len()
+            ));
+        }
+        for (root, status) in &statuses {
+            let marker = if *status == RootStatus::Ok {
+                "[OK]"
+            } else {
+                "[PROBLEM]"
+            };
+            out.push_str(&format!(
+                "\n{marker} {}: {}",
+                root.display(),
+                status.describe()
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn make_service(dirs: &[&TempDir]) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: dirs
+                .iter()
+                .map(|d| d.path().canonicalize().unwrap())
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn healthy_roots_are_ok() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let service = make_service(&[&dir]);
+        let report = service.health_check().await.unwrap();
+        assert!(
+            report.starts_with(&format!(
+                "ironbeard-mcp-filesystem {} (uptime 0h 00m",
+                env!("CARGO_PKG_VERSION")
+            )),
+            "{report}"
+        );
+        assert!(report.contains("Status: healthy, all 1 allowed directories reachable"));
+        assert!(report.contains("[OK]"));
+        assert!(!report.contains("PROBLEM"));
+    }
+
+    #[tokio::test]
+    async fn vanished_root_is_flagged() {
+        let kept = TempDir::new().unwrap();
+        let vanished = TempDir::new().unwrap();
+        let service = make_service(&[&kept, &vanished]);
+        let vanished_path = vanished.path().canonicalize().unwrap();
+        std::fs::remove_dir(&vanished_path).unwrap();
+
+        let report = service.health_check().await.unwrap();
+        assert!(
+            report.contains("Status: PROBLEM, 1 of 2 allowed directories unreachable"),
+            "{report}"
+        );
+        assert!(report.contains(&format!("[PROBLEM] {}: missing", vanished_path.display())));
+        assert!(report.contains(&format!(
+            "[OK] {}: ok",
+            kept.path().canonicalize().unwrap().display()
+        )));
+    }
+
+    #[test]
+    fn root_replaced_by_file_is_not_a_directory() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("root");
+        std::fs::write(&root, "").unwrap();
+        assert_eq!(probe_root(&root), RootStatus::NotADirectory);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn root_resolving_elsewhere_is_moved() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        let target = base.join("target");
+        std::fs::create_dir(&target).unwrap();
+        let root = base.join("root");
+        std::os::unix::fs::symlink(&target, &root).unwrap();
+        assert_eq!(probe_root(&root), RootStatus::Moved(target));
+    }
+}
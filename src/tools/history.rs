@@ -0,0 +1,108 @@
+use crate::FilesystemService;
+use crate::error::ToolError;
+use crate::tools::util::format_timestamp;
+
+#[rmcp::tool_router(router = "history_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Lists the tool calls made in this session, oldest first.
+    #[rmcp::tool(
+        name = "list_operation_history",
+        description = "Lists the most recent tool calls in this session, oldest first, with time, tool, path, outcome, and duration. Only the last N calls configured by the server are kept.",
+        annotations(
+            title = "List Operation History",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn list_operation_history(&self) -> Result<String, ToolError> {
+        let Some(history) = &self.operation_history else {
+            return Err("Operation history is disabled".to_string().into());
+        };
+        let history = history.lock().await;
+        if history.is_empty() {
+            return Ok("Operation history: (no operations yet)".to_string());
+        }
+        let mut output = format!(
+            "Operation history (last {} of up to {}):\n",
+            history.len(),
+            self.config.history_size.unwrap_or_default()
+        );
+        for (index, record) in history.iter().enumerate() {
+            let timestamp = format_timestamp(record.timestamp);
+            let timestamp = timestamp.split('.').next().unwrap_or_default();
+            output.push_str(&format!(
+                "\n{}. {} {}{} — {} ({} ms)",
+                index + 1,
+                timestamp,
+                record.tool,
+                record
+                    .path
+                    .as_ref()
+                    .map(|p| format!(" {p}"))
+                    .unwrap_or_default(),
+                if record.success { "ok" } else { "failed" },
+                record.duration_ms
+            ));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, FilesystemService};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn make_service(history_size: usize) -> FilesystemService {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            history_size: Some(history_size),
+            ..Default::default()
+        };
+        FilesystemService::new(config)
+    }
+
+    #[test]
+    fn history_tools_router_contains_list_operation_history() {
+        let tools = FilesystemService::history_tools_router().list_all();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "list_operation_history");
+        let ann = tools[0].annotations.as_ref().unwrap();
+        assert_eq!(ann.read_only_hint, Some(true));
+    }
+
+    #[tokio::test]
+    async fn list_operation_history_empty() {
+        let service = make_service(5);
+        let output = service.list_operation_history().await.unwrap();
+        assert!(output.contains("no operations yet"));
+    }
+
+    #[tokio::test]
+    async fn list_operation_history_returns_entries_in_order() {
+        let service = make_service(5);
+        service
+            .record_operation("read_file", Some("/a.txt".into()), true, Duration::ZERO)
+            .await;
+        service
+            .record_operation("write_file", Some("/b.txt".into()), false, Duration::ZERO)
+            .await;
+        service
+            .record_operation("list_allowed_directories", None, true, Duration::ZERO)
+            .await;
+
+        let output = service.list_operation_history().await.unwrap();
+        assert!(output.starts_with("Operation history (last 3 of up to 5)"));
+        let first = output.find("1. ").unwrap();
+        let second = output.find("2. ").unwrap();
+        let third = output.find("3. ").unwrap();
+        assert!(first < second && second < third);
+        assert!(output.contains("read_file /a.txt — ok"));
+        assert!(output.contains("write_file /b.txt — failed"));
+        assert!(output.contains("list_allowed_directories — ok"));
+    }
+}
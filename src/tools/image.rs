@@ -0,0 +1,436 @@
+use crate::FilesystemService;
+use crate::error::ToolError;
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::util::{format_size, spawn_blocking};
+
+/// Leading bytes read to recognize a format; enough for every header except
+/// JPEG, whose segments are walked separately.
+const HEADER_SIZE: usize = 32;
+
+/// JPEG segments skipped before giving up on finding the frame header.
+const MAX_JPEG_SEGMENTS: usize = 256;
+
+/// Parameters for the image_info tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ImageInfoParams {
+    /// Absolute path to a PNG, JPEG, GIF, WebP, or BMP file
+    path: String,
+}
+
+/// Dimensions parsed from an image header.
+#[derive(Debug, PartialEq, Eq)]
+struct ImageDimensions {
+    format: &'static str,
+    width: u32,
+    height: u32,
+}
+
+/// Why an image header could not be parsed.
+#[derive(Debug, PartialEq, Eq)]
+enum ImageError {
+    /// The file does not start with a supported signature
+    Unsupported,
+    /// The signature matched but the header is cut short or malformed
+    Truncated(&'static str),
+    Io(String),
+}
+
+impl From<std::io::Error> for ImageError {
+    fn from(err: std::io::Error) -> Self {
+        ImageError::Io(err.to_string())
+    }
+}
+
+fn u16_be(b: &[u8]) -> u32 {
+    u32::from(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn u16_le(b: &[u8]) -> u32 {
+    u32::from(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u24_le(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], 0])
+}
+
+fn u32_be(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn u32_le(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+/// Reads the image's dimensions from its header. Only [`HEADER_SIZE`] bytes
+/// are read, plus a few bytes per segment header for JPEG, whatever the file size.
+fn read_dimensions<R: Read + Seek>(reader: &mut R) -> Result<ImageDimensions, ImageError> {
+    let mut head = Vec::with_capacity(HEADER_SIZE);
+    reader.take(HEADER_SIZE as u64).read_to_end(&mut head)?;
+    let dims = |format, width, height| {
+        Ok(ImageDimensions {
+            format,
+            width,
+            height,
+        })
+    };
+
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        if head.len() < 24 || &head[12..16] != b"IHDR" {
+            return Err(ImageError::Truncated("PNG"));
+        }
+        return dims("PNG", u32_be(&head[16..20]), u32_be(&head[20..24]));
+    }
+    if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        if head.len() < 10 {
+            return Err(ImageError::Truncated("GIF"));
+        }
+        return dims("GIF", u16_le(&head[6..8]), u16_le(&head[8..10]));
+    }
+    if head.starts_with(b"BM") {
+        if head.len() < 26 {
+            return Err(ImageError::Truncated("BMP"));
+        }
+        // OS/2 core headers (12 bytes) use 16-bit sizes; later ones use 32-bit,
+        // with a negative height for top-down bitmaps
+        if u32_le(&head[14..18]) == 12 {
+            return dims("BMP", u16_le(&head[18..20]), u16_le(&head[20..22]));
+        }
+        let width = i32::from_le_bytes([head[18], head[19], head[20], head[21]]);
+        let height = i32::from_le_bytes([head[22], head[23], head[24], head[25]]);
+        return dims("BMP", width.unsigned_abs(), height.unsigned_abs());
+    }
+    if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        if head.len() < 30 {
+            return Err(ImageError::Truncated("WebP"));
+        }
+        return match &head[12..16] {
+            b"VP8 " if head[23..26] == [0x9d, 0x01, 0x2a] => dims(
+                "WebP",
+                u16_le(&head[26..28]) & 0x3fff,
+                u16_le(&head[28..30]) & 0x3fff,
+            ),
+            b"VP8L" if head[20] == 0x2f => {
+                let bits = u32_le(&head[21..25]);
+                dims("WebP", (bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1)
+            }
+            b"VP8X" => dims("WebP", u24_le(&head[24..27]) + 1, u24_le(&head[27..30]) + 1),
+            _ => Err(ImageError::Truncated("WebP")),
+        };
+    }
+    if head.starts_with(&[0xff, 0xd8]) {
+        return jpeg_dimensions(reader);
+    }
+    Err(ImageError::Unsupported)
+}
+
+/// Walks JPEG segment headers from just after the SOI marker, seeking over
+/// segment bodies, until a start-of-frame marker gives the dimensions.
+fn jpeg_dimensions<R: Read + Seek>(reader: &mut R) -> Result<ImageDimensions, ImageError> {
+    let truncated = |_| ImageError::Truncated("JPEG");
+    reader.seek(SeekFrom::Start(2))?;
+    for _ in 0..MAX_JPEG_SEGMENTS {
+        let mut marker = [0u8; 2];
+        reader.read_exact(&mut marker).map_err(truncated)?;
+        if marker[0] != 0xff {
+            return Err(ImageError::Truncated("JPEG"));
+        }
+        // Fill bytes: 0xFF may repeat before the marker code
+        while marker[1] == 0xff {
+            reader.read_exact(&mut marker[1..]).map_err(truncated)?;
+        }
+        match marker[1] {
+            // Standalone markers carry no length
+            0x01 | 0xd0..=0xd7 => continue,
+            // End of image or start of scan before any frame header
+            0xd9 | 0xda => return Err(ImageError::Truncated("JPEG")),
+            _ => {}
+        }
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length).map_err(truncated)?;
+        let length = u16_be(&length);
+        if length < 2 {
+            return Err(ImageError::Truncated("JPEG"));
+        }
+        let is_frame = matches!(marker[1], 0xc0..=0xcf) && !matches!(marker[1], 0xc4 | 0xc8 | 0xcc);
+        if is_frame {
+            let mut frame = [0u8; 5];
+            reader.read_exact(&mut frame).map_err(truncated)?;
+            return Ok(ImageDimensions {
+                format: "JPEG",
+                width: u16_be(&frame[3..5]),
+                height: u16_be(&frame[1..3]),
+            });
+        }
+        reader.seek(SeekFrom::Current(i64::from(length) - 2))?;
+    }
+    Err(ImageError::Truncated("JPEG"))
+}
+
+#[rmcp::tool_router(router = "image_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Reports an image's format and pixel dimensions from its header.
+    #[rmcp::tool(
+        name = "image_info",
+        description = "Reports the format, width and height in pixels, and file size of a PNG, JPEG, GIF, WebP, or BMP image by parsing only its header, so it is cheap on any file size. Fails on truncated headers and on files that are not one of these formats.",
+        annotations(
+            title = "Image Info",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn image_info(
+        &self,
+        Parameters(params): Parameters<ImageInfoParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let (dimensions, size, bytes_read) = spawn_blocking(move || {
+            let mut file = std::fs::File::open(&canonical)?;
+            let size = file.metadata()?.len();
+            let dimensions = read_dimensions(&mut file);
+            let bytes_read = file.stream_position().unwrap_or(0).min(size);
+            Ok::<_, std::io::Error>((dimensions, size, bytes_read))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(bytes_read);
+
+        let dimensions = dimensions.map_err(|err| -> ToolError {
+            match err {
+                ImageError::Unsupported => format!(
+                    "Not a supported image: {}. image_info reads PNG, JPEG, GIF, WebP, and BMP.",
+                    params.path
+                )
+                .into(),
+                ImageError::Truncated(format) => {
+                    format!("Truncated or malformed {format} header: {}", params.path).into()
+                }
+                ImageError::Io(message) => message.into(),
+            }
+        })?;
+        Ok(format!(
+            "Path: {}\nFormat: {}\nDimensions: {} x {} px\nFile size: {}",
+            params.path,
+            dimensions.format,
+            dimensions.width,
+            dimensions.height,
+            format_size(size)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        bytes.extend(width.to_be_bytes());
+        bytes.extend(height.to_be_bytes());
+        bytes.extend([8, 6, 0, 0, 0]);
+        bytes
+    }
+
+    fn jpeg_with_exif(width: u16, height: u16, exif_len: usize) -> Vec<u8> {
+        let mut bytes = vec![0xff, 0xd8];
+        // APP1 segment whose body must be skipped, not read
+        bytes.extend([0xff, 0xe1]);
+        bytes.extend(((exif_len + 2) as u16).to_be_bytes());
+        bytes.extend(vec![0xaa; exif_len]);
+        // SOF0: length, precision, height, width, components
+        bytes.extend([0xff, 0xc0, 0x00, 0x11, 0x08]);
+        bytes.extend(height.to_be_bytes());
+        bytes.extend(width.to_be_bytes());
+        bytes.extend([0x03, 0x01, 0x22, 0x00]);
+        bytes
+    }
+
+    fn webp(chunk: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WEBP".to_vec();
+        bytes.extend(chunk);
+        bytes.extend([0u8; 4]);
+        bytes.extend(payload);
+        bytes.resize(32, 0);
+        bytes
+    }
+
+    fn bmp(width: i32, height: i32) -> Vec<u8> {
+        let mut bytes = b"BM".to_vec();
+        bytes.extend([0u8; 12]);
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn parses_dimensions_from_headers() {
+        let vp8l_bits: u32 = (640 - 1) | ((480 - 1) << 14);
+        let mut vp8l = vec![0x2f];
+        vp8l.extend(vp8l_bits.to_le_bytes());
+        let cases: Vec<(&str, Vec<u8>, ImageDimensions)> = vec![
+            (
+                "png",
+                png(640, 480),
+                ImageDimensions {
+                    format: "PNG",
+                    width: 640,
+                    height: 480,
+                },
+            ),
+            (
+                "gif",
+                b"GIF89a\x40\x01\xf0\x00".to_vec(),
+                ImageDimensions {
+                    format: "GIF",
+                    width: 320,
+                    height: 240,
+                },
+            ),
+            (
+                "jpeg",
+                jpeg_with_exif(1920, 1080, 10),
+                ImageDimensions {
+                    format: "JPEG",
+                    width: 1920,
+                    height: 1080,
+                },
+            ),
+            (
+                "bmp",
+                bmp(100, 50),
+                ImageDimensions {
+                    format: "BMP",
+                    width: 100,
+                    height: 50,
+                },
+            ),
+            (
+                "bmp top-down",
+                bmp(100, -50),
+                ImageDimensions {
+                    format: "BMP",
+                    width: 100,
+                    height: 50,
+                },
+            ),
+            (
+                "webp lossy",
+                webp(
+                    b"VP8 ",
+                    &[0, 0, 0, 0x9d, 0x01, 0x2a, 0x80, 0x02, 0xe0, 0x01],
+                ),
+                ImageDimensions {
+                    format: "WebP",
+                    width: 640,
+                    height: 480,
+                },
+            ),
+            (
+                "webp lossless",
+                webp(b"VP8L", &vp8l),
+                ImageDimensions {
+                    format: "WebP",
+                    width: 640,
+                    height: 480,
+                },
+            ),
+            (
+                "webp extended",
+                webp(b"VP8X", &[0, 0, 0, 0, 0x7f, 0x02, 0, 0xdf, 0x01, 0]),
+                ImageDimensions {
+                    format: "WebP",
+                    width: 640,
+                    height: 480,
+                },
+            ),
+        ];
+        for (name, bytes, expected) in cases {
+            let parsed = read_dimensions(&mut Cursor::new(bytes));
+            assert_eq!(parsed, Ok(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_unsupported() {
+        let cases: Vec<(&str, Vec<u8>, ImageError)> = vec![
+            (
+                "png",
+                png(1, 1)[..20].to_vec(),
+                ImageError::Truncated("PNG"),
+            ),
+            ("gif", b"GIF89a\x01".to_vec(), ImageError::Truncated("GIF")),
+            (
+                "jpeg",
+                jpeg_with_exif(1, 1, 10)[..20].to_vec(),
+                ImageError::Truncated("JPEG"),
+            ),
+            (
+                "jpeg without frame",
+                vec![0xff, 0xd8, 0xff, 0xd9],
+                ImageError::Truncated("JPEG"),
+            ),
+            ("text", b"hello world".to_vec(), ImageError::Unsupported),
+            ("empty", Vec::new(), ImageError::Unsupported),
+        ];
+        for (name, bytes, expected) in cases {
+            let parsed = read_dimensions(&mut Cursor::new(bytes));
+            assert_eq!(parsed, Err(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn jpeg_reads_only_segment_headers() {
+        let bytes = jpeg_with_exif(800, 600, 60_000);
+        let mut cursor = Cursor::new(bytes);
+        let parsed = read_dimensions(&mut cursor).unwrap();
+        assert_eq!((parsed.width, parsed.height), (800, 600));
+        // The 60 KB APP1 body was seeked over; the reader sits just past the frame header
+        assert_eq!(cursor.position() as usize, 2 + 4 + 60_000 + 9);
+    }
+
+    #[tokio::test]
+    async fn image_info_reports_dimensions_and_size() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("logo.png");
+        let mut bytes = png(64, 32);
+        bytes.extend(vec![0u8; 2000]);
+        std::fs::write(&file, bytes).unwrap();
+        let text = dir.path().join("notes.png");
+        std::fs::write(&text, "not really a png").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+
+        let output = service
+            .image_info(Parameters(ImageInfoParams {
+                path: file.to_string_lossy().to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(
+            output.ends_with("\nFormat: PNG\nDimensions: 64 x 32 px\nFile size: 2.0 KB"),
+            "{output}"
+        );
+
+        let err = service
+            .image_info(Parameters(ImageInfoParams {
+                path: text.to_string_lossy().to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Not a supported image:"), "{err}");
+    }
+}
@@ -1,18 +1,97 @@
 use crate::FilesystemService;
-use crate::error::io_error_message;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
 use rmcp::handler::server::wrapper::Parameters;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 
-use super::util::{format_date, format_permissions, format_size};
-
-const MAX_TREE_ENTRIES: usize = 1000;
+use super::magic::{DETECT_SIZE, detect, hex_prefix};
+use super::structured::{OutputFormat, Structured, output_schema};
+use super::util::{
+    HASH_CHUNK_SIZE, SkippedEntries, SkippedPath, allocated_size, display_name, display_path,
+    format_date, format_permissions, format_size, format_size_allocated, is_sparse,
+    matches_extension_filter, resolve_extension_filter, sha256_file, spawn_blocking, stream_file,
+    to_hex,
+};
 
 /// Parameters for the get_file_info tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct GetFileInfoParams {
     /// Absolute path to the file or directory
     path: String,
+    /// Include the SHA-256 of the file contents (default: false). Also accepted as include_hash.
+    #[schemars(
+        description = "Include the SHA-256 of the file contents (default: false). Also accepted as include_hash."
+    )]
+    #[serde(alias = "include_hash")]
+    include_checksum: Option<bool>,
+}
+
+/// Parameters for the identify_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct IdentifyFileParams {
+    /// Absolute path to the file
+    path: String,
+}
+
+/// Parameters for the get_file_checksum tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct GetFileChecksumParams {
+    /// Absolute path to the file
+    path: String,
+    /// Digest algorithm: "sha256" or "md5" (default: "sha256")
+    #[schemars(description = "Digest algorithm: \"sha256\" or \"md5\" (default: \"sha256\")")]
+    algorithm: Option<String>,
+}
+
+/// Digest algorithms get_file_checksum supports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChecksumAlgorithm {
+    Sha256,
+    Md5,
+}
+
+impl ChecksumAlgorithm {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            "md5" => Ok(ChecksumAlgorithm::Md5),
+            _ => Err(format!(
+                "Unknown checksum algorithm \"{name}\". Use \"sha256\" or \"md5\"."
+            )),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Md5 => "md5",
+        }
+    }
+
+    /// Hex digest of the file at `path` and the number of bytes hashed, read
+    /// `chunk_size` bytes at a time.
+    fn digest_file(
+        self,
+        path: &std::path::Path,
+        chunk_size: usize,
+    ) -> std::io::Result<(String, u64)> {
+        match self {
+            ChecksumAlgorithm::Sha256 => {
+                use sha2::{Digest, Sha256};
+                let mut hasher = Sha256::new();
+                let size = stream_file(path, chunk_size, |chunk| hasher.update(chunk))?;
+                Ok((to_hex(&hasher.finalize()), size))
+            }
+            ChecksumAlgorithm::Md5 => {
+                let mut context = md5::Context::new();
+                let size = stream_file(path, chunk_size, |chunk| context.consume(chunk))?;
+                Ok((to_hex(&context.finalize().0), size))
+            }
+        }
+    }
 }
 
 /// Parameters for the directory_tree tool.
@@ -23,6 +102,85 @@ struct DirectoryTreeParams {
     /// Maximum depth to traverse (defaults to config max_depth)
     #[schemars(description = "Maximum depth to traverse")]
     max_depth: Option<u32>,
+    /// Only show files with these extensions (overrides the server default; empty list shows all)
+    #[schemars(
+        description = "Only show files with these extensions (overrides the server default; empty list shows all)"
+    )]
+    extensions: Option<Vec<String>>,
+    /// Include hidden files and directories (names starting with '.') (default: false)
+    #[schemars(
+        description = "Include hidden files and directories (names starting with '.') (default: false)"
+    )]
+    include_hidden: Option<bool>,
+    /// Output format: "text" (default) for readable text, or "json" for the structured result as JSON
+    #[schemars(
+        description = "Output format: \"text\" (default) for readable text, or \"json\" for the structured result as JSON"
+    )]
+    format: Option<OutputFormat>,
+}
+
+/// Structured result of get_file_info.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct FileInfo {
+    /// Canonical path
+    pub path: String,
+    /// "file", "directory", "symlink", or "other"
+    pub file_type: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Bytes allocated on disk, for files (Unix)
+    pub allocated: Option<u64>,
+    /// Whether the file occupies materially less than its size on disk (Unix)
+    pub sparse: Option<bool>,
+    /// MIME type guessed from the extension, for files
+    pub mime: Option<String>,
+    /// Modification time, when the platform reports it
+    pub modified: Option<String>,
+    /// Creation time, when the platform reports it
+    pub created: Option<String>,
+    pub permissions: String,
+    /// Hard link count (Unix)
+    pub links: Option<u64>,
+    /// Inode number (Unix)
+    pub inode: Option<u64>,
+    /// Device the inode lives on (Unix)
+    pub device: Option<u64>,
+    /// SHA-256 of the contents, when include_checksum is set on a file
+    pub sha256: Option<String>,
+    /// Why no SHA-256 was computed despite include_checksum: not a regular
+    /// file, or larger than --max-hash-size
+    pub hash_skipped: Option<String>,
+}
+
+/// Structured result of directory_tree.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DirectoryTree {
+    /// Canonical path of the root directory
+    pub path: String,
+    /// Entries of the root: directories first, then files, each sorted by name
+    pub entries: Vec<TreeNode>,
+    /// Whether the walk stopped at --max-tree-entries, or entries were dropped
+    /// to fit `--max-output-bytes`
+    pub truncated: bool,
+    /// Extension filter in effect; empty when unfiltered
+    pub extensions: Vec<String>,
+    /// Subdirectories and entries that could not be read
+    pub skipped: Vec<SkippedPath>,
+    /// Entries left out because a deny pattern matches them
+    pub denied: usize,
+}
+
+/// One entry of a [`DirectoryTree`].
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct TreeNode {
+    pub name: String,
+    /// "directory" or "file"
+    pub kind: String,
+    /// Size in bytes, for files
+    pub size: Option<u64>,
+    /// Entries of a directory, in the same order as the root's; `None` for
+    /// files and for directories below max_depth
+    pub children: Option<Vec<TreeNode>>,
 }
 
 #[rmcp::tool_router(router = "info_tools_router", vis = "pub(crate)")]
@@ -30,22 +188,203 @@ impl FilesystemService {
     /// Returns detailed metadata about a file or directory.
     #[rmcp::tool(
         name = "get_file_info",
-        description = "Returns detailed metadata about a file or directory including size, type, MIME type, timestamps, and permissions.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        description = "Returns detailed metadata about a file or directory including size, type, MIME type, timestamps, permissions, and (on Unix) hard link count and inode. On Unix, a sparse file also shows the much smaller size it occupies on disk. Set include_checksum to also compute the SHA-256 of a file's contents; directories and files over --max-hash-size report the hash as skipped with the reason.",
+        output_schema = output_schema::<FileInfo>(),
+        annotations(
+            title = "Get File Info",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn get_file_info(
         &self,
         Parameters(params): Parameters<GetFileInfoParams>,
-    ) -> Result<String, String> {
-        let path = std::path::Path::new(&params.path);
+    ) -> Structured<FileInfo> {
+        self.get_file_info_report(&params.path, params.include_checksum.unwrap_or(false))
+            .await
+            .into()
+    }
+
+    /// Displays a visual tree of directory structure with box-drawing characters.
+    #[rmcp::tool(
+        name = "directory_tree",
+        description = "Displays a visual tree of directory structure with box-drawing characters. Shows directories first (sorted), then files with sizes. Hidden files/directories (starting with '.') are skipped unless include_hidden is true. With format \"json\" the text is the structured result as JSON, with each directory's entries nested under it.",
+        output_schema = output_schema::<DirectoryTree>(),
+        annotations(
+            title = "Directory Tree",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn directory_tree(
+        &self,
+        Parameters(params): Parameters<DirectoryTreeParams>,
+        ct: CancellationToken,
+    ) -> Structured<DirectoryTree> {
+        let format = params.format;
+        Structured::from(self.directory_tree_report(params, ct).await).formatted(format)
+    }
+
+    /// Identifies a file's format from its leading bytes.
+    #[rmcp::tool(
+        name = "identify_file",
+        description = "Identifies a file's format from its first 4 KB rather than its name: executables (ELF, Mach-O, PE), images (PNG, JPEG, GIF), PDF, archives (ZIP, gzip, tar), SQLite, text (UTF-8/UTF-16, with line ending style), and shebang scripts (with interpreter). Reports format, MIME type, confidence, and the first 16 bytes in hex. Works on binary files that read_file refuses.",
+        annotations(
+            title = "Identify File",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn identify_file(
+        &self,
+        Parameters(params): Parameters<IdentifyFileParams>,
+    ) -> Result<String, ToolError> {
         let canonical = self
             .security
-            .validate_path_exists(path)
-            .map_err(|e| e.to_string())?;
+            .validate_file(std::path::Path::new(&params.path))?;
+        let head = spawn_blocking(move || {
+            use std::io::Read;
+            let mut head = Vec::with_capacity(DETECT_SIZE);
+            std::fs::File::open(&canonical)?
+                .take(DETECT_SIZE as u64)
+                .read_to_end(&mut head)?;
+            Ok::<_, std::io::Error>(head)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(head.len() as u64);
+
+        let detection = detect(&head);
+        let mut out = format!(
+            "Path: {}\nFormat: {}\nMIME: {}\nConfidence: {}",
+            params.path, detection.format, detection.mime, detection.confidence
+        );
+        if let Some(detail) = &detection.detail {
+            out.push_str(&format!("\nDetails: {detail}"));
+        }
+        out.push_str(&format!("\nFirst 16 bytes: {}", hex_prefix(&head)));
+        Ok(out)
+    }
+
+    /// Computes the SHA-256 or MD5 digest of a file.
+    #[rmcp::tool(
+        name = "get_file_checksum",
+        description = "Computes the checksum of a file's contents to verify it was not corrupted or changed. Returns \"<algorithm>: <hex digest> <path>\". algorithm is \"sha256\" (default) or \"md5\"; prefer sha256, as MD5 is only suitable for matching existing checksums. Binary files are hashed like any other file, and the file is streamed rather than loaded whole.",
+        annotations(
+            title = "Get File Checksum",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_file_checksum(
+        &self,
+        Parameters(params): Parameters<GetFileChecksumParams>,
+    ) -> Result<String, ToolError> {
+        let algorithm = ChecksumAlgorithm::parse(params.algorithm.as_deref().unwrap_or("sha256"))?;
+        let canonical = self
+            .security
+            .validate_file(std::path::Path::new(&params.path))?;
+        // Like get_file_info's checksum, the digest is not returned content, so
+        // any size may be hashed; the read limit only caps each chunk held in memory
+        let chunk_size = HASH_CHUNK_SIZE.min(self.config.max_read_size);
+        let file = canonical.clone();
+        let (digest, size) = spawn_blocking(move || algorithm.digest_file(&file, chunk_size))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(size);
+
+        Ok(format!(
+            "{}: {digest} {}",
+            algorithm.label(),
+            display_path(&canonical)
+        ))
+    }
+}
+
+impl FilesystemService {
+    /// Walks the directory named in `params` and renders it as text and as
+    /// nested [`TreeNode`]s.
+    async fn directory_tree_report(
+        &self,
+        params: DirectoryTreeParams,
+        ct: CancellationToken,
+    ) -> Result<(String, DirectoryTree), ToolError> {
+        let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_directory(path)?;
+
+        let max_depth = params
+            .max_depth
+            .map(|d| d as usize)
+            .unwrap_or(self.config.max_depth);
+
+        let extensions =
+            resolve_extension_filter(&self.config.only_extensions, params.extensions.as_deref());
+        let filter_note = if extensions.is_empty() {
+            String::new()
+        } else {
+            format!(" (extensions: {})", extensions.join(", "))
+        };
+
+        let max_entries = self.config.max_tree_entries;
+        let options = TreeOptions {
+            max_depth,
+            max_entries,
+            extensions: extensions.clone(),
+            security: self.security.clone(),
+            include_hidden: params.include_hidden.unwrap_or(false),
+        };
+        let canonical_clone = canonical.clone();
+        let (tree, nodes, walk) = spawn_blocking(move || {
+            let mut walk = TreeWalk::default();
+            build_tree_sync(&canonical_clone, "", 0, &options, &mut walk, &ct)
+                .map(|(tree, nodes)| (tree, nodes, walk))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+        let mut output = format!("{}/{}\n{}", canonical.display(), filter_note, tree);
+        if let Some(skipped) = walk.skipped.summary(&canonical) {
+            output.push_str(&format!("\n({skipped})"));
+        }
+        if walk.denied > 0 {
+            output.push_str(&format!(
+                "\n({} entr{} skipped by deny patterns)",
+                walk.denied,
+                if walk.denied == 1 { "y" } else { "ies" }
+            ));
+        }
+        let tree = DirectoryTree {
+            path: canonical.display().to_string(),
+            entries: nodes,
+            truncated: walk.entries > max_entries,
+            extensions,
+            skipped: walk.skipped.report(&canonical),
+            denied: walk.denied,
+        };
+        Ok((output, tree))
+    }
+
+    pub(crate) async fn get_file_info_report(
+        &self,
+        requested: &str,
+        include_checksum: bool,
+    ) -> Result<(String, FileInfo), ToolError> {
+        let path = std::path::Path::new(requested);
+        let canonical = self.security.validate_path_exists(path)?;
 
         let metadata = tokio::fs::symlink_metadata(&canonical)
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            .map_err(|e| ToolError::io(e, requested))?;
 
         let file_type = if metadata.is_file() {
             "file"
@@ -57,7 +396,16 @@ impl FilesystemService {
             "other"
         };
 
-        let size_str = format_size(metadata.len());
+        // Apparent size only where the platform does not report blocks
+        let allocated = metadata
+            .is_file()
+            .then(|| allocated_size(&metadata))
+            .flatten();
+        let size_str = if metadata.is_file() {
+            format_size_allocated(metadata.len(), allocated)
+        } else {
+            format_size(metadata.len())
+        };
 
         let mime = if metadata.is_file() {
             mime_guess::from_path(&canonical)
@@ -79,8 +427,24 @@ impl FilesystemService {
             .unwrap_or_else(|_| "unknown".to_string());
 
         let permissions = format_permissions(&metadata);
+        let mut info = FileInfo {
+            path: canonical.display().to_string(),
+            file_type: file_type.to_string(),
+            size: metadata.len(),
+            allocated,
+            sparse: allocated.map(|allocated| is_sparse(metadata.len(), allocated)),
+            mime: metadata.is_file().then(|| mime.clone()),
+            modified: (modified != "unknown").then(|| modified.clone()),
+            created: (created != "unknown").then(|| created.clone()),
+            permissions: permissions.clone(),
+            links: None,
+            inode: None,
+            device: None,
+            sha256: None,
+            hash_skipped: None,
+        };
 
-        Ok(format!(
+        let mut output = format!(
             "Path: {}\nType: {}\nSize: {}\nMIME: {}\nModified: {}\nCreated: {}\nPermissions: {}",
             canonical.display(),
             file_type,
@@ -89,50 +453,106 @@ impl FilesystemService {
             modified,
             created,
             permissions,
-        ))
-    }
-
-    /// Displays a visual tree of directory structure with box-drawing characters.
-    #[rmcp::tool(
-        name = "directory_tree",
-        description = "Displays a visual tree of directory structure with box-drawing characters. Shows directories first (sorted), then files with sizes. Hidden files/directories (starting with '.') are skipped by default.",
-        annotations(read_only_hint = true, destructive_hint = false)
-    )]
-    async fn directory_tree(
-        &self,
-        Parameters(params): Parameters<DirectoryTreeParams>,
-    ) -> Result<String, String> {
-        let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_directory(path)
-            .map_err(|e| e.to_string())?;
+        );
 
-        let max_depth = params
-            .max_depth
-            .map(|d| d as usize)
-            .unwrap_or(self.config.max_depth);
+        // Paths that share a device and inode are hard links to the same file
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            output.push_str(&format!(
+                "\nLinks: {}\nInode: {} (device {})",
+                metadata.nlink(),
+                metadata.ino(),
+                metadata.dev()
+            ));
+            info.links = Some(metadata.nlink());
+            info.inode = Some(metadata.ino());
+            info.device = Some(metadata.dev());
+        }
 
-        let canonical_clone = canonical.clone();
-        let tree = tokio::task::spawn_blocking(move || {
-            let mut count = 0;
-            build_tree_sync(&canonical_clone, "", max_depth, 0, &mut count)
-        })
-        .await
-        .map_err(|e| e.to_string())??;
+        // Hashing reads the whole file, so only do it on request. The read size
+        // limit does not apply: the digest is metadata, not returned content.
+        if include_checksum {
+            let max = self.config.max_hash_size;
+            if !metadata.is_file() {
+                info.hash_skipped = Some("not a regular file".to_string());
+            } else if metadata.len() > max {
+                info.hash_skipped = Some(format!(
+                    "{} exceeds --max-hash-size of {}",
+                    format_size(metadata.len()),
+                    format_size(max)
+                ));
+            } else {
+                let canonical_clone = canonical.clone();
+                let checksum = spawn_blocking(move || sha256_file(&canonical_clone))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| ToolError::io(e, requested))?;
+                output.push_str(&format!("\nSHA-256: {checksum}"));
+                info.sha256 = Some(checksum);
+            }
+            if let Some(reason) = &info.hash_skipped {
+                output.push_str(&format!("\nSHA-256: hash skipped ({reason})"));
+            }
+        }
 
-        Ok(format!("{}/\n{}", canonical.display(), tree))
+        Ok((output, info))
     }
 }
 
+/// What a directory_tree walk shows, fixed for the whole walk.
+struct TreeOptions {
+    max_depth: usize,
+    /// Entries printed before the walk stops with a truncation note
+    max_entries: usize,
+    /// Extension filter for files; empty shows all
+    extensions: Vec<String>,
+    /// Context whose deny patterns hide entries; denied directories are not
+    /// entered
+    security: SecurityContext,
+    /// Show entries whose names start with '.'
+    include_hidden: bool,
+}
+
+/// Progress of a directory_tree walk, shared across recursion levels.
+#[derive(Default)]
+struct TreeWalk {
+    /// Entries printed so far, checked against `TreeOptions::max_entries`
+    entries: usize,
+    /// Subdirectories and entries that could not be read
+    skipped: SkippedEntries,
+    /// Entries left out by deny patterns
+    denied: usize,
+}
+
+/// The line ending a directory_tree that reached `max_entries`.
+fn truncation_note(prefix: &str, max_entries: usize) -> String {
+    format!(
+        "{prefix}... (truncated, exceeded {max_entries} entries. Use search_files to find specific files.)\n"
+    )
+}
+
 fn build_tree_sync(
     dir: &std::path::Path,
     prefix: &str,
-    max_depth: usize,
     current_depth: usize,
-    entry_count: &mut usize,
-) -> Result<String, String> {
-    let read_dir = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+    options: &TreeOptions,
+    walk: &mut TreeWalk,
+    ct: &CancellationToken,
+) -> Result<(String, Vec<TreeNode>), ToolError> {
+    // Checked once per directory so a cancelled walk stops promptly
+    if ct.is_cancelled() {
+        return Err(FsError::Cancelled.into());
+    }
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        // An unreadable subdirectory is listed but left empty
+        Err(e) if current_depth > 0 => {
+            walk.skipped.record(dir.to_path_buf(), &e, true);
+            return Ok((String::new(), Vec::new()));
+        }
+        Err(e) => return Err(FsError::from(e).into()),
+    };
 
     let mut dirs: Vec<(String, std::path::PathBuf)> = Vec::new();
     let mut files: Vec<(String, u64)> = Vec::new();
@@ -143,20 +563,29 @@ fn build_tree_sync(
             Err(_) => continue,
         };
 
-        let name = entry.file_name().to_string_lossy().to_string();
+        let name = display_name(&entry.file_name());
 
-        if name.starts_with('.') {
+        if !options.include_hidden && name.starts_with('.') {
+            continue;
+        }
+        if options.security.is_denied(&entry.path()) {
+            walk.denied += 1;
             continue;
         }
 
         let metadata = match entry.metadata() {
             Ok(m) => m,
-            Err(_) => continue,
+            Err(e) => {
+                let directory = entry.file_type().is_ok_and(|t| t.is_dir());
+                walk.skipped.record(entry.path(), &e, directory);
+                continue;
+            }
         };
 
         if metadata.is_dir() {
             dirs.push((name, entry.path()));
-        } else if metadata.is_file() {
+        } else if metadata.is_file() && matches_extension_filter(&entry.path(), &options.extensions)
+        {
             files.push((name, metadata.len()));
         }
     }
@@ -166,19 +595,18 @@ fn build_tree_sync(
 
     let total = dirs.len() + files.len();
     if total == 0 {
-        return Ok(String::new());
+        return Ok((String::new(), Vec::new()));
     }
 
     let mut output = String::new();
+    let mut nodes = Vec::with_capacity(total);
     let mut index = 0;
 
     for (name, path) in &dirs {
-        *entry_count += 1;
-        if *entry_count > MAX_TREE_ENTRIES {
-            output.push_str(&format!(
-                "{prefix}... (truncated, exceeded {MAX_TREE_ENTRIES} entries. Use search_files to find specific files.)\n"
-            ));
-            return Ok(output);
+        walk.entries += 1;
+        if walk.entries > options.max_entries {
+            output.push_str(&truncation_note(prefix, options.max_entries));
+            return Ok((output, nodes));
         }
         let is_last = index == total - 1;
         let connector = if is_last {
@@ -188,35 +616,37 @@ fn build_tree_sync(
         };
         output.push_str(&format!("{prefix}{connector}{name}/\n"));
 
-        if current_depth < max_depth {
+        let mut node = TreeNode {
+            name: name.clone(),
+            kind: "directory".to_string(),
+            size: None,
+            children: None,
+        };
+        if current_depth < options.max_depth {
             let child_prefix = if is_last {
                 format!("{prefix}    ")
             } else {
                 format!("{prefix}\u{2502}   ")
             };
-            let subtree = build_tree_sync(
-                path,
-                &child_prefix,
-                max_depth,
-                current_depth + 1,
-                entry_count,
-            )?;
+            let (subtree, children) =
+                build_tree_sync(path, &child_prefix, current_depth + 1, options, walk, ct)?;
             output.push_str(&subtree);
-            if *entry_count > MAX_TREE_ENTRIES {
-                return Ok(output);
+            node.children = Some(children);
+            if walk.entries > options.max_entries {
+                nodes.push(node);
+                return Ok((output, nodes));
             }
         }
+        nodes.push(node);
 
         index += 1;
     }
 
     for (name, size) in &files {
-        *entry_count += 1;
-        if *entry_count > MAX_TREE_ENTRIES {
-            output.push_str(&format!(
-                "{prefix}... (truncated, exceeded {MAX_TREE_ENTRIES} entries. Use search_files to find specific files.)\n"
-            ));
-            return Ok(output);
+        walk.entries += 1;
+        if walk.entries > options.max_entries {
+            output.push_str(&truncation_note(prefix, options.max_entries));
+            return Ok((output, nodes));
         }
         let is_last = index == total - 1;
         let connector = if is_last {
@@ -226,15 +656,22 @@ fn build_tree_sync(
         };
         let size_str = format_size(*size);
         output.push_str(&format!("{prefix}{connector}{name} ({size_str})\n"));
+        nodes.push(TreeNode {
+            name: name.clone(),
+            kind: "file".to_string(),
+            size: Some(*size),
+            children: None,
+        });
         index += 1;
     }
 
-    Ok(output)
+    Ok((output, nodes))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::structured::client_view;
     use crate::{Config, FilesystemService};
     use rmcp::handler::server::wrapper::Parameters;
     use std::path::PathBuf;
@@ -247,6 +684,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -255,10 +693,146 @@ mod tests {
     fn info_tools_router_contains_get_file_info() {
         let router = FilesystemService::info_tools_router();
         let tools = router.list_all();
-        assert_eq!(tools.len(), 2);
+        assert_eq!(tools.len(), 4);
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
         assert!(names.contains(&"get_file_info"));
         assert!(names.contains(&"directory_tree"));
+        assert!(names.contains(&"identify_file"));
+        assert!(names.contains(&"get_file_checksum"));
+    }
+
+    fn checksum_params(path: &std::path::Path, algorithm: Option<&str>) -> GetFileChecksumParams {
+        GetFileChecksumParams {
+            path: path.to_string_lossy().to_string(),
+            algorithm: algorithm.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_file_checksum_known_digests() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("abc.txt");
+        std::fs::write(&file, "abc").unwrap();
+        let service = make_service(vec![canon]);
+
+        let sha256 = service
+            .get_file_checksum(Parameters(checksum_params(&file, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            sha256,
+            format!(
+                "sha256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad {}",
+                file.display()
+            )
+        );
+        let md5 = service
+            .get_file_checksum(Parameters(checksum_params(&file, Some("MD5"))))
+            .await
+            .unwrap();
+        assert_eq!(
+            md5,
+            format!("md5: 900150983cd24fb0d6963f7d28e17f72 {}", file.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_checksum_rejects_unknown_algorithm() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("abc.txt");
+        std::fs::write(&file, "abc").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .get_file_checksum(Parameters(checksum_params(&file, Some("sha1"))))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("Unknown checksum algorithm \"sha1\""),
+            "{}",
+            err.message
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_checksum_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let other = TempDir::new().unwrap();
+        let outside = other.path().join("secret.txt");
+        std::fs::write(&outside, "secret").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .get_file_checksum(Parameters(checksum_params(&outside, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn get_file_checksum_hashes_binary_files_past_read_limit() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("blob.bin");
+        let content: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        std::fs::write(&file, &content).unwrap();
+        let config = Config {
+            allowed_directories: vec![canon],
+            max_read_size: 100,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        let output = service
+            .get_file_checksum(Parameters(checksum_params(&file, None)))
+            .await
+            .unwrap();
+        let expected = sha256_file(&file).unwrap();
+        assert_eq!(output, format!("sha256: {expected} {}", file.display()));
+    }
+
+    #[tokio::test]
+    async fn identify_file_reports_format_and_hex() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("data");
+        std::fs::write(&file, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01").unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .identify_file(Parameters(IdentifyFileParams {
+                path: file.to_string_lossy().to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(output.contains("\nFormat: PNG image\nMIME: image/png\nConfidence: high\n"));
+        assert!(
+            output.ends_with("First 16 bytes: 89 50 4e 47 0d 0a 1a 0a 00 00 00 0d 49 48 44 52")
+        );
+    }
+
+    #[tokio::test]
+    async fn identify_file_shows_script_details() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("build");
+        std::fs::write(&file, "#!/usr/bin/env bash\r\nmake\r\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .identify_file(Parameters(IdentifyFileParams {
+                path: file.to_string_lossy().to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(
+            output.contains("Format: script\nMIME: text/x-shellscript"),
+            "{output}"
+        );
+        assert!(output.contains("Details: interpreter: bash, line endings: CRLF"));
     }
 
     #[tokio::test]
@@ -271,8 +845,10 @@ mod tests {
         let result = service
             .get_file_info(Parameters(GetFileInfoParams {
                 path: dir.path().join("test.txt").to_string_lossy().to_string(),
+                include_checksum: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("Type: file"));
@@ -280,6 +856,32 @@ mod tests {
         assert!(output.contains("text/plain"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn get_file_info_reports_sparse_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let path = canon.join("disk.img");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+        drop(file);
+        let metadata = std::fs::metadata(&path).unwrap();
+        if !is_sparse(metadata.len(), allocated_size(&metadata).unwrap()) {
+            // The filesystem allocated the whole file; nothing to report
+            return;
+        }
+
+        let service = make_service(vec![canon]);
+        let (output, info) = service
+            .get_file_info_report(&path.to_string_lossy(), false)
+            .await
+            .unwrap();
+        assert!(output.contains("Size: 64.0 MB (allocated: "), "{output}");
+        assert!(output.contains(", sparse)\n"), "{output}");
+        assert_eq!(info.sparse, Some(true));
+        assert!(info.allocated.unwrap() < info.size);
+    }
+
     #[tokio::test]
     async fn get_file_info_for_directory() {
         let dir = TempDir::new().unwrap();
@@ -291,8 +893,10 @@ mod tests {
         let result = service
             .get_file_info(Parameters(GetFileInfoParams {
                 path: sub.to_string_lossy().to_string(),
+                include_checksum: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("Type: directory"));
@@ -309,8 +913,10 @@ mod tests {
         let result = service
             .get_file_info(Parameters(GetFileInfoParams {
                 path: dir.path().join("image.png").to_string_lossy().to_string(),
+                include_checksum: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("image/png"));
@@ -331,10 +937,12 @@ mod tests {
                     .join("secret.txt")
                     .to_string_lossy()
                     .to_string(),
+                include_checksum: None,
             }))
-            .await;
+            .await
+            .text();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
     }
 
     #[tokio::test]
@@ -346,10 +954,248 @@ mod tests {
         let result = service
             .get_file_info(Parameters(GetFileInfoParams {
                 path: dir.path().join("nope.txt").to_string_lossy().to_string(),
+                include_checksum: None,
             }))
-            .await;
+            .await
+            .text();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Not found"));
+        assert!(result.unwrap_err().message.contains("Not found"));
+    }
+
+    #[tokio::test]
+    async fn get_file_info_includes_checksum() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("data.bin"), b"\x00\x01binary\xff").unwrap();
+
+        let service = make_service(vec![canon]);
+        let path = dir.path().join("data.bin").to_string_lossy().to_string();
+        let first = service
+            .get_file_info(Parameters(GetFileInfoParams {
+                path: path.clone(),
+                include_checksum: Some(true),
+            }))
+            .await
+            .text()
+            .unwrap();
+        let second = service
+            .get_file_info(Parameters(GetFileInfoParams {
+                path,
+                include_checksum: Some(true),
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        let hash_line = first.lines().find(|l| l.starts_with("SHA-256: ")).unwrap();
+        let hash = hash_line.trim_start_matches("SHA-256: ");
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn get_file_info_omits_checksum_by_default() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("test.txt"), "hello").unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .get_file_info(Parameters(GetFileInfoParams {
+                path: dir.path().join("test.txt").to_string_lossy().to_string(),
+                include_checksum: Some(false),
+            }))
+            .await
+            .text();
+
+        assert!(!result.unwrap().contains("SHA-256"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn get_file_info_reports_hard_links() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::hard_link(dir.path().join("a.txt"), dir.path().join("b.txt")).unwrap();
+
+        let service = make_service(vec![canon]);
+        let info = |name: &str| GetFileInfoParams {
+            path: dir.path().join(name).to_string_lossy().to_string(),
+            include_checksum: None,
+        };
+        let a = service
+            .get_file_info(Parameters(info("a.txt")))
+            .await
+            .text()
+            .unwrap();
+        let b = service
+            .get_file_info(Parameters(info("b.txt")))
+            .await
+            .text()
+            .unwrap();
+        assert!(a.contains("Links: 2"), "{a}");
+        let inode = |out: &str| {
+            out.lines()
+                .find(|l| l.starts_with("Inode:"))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(inode(&a), inode(&b));
+    }
+
+    #[tokio::test]
+    async fn get_file_info_checksum_directory_is_na() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let sub = dir.path().join("subdir");
+        std::fs::create_dir(&sub).unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .get_file_info(Parameters(GetFileInfoParams {
+                path: sub.to_string_lossy().to_string(),
+                include_checksum: Some(true),
+            }))
+            .await
+            .text();
+
+        assert!(
+            result
+                .unwrap()
+                .contains("SHA-256: hash skipped (not a regular file)")
+        );
+    }
+
+    #[tokio::test]
+    async fn get_file_info_checksum_ignores_max_read_size() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("big.txt"), "x".repeat(500)).unwrap();
+
+        let config = Config {
+            allowed_directories: vec![canon],
+            allow_write: false,
+            allow_destructive: false,
+            max_read_size: 100,
+            max_depth: 10,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let result = service
+            .get_file_info(Parameters(GetFileInfoParams {
+                path: dir.path().join("big.txt").to_string_lossy().to_string(),
+                include_checksum: Some(true),
+            }))
+            .await
+            .text();
+
+        assert!(!result.unwrap().contains("hash skipped"));
+    }
+
+    #[tokio::test]
+    async fn get_file_info_skips_hash_over_max_hash_size() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(canon.join("big.bin"), vec![0u8; 2048]).unwrap();
+        let config = Config {
+            allowed_directories: vec![canon.clone()],
+            max_hash_size: 1024,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        // include_hash is accepted as another name for include_checksum
+        let params: GetFileInfoParams = serde_json::from_value(serde_json::json!({
+            "path": canon.join("big.bin").to_string_lossy(),
+            "include_hash": true,
+        }))
+        .unwrap();
+        let (output, info) = service
+            .get_file_info_report(&params.path, params.include_checksum.unwrap())
+            .await
+            .unwrap();
+        assert!(
+            output.ends_with("SHA-256: hash skipped (2.0 KB exceeds --max-hash-size of 1.0 KB)"),
+            "{output}"
+        );
+        assert_eq!(info.sha256, None);
+        assert!(info.hash_skipped.unwrap().contains("--max-hash-size"));
+    }
+
+    #[tokio::test]
+    async fn directory_tree_json_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("src")).unwrap();
+        std::fs::write(canon.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(canon.join("Cargo.toml"), "[package]").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let text = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: canon.to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: Some(OutputFormat::Json),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        let tree: DirectoryTree = serde_json::from_str(&text).unwrap();
+        assert_eq!(tree.path, canon.display().to_string());
+        assert!(!tree.truncated);
+        let names: Vec<&str> = tree.entries.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["src", "Cargo.toml"]);
+        let src = &tree.entries[0];
+        assert_eq!(src.kind, "directory");
+        let children = src.children.as_ref().unwrap();
+        assert_eq!(children[0].name, "main.rs");
+        assert_eq!(children[0].size, Some(12));
+        assert!(tree.entries[1].children.is_none());
+    }
+
+    #[tokio::test]
+    async fn directory_tree_leaves_out_denied_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("sub")).unwrap();
+        std::fs::write(canon.join("key.pem"), "secret").unwrap();
+        std::fs::write(canon.join("sub/other.pem"), "secret").unwrap();
+        std::fs::write(canon.join("notes.txt"), "hello").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon.clone()],
+            deny: vec!["*.pem".into()],
+            ..Default::default()
+        });
+
+        let (text, tree) = client_view(
+            service
+                .directory_tree(
+                    Parameters(DirectoryTreeParams {
+                        path: canon.to_string_lossy().to_string(),
+                        max_depth: None,
+                        extensions: None,
+                        include_hidden: None,
+                        format: None,
+                    }),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+        assert!(!text.contains(".pem"), "{text}");
+        assert!(
+            text.ends_with("(2 entries skipped by deny patterns)"),
+            "{text}"
+        );
+        assert_eq!(tree.denied, 2);
+        assert!(tree.entries[0].children.as_ref().unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -362,11 +1208,18 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: dir.path().to_string_lossy().to_string(),
-                max_depth: None,
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("src/"));
@@ -390,11 +1243,18 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: dir.path().to_string_lossy().to_string(),
-                max_depth: Some(0),
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: Some(0),
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("level1/"));
@@ -402,6 +1262,46 @@ mod tests {
         assert!(!output.contains("deep.txt"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn directory_tree_reports_unreadable_subdirectories() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let locked = dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::write(locked.join("secret.txt"), "x").unwrap();
+        std::fs::write(dir.path().join("open.txt"), "x").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        let readable_anyway = std::fs::read_dir(&locked).is_ok();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        if readable_anyway {
+            // Running as root, so permissions are not enforced
+            return;
+        }
+
+        let output = output.unwrap();
+        assert!(output.contains("locked/"));
+        assert!(output.contains("open.txt"));
+        assert!(!output.contains("secret.txt"));
+        assert!(output.ends_with("(1 directory skipped: permission denied: ./locked)"));
+    }
+
     #[tokio::test]
     async fn directory_tree_skips_hidden() {
         let dir = TempDir::new().unwrap();
@@ -412,11 +1312,18 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: dir.path().to_string_lossy().to_string(),
-                max_depth: None,
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("visible.txt"));
@@ -424,6 +1331,78 @@ mod tests {
         assert!(!output.contains(".gitignore"));
     }
 
+    #[tokio::test]
+    async fn directory_tree_include_hidden_shows_dot_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        std::fs::write(dir.path().join(".github/workflows/ci.yml"), "on: push").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/").unwrap();
+        std::fs::write(dir.path().join("visible.txt"), "visible").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: Some(true),
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains(".github/"), "{output}");
+        assert!(output.contains("workflows/"), "{output}");
+        assert!(output.contains("ci.yml"), "{output}");
+        assert!(output.contains(".gitignore"), "{output}");
+        assert!(output.contains("visible.txt"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn directory_tree_truncates_at_max_tree_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("a")).unwrap();
+        std::fs::write(canon.join("a").join("inner.txt"), "x").unwrap();
+        for name in ["b.txt", "c.txt", "d.txt"] {
+            std::fs::write(canon.join(name), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon.clone()],
+            max_tree_entries: 3,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let output = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: canon.to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("a/"), "{output}");
+        assert!(output.contains("inner.txt"), "{output}");
+        assert!(output.contains("b.txt"), "{output}");
+        assert!(!output.contains("c.txt"), "{output}");
+        assert!(
+            output.contains("... (truncated, exceeded 3 entries."),
+            "{output}"
+        );
+    }
+
     #[tokio::test]
     async fn directory_tree_denied_outside() {
         let dir = TempDir::new().unwrap();
@@ -432,14 +1411,21 @@ mod tests {
 
         let other = TempDir::new().unwrap();
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: other.path().to_string_lossy().to_string(),
-                max_depth: None,
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: other.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
     }
 
     #[tokio::test]
@@ -449,11 +1435,18 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: dir.path().to_string_lossy().to_string(),
-                max_depth: None,
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.ends_with("/\n"));
@@ -469,14 +1462,194 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .directory_tree(Parameters(DirectoryTreeParams {
-                path: dir.path().to_string_lossy().to_string(),
-                max_depth: None,
-            }))
-            .await;
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("truncated"));
         assert!(output.contains("search_files"));
     }
+
+    #[tokio::test]
+    async fn directory_tree_include_hidden_still_truncates() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for i in 0..1005 {
+            std::fs::write(dir.path().join(format!(".dot{i:04}")), "x").unwrap();
+        }
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: Some(true),
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains(".dot0999"), "{output}");
+        assert!(!output.contains(".dot1000"), "{output}");
+        assert!(
+            output.contains("truncated, exceeded 1000 entries"),
+            "{output}"
+        );
+    }
+
+    #[tokio::test]
+    async fn directory_tree_returns_promptly_when_cancelled() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for d in 0..200 {
+            let sub = dir.path().join(format!("d{d:03}"));
+            std::fs::create_dir(&sub).unwrap();
+            for f in 0..10 {
+                std::fs::write(sub.join(format!("f{f}.txt")), "x").unwrap();
+            }
+        }
+
+        let service = make_service(vec![canon]);
+        let ct = CancellationToken::new();
+        ct.cancel();
+        let started = std::time::Instant::now();
+        let err = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                ct,
+            )
+            .await
+            .text()
+            .unwrap_err();
+        assert!(err.message.contains("cancelled"), "{err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn directory_tree_applies_extension_filter() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src").join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+
+        let config = Config {
+            allowed_directories: vec![canon],
+            only_extensions: vec!["rs".to_string()],
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let output = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: None,
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.lines().next().unwrap().ends_with("(extensions: rs)"));
+        assert!(output.contains("main.rs"));
+        assert!(!output.contains("notes.txt"));
+
+        let unfiltered = service
+            .directory_tree(
+                Parameters(DirectoryTreeParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    max_depth: None,
+                    extensions: Some(vec![]),
+                    include_hidden: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+        assert!(unfiltered.contains("notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn get_file_info_structured_content_matches_text() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("test.txt"), "hello world").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, info) = client_view(
+            service
+                .get_file_info(Parameters(GetFileInfoParams {
+                    path: dir.path().join("test.txt").to_string_lossy().to_string(),
+                    include_checksum: Some(true),
+                }))
+                .await,
+        );
+
+        let field = |name: &str| {
+            text.lines()
+                .find_map(|l| l.strip_prefix(&format!("{name}: ")))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(info.path, field("Path"));
+        assert_eq!(info.path, canon.join("test.txt").display().to_string());
+        assert_eq!(info.file_type, field("Type"));
+        assert_eq!(info.size, 11);
+        assert_eq!(info.mime.as_deref(), Some(field("MIME").as_str()));
+        assert_eq!(info.modified.as_deref(), Some(field("Modified").as_str()));
+        assert_eq!(info.permissions, field("Permissions"));
+        assert_eq!(info.sha256.as_deref(), Some(field("SHA-256").as_str()));
+        #[cfg(unix)]
+        assert_eq!(info.links.map(|n| n.to_string()), Some(field("Links")));
+    }
+
+    #[tokio::test]
+    async fn get_file_info_structured_directory_has_no_mime_or_checksum() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+
+        let service = make_service(vec![canon]);
+        let (text, info) = client_view(
+            service
+                .get_file_info(Parameters(GetFileInfoParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    include_checksum: Some(true),
+                }))
+                .await,
+        );
+
+        assert_eq!(info.file_type, "directory");
+        assert!(info.mime.is_none());
+        assert!(info.sha256.is_none());
+        assert!(text.contains("MIME: N/A"));
+        assert_eq!(info.hash_skipped.as_deref(), Some("not a regular file"));
+        assert!(text.contains("SHA-256: hash skipped (not a regular file)"));
+    }
 }
@@ -0,0 +1,488 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use globset::{Glob, GlobMatcher};
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use super::checksum::manifest_path;
+use super::count::{Visit, walk_sync};
+use super::destructive::dry_run_report;
+use super::util::{format_size, is_binary, spawn_blocking, write_atomic_sync};
+
+/// Line ending styles normalize_line_endings converts to.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum LineStyle {
+    Lf,
+    Crlf,
+}
+
+impl std::fmt::Display for LineStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LineStyle::Lf => "LF",
+            LineStyle::Crlf => "CRLF",
+        })
+    }
+}
+
+/// Parameters for the normalize_line_endings tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct NormalizeLineEndingsParams {
+    /// Absolute path to the directory to normalize under
+    path: String,
+    /// Glob pattern matched against paths relative to `path` (e.g. "**/*.rs")
+    include: String,
+    /// Line ending to convert to: "lf" or "crlf"
+    style: LineStyle,
+    /// Set to true to rewrite the files; without it the call only reports
+    /// what would change
+    #[schemars(
+        description = "Set to true to rewrite the files; without it the call only reports what would change"
+    )]
+    confirm: Option<bool>,
+}
+
+/// A file whose line endings differ from the target style.
+#[derive(Debug, PartialEq)]
+struct Conversion {
+    /// Path relative to the root, with `/` separators
+    relative: String,
+    /// `\r\n` line endings found
+    crlf: u64,
+    /// Bare `\n` line endings found
+    lf: u64,
+    /// Line endings rewritten (or to rewrite) to the target style
+    converted: u64,
+}
+
+/// What a [`normalize_sync`] pass found and did.
+#[derive(Debug, Default)]
+struct Normalization {
+    /// Files matching the include pattern, skipped ones included
+    matched: usize,
+    conversions: Vec<Conversion>,
+    /// Files that could not be rewritten, with the reason
+    failed: Vec<(String, String)>,
+    binary: u64,
+    too_large: u64,
+    denied: u64,
+    unreadable: u64,
+    /// Whether more files needed converting after the per-call cap
+    truncated: bool,
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+/// Counts `\r\n` and bare `\n` line endings in `bytes`.
+fn count_line_endings(bytes: &[u8]) -> (u64, u64) {
+    let mut crlf = 0;
+    let mut lf = 0;
+    for (index, _) in bytes.iter().enumerate().filter(|(_, b)| **b == b'\n') {
+        if index > 0 && bytes[index - 1] == b'\r' {
+            crlf += 1;
+        } else {
+            lf += 1;
+        }
+    }
+    (crlf, lf)
+}
+
+/// Rewrites every line ending in `bytes` to `style`. Works on bytes so any
+/// ASCII-compatible encoding survives, and never adds or removes a final
+/// newline.
+fn convert_line_endings(bytes: &[u8], style: LineStyle) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + bytes.len() / 32);
+    for (index, byte) in bytes.iter().enumerate() {
+        let before_newline = bytes.get(index + 1) == Some(&b'\n');
+        match (style, *byte) {
+            (LineStyle::Lf, b'\r') if before_newline => {}
+            (LineStyle::Crlf, b'\n') if index == 0 || bytes[index - 1] != b'\r' => {
+                out.extend_from_slice(b"\r\n");
+            }
+            _ => out.push(*byte),
+        }
+    }
+    out
+}
+
+/// Walks `root` up to `max_depth` levels and collects the regular files
+/// matching `include`, sorted by relative path. Denied paths and paths in
+/// read-only directories are skipped and counted in the returned result.
+fn find_candidates_sync(
+    root: &Path,
+    security: &SecurityContext,
+    include: &GlobMatcher,
+    max_depth: usize,
+    ct: &CancellationToken,
+) -> Result<(Vec<(String, PathBuf)>, Normalization), FsError> {
+    let mut result = Normalization::default();
+    let mut candidates: Vec<(String, PathBuf)> = Vec::new();
+    let mut denied = 0;
+    let mut read_only = 0;
+    result.unreadable = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if security.is_read_only(path) {
+                read_only += 1;
+                return Visit::Skip;
+            }
+            if file_type.is_file() {
+                let relative = manifest_path(root, path);
+                if include.is_match(&relative) {
+                    candidates.push((relative, path.to_path_buf()));
+                }
+            }
+            Visit::Continue
+        },
+    )?;
+    result.denied = denied + read_only;
+    result.matched = candidates.len();
+    candidates.sort();
+    Ok((candidates, result))
+}
+
+/// Finds the `candidates` whose line endings are not all `style`, recording
+/// them in `result`. Binary files and files over `max_size` are skipped.
+/// Stops after `max_files` conversions, and rewrites each file when `apply`
+/// is set.
+fn normalize_sync(
+    candidates: Vec<(String, PathBuf)>,
+    mut result: Normalization,
+    style: LineStyle,
+    max_size: u64,
+    max_files: usize,
+    apply: bool,
+    ct: &CancellationToken,
+) -> Result<Normalization, FsError> {
+    for (relative, path) in candidates {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        if std::fs::metadata(&path).is_ok_and(|m| m.len() > max_size) {
+            result.too_large += 1;
+            continue;
+        }
+        let Ok(content) = std::fs::read(&path) else {
+            result.unreadable += 1;
+            continue;
+        };
+        result.bytes_read += content.len() as u64;
+        if is_binary(&content) {
+            result.binary += 1;
+            continue;
+        }
+        let (crlf, lf) = count_line_endings(&content);
+        let converted = match style {
+            LineStyle::Lf => crlf,
+            LineStyle::Crlf => lf,
+        };
+        if converted == 0 {
+            continue;
+        }
+        if result.conversions.len() >= max_files {
+            result.truncated = true;
+            break;
+        }
+        if apply {
+            let data = convert_line_endings(&content, style);
+            if let Err(e) = write_atomic_sync(&path, &data) {
+                result.failed.push((relative, e.to_string()));
+                continue;
+            }
+            result.bytes_written += data.len() as u64;
+        }
+        result.conversions.push(Conversion {
+            relative,
+            crlf,
+            lf,
+            converted,
+        });
+    }
+    Ok(result)
+}
+
+/// Renders the per-file conversion list with a total, failures, skipped
+/// files, and the cap note.
+fn format_normalization(
+    root: &Path,
+    include: &str,
+    result: &Normalization,
+    style: LineStyle,
+    apply: bool,
+    max_size: u64,
+    max_files: usize,
+) -> String {
+    let total: u64 = result.conversions.iter().map(|c| c.converted).sum();
+    let mut out = if result.matched == 0 {
+        format!("No files match \"{include}\" in {}", root.display())
+    } else if result.conversions.is_empty() && result.failed.is_empty() {
+        format!(
+            "No changes needed: every text file matching \"{include}\" in {} already uses {style} line endings ({} matching files)",
+            root.display(),
+            result.matched
+        )
+    } else {
+        let summary = format!(
+            "{} of {} matching files in {} to {style} ({total} line endings)",
+            result.conversions.len(),
+            result.matched,
+            root.display()
+        );
+        let (mut out, verb) = if apply {
+            (format!("Converted {summary}:\n\n"), "converted")
+        } else {
+            (
+                dry_run_report(&format!("would convert {summary}:\n\n")),
+                "to convert",
+            )
+        };
+        for conversion in &result.conversions {
+            out.push_str(&format!(
+                "  {}: {} CRLF, {} LF; {} {verb}\n",
+                conversion.relative, conversion.crlf, conversion.lf, conversion.converted
+            ));
+        }
+        out.pop();
+        out
+    };
+
+    if !result.failed.is_empty() {
+        out.push_str(&format!("\n\nFailed ({}):", result.failed.len()));
+        for (relative, reason) in &result.failed {
+            out.push_str(&format!("\n  {relative}: {reason}"));
+        }
+    }
+    let mut skipped = Vec::new();
+    if result.binary > 0 {
+        skipped.push(format!("{} binary", result.binary));
+    }
+    if result.too_large > 0 {
+        skipped.push(format!(
+            "{} over max read size ({})",
+            result.too_large,
+            format_size(max_size)
+        ));
+    }
+    if result.denied > 0 {
+        skipped.push(format!("{} denied", result.denied));
+    }
+    if result.unreadable > 0 {
+        skipped.push(format!("{} unreadable", result.unreadable));
+    }
+    if !skipped.is_empty() {
+        out.push_str(&format!("\n\nSkipped: {}", skipped.join(", ")));
+    }
+    if result.truncated {
+        out.push_str(&format!(
+            "\n\nStopped at the {max_files}-file limit (--max-normalize-files); more files need converting, so call again to continue."
+        ));
+    }
+    if !apply && !result.conversions.is_empty() {
+        out.push_str(
+            "\n\nTo rewrite these files, call normalize_line_endings again with confirm: true.",
+        );
+    }
+    out
+}
+
+#[rmcp::tool_router(router = "line_ending_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Converts the line endings of matching files under a directory.
+    #[rmcp::tool(
+        name = "normalize_line_endings",
+        description = "Converts every line ending in the files matching include (a glob relative to path, e.g. \"**/*.rs\") to style \"lf\" or \"crlf\". Without confirm it is a dry run listing each file that would change with its current CRLF/LF counts; call again with confirm: true to rewrite them. Files are replaced atomically, a missing final newline stays missing, and binary files, files over --max-read-size, and denied paths are skipped. At most --max-normalize-files files are converted per call.",
+        annotations(
+            title = "Normalize Line Endings",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn normalize_line_endings(
+        &self,
+        Parameters(params): Parameters<NormalizeLineEndingsParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        if self.security.is_read_only(&root) {
+            return Err(FsError::ReadOnly {
+                path: params.path.clone(),
+            }
+            .into());
+        }
+        let include = Glob::new(&params.include)
+            .map_err(|e| FsError::PatternError(e.to_string()))?
+            .compile_matcher();
+        let apply = params.confirm.unwrap_or(false);
+
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let max_size = self.config.max_read_size as u64;
+        let max_files = self.config.max_normalize_files;
+        let style = params.style;
+        let walk_root = root.clone();
+        let result = spawn_blocking(move || {
+            let (candidates, found) =
+                find_candidates_sync(&walk_root, &security, &include, max_depth, &ct)?;
+            normalize_sync(candidates, found, style, max_size, max_files, apply, &ct)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(result.bytes_read);
+        if result.bytes_written > 0 {
+            stats::record_written(result.bytes_written);
+        }
+        Ok(format_normalization(
+            &root,
+            &params.include,
+            &result,
+            style,
+            apply,
+            max_size,
+            max_files,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    #[test]
+    fn converts_both_ways_and_keeps_missing_final_newline() {
+        let mixed = b"a\r\nb\nc\r\nd";
+        assert_eq!(count_line_endings(mixed), (2, 1));
+        assert_eq!(convert_line_endings(mixed, LineStyle::Lf), b"a\nb\nc\nd");
+        assert_eq!(
+            convert_line_endings(mixed, LineStyle::Crlf),
+            b"a\r\nb\r\nc\r\nd"
+        );
+        // A lone \r is not a line ending and is left alone
+        assert_eq!(
+            convert_line_endings(b"\n\rx\r", LineStyle::Crlf),
+            b"\r\n\rx\r"
+        );
+        assert_eq!(convert_line_endings(b"x\r\r\n", LineStyle::Lf), b"x\r\n");
+    }
+
+    fn service(dir: &TempDir, max_normalize_files: usize) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            allow_write: true,
+            max_read_size: 64,
+            max_normalize_files,
+            deny: vec!["*.pem".into()],
+            ..Default::default()
+        })
+    }
+
+    async fn normalize(
+        service: &FilesystemService,
+        root: &Path,
+        include: &str,
+        style: LineStyle,
+        confirm: bool,
+    ) -> String {
+        service
+            .normalize_line_endings(
+                Parameters(NormalizeLineEndingsParams {
+                    path: root.to_string_lossy().to_string(),
+                    include: include.to_string(),
+                    style,
+                    confirm: Some(confirm),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn dry_run_then_confirm_rewrites_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/mixed.txt"), "a\r\nb\nc\r\n").unwrap();
+        std::fs::write(root.join("src/unix.txt"), "a\nb\n").unwrap();
+        std::fs::write(root.join("src/no_newline.txt"), "a\r\nb").unwrap();
+        std::fs::write(root.join("src/blob.txt"), b"\0\r\n").unwrap();
+        std::fs::write(root.join("src/big.txt"), "x\r\n".repeat(30)).unwrap();
+        std::fs::write(root.join("key.pem"), "k\r\n").unwrap();
+        std::fs::write(root.join("other.md"), "a\r\n").unwrap();
+        let service = service(&dir, 100);
+
+        let preview = normalize(&service, &root, "**/*.txt", LineStyle::Lf, false).await;
+        assert_eq!(
+            preview,
+            format!(
+                "DRY RUN (nothing was changed): would convert 2 of 5 matching files in {} to LF (3 line endings):\n\n  \
+                 src/mixed.txt: 2 CRLF, 1 LF; 2 to convert\n  \
+                 src/no_newline.txt: 1 CRLF, 0 LF; 1 to convert\n\n\
+                 Skipped: 1 binary, 1 over max read size (64 B), 1 denied\n\n\
+                 To rewrite these files, call normalize_line_endings again with confirm: true.",
+                root.display()
+            )
+        );
+        assert_eq!(
+            std::fs::read(root.join("src/mixed.txt")).unwrap(),
+            b"a\r\nb\nc\r\n"
+        );
+
+        let applied = normalize(&service, &root, "**/*.txt", LineStyle::Lf, true).await;
+        assert!(
+            applied.starts_with("Converted 2 of 5 matching files in "),
+            "{applied}"
+        );
+        assert_eq!(
+            std::fs::read(root.join("src/mixed.txt")).unwrap(),
+            b"a\nb\nc\n"
+        );
+        assert_eq!(
+            std::fs::read(root.join("src/no_newline.txt")).unwrap(),
+            b"a\nb"
+        );
+        assert_eq!(std::fs::read(root.join("other.md")).unwrap(), b"a\r\n");
+        assert_eq!(std::fs::read_dir(root.join("src")).unwrap().count(), 5);
+
+        let again = normalize(&service, &root, "**/*.txt", LineStyle::Lf, true).await;
+        assert!(again.starts_with("No changes needed: "), "{again}");
+    }
+
+    #[tokio::test]
+    async fn stops_at_file_limit() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(root.join(name), "x\n").unwrap();
+        }
+        let service = service(&dir, 2);
+
+        let output = normalize(&service, &root, "*.txt", LineStyle::Crlf, true).await;
+        assert!(
+            output.contains("Converted 2 of 3 matching files") && output.contains("2-file limit"),
+            "{output}"
+        );
+        assert_eq!(std::fs::read(root.join("a.txt")).unwrap(), b"x\r\n");
+        assert_eq!(std::fs::read(root.join("c.txt")).unwrap(), b"x\n");
+
+        let output = normalize(&service, &root, "*.txt", LineStyle::Crlf, true).await;
+        assert!(output.starts_with("Converted 1 of 3"), "{output}");
+        assert!(!output.contains("limit"));
+
+        let output = normalize(&service, &root, "*.rs", LineStyle::Crlf, true).await;
+        assert!(output.starts_with("No files match \"*.rs\""), "{output}");
+    }
+}
@@ -1,25 +1,200 @@
 use crate::FilesystemService;
-use crate::error::io_error_message;
+use crate::error::ToolError;
+use crate::security::SecurityContext;
 use rmcp::handler::server::wrapper::Parameters;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::util::{format_date, format_size};
+use super::structured::{OutputFormat, Structured, output_schema};
+use super::util::{
+    SkippedEntries, SkippedPath, display_name, format_date, format_size, matches_extension_filter,
+    resolve_extension_filter, spawn_blocking, unix_seconds,
+};
 
-const MAX_DIR_ENTRIES: usize = 1000;
+/// Structured result of list_directory.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DirectoryListing {
+    /// Canonical path of the listed directory
+    pub path: String,
+    /// Entries of this page in display order: directories first, then files
+    pub entries: Vec<ListingEntry>,
+    /// 0-based position of the first of `entries` in the full listing
+    pub offset: usize,
+    /// Entries in the full listing
+    pub total_entries: usize,
+    /// Whether entries remain after this page, including entries dropped to
+    /// fit `--max-output-bytes`
+    pub truncated: bool,
+    /// Extension filter in effect; empty when unfiltered
+    pub extensions: Vec<String>,
+    pub summary: ListingTotals,
+    /// Entries whose metadata could not be read and were left out
+    pub skipped: Vec<SkippedPath>,
+    /// Entries left out because a deny pattern matches them
+    pub denied: usize,
+}
+
+/// One entry of a [`DirectoryListing`].
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListingEntry {
+    pub name: String,
+    /// "directory" or "file"
+    pub kind: String,
+    /// Size in bytes, for files
+    pub size: Option<u64>,
+    /// Modification date (YYYY-MM-DD), for files
+    pub modified: Option<String>,
+    /// Modification time in seconds since the Unix epoch, when the platform
+    /// reports it
+    pub modified_unix: Option<u64>,
+}
+
+/// Counts from the list_directory footer.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListingTotals {
+    pub directories: usize,
+    pub files: usize,
+    pub symlinks: usize,
+    pub total_size: u64,
+    pub text: usize,
+    pub image: usize,
+    pub audio: usize,
+    pub video: usize,
+    pub application: usize,
+    pub binary: usize,
+    pub other: usize,
+}
+
+/// Running totals for the list_directory summary footer.
+#[derive(Default)]
+struct ListingSummary {
+    dirs: usize,
+    files: usize,
+    symlinks: usize,
+    total_size: u64,
+    text: usize,
+    image: usize,
+    audio: usize,
+    video: usize,
+    application: usize,
+    binary: usize,
+    other: usize,
+    /// Entries left out by deny patterns; not part of the footer totals
+    denied: usize,
+}
+
+impl ListingSummary {
+    /// Counts a file under its MIME primary type.
+    /// Unrecognized extensions count as binary, matching get_file_info's octet-stream fallback.
+    fn add_file(&mut self, path: &std::path::Path, size: u64) {
+        self.files += 1;
+        self.total_size += size;
+        let Some(mime) = mime_guess::from_path(path).first() else {
+            self.binary += 1;
+            return;
+        };
+        if mime == mime_guess::mime::APPLICATION_OCTET_STREAM {
+            self.binary += 1;
+            return;
+        }
+        match mime.type_().as_str() {
+            "text" => self.text += 1,
+            "image" => self.image += 1,
+            "audio" => self.audio += 1,
+            "video" => self.video += 1,
+            "application" => self.application += 1,
+            _ => self.other += 1,
+        }
+    }
+
+    fn totals(&self) -> ListingTotals {
+        ListingTotals {
+            directories: self.dirs,
+            files: self.files,
+            symlinks: self.symlinks,
+            total_size: self.total_size,
+            text: self.text,
+            image: self.image,
+            audio: self.audio,
+            video: self.video,
+            application: self.application,
+            binary: self.binary,
+            other: self.other,
+        }
+    }
+
+    /// Formats the footer, e.g. `Total: 2 directories, 15 files (12 text, 2 image, 1 binary), 456.7 KB`.
+    /// Categories beyond text/image/binary are only shown when non-zero.
+    fn footer(&self) -> String {
+        let mut breakdown = vec![
+            format!("{} text", self.text),
+            format!("{} image", self.image),
+            format!("{} binary", self.binary),
+        ];
+        for (count, label) in [
+            (self.audio, "audio"),
+            (self.video, "video"),
+            (self.application, "application"),
+            (self.other, "other"),
+        ] {
+            if count > 0 {
+                breakdown.push(format!("{count} {label}"));
+            }
+        }
+        let symlinks = if self.symlinks > 0 {
+            format!(
+                ", {} symlink{}",
+                self.symlinks,
+                if self.symlinks == 1 { "" } else { "s" }
+            )
+        } else {
+            String::new()
+        };
+        format!(
+            "Total: {} director{}, {} file{} ({}){}, {}",
+            self.dirs,
+            if self.dirs == 1 { "y" } else { "ies" },
+            self.files,
+            if self.files == 1 { "" } else { "s" },
+            breakdown.join(", "),
+            symlinks,
+            format_size(self.total_size),
+        )
+    }
+}
 
 /// Parameters for the list_directory tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct ListDirectoryParams {
     /// Absolute path to the directory to list
     path: String,
+    /// Only list files with these extensions (overrides the server default; empty list shows all)
+    #[schemars(
+        description = "Only list files with these extensions (overrides the server default; empty list shows all)"
+    )]
+    extensions: Option<Vec<String>>,
+    /// Number of entries to skip, for paging through large directories (default: 0)
+    #[schemars(
+        description = "Number of entries to skip, for paging through large directories (default: 0)"
+    )]
+    offset: Option<u64>,
+    /// Maximum number of entries to return (default and maximum: the server's --max-dir-entries)
+    #[schemars(
+        description = "Maximum number of entries to return (default and maximum: the server's --max-dir-entries)"
+    )]
+    limit: Option<u64>,
+    /// Output format: "text" (default) for readable text, or "json" for the structured result as JSON
+    #[schemars(
+        description = "Output format: \"text\" (default) for readable text, or \"json\" for the structured result as JSON"
+    )]
+    format: Option<OutputFormat>,
 }
 
 impl FilesystemService {
     /// Formats the allowed directories as a newline-separated string of canonical paths.
     pub fn format_allowed_directories(&self) -> String {
-        self.config
-            .allowed_directories
+        self.security
+            .allowed_dirs()
             .iter()
             .map(|d| d.display().to_string())
             .collect::<Vec<_>>()
@@ -36,7 +211,13 @@ impl FilesystemService {
     #[rmcp::tool(
         name = "list_allowed_directories",
         description = "Lists all directories that this server is allowed to access. Returns each allowed directory on its own line as a fully canonicalized path.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        annotations(
+            title = "List Allowed Directories",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     fn list_allowed_directories(&self) -> String {
         self.format_allowed_directories()
@@ -45,75 +226,209 @@ impl FilesystemService {
     /// Lists the contents of a directory with type, name, size, and modification date.
     #[rmcp::tool(
         name = "list_directory",
-        description = "Lists the contents of a directory. Returns entries sorted with directories first, then files, each alphabetically. Each entry shows type, name, and for files, size and modification date.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        description = "Lists the contents of a directory. Returns entries sorted with directories first, then files, each alphabetically. Each entry shows type, name, and for files, size and modification date. A footer summarizes directory and file counts, a file breakdown by type, and total size. Large directories are returned a page at a time; pass offset (and optionally limit) to get later pages. With format \"json\" the text is the structured result as JSON.",
+        output_schema = output_schema::<DirectoryListing>(),
+        annotations(
+            title = "List Directory",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn list_directory(
         &self,
         Parameters(params): Parameters<ListDirectoryParams>,
-    ) -> Result<String, String> {
-        let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_directory(path)
-            .map_err(|e| e.to_string())?;
+    ) -> Structured<DirectoryListing> {
+        Structured::from(
+            self.list_directory_report(
+                &params.path,
+                params.extensions.as_deref(),
+                params.offset.unwrap_or(0) as usize,
+                params.limit.map(|l| l as usize),
+            )
+            .await,
+        )
+        .formatted(params.format)
+    }
+}
 
-        let mut dirs: Vec<String> = Vec::new();
-        let mut files: Vec<String> = Vec::new();
+impl FilesystemService {
+    /// Lists `requested` and returns the page of at most `limit` entries
+    /// (capped at `--max-dir-entries`) starting at `offset`.
+    pub(crate) async fn list_directory_report(
+        &self,
+        requested: &str,
+        extensions: Option<&[String]>,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<(String, DirectoryListing), ToolError> {
+        if limit == Some(0) {
+            return Err("limit must be at least 1".into());
+        }
+        let path = std::path::Path::new(requested);
+        let canonical = self.security.validate_directory(path)?;
 
-        let mut entries = tokio::fs::read_dir(&canonical)
-            .await
-            .map_err(|e| io_error_message(e, &params.path))?;
-
-        loop {
-            match entries.next_entry().await {
-                Ok(Some(entry)) => {
-                    let name = entry.file_name().to_string_lossy().to_string();
-                    let metadata = match entry.metadata().await {
-                        Ok(m) => m,
-                        Err(_) => continue,
-                    };
-
-                    if metadata.is_dir() {
-                        dirs.push(format!("[DIR]  {name}/"));
-                    } else if metadata.is_file() {
-                        let size = format_size(metadata.len());
-                        let modified = metadata
-                            .modified()
-                            .map(format_date)
-                            .unwrap_or_else(|_| "unknown".to_string());
-                        files.push(format!("[FILE] {name} ({size}, {modified})"));
-                    }
-                }
-                Ok(None) => break,
-                Err(_) => break,
-            }
+        let extensions = resolve_extension_filter(&self.config.only_extensions, extensions);
+
+        // Per-entry metadata calls are blocking I/O; keep them off the runtime
+        let (dir, filter, security) =
+            (canonical.clone(), extensions.clone(), self.security.clone());
+        let (mut dirs, mut files, summary, skipped) =
+            spawn_blocking(move || read_listing_sync(&dir, &filter, &security))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| ToolError::io(e, requested))?;
+
+        dirs.sort_by(|a, b| a.0.cmp(&b.0));
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let (mut lines, mut listed): (Vec<String>, Vec<ListingEntry>) =
+            dirs.into_iter().chain(files).unzip();
+        let total_entries = lines.len();
+        if offset > 0 && offset >= total_entries {
+            return Err(format!(
+                "Offset {offset} is beyond the end of the listing ({total_entries} entries)"
+            )
+            .into());
         }
+        let page_size = limit
+            .unwrap_or(self.config.max_dir_entries)
+            .min(self.config.max_dir_entries);
+        let end = total_entries.min(offset + page_size);
+        let truncated = end < total_entries;
+        lines.truncate(end);
+        lines.drain(..offset);
+        listed.truncate(end);
+        listed.drain(..offset);
+        let listing = DirectoryListing {
+            path: canonical.display().to_string(),
+            entries: listed,
+            offset,
+            total_entries,
+            truncated,
+            extensions: extensions.clone(),
+            summary: summary.totals(),
+            skipped: skipped.report(&canonical),
+            denied: summary.denied,
+        };
 
-        dirs.sort();
-        files.sort();
+        let header = if extensions.is_empty() {
+            String::new()
+        } else {
+            format!("(Filtered to extensions: {})\n\n", extensions.join(", "))
+        };
 
-        let mut lines = dirs;
-        lines.extend(files);
+        let mut footer = summary.footer();
+        if let Some(skipped) = skipped.summary(&canonical) {
+            footer.push_str(&format!("\n({skipped})"));
+        }
+        if summary.denied > 0 {
+            footer.push_str(&format!(
+                "\n({} entr{} skipped by deny patterns)",
+                summary.denied,
+                if summary.denied == 1 { "y" } else { "ies" }
+            ));
+        }
 
-        if lines.is_empty() {
-            Ok("(empty directory)".to_string())
-        } else if lines.len() > MAX_DIR_ENTRIES {
-            let total = lines.len();
-            lines.truncate(MAX_DIR_ENTRIES);
+        if truncated {
             lines.push(format!(
-                "\n(Showing first {MAX_DIR_ENTRIES} of {total} entries. Use search_files to find specific files.)"
+                "\n(Showing entries {}\u{2013}{end} of {total_entries}. More entries remain: pass offset: {end} for the next page, or use search_files to find specific files.)",
+                offset + 1
             ));
-            Ok(lines.join("\n"))
+        } else if offset > 0 {
+            lines.push(format!(
+                "\n(Showing entries {}\u{2013}{end} of {total_entries}. This is the last page.)",
+                offset + 1
+            ));
+        }
+        let text = if lines.is_empty() {
+            format!("{header}(empty directory)\n\n{footer}")
         } else {
-            Ok(lines.join("\n"))
+            format!("{header}{}\n\n{footer}", lines.join("\n"))
+        };
+        Ok((text, listing))
+    }
+}
+
+/// Display lines paired with their structured entries.
+type ListingLines = Vec<(String, ListingEntry)>;
+
+/// Reads `dir` once, collecting its directories and the files passing the
+/// extension filter. Entries whose metadata cannot be read are left out and
+/// recorded as skipped; denied entries are left out and counted.
+fn read_listing_sync(
+    dir: &std::path::Path,
+    extensions: &[String],
+    security: &SecurityContext,
+) -> std::io::Result<(ListingLines, ListingLines, ListingSummary, SkippedEntries)> {
+    let mut dirs: ListingLines = Vec::new();
+    let mut files: ListingLines = Vec::new();
+    let mut summary = ListingSummary::default();
+    let mut skipped = SkippedEntries::default();
+
+    for entry in std::fs::read_dir(dir)? {
+        let Ok(entry) = entry else {
+            break;
+        };
+        if security.is_denied(&entry.path()) {
+            summary.denied += 1;
+            continue;
+        }
+        let name = display_name(&entry.file_name());
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(e) => {
+                let directory = entry.file_type().is_ok_and(|t| t.is_dir());
+                skipped.record(entry.path(), &e, directory);
+                continue;
+            }
+        };
+
+        if metadata.is_dir() {
+            summary.dirs += 1;
+            dirs.push((
+                format!("[DIR]  {name}/"),
+                ListingEntry {
+                    name,
+                    kind: "directory".to_string(),
+                    size: None,
+                    modified: None,
+                    modified_unix: metadata.modified().ok().and_then(unix_seconds),
+                },
+            ));
+        } else if metadata.file_type().is_symlink() {
+            summary.symlinks += 1;
+        } else if metadata.is_file() {
+            if !matches_extension_filter(&entry.path(), extensions) {
+                continue;
+            }
+            summary.add_file(&entry.path(), metadata.len());
+            let size = format_size(metadata.len());
+            let mtime = metadata.modified().ok();
+            let modified = mtime
+                .map(format_date)
+                .unwrap_or_else(|| "unknown".to_string());
+            files.push((
+                format!("[FILE] {name} ({size}, {modified})"),
+                ListingEntry {
+                    name,
+                    kind: "file".to_string(),
+                    size: Some(metadata.len()),
+                    modified: Some(modified),
+                    modified_unix: mtime.and_then(unix_seconds),
+                },
+            ));
         }
     }
+    Ok((dirs, files, summary, skipped))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_MAX_DIR_ENTRIES;
+    use crate::tools::structured::client_view;
     use crate::{Config, FilesystemService};
     use rmcp::handler::server::wrapper::Parameters;
     use std::path::PathBuf;
@@ -126,6 +441,16 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
+        };
+        FilesystemService::new(config)
+    }
+
+    fn make_service_with_extensions(dirs: Vec<PathBuf>, exts: &[&str]) -> FilesystemService {
+        let config = Config {
+            allowed_directories: dirs,
+            only_extensions: exts.iter().map(|e| e.to_string()).collect(),
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -190,8 +515,13 @@ mod tests {
         let result = service
             .list_directory(Parameters(ListDirectoryParams {
                 path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("[DIR]  subdir/"));
@@ -203,6 +533,77 @@ mod tests {
         assert!(dir_pos < file_pos);
     }
 
+    #[tokio::test]
+    async fn list_directory_json_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("docs")).unwrap();
+        std::fs::write(canon.join("notes.txt"), "hello").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let text = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: canon.to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: Some(OutputFormat::Json),
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        let listing: DirectoryListing = serde_json::from_str(&text).unwrap();
+        assert_eq!(listing.total_entries, 2);
+        let docs = &listing.entries[0];
+        assert_eq!(
+            (docs.name.as_str(), docs.kind.as_str()),
+            ("docs", "directory")
+        );
+        let notes = &listing.entries[1];
+        assert_eq!((notes.name.as_str(), notes.size), ("notes.txt", Some(5)));
+        let mtime = std::fs::metadata(canon.join("notes.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(notes.modified_unix, unix_seconds(mtime));
+    }
+
+    #[tokio::test]
+    async fn list_directory_leaves_out_denied_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("sub")).unwrap();
+        std::fs::write(canon.join("key.pem"), "secret").unwrap();
+        std::fs::write(canon.join("sub/other.pem"), "secret").unwrap();
+        std::fs::write(canon.join("notes.txt"), "hello").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon.clone()],
+            deny: vec!["*.pem".into()],
+            ..Default::default()
+        });
+
+        let (text, listing) = client_view(
+            service
+                .list_directory(Parameters(ListDirectoryParams {
+                    path: canon.to_string_lossy().to_string(),
+                    extensions: None,
+                    offset: None,
+                    limit: None,
+                    format: None,
+                }))
+                .await,
+        );
+        assert!(!text.contains("key.pem"), "{text}");
+        assert!(
+            text.ends_with("(1 entry skipped by deny patterns)"),
+            "{text}"
+        );
+        let names: Vec<&str> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["sub", "notes.txt"]);
+        assert_eq!(listing.denied, 1);
+    }
+
     #[tokio::test]
     async fn list_directory_empty_dir() {
         let dir = TempDir::new().unwrap();
@@ -211,9 +612,16 @@ mod tests {
         let result = service
             .list_directory(Parameters(ListDirectoryParams {
                 path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
             }))
-            .await;
-        assert_eq!(result.unwrap(), "(empty directory)");
+            .await
+            .text();
+        let output = result.unwrap();
+        assert!(output.starts_with("(empty directory)"));
+        assert!(output.contains("Total: 0 directories, 0 files (0 text, 0 image, 0 binary), 0 B"));
     }
 
     #[tokio::test]
@@ -226,10 +634,15 @@ mod tests {
         let result = service
             .list_directory(Parameters(ListDirectoryParams {
                 path: other.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
             }))
-            .await;
+            .await
+            .text();
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
     }
 
     #[tokio::test]
@@ -245,8 +658,13 @@ mod tests {
         let result = service
             .list_directory(Parameters(ListDirectoryParams {
                 path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
         let lines: Vec<&str> = output.lines().collect();
@@ -268,14 +686,366 @@ mod tests {
         let result = service
             .list_directory(Parameters(ListDirectoryParams {
                 path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
             }))
-            .await;
+            .await
+            .text();
 
         let output = result.unwrap();
-        assert!(output.contains("Showing first 1000"));
-        assert!(output.contains("1005 entries"));
+        assert!(output.contains("Showing entries 1\u{2013}1000 of 1005."));
+        assert!(output.contains("pass offset: 1000 for the next page"));
         assert!(output.contains("search_files"));
         let file_lines: Vec<&str> = output.lines().filter(|l| l.starts_with("[FILE]")).collect();
         assert_eq!(file_lines.len(), 1000);
     }
+
+    #[tokio::test]
+    async fn list_directory_applies_configured_extension_filter() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+
+        let service = make_service_with_extensions(vec![canon], &["rs"]);
+        let output = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.starts_with("(Filtered to extensions: rs)"));
+        assert!(output.contains("[DIR]  src/"));
+        assert!(output.contains("main.rs"));
+        assert!(!output.contains("notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn list_directory_per_call_extensions_override() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+
+        let service = make_service_with_extensions(vec![canon], &["rs"]);
+        let output = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: dir.path().to_string_lossy().to_string(),
+                extensions: Some(vec![]),
+                offset: None,
+                limit: None,
+                format: None,
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        assert!(!output.contains("Filtered"));
+        assert!(output.contains("main.rs"));
+        assert!(output.contains("notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn list_directory_footer_counts_types() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(dir.path().join(name), "fn x() {}").unwrap();
+        }
+        std::fs::write(dir.path().join("logo.png"), "png").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), "bin").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        let footer = output.lines().last().unwrap();
+        assert!(footer.starts_with("Total: 1 directory, 5 files (3 text, 1 image, 1 binary), "));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn list_directory_footer_counts_symlinks() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("target.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("target.txt"), dir.path().join("link")).unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
+            }))
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("1 file (1 text, 0 image, 0 binary), 1 symlink, 1 B"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn list_directory_reports_entries_it_cannot_stat() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let listed = dir.path().join("listed");
+        std::fs::create_dir(&listed).unwrap();
+        std::fs::write(listed.join("a.txt"), "x").unwrap();
+        // Readable but not searchable: names can be listed, metadata cannot be read
+        std::fs::set_permissions(&listed, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let readable_anyway = std::fs::metadata(listed.join("a.txt")).is_ok();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .list_directory_report(&listed.to_string_lossy(), None, 0, None)
+            .await;
+        std::fs::set_permissions(&listed, std::fs::Permissions::from_mode(0o755)).unwrap();
+        if readable_anyway {
+            // Running as root, so permissions are not enforced
+            return;
+        }
+
+        let (output, listing) = result.unwrap();
+        assert!(output.ends_with("(1 entry skipped: permission denied: ./a.txt)"));
+        assert_eq!(listing.skipped.len(), 1);
+        assert_eq!(listing.skipped[0].path, "./a.txt");
+        assert_eq!(listing.skipped[0].reason, "permission denied");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn list_directory_marks_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let name = std::ffi::OsStr::from_bytes(b"caf\xe9.txt");
+        if std::fs::write(dir.path().join(name), "menu").is_err() {
+            // The filesystem only accepts UTF-8 names
+            return;
+        }
+
+        let service = make_service(vec![canon.clone()]);
+        let output = service
+            .list_directory(Parameters(ListDirectoryParams {
+                path: dir.path().to_string_lossy().to_string(),
+                extensions: None,
+                offset: None,
+                limit: None,
+                format: None,
+            }))
+            .await
+            .text()
+            .unwrap();
+        assert!(
+            output
+                .contains("[FILE] caf\u{FFFD}.txt (non-UTF-8 name, hex: 636166e92e747874) (4 B, "),
+            "{output}"
+        );
+
+        // The hex form reaches the real file
+        let escaped = canon.join("{hex:636166e92e747874}");
+        let (info, _) = service
+            .get_file_info_report(&escaped.to_string_lossy(), false)
+            .await
+            .unwrap();
+        assert!(info.contains("Size: 4 B"), "{info}");
+    }
+
+    #[tokio::test]
+    async fn list_directory_structured_content_matches_text() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("b.txt"), "hello").unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, listing) = client_view(
+            service
+                .list_directory(Parameters(ListDirectoryParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    extensions: None,
+                    offset: None,
+                    limit: None,
+                    format: None,
+                }))
+                .await,
+        );
+
+        assert_eq!(listing.path, canon.display().to_string());
+        assert!(!listing.truncated);
+        assert_eq!(listing.total_entries, 3);
+        let names: Vec<&str> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["src", "a.rs", "b.txt"]);
+        // Entries follow the text lines one for one
+        let lines: Vec<&str> = text.lines().filter(|l| l.starts_with('[')).collect();
+        assert_eq!(lines.len(), listing.entries.len());
+        for (line, entry) in lines.iter().zip(&listing.entries) {
+            assert!(line.contains(&entry.name), "{line}");
+            assert_eq!(line.starts_with("[DIR]"), entry.kind == "directory");
+            if let Some(modified) = &entry.modified {
+                assert!(line.contains(modified), "{line}");
+            }
+        }
+        assert_eq!(listing.entries[2].size, Some(5));
+        assert_eq!(listing.summary.directories, 1);
+        assert_eq!(listing.summary.files, 2);
+        assert_eq!(listing.summary.total_size, 17);
+        assert!(text.contains("1 directory, 2 files"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn list_directory_over_10k_files_is_deterministic() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for i in 0..10_000 {
+            std::fs::write(canon.join(format!("f{i:05}.txt")), "x").unwrap();
+        }
+        std::fs::create_dir(canon.join("zz_dir")).unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, listing) = client_view(
+            service
+                .list_directory(Parameters(ListDirectoryParams {
+                    path: canon.to_string_lossy().to_string(),
+                    extensions: None,
+                    offset: None,
+                    limit: None,
+                    format: None,
+                }))
+                .await,
+        );
+
+        assert!(listing.truncated);
+        assert_eq!(listing.total_entries, 10_001);
+        assert_eq!(listing.entries.len(), DEFAULT_MAX_DIR_ENTRIES);
+        assert_eq!(listing.entries[0].name, "zz_dir");
+        assert_eq!(listing.entries[1].name, "f00000.txt");
+        assert_eq!(
+            listing.entries[DEFAULT_MAX_DIR_ENTRIES - 1].name,
+            "f00998.txt"
+        );
+        assert!(
+            text.contains("(Showing entries 1\u{2013}1000 of 10001."),
+            "{text}"
+        );
+        assert!(
+            text.contains("Total: 1 directory, 10000 files (10000 text, 0 image, 0 binary)"),
+            "{text}"
+        );
+        assert_eq!(listing.summary.total_size, 10_000);
+    }
+
+    #[tokio::test]
+    async fn list_directory_truncates_at_max_dir_entries() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for i in 0..5 {
+            std::fs::write(canon.join(format!("f{i}.txt")), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon.clone()],
+            max_dir_entries: 3,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let (text, listing) = client_view(
+            service
+                .list_directory(Parameters(ListDirectoryParams {
+                    path: canon.to_string_lossy().to_string(),
+                    extensions: None,
+                    offset: None,
+                    limit: None,
+                    format: None,
+                }))
+                .await,
+        );
+
+        assert!(listing.truncated);
+        assert_eq!(listing.total_entries, 5);
+        assert_eq!(listing.entries.len(), 3);
+        assert_eq!(listing.entries[2].name, "f2.txt");
+        assert!(text.contains("(Showing entries 1\u{2013}3 of 5."), "{text}");
+        assert!(!text.contains("f3.txt"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn list_directory_pages_with_offset_and_limit() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("sub")).unwrap();
+        for i in 0..6 {
+            std::fs::write(canon.join(format!("f{i}.txt")), "x").unwrap();
+        }
+        let config = Config {
+            allowed_directories: vec![canon.clone()],
+            max_dir_entries: 4,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let page = |offset, limit| {
+            service.list_directory(Parameters(ListDirectoryParams {
+                path: canon.to_string_lossy().to_string(),
+                extensions: None,
+                offset,
+                limit,
+                format: None,
+            }))
+        };
+        let names = |listing: &DirectoryListing| -> Vec<String> {
+            listing.entries.iter().map(|e| e.name.clone()).collect()
+        };
+
+        let (text, listing) = client_view(page(Some(1), Some(2)).await);
+        assert_eq!(names(&listing), ["f0.txt", "f1.txt"]);
+        assert_eq!(listing.offset, 1);
+        assert!(listing.truncated);
+        assert!(
+            text.contains("(Showing entries 2\u{2013}3 of 7. More entries remain: pass offset: 3"),
+            "{text}"
+        );
+        // The footer still totals the whole directory
+        assert!(text.contains("1 directory, 6 files"), "{text}");
+
+        // A limit above --max-dir-entries is capped
+        let (text, listing) = client_view(page(Some(3), Some(100)).await);
+        assert_eq!(names(&listing), ["f2.txt", "f3.txt", "f4.txt", "f5.txt"]);
+        assert!(!listing.truncated);
+        assert!(
+            text.contains("(Showing entries 4\u{2013}7 of 7. This is the last page.)"),
+            "{text}"
+        );
+
+        let err = page(Some(7), None).await.text().unwrap_err();
+        assert_eq!(
+            err.message,
+            "Offset 7 is beyond the end of the listing (7 entries)"
+        );
+        let err = page(None, Some(0)).await.text().unwrap_err();
+        assert_eq!(err.message, "limit must be at least 1");
+    }
 }
@@ -0,0 +1,437 @@
+//! Content-based file type detection from leading bytes.
+
+use std::fmt;
+
+/// Bytes of a file's head that [`detect`] looks at.
+pub const DETECT_SIZE: usize = 4096;
+
+/// How sure [`detect`] is about its answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    /// A fixed signature matched
+    High,
+    /// A weak signature or a heuristic matched, e.g. decodable text
+    Medium,
+    /// Nothing matched
+    Low,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        })
+    }
+}
+
+/// Best guess at a file's format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    /// Human-readable format name, e.g. "PNG image"
+    pub format: String,
+    pub mime: &'static str,
+    pub confidence: Confidence,
+    /// Extra facts such as line endings or a script's interpreter
+    pub detail: Option<String>,
+}
+
+impl Detection {
+    fn new(format: impl Into<String>, mime: &'static str, confidence: Confidence) -> Self {
+        Self {
+            format: format.into(),
+            mime,
+            confidence,
+            detail: None,
+        }
+    }
+
+    fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// A fixed byte sequence at a fixed offset.
+struct Signature {
+    offset: usize,
+    magic: &'static [u8],
+    format: &'static str,
+    mime: &'static str,
+}
+
+const fn sig(
+    offset: usize,
+    magic: &'static [u8],
+    format: &'static str,
+    mime: &'static str,
+) -> Signature {
+    Signature {
+        offset,
+        magic,
+        format,
+        mime,
+    }
+}
+
+/// Signatures checked in order; the first match wins.
+const SIGNATURES: &[Signature] = &[
+    sig(0, b"\x7fELF", "ELF executable", "application/x-executable"),
+    sig(
+        0,
+        b"\xfe\xed\xfa\xce",
+        "Mach-O binary (32-bit)",
+        "application/x-mach-binary",
+    ),
+    sig(
+        0,
+        b"\xce\xfa\xed\xfe",
+        "Mach-O binary (32-bit)",
+        "application/x-mach-binary",
+    ),
+    sig(
+        0,
+        b"\xfe\xed\xfa\xcf",
+        "Mach-O binary (64-bit)",
+        "application/x-mach-binary",
+    ),
+    sig(
+        0,
+        b"\xcf\xfa\xed\xfe",
+        "Mach-O binary (64-bit)",
+        "application/x-mach-binary",
+    ),
+    sig(0, b"\x89PNG\r\n\x1a\n", "PNG image", "image/png"),
+    sig(0, b"\xff\xd8\xff", "JPEG image", "image/jpeg"),
+    sig(0, b"GIF87a", "GIF image", "image/gif"),
+    sig(0, b"GIF89a", "GIF image", "image/gif"),
+    sig(0, b"%PDF-", "PDF document", "application/pdf"),
+    sig(0, b"PK\x03\x04", "ZIP archive", "application/zip"),
+    sig(0, b"PK\x05\x06", "ZIP archive (empty)", "application/zip"),
+    sig(0, b"\x1f\x8b", "gzip compressed data", "application/gzip"),
+    sig(257, b"ustar", "tar archive", "application/x-tar"),
+    sig(
+        0,
+        b"SQLite format 3\0",
+        "SQLite database",
+        "application/vnd.sqlite3",
+    ),
+];
+
+/// Identifies a file from its first bytes (ideally [`DETECT_SIZE`] of them).
+pub fn detect(head: &[u8]) -> Detection {
+    if let Some(s) = SIGNATURES
+        .iter()
+        .find(|s| head.get(s.offset..s.offset + s.magic.len()) == Some(s.magic))
+    {
+        return Detection::new(s.format, s.mime, Confidence::High);
+    }
+    if head.starts_with(b"\xca\xfe\xba\xbe") {
+        // Shared by Mach-O universal binaries and Java class files
+        return Detection::new(
+            "Mach-O universal binary or Java class file",
+            "application/octet-stream",
+            Confidence::Medium,
+        );
+    }
+    if head.starts_with(b"MZ") {
+        return detect_pe(head);
+    }
+    if let Some(rest) = head.strip_prefix(b"\xef\xbb\xbf") {
+        return detect_text(rest, "UTF-8 text with BOM");
+    }
+    if head.starts_with(b"\xff\xfe") || head.starts_with(b"\xfe\xff") {
+        let order = if head[0] == 0xff { "LE" } else { "BE" };
+        return Detection::new(
+            format!("UTF-16 {order} text"),
+            "text/plain",
+            Confidence::High,
+        );
+    }
+    if head.starts_with(b"#!") && !head.contains(&0) {
+        return detect_script(head);
+    }
+    if !head.contains(&0) && is_utf8_prefix(head) {
+        let kind = if head.is_ascii() {
+            "ASCII text"
+        } else {
+            "UTF-8 text"
+        };
+        return detect_text(head, kind);
+    }
+    Detection::new("data", "application/octet-stream", Confidence::Low)
+}
+
+/// DOS `MZ` executables; a `PE\0\0` header at the offset stored at 0x3C makes
+/// it a Windows PE image.
+fn detect_pe(head: &[u8]) -> Detection {
+    let pe_offset = head
+        .get(0x3c..0x40)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+    let is_pe = pe_offset.and_then(|o| head.get(o..o + 4)) == Some(b"PE\0\0");
+    if is_pe {
+        Detection::new(
+            "PE executable (Windows)",
+            "application/vnd.microsoft.portable-executable",
+            Confidence::High,
+        )
+    } else {
+        Detection::new(
+            "DOS/PE executable",
+            "application/vnd.microsoft.portable-executable",
+            Confidence::Medium,
+        )
+    }
+}
+
+/// A `#!` script: reports the interpreter, looking through `env`.
+fn detect_script(head: &[u8]) -> Detection {
+    let first_line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(&first_line[2..]);
+    let mut words = line.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut interpreter = program.rsplit('/').next().unwrap_or_default();
+    if interpreter == "env" {
+        interpreter = words.find(|w| !w.starts_with('-')).unwrap_or_default();
+    }
+    let mime = match interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => "text/x-shellscript",
+        "python" => "text/x-python",
+        "perl" => "text/x-perl",
+        "ruby" => "text/x-ruby",
+        "node" | "deno" | "bun" => "text/javascript",
+        _ => "text/plain",
+    };
+    Detection::new("script", mime, Confidence::High).with_detail(format!(
+        "interpreter: {interpreter}, line endings: {}",
+        line_endings(head)
+    ))
+}
+
+fn detect_text(body: &[u8], kind: &str) -> Detection {
+    Detection::new(kind, "text/plain", Confidence::Medium)
+        .with_detail(format!("line endings: {}", line_endings(body)))
+}
+
+/// Valid UTF-8, allowing a character cut off at the end of the buffer.
+fn is_utf8_prefix(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Names the newline style: LF, CRLF, CR, mixed, or none.
+fn line_endings(text: &[u8]) -> &'static str {
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'\r' if text.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => "none",
+        (true, false, false) => "LF",
+        (false, true, false) => "CRLF",
+        (false, false, true) => "CR",
+        _ => "mixed",
+    }
+}
+
+/// Formats up to the first 16 bytes as space-separated lowercase hex.
+pub fn hex_prefix(head: &[u8]) -> String {
+    head.iter()
+        .take(16)
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_head() -> Vec<u8> {
+        let mut head = vec![0u8; 512];
+        head[..4].copy_from_slice(b"file");
+        head[257..263].copy_from_slice(b"ustar\0");
+        head
+    }
+
+    fn pe_head() -> Vec<u8> {
+        let mut head = vec![0u8; 256];
+        head[..2].copy_from_slice(b"MZ");
+        head[0x3c] = 0x80;
+        head[0x80..0x84].copy_from_slice(b"PE\0\0");
+        head
+    }
+
+    #[test]
+    fn detects_signatures() {
+        let cases: Vec<(Vec<u8>, &str, &str, Confidence)> = vec![
+            (
+                b"\x7fELF\x02\x01\x01".to_vec(),
+                "ELF executable",
+                "application/x-executable",
+                Confidence::High,
+            ),
+            (
+                b"\xcf\xfa\xed\xfe\x07".to_vec(),
+                "Mach-O binary (64-bit)",
+                "application/x-mach-binary",
+                Confidence::High,
+            ),
+            (
+                b"\xca\xfe\xba\xbe\0\0".to_vec(),
+                "Mach-O universal binary or Java class file",
+                "application/octet-stream",
+                Confidence::Medium,
+            ),
+            (
+                pe_head(),
+                "PE executable (Windows)",
+                "application/vnd.microsoft.portable-executable",
+                Confidence::High,
+            ),
+            (
+                b"MZ\x90\0".to_vec(),
+                "DOS/PE executable",
+                "application/vnd.microsoft.portable-executable",
+                Confidence::Medium,
+            ),
+            (
+                b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec(),
+                "PNG image",
+                "image/png",
+                Confidence::High,
+            ),
+            (
+                b"\xff\xd8\xff\xe0\0\x10JFIF".to_vec(),
+                "JPEG image",
+                "image/jpeg",
+                Confidence::High,
+            ),
+            (
+                b"GIF89a\x01\0".to_vec(),
+                "GIF image",
+                "image/gif",
+                Confidence::High,
+            ),
+            (
+                b"%PDF-1.7\n".to_vec(),
+                "PDF document",
+                "application/pdf",
+                Confidence::High,
+            ),
+            (
+                b"PK\x03\x04\x14\0".to_vec(),
+                "ZIP archive",
+                "application/zip",
+                Confidence::High,
+            ),
+            (
+                b"\x1f\x8b\x08\0".to_vec(),
+                "gzip compressed data",
+                "application/gzip",
+                Confidence::High,
+            ),
+            (
+                tar_head(),
+                "tar archive",
+                "application/x-tar",
+                Confidence::High,
+            ),
+            (
+                b"SQLite format 3\0\x10\0".to_vec(),
+                "SQLite database",
+                "application/vnd.sqlite3",
+                Confidence::High,
+            ),
+            (
+                b"\xff\xfeh\0i\0".to_vec(),
+                "UTF-16 LE text",
+                "text/plain",
+                Confidence::High,
+            ),
+            (
+                b"\0\x01\x02\x03".to_vec(),
+                "data",
+                "application/octet-stream",
+                Confidence::Low,
+            ),
+            (Vec::new(), "ASCII text", "text/plain", Confidence::Medium),
+        ];
+        for (head, format, mime, confidence) in cases {
+            let detection = detect(&head);
+            assert_eq!(detection.format, format, "{}", hex_prefix(&head));
+            assert_eq!(detection.mime, mime, "{format}");
+            assert_eq!(detection.confidence, confidence, "{format}");
+        }
+    }
+
+    #[test]
+    fn detects_text_and_line_endings() {
+        let cases: &[(&[u8], &str, &str)] = &[
+            (b"a\nb\n", "ASCII text", "line endings: LF"),
+            (b"a\r\nb\r\n", "ASCII text", "line endings: CRLF"),
+            (b"a\rb\r", "ASCII text", "line endings: CR"),
+            (b"a\r\nb\n", "ASCII text", "line endings: mixed"),
+            (b"one line", "ASCII text", "line endings: none"),
+            ("h\u{e9}llo\n".as_bytes(), "UTF-8 text", "line endings: LF"),
+            (
+                b"\xef\xbb\xbfhi\r\n",
+                "UTF-8 text with BOM",
+                "line endings: CRLF",
+            ),
+            // A multi-byte character cut off by the read buffer is still text
+            (b"caf\xc3", "UTF-8 text", "line endings: none"),
+        ];
+        for (head, format, detail) in cases {
+            let detection = detect(head);
+            assert_eq!(detection.format, *format, "{head:?}");
+            assert_eq!(detection.detail.as_deref(), Some(*detail), "{head:?}");
+        }
+        assert_eq!(detect(b"caf\xc3(").format, "data");
+    }
+
+    #[test]
+    fn detects_shebang_interpreter() {
+        let cases: &[(&[u8], &str, &str)] = &[
+            (b"#!/bin/sh\necho hi\n", "sh", "text/x-shellscript"),
+            (b"#!/usr/bin/env python3\n", "python3", "text/x-python"),
+            (
+                b"#!/usr/bin/env -S node --harmony\n",
+                "node",
+                "text/javascript",
+            ),
+            (b"#! /usr/bin/perl -w\r\n", "perl", "text/x-perl"),
+            (b"#!/opt/custom/run\n", "run", "text/plain"),
+        ];
+        for (head, interpreter, mime) in cases {
+            let detection = detect(head);
+            assert_eq!(detection.format, "script");
+            assert_eq!(detection.mime, *mime, "{interpreter}");
+            let detail = detection.detail.unwrap();
+            assert!(
+                detail.starts_with(&format!("interpreter: {interpreter},")),
+                "{detail}"
+            );
+        }
+    }
+
+    #[test]
+    fn hex_prefix_shows_at_most_16_bytes() {
+        assert_eq!(hex_prefix(b"\x89PNG"), "89 50 4e 47");
+        assert_eq!(hex_prefix(&[0xab; 20]).split(' ').count(), 16);
+        assert_eq!(hex_prefix(b""), "");
+    }
+}
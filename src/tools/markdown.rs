@@ -0,0 +1,391 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::util::is_binary;
+
+/// Parameters for the outline_markdown tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct OutlineMarkdownParams {
+    /// Absolute path to the markdown file
+    path: String,
+    /// Deepest heading level to include, 1-6 (default: 6)
+    max_depth: Option<u8>,
+}
+
+/// A heading found by [`outline`].
+#[derive(Debug, PartialEq)]
+struct Heading {
+    level: u8,
+    text: String,
+    /// 1-based line the heading starts on
+    line: usize,
+}
+
+/// A front-matter block found by [`outline`].
+#[derive(Debug, PartialEq)]
+struct FrontMatter {
+    format: &'static str,
+    /// The block verbatim, delimiters included
+    text: String,
+    /// 1-based line of the closing delimiter
+    end_line: usize,
+}
+
+/// The structure of a markdown document.
+#[derive(Debug, PartialEq)]
+struct Outline {
+    front_matter: Option<FrontMatter>,
+    headings: Vec<Heading>,
+}
+
+/// An open fenced code block: its fence character and length.
+struct Fence {
+    marker: char,
+    len: usize,
+}
+
+/// Strips up to three leading spaces, or returns `None` for lines indented
+/// four or more, which markdown treats as code.
+fn block_content(line: &str) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    (indent < 4).then(|| &line[indent..])
+}
+
+/// A fence opening or closing line: three or more backticks or tildes.
+fn fence_of(content: &str) -> Option<Fence> {
+    let marker = content.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = content.chars().take_while(|c| *c == marker).count();
+    // Backtick fences cannot have backticks in their info string
+    let info = &content[len..];
+    (len >= 3 && !(marker == '`' && info.contains('`'))).then_some(Fence { marker, len })
+}
+
+/// An ATX heading such as `## Install ##`: the level and text.
+fn atx_heading(content: &str) -> Option<(u8, String)> {
+    let level = content.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &content[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let mut text = rest.trim();
+    // An optional closing sequence of #s, separated by a space
+    let without_hashes = text.trim_end_matches('#');
+    if without_hashes.is_empty() || without_hashes.ends_with([' ', '\t']) {
+        text = without_hashes.trim_end();
+    }
+    Some((level as u8, text.to_string()))
+}
+
+/// A setext underline: the level it gives the paragraph above it.
+fn setext_level(content: &str) -> Option<u8> {
+    let underline = content.trim_end();
+    let marker = underline.chars().next()?;
+    if !underline.chars().all(|c| c == marker) {
+        return None;
+    }
+    match marker {
+        '=' => Some(1),
+        '-' => Some(2),
+        _ => None,
+    }
+}
+
+/// A YAML (`---`) or TOML (`+++`) block opening the document.
+fn front_matter(lines: &[&str]) -> Option<FrontMatter> {
+    let (format, closers): (_, &[&str]) = match lines.first()?.trim_end() {
+        "---" => ("yaml", &["---", "..."]),
+        "+++" => ("toml", &["+++"]),
+        _ => return None,
+    };
+    let end = lines
+        .iter()
+        .skip(1)
+        .position(|line| closers.contains(&line.trim_end()))?
+        + 1;
+    Some(FrontMatter {
+        format,
+        text: lines[..=end].join("\n"),
+        end_line: end + 1,
+    })
+}
+
+/// Extracts the front matter and headings of `text`. Headings inside fenced
+/// or indented code blocks are ignored, and setext headings take the text of
+/// the whole paragraph they underline.
+fn outline(text: &str) -> Outline {
+    let lines: Vec<&str> = text.lines().collect();
+    let front_matter = front_matter(&lines);
+    let start = front_matter.as_ref().map_or(0, |fm| fm.end_line);
+
+    let mut headings = Vec::new();
+    let mut fence: Option<Fence> = None;
+    // Lines of the paragraph being read: (line number, text)
+    let mut paragraph: Vec<(usize, &str)> = Vec::new();
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let number = index + 1;
+        if let Some(open) = &fence {
+            let closes = block_content(line).and_then(fence_of).is_some_and(|f| {
+                f.marker == open.marker
+                    && f.len >= open.len
+                    && line.trim().chars().all(|c| c == open.marker)
+            });
+            if closes {
+                fence = None;
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            paragraph.clear();
+            continue;
+        }
+        let Some(content) = block_content(line) else {
+            // Indented code, unless it continues a paragraph
+            if !paragraph.is_empty() {
+                paragraph.push((number, line.trim()));
+            }
+            continue;
+        };
+        if let Some(open) = fence_of(content) {
+            fence = Some(open);
+            paragraph.clear();
+            continue;
+        }
+        if let Some((level, text)) = atx_heading(content) {
+            headings.push(Heading {
+                level,
+                text,
+                line: number,
+            });
+            paragraph.clear();
+            continue;
+        }
+        if !paragraph.is_empty()
+            && let Some(level) = setext_level(content)
+        {
+            let text = paragraph
+                .iter()
+                .map(|(_, text)| *text)
+                .collect::<Vec<_>>()
+                .join(" ");
+            headings.push(Heading {
+                level,
+                text,
+                line: paragraph[0].0,
+            });
+            paragraph.clear();
+            continue;
+        }
+        if setext_level(content) == Some(2) {
+            // A lone --- is a thematic break, not paragraph text
+            continue;
+        }
+        paragraph.push((number, content.trim()));
+    }
+    Outline {
+        front_matter,
+        headings,
+    }
+}
+
+/// Renders the outline as a list indented by heading level.
+fn format_outline(outline: &Outline, max_depth: u8) -> String {
+    let mut out = String::new();
+    if let Some(fm) = &outline.front_matter {
+        out.push_str(&format!(
+            "Front matter ({}, lines 1-{}):\n{}\n\n",
+            fm.format, fm.end_line, fm.text
+        ));
+    }
+    let shown: Vec<&Heading> = outline
+        .headings
+        .iter()
+        .filter(|h| h.level <= max_depth)
+        .collect();
+    let hidden = outline.headings.len() - shown.len();
+    if shown.is_empty() {
+        out.push_str("Headings: none");
+    } else {
+        let top = shown.iter().map(|h| h.level).min().unwrap_or(1);
+        out.push_str(&format!("Headings ({}):\n", shown.len()));
+        for heading in &shown {
+            out.push_str(&format!(
+                "{}- {} {} (line {})\n",
+                "  ".repeat(usize::from(heading.level - top)),
+                "#".repeat(usize::from(heading.level)),
+                heading.text,
+                heading.line
+            ));
+        }
+        out.pop();
+    }
+    if hidden > 0 {
+        let noun = if hidden == 1 { "heading" } else { "headings" };
+        out.push_str(&format!(
+            "\n({hidden} {noun} deeper than level {max_depth} not shown)"
+        ));
+    }
+    out
+}
+
+#[rmcp::tool_router(router = "markdown_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Lists the front matter and heading hierarchy of a markdown file.
+    #[rmcp::tool(
+        name = "outline_markdown",
+        description = "Outlines a markdown file without reading it all: the YAML (---) or TOML (+++) front-matter block verbatim, then every heading (ATX # and setext underlined) as a list indented by level, each with its line number. Headings inside code blocks are ignored. Use max_depth to keep only the top levels, and read_file with offset = line - 1 to jump to a section.",
+        annotations(
+            title = "Outline Markdown",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn outline_markdown(
+        &self,
+        Parameters(params): Parameters<OutlineMarkdownParams>,
+    ) -> Result<String, ToolError> {
+        let max_depth = params.max_depth.unwrap_or(6);
+        if !(1..=6).contains(&max_depth) {
+            return Err(format!("max_depth must be between 1 and 6, got {max_depth}").into());
+        }
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max = self.config.max_read_size as u64;
+        if size > max {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max,
+            }
+            .into());
+        }
+        let content = tokio::fs::read(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+        if is_binary(&content) {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        }
+        let text = String::from_utf8_lossy(&content);
+        Ok(format_outline(&outline(&text), max_depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn levels_and_lines(text: &str) -> Vec<(u8, String, usize)> {
+        outline(text)
+            .headings
+            .into_iter()
+            .map(|h| (h.level, h.text, h.line))
+            .collect()
+    }
+
+    #[test]
+    fn finds_atx_and_setext_headings() {
+        let text = "# Title #\n\nIntro\ntext\n\nUsage\n=====\n\nTwo line\nheading\n---\n\n###### Deep\n#NotAHeading\n####### Seven\n";
+        assert_eq!(
+            levels_and_lines(text),
+            [
+                (1, "Title".to_string(), 1),
+                (1, "Usage".to_string(), 6),
+                (2, "Two line heading".to_string(), 9),
+                (6, "Deep".to_string(), 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_headings_in_code() {
+        let text = "# Real\n\n```bash\n# comment\n```\n\n~~~~\n## inside\n~~~\nstill inside\n~~~~\n\n    # indented code\n\n---\n\n## After\n";
+        assert_eq!(
+            levels_and_lines(text),
+            [(1, "Real".to_string(), 1), (2, "After".to_string(), 17)]
+        );
+    }
+
+    #[test]
+    fn extracts_front_matter() {
+        let cases = [
+            (
+                "---\ntitle: Hi\n---\n# Body\n",
+                Some(("yaml", "---\ntitle: Hi\n---", 3)),
+            ),
+            (
+                "+++\ntitle = \"Hi\"\n+++\n# Body\n",
+                Some(("toml", "+++\ntitle = \"Hi\"\n+++", 3)),
+            ),
+            ("---\nunclosed: true\n# Body\n", None),
+            ("# Body\n---\n", None),
+        ];
+        for (text, expected) in cases {
+            let found = outline(text).front_matter;
+            assert_eq!(
+                found
+                    .as_ref()
+                    .map(|fm| (fm.format, fm.text.as_str(), fm.end_line)),
+                expected,
+                "{text:?}"
+            );
+        }
+        // The closing --- of front matter is not a setext underline
+        assert_eq!(
+            levels_and_lines("---\na: 1\n---\n# Body\n"),
+            [(1, "Body".to_string(), 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn outline_markdown_indents_by_level() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("README.md");
+        std::fs::write(
+            &file,
+            "---\ntitle: Guide\n---\n# Guide\n## Install\n### From source\n## Usage\n",
+        )
+        .unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+
+        let output = service
+            .outline_markdown(Parameters(OutlineMarkdownParams {
+                path: file.to_string_lossy().to_string(),
+                max_depth: Some(2),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "Front matter (yaml, lines 1-3):\n---\ntitle: Guide\n---\n\n\
+             Headings (3):\n- # Guide (line 4)\n  - ## Install (line 5)\n  - ## Usage (line 7)\n\
+             (1 heading deeper than level 2 not shown)"
+        );
+
+        let err = service
+            .outline_markdown(Parameters(OutlineMarkdownParams {
+                path: file.to_string_lossy().to_string(),
+                max_depth: Some(0),
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("max_depth must be"), "{err}");
+    }
+}
@@ -1,7 +1,26 @@
+pub mod archive;
+pub mod checksum;
+pub mod code;
+pub mod compare;
+pub mod compress;
+pub mod count;
 pub mod destructive;
+pub mod gitignore;
+pub mod health;
+pub mod history;
+pub mod image;
 pub mod info;
+pub mod line_endings;
 pub mod list;
+pub mod magic;
+pub mod markdown;
 pub mod read;
 pub mod search;
+pub mod stats;
+pub mod structured;
+pub mod syntax;
+pub mod table;
+pub mod trash;
 pub mod util;
+pub mod watch;
 pub mod write;
@@ -1,13 +1,14 @@
 use crate::FilesystemService;
-use crate::error::{FsError, io_error_message};
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use rmcp::handler::server::wrapper::Parameters;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
 
-use super::util::format_size;
-
-/// Number of bytes to check for null bytes when detecting binary files.
-const BINARY_CHECK_SIZE: usize = 8192;
+use super::util::{BINARY_CHECK_SIZE, format_size, is_binary, spawn_blocking};
 
 /// Parameters for the read_file tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -20,8 +21,63 @@ struct ReadFileParams {
     /// Maximum number of lines to read
     #[schemars(description = "Maximum number of lines to read")]
     limit: Option<u64>,
+    /// Byte offset (0-based) to start reading from; cannot be combined with offset/limit
+    #[schemars(
+        description = "Byte offset (0-based) to start reading from; cannot be combined with offset/limit"
+    )]
+    byte_offset: Option<u64>,
+    /// Maximum number of bytes to read (default: to the end of the file, within the read limit)
+    #[schemars(
+        description = "Maximum number of bytes to read (default: to the end of the file, within the read limit)"
+    )]
+    byte_length: Option<u64>,
+    /// Read the file even if it looks binary, converting invalid UTF-8 lossily (default: false)
+    #[schemars(
+        description = "Read the file even if it looks binary, converting invalid UTF-8 lossily (default: false)"
+    )]
+    force_text: Option<bool>,
+}
+
+/// Warning shown above content read with `force_text` from a file that looks binary.
+const FORCED_TEXT_WARNING: &str =
+    "(Warning: file contains binary data, showing lossy UTF-8 conversion)";
+
+/// Parameters for the read_file_as_hex tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ReadFileAsHexParams {
+    /// Absolute path to the file to dump
+    path: String,
+    /// Byte offset (0-based) to start from (default: 0)
+    #[schemars(description = "Byte offset (0-based) to start from (default: 0)")]
+    offset: Option<u64>,
+    /// Maximum number of bytes to dump (default: to the end of the file, within the read limit)
+    #[schemars(
+        description = "Maximum number of bytes to dump (default: to the end of the file, within the read limit)"
+    )]
+    length: Option<u64>,
 }
 
+/// Parameters for the read_file_base64 tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ReadFileBase64Params {
+    /// Absolute path to the file to read
+    path: String,
+    /// Byte offset (0-based) to start from (default: 0)
+    #[schemars(description = "Byte offset (0-based) to start from (default: 0)")]
+    offset: Option<u64>,
+    /// Maximum number of bytes to encode (default: as many as fit in one response)
+    #[schemars(
+        description = "Maximum number of bytes to encode (default: as many as fit in one response)"
+    )]
+    length: Option<u64>,
+}
+
+/// Output bytes set aside for read_file_base64's header line, on top of the path.
+const BASE64_HEADER_RESERVE: usize = 160;
+
+/// Bytes shown on each line of a read_file_as_hex dump.
+const HEX_BYTES_PER_LINE: usize = 16;
+
 /// Parameters for the read_multiple_files tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct ReadMultipleFilesParams {
@@ -29,164 +85,635 @@ struct ReadMultipleFilesParams {
     paths: Vec<String>,
 }
 
+/// Lines of a file to read: up to `limit` lines starting at the 0-based `offset`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadRange {
+    pub offset: u64,
+    pub limit: Option<u64>,
+}
+
+/// Lines read from a text file by read_file.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ReadResult {
+    /// Canonical path of the file
+    pub path: String,
+    /// File size in bytes
+    pub size: u64,
+    /// 0-based index of the first line in `lines`
+    pub offset: usize,
+    /// Requested lines, without line terminators
+    pub lines: Vec<String>,
+    /// Lines in the whole file; `None` when a ranged read of a file over the
+    /// read limit stopped after the range instead of counting to the end
+    pub total_lines: Option<usize>,
+    /// Whether the file looks binary and was read anyway with `force_text`
+    pub binary: bool,
+}
+
+impl ReadResult {
+    /// Formats the result the way the read_file tool returns it.
+    pub fn to_text(&self) -> String {
+        if self.total_lines == Some(0) {
+            return format!("File: {} (0 B)\n\n(empty file)", self.path);
+        }
+        let end = self.offset + self.lines.len();
+        let total = match self.total_lines {
+            Some(total) => format!("{total} total"),
+            None => format!("more than {end} total"),
+        };
+        format!(
+            "File: {} (Lines {}-{} of {}, {})\n{}\n{}",
+            self.path,
+            self.offset + 1,
+            end,
+            total,
+            format_size(self.size),
+            forced_text_note(self.binary),
+            self.lines.join("\n")
+        )
+    }
+}
+
+/// The line between a read_file header and the content: the binary data
+/// warning when the file was forced to text, otherwise empty.
+fn forced_text_note(binary: bool) -> String {
+    if binary {
+        format!("{FORCED_TEXT_WARNING}\n")
+    } else {
+        String::new()
+    }
+}
+
+/// Lines selected by [`read_line_range`].
+struct LineRange {
+    selected: Vec<String>,
+    /// Lines read from the start of the file, including those before `offset`
+    lines_seen: usize,
+    /// Whether the whole file was read, making `lines_seen` the exact total
+    reached_eof: bool,
+}
+
+/// Reads up to `max` bytes from the start of `file`.
+async fn read_head(file: &mut tokio::fs::File, max: usize) -> std::io::Result<Vec<u8>> {
+    let mut head = vec![0; max];
+    let mut filled = 0;
+    while filled < max {
+        let n = file.read(&mut head[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    head.truncate(filled);
+    Ok(head)
+}
+
+/// Reads up to `length` bytes of `file` starting at byte `offset`.
+async fn read_at(file: &mut tokio::fs::File, offset: u64, length: u64) -> std::io::Result<Vec<u8>> {
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut content = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut content).await?;
+    Ok(content)
+}
+
+/// Formats `bytes` like `xxd`: each line holds the file offset (counting from
+/// `start`), 16 bytes as hex in pairs, and the bytes as ASCII with `.` for
+/// anything unprintable.
+fn hex_dump(bytes: &[u8], start: u64) -> String {
+    let mut out = String::new();
+    for (i, line) in bytes.chunks(HEX_BYTES_PER_LINE).enumerate() {
+        let mut hex = String::new();
+        for (j, byte) in line.iter().enumerate() {
+            if j > 0 && j % 2 == 0 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{byte:02x}"));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        // A full line of hex is 16 digit pairs plus 7 spaces between groups
+        out.push_str(&format!(
+            "{:08x}: {hex:<39}  {ascii}\n",
+            start + (i * HEX_BYTES_PER_LINE) as u64
+        ));
+    }
+    out
+}
+
+/// Streams lines `offset..offset + limit` from `reader`, splitting like `str::lines`.
+///
+/// With `count_rest` the remaining lines are counted so `lines_seen` is the file's
+/// total; otherwise reading stops one line past the range.
+async fn read_line_range<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    offset: usize,
+    limit: Option<usize>,
+    count_rest: bool,
+) -> std::io::Result<LineRange> {
+    let end = limit.map(|l| offset.saturating_add(l));
+    let mut selected = Vec::new();
+    let mut lines_seen = 0;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).await? == 0 {
+            return Ok(LineRange {
+                selected,
+                lines_seen,
+                reached_eof: true,
+            });
+        }
+        if end.is_some_and(|end| lines_seen >= end) && !count_rest {
+            // One line past the range proves more follow; stop here
+            return Ok(LineRange {
+                selected,
+                lines_seen,
+                reached_eof: false,
+            });
+        }
+        if lines_seen >= offset && end.is_none_or(|end| lines_seen < end) {
+            let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            selected.push(String::from_utf8_lossy(line).into_owned());
+        }
+        lines_seen += 1;
+    }
+}
+
 #[rmcp::tool_router(router = "read_tools_router", vis = "pub(crate)")]
 impl FilesystemService {
     /// Reads a file and returns its contents, optionally reading a specific line range.
     #[rmcp::tool(
         name = "read_file",
-        description = "Reads a file and returns its contents. Supports reading specific line ranges using offset (0-based) and limit parameters. Returns a header with file path and line information.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        description = "Reads a file and returns its contents. Supports reading specific line ranges using offset (0-based) and limit parameters, or byte ranges using byte_offset (0-based) and byte_length, which read only that region and suit very large files such as logs. Line and byte ranges cannot be combined. Returns a header with file path and line or byte range information. Files that look binary are refused unless force_text is true, which shows them with invalid UTF-8 replaced and a warning.",
+        annotations(
+            title = "Read File",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn read_file(
         &self,
         Parameters(params): Parameters<ReadFileParams>,
-    ) -> Result<String, String> {
-        let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_file(path)
-            .map_err(|e| e.to_string())?;
-
-        let metadata = tokio::fs::metadata(&canonical)
+    ) -> Result<String, ToolError> {
+        let by_lines = params.offset.is_some() || params.limit.is_some();
+        let force_text = params.force_text.unwrap_or(false);
+        if params.byte_offset.is_some() || params.byte_length.is_some() {
+            if by_lines {
+                return Err(
+                    "Pass either offset/limit (lines) or byte_offset/byte_length (bytes), not both"
+                        .into(),
+                );
+            }
+            return self
+                .read_file_bytes(
+                    &params.path,
+                    params.byte_offset.unwrap_or(0),
+                    params.byte_length,
+                    force_text,
+                )
+                .await;
+        }
+        let range = by_lines.then(|| ReadRange {
+            offset: params.offset.unwrap_or(0),
+            limit: params.limit,
+        });
+        self.read_file_lines(&params.path, range, force_text)
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
-        let file_size = metadata.len();
+            .map(|result| result.to_text())
+    }
 
-        let has_range = params.offset.is_some() || params.limit.is_some();
+    /// Reads multiple files and returns their contents with clear separators.
+    #[rmcp::tool(
+        name = "read_multiple_files",
+        description = "Reads multiple files and returns their contents with clear separators between each file. If any file fails to read, the error is included inline and remaining files are still processed.",
+        annotations(
+            title = "Read Multiple Files",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn read_multiple_files(
+        &self,
+        Parameters(params): Parameters<ReadMultipleFilesParams>,
+    ) -> Result<String, ToolError> {
+        let mut sections = Vec::new();
 
-        // Check file size limit (relaxed when offset/limit narrows the read)
-        if !has_range && file_size > self.config.max_read_size as u64 {
-            return Err(FsError::FileTooLarge {
-                path: params.path,
-                size: file_size,
-                max: self.config.max_read_size as u64,
-            }
-            .to_string());
-        }
+        for file_path in &params.paths {
+            let path = std::path::Path::new(file_path);
 
-        let content = tokio::fs::read(&canonical)
-            .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            let result: Result<String, ToolError> = async {
+                let canonical = self.security.validate_file(path)?;
+
+                let max_read_size = self.config.max_read_size as u64;
+                let path = file_path.clone();
+                let (section, bytes_read) =
+                    spawn_blocking(move || read_section_sync(&canonical, &path, max_read_size))
+                        .await
+                        .map_err(|e| e.to_string())??;
+                stats::record_read(bytes_read);
+                Ok(section)
+            }
+            .await;
 
-        // Detect binary files (null bytes in first 8KB)
-        let check_len = content.len().min(BINARY_CHECK_SIZE);
-        if content[..check_len].contains(&0) {
-            return Err(FsError::BinaryFile { path: params.path }.to_string());
+            match result {
+                Ok(section) => sections.push(section),
+                Err(err) => sections.push(format!("=== {file_path} ===\nError: {err}")),
+            }
         }
 
-        let text = String::from_utf8_lossy(&content);
-        let lines: Vec<&str> = text.lines().collect();
-        let total_lines = lines.len();
-
-        let size_str = format_size(file_size);
+        Ok(sections.join("\n\n"))
+    }
 
-        // Handle empty files
-        if total_lines == 0 {
+    /// Dumps a file's raw bytes as hex and ASCII.
+    #[rmcp::tool(
+        name = "read_file_as_hex",
+        description = "Reads the raw bytes of any file, including binary files that read_file refuses, and returns a hex dump in xxd style: '00000000: 7f45 4c46 0201 0100 ...  .ELF....' with 16 bytes per line. Use offset (0-based) and length to inspect a slice, such as a header or magic bytes. At most the read limit is dumped per call; the output says where to continue.",
+        annotations(
+            title = "Read File as Hex",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn read_file_as_hex(
+        &self,
+        Parameters(params): Parameters<ReadFileAsHexParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self
+            .security
+            .validate_file(std::path::Path::new(&params.path))?;
+        let offset = params.offset.unwrap_or(0);
+        if params.length == Some(0) {
+            return Err("length must be at least 1".into());
+        }
+        let mut file = tokio::fs::File::open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        if size == 0 {
             return Ok(format!(
                 "File: {} (0 B)\n\n(empty file)",
                 canonical.display()
             ));
         }
-
-        let offset = params.offset.unwrap_or(0) as usize;
-        let limit = params.limit.map(|l| l as usize);
-
-        if offset >= total_lines {
-            return Err(format!(
-                "Offset {offset} is beyond end of file ({total_lines} lines)"
-            ));
+        if offset >= size {
+            return Err(format!("Offset {offset} is beyond end of file ({size} bytes)").into());
         }
 
-        let end = match limit {
-            Some(l) => (offset + l).min(total_lines),
-            None => total_lines,
-        };
-
-        let selected = &lines[offset..end];
+        let max = self.config.max_read_size as u64;
+        let wanted = params.length.unwrap_or(size - offset).min(size - offset);
+        let content = read_at(&mut file, offset, wanted.min(max))
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
 
-        let header = format!(
-            "File: {} (Lines {}-{} of {} total, {})",
+        let read = content.len() as u64;
+        let mut out = format!(
+            "File: {} (Bytes {}-{} of {size} total, {})\n\n{}",
             canonical.display(),
-            offset + 1,
-            end,
-            total_lines,
-            size_str,
+            offset,
+            offset + read - 1,
+            format_size(read),
+            hex_dump(&content, offset)
         );
-
-        Ok(format!("{header}\n\n{}", selected.join("\n")))
+        if read < wanted {
+            out.push_str(&format!(
+                "\n(Stopped at the read limit of {}; pass offset: {} to continue)",
+                format_size(max),
+                offset + read
+            ));
+        }
+        Ok(out)
     }
 
-    /// Reads multiple files and returns their contents with clear separators.
+    /// Reads a file's raw bytes and returns them base64-encoded.
     #[rmcp::tool(
-        name = "read_multiple_files",
-        description = "Reads multiple files and returns their contents with clear separators between each file. If any file fails to read, the error is included inline and remaining files are still processed.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        name = "read_file_base64",
+        description = "Reads the raw bytes of any file, including binary files that read_file refuses, and returns them as standard base64 after a one-line header and a blank line. Use it to transfer binary content; write_file_base64 writes it back. Each call encodes at most as many bytes as fit in the output limit (about 3/4 of --max-output-bytes); when the file is longer, the header gives the byte range and the offset to pass for the rest. Use offset (0-based) and length to pick a slice. Files over the read limit are refused.",
+        annotations(
+            title = "Read File as Base64",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
-    async fn read_multiple_files(
+    async fn read_file_base64(
         &self,
-        Parameters(params): Parameters<ReadMultipleFilesParams>,
-    ) -> Result<String, String> {
-        let mut sections = Vec::new();
+        Parameters(params): Parameters<ReadFileBase64Params>,
+    ) -> Result<String, ToolError> {
+        let canonical = self
+            .security
+            .validate_file(std::path::Path::new(&params.path))?;
+        let offset = params.offset.unwrap_or(0);
+        if params.length == Some(0) {
+            return Err("length must be at least 1".into());
+        }
+        let mut file = tokio::fs::File::open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max = self.config.max_read_size as u64;
+        if size > max {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max,
+            }
+            .into());
+        }
+        if offset > 0 && offset >= size {
+            return Err(format!("Offset {offset} is beyond end of file ({size} bytes)").into());
+        }
 
-        for file_path in &params.paths {
-            let path = std::path::Path::new(file_path);
+        // Whole base64 quanta only, so every page decodes on its own
+        let budget = self
+            .config
+            .max_output_bytes
+            .saturating_sub(canonical.as_os_str().len() + BASE64_HEADER_RESERVE);
+        let per_call = (budget / 4 * 3).max(3) as u64;
+        let remaining = size - offset;
+        let wanted = params.length.unwrap_or(remaining).min(remaining);
+        let content = read_at(&mut file, offset, wanted.min(per_call))
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+
+        let read = content.len() as u64;
+        let header = if offset == 0 && read == size {
+            format!(
+                "File: {} ({}, base64)",
+                canonical.display(),
+                format_size(read)
+            )
+        } else {
+            let mut header = format!(
+                "File: {} (Bytes {}-{} of {size} total, {}, base64",
+                canonical.display(),
+                offset,
+                (offset + read).saturating_sub(1),
+                format_size(read)
+            );
+            if read < wanted {
+                header.push_str(&format!(
+                    "; stopped at the output limit, pass offset: {} to continue",
+                    offset + read
+                ));
+            }
+            header + ")"
+        };
+        Ok(format!("{header}\n\n{}", STANDARD.encode(&content)))
+    }
+}
 
-            let result: Result<String, String> = async {
-                let canonical = self
-                    .security
-                    .validate_file(path)
-                    .map_err(|e| e.to_string())?;
-
-                let metadata = tokio::fs::metadata(&canonical)
-                    .await
-                    .map_err(|e| io_error_message(e, file_path))?;
-                let file_size = metadata.len();
-
-                if file_size > self.config.max_read_size as u64 {
-                    return Err(FsError::FileTooLarge {
-                        path: file_path.clone(),
-                        size: file_size,
-                        max: self.config.max_read_size as u64,
-                    }
-                    .to_string());
-                }
+impl FilesystemService {
+    /// Reads the lines of a text file, all of them or just `range`.
+    ///
+    /// Only the first `BINARY_CHECK_SIZE` bytes are read before the binary check,
+    /// and ranged reads stream lines instead of loading the whole file. With
+    /// `force_text` a file that looks binary is read anyway and marked as such.
+    pub(crate) async fn read_file_lines(
+        &self,
+        requested: &str,
+        range: Option<ReadRange>,
+        force_text: bool,
+    ) -> Result<ReadResult, ToolError> {
+        let canonical = self
+            .security
+            .validate_file(std::path::Path::new(requested))?;
 
-                let content = tokio::fs::read(&canonical)
-                    .await
-                    .map_err(|e| io_error_message(e, file_path))?;
+        let mut file = tokio::fs::File::open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| ToolError::io(e, requested))?
+            .len();
 
-                let check_len = content.len().min(BINARY_CHECK_SIZE);
-                if content[..check_len].contains(&0) {
-                    return Err(FsError::BinaryFile {
-                        path: file_path.clone(),
-                    }
-                    .to_string());
-                }
+        // Detect binary files (null bytes in first 8KB) before reading the rest
+        let head = read_head(&mut file, BINARY_CHECK_SIZE)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+        let binary = is_binary(&head);
+        if binary && !force_text {
+            return Err(FsError::BinaryFile {
+                path: requested.to_string(),
+            }
+            .into());
+        }
+        file.seek(std::io::SeekFrom::Start(0))
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+
+        let over_limit = size > self.config.max_read_size as u64;
+        let path = canonical.display().to_string();
 
-                let text = String::from_utf8_lossy(&content);
-                let total_lines = text.lines().count();
-                let size_str = format_size(file_size);
-
-                Ok(format!(
-                    "=== {} ({} lines, {}) ===\n{}",
-                    canonical.display(),
-                    total_lines,
-                    size_str,
-                    text,
-                ))
+        let Some(range) = range else {
+            if over_limit {
+                return Err(FsError::FileTooLarge {
+                    path: requested.to_string(),
+                    size,
+                    max: self.config.max_read_size as u64,
+                }
+                .into());
             }
-            .await;
+            let mut content = Vec::with_capacity(size as usize);
+            file.read_to_end(&mut content)
+                .await
+                .map_err(|e| ToolError::io(e, requested))?;
+            stats::record_read(content.len() as u64);
+            let lines: Vec<String> = String::from_utf8_lossy(&content)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            return Ok(ReadResult {
+                path,
+                size,
+                offset: 0,
+                total_lines: Some(lines.len()),
+                lines,
+                binary,
+            });
+        };
 
-            match result {
-                Ok(section) => sections.push(section),
-                Err(err) => sections.push(format!("=== {file_path} ===\nError: {err}")),
+        // Stream only the requested lines. Files over the read limit stop just
+        // past the range instead of being scanned to the end for a line count.
+        let offset = range.offset as usize;
+        let limit = range.limit.map(|l| l as usize);
+        let lines = read_line_range(BufReader::new(file), offset, limit, !over_limit)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+        stats::record_read(lines.selected.iter().map(|l| l.len() as u64 + 1).sum());
+
+        if lines.lines_seen == 0 {
+            return Ok(ReadResult {
+                path,
+                size,
+                offset: 0,
+                lines: Vec::new(),
+                total_lines: Some(0),
+                binary,
+            });
+        }
+        if lines.selected.is_empty() {
+            return Err(format!(
+                "Offset {offset} is beyond end of file ({} lines)",
+                lines.lines_seen
+            )
+            .into());
+        }
+        Ok(ReadResult {
+            path,
+            size,
+            offset,
+            lines: lines.selected,
+            total_lines: lines.reached_eof.then_some(lines.lines_seen),
+            binary,
+        })
+    }
+}
+
+impl FilesystemService {
+    /// Reads up to `length` bytes of a text file starting at byte `offset`,
+    /// seeking past the rest so only that region is loaded. The binary check
+    /// applies to the bytes read, unless `force_text` is set.
+    async fn read_file_bytes(
+        &self,
+        requested: &str,
+        offset: u64,
+        length: Option<u64>,
+        force_text: bool,
+    ) -> Result<String, ToolError> {
+        let canonical = self
+            .security
+            .validate_file(std::path::Path::new(requested))?;
+        let mut file = tokio::fs::File::open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| ToolError::io(e, requested))?
+            .len();
+        if length == Some(0) {
+            return Err("byte_length must be at least 1".into());
+        }
+        if size == 0 {
+            return Ok(format!(
+                "File: {} (0 B)\n\n(empty file)",
+                canonical.display()
+            ));
+        }
+        if offset >= size {
+            return Err(
+                format!("Byte offset {offset} is beyond end of file ({size} bytes)").into(),
+            );
+        }
+
+        let max = self.config.max_read_size as u64;
+        let length = length.unwrap_or(size - offset).min(size - offset);
+        if length > max {
+            return Err(format!(
+                "Requested {length} bytes of {}, more than the read limit ({}). Pass a smaller byte_length.",
+                canonical.display(),
+                format_size(max)
+            )
+            .into());
+        }
+
+        let content = read_at(&mut file, offset, length)
+            .await
+            .map_err(|e| ToolError::io(e, requested))?;
+        let binary = is_binary(&content);
+        if binary && !force_text {
+            return Err(FsError::BinaryFile {
+                path: requested.to_string(),
             }
+            .into());
         }
+        stats::record_read(content.len() as u64);
 
-        Ok(sections.join("\n\n"))
+        let read = content.len() as u64;
+        Ok(format!(
+            "File: {} (Bytes {}-{} of {size} total, {})\n{}\n{}",
+            canonical.display(),
+            offset,
+            offset + read - 1,
+            format_size(read),
+            forced_text_note(binary),
+            String::from_utf8_lossy(&content)
+        ))
     }
 }
 
+/// Reads one file for read_multiple_files and formats its section, returning
+/// the bytes read alongside. Runs on the blocking pool.
+fn read_section_sync(
+    canonical: &std::path::Path,
+    file_path: &str,
+    max_read_size: u64,
+) -> Result<(String, u64), ToolError> {
+    let file_size = std::fs::metadata(canonical)
+        .map_err(|e| ToolError::io(e, file_path))?
+        .len();
+
+    if file_size > max_read_size {
+        return Err(FsError::FileTooLarge {
+            path: file_path.to_string(),
+            size: file_size,
+            max: max_read_size,
+        }
+        .into());
+    }
+
+    let content = std::fs::read(canonical).map_err(|e| ToolError::io(e, file_path))?;
+
+    if is_binary(&content) {
+        return Err(FsError::BinaryFile {
+            path: file_path.to_string(),
+        }
+        .into());
+    }
+
+    let text = String::from_utf8_lossy(&content);
+    let total_lines = text.lines().count();
+    let size_str = format_size(file_size);
+
+    Ok((
+        format!(
+            "=== {} ({} lines, {}) ===\n{}",
+            canonical.display(),
+            total_lines,
+            size_str,
+            text,
+        ),
+        content.len() as u64,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,6 +729,7 @@ mod tests {
             allow_destructive: false,
             max_read_size,
             max_depth: 10,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -214,10 +742,12 @@ mod tests {
     fn read_tools_router_contains_read_file() {
         let router = FilesystemService::read_tools_router();
         let tools = router.list_all();
-        assert_eq!(tools.len(), 2);
+        assert_eq!(tools.len(), 4);
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
         assert!(names.contains(&"read_file"));
         assert!(names.contains(&"read_multiple_files"));
+        assert!(names.contains(&"read_file_as_hex"));
+        assert!(names.contains(&"read_file_base64"));
     }
 
     #[tokio::test]
@@ -236,6 +766,9 @@ mod tests {
                 path: dir.path().join("test.txt").to_string_lossy().to_string(),
                 offset: None,
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
@@ -262,6 +795,9 @@ mod tests {
                 path: dir.path().join("test.txt").to_string_lossy().to_string(),
                 offset: Some(1),
                 limit: Some(2),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
@@ -285,6 +821,9 @@ mod tests {
                 path: dir.path().join("test.txt").to_string_lossy().to_string(),
                 offset: None,
                 limit: Some(2),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
@@ -306,11 +845,14 @@ mod tests {
                 path: dir.path().join("big.txt").to_string_lossy().to_string(),
                 offset: None,
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("File too large"));
+        assert!(result.unwrap_err().message.contains("File too large"));
     }
 
     #[tokio::test]
@@ -325,6 +867,9 @@ mod tests {
                 path: dir.path().join("big.txt").to_string_lossy().to_string(),
                 offset: Some(0),
                 limit: Some(1),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
@@ -332,6 +877,92 @@ mod tests {
         assert!(result.unwrap().contains("line1"));
     }
 
+    #[tokio::test]
+    async fn read_file_range_on_large_file_stops_after_range() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let content: String = (0..1000).map(|i| format!("line{i}\n")).collect();
+        std::fs::write(dir.path().join("big.log"), content).unwrap();
+
+        let service = make_service_with_max(vec![canon], 100);
+        let output = service
+            .read_file(Parameters(ReadFileParams {
+                path: dir.path().join("big.log").to_string_lossy().to_string(),
+                offset: Some(2),
+                limit: Some(2),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
+            }))
+            .await
+            .unwrap();
+
+        assert!(
+            output.contains("Lines 3-4 of more than 4 total"),
+            "{output}"
+        );
+        assert!(output.ends_with("line2\nline3"));
+    }
+
+    #[tokio::test]
+    async fn read_file_range_on_large_file_reports_total_at_eof() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let content: String = (0..50).map(|i| format!("line{i}\n")).collect();
+        std::fs::write(dir.path().join("big.log"), content).unwrap();
+
+        let service = make_service_with_max(vec![canon], 100);
+        let output = service
+            .read_file(Parameters(ReadFileParams {
+                path: dir.path().join("big.log").to_string_lossy().to_string(),
+                offset: Some(48),
+                limit: Some(10),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
+            }))
+            .await
+            .unwrap();
+
+        assert!(output.contains("Lines 49-50 of 50 total"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn read_file_binary_detected_with_range_on_large_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let mut content = b"text\n".repeat(100);
+        content.push(0);
+        std::fs::write(dir.path().join("mixed.bin"), content).unwrap();
+
+        let service = make_service_with_max(vec![canon], 100);
+        let result = service
+            .read_file(Parameters(ReadFileParams {
+                path: dir.path().join("mixed.bin").to_string_lossy().to_string(),
+                offset: Some(0),
+                limit: Some(1),
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
+            }))
+            .await;
+
+        assert!(result.unwrap_err().message.contains("Binary file"));
+    }
+
+    #[tokio::test]
+    async fn read_line_range_strips_crlf_and_counts_rest() {
+        let input: &[u8] = b"a\r\nb\r\nc\r\nd";
+        let range = read_line_range(input, 1, Some(2), true).await.unwrap();
+        assert_eq!(range.selected, vec!["b", "c"]);
+        assert_eq!(range.lines_seen, 4);
+        assert!(range.reached_eof);
+
+        let range = read_line_range(input, 1, Some(1), false).await.unwrap();
+        assert_eq!(range.selected, vec!["b"]);
+        assert!(!range.reached_eof);
+    }
+
     #[tokio::test]
     async fn read_file_binary_detected() {
         let dir = TempDir::new().unwrap();
@@ -344,11 +975,54 @@ mod tests {
                 path: dir.path().join("binary.bin").to_string_lossy().to_string(),
                 offset: None,
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Binary file"));
+        assert!(result.unwrap_err().message.contains("Binary file"));
+    }
+
+    #[tokio::test]
+    async fn read_file_force_text_reads_binary_with_warning() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("page.html");
+        std::fs::write(&file, b"<p>one\x00</p>\n<p>\xfftwo</p>\n").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .read_file(Parameters(ReadFileParams {
+                path: file.to_string_lossy().to_string(),
+                offset: None,
+                limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: Some(true),
+            }))
+            .await
+            .unwrap();
+        let (header, content) = output.split_once("\n\n").unwrap();
+        assert!(
+            header.ends_with(&format!("\n{FORCED_TEXT_WARNING}")),
+            "{output}"
+        );
+        assert_eq!(content, "<p>one\0</p>\n<p>\u{fffd}two</p>");
+
+        let mut params = byte_params(&file, Some(3), Some(4));
+        params.force_text = Some(true);
+        let output = service.read_file(Parameters(params)).await.unwrap();
+        assert!(output.contains(FORCED_TEXT_WARNING), "{output}");
+        assert!(output.ends_with("one\0"), "{output}");
+
+        // Text files get no warning
+        std::fs::write(&file, "<p>plain</p>\n").unwrap();
+        let mut params = byte_params(&file, None, None);
+        params.force_text = Some(true);
+        let output = service.read_file(Parameters(params)).await.unwrap();
+        assert!(!output.contains("Warning"), "{output}");
     }
 
     #[tokio::test]
@@ -363,6 +1037,9 @@ mod tests {
                 path: dir.path().join("empty.txt").to_string_lossy().to_string(),
                 offset: None,
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
@@ -383,11 +1060,14 @@ mod tests {
                 path: dir.path().join("test.txt").to_string_lossy().to_string(),
                 offset: Some(10),
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("beyond end of file"));
+        assert!(result.unwrap_err().message.contains("beyond end of file"));
     }
 
     #[tokio::test]
@@ -407,10 +1087,13 @@ mod tests {
                     .to_string(),
                 offset: None,
                 limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
             }))
             .await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
     }
 
     #[tokio::test]
@@ -519,4 +1202,339 @@ mod tests {
         assert!(output.contains("Error:"));
         assert!(output.contains("Binary file"));
     }
+
+    fn byte_params(
+        file: &std::path::Path,
+        byte_offset: Option<u64>,
+        byte_length: Option<u64>,
+    ) -> ReadFileParams {
+        ReadFileParams {
+            path: file.to_string_lossy().to_string(),
+            offset: None,
+            limit: None,
+            byte_offset,
+            byte_length,
+            force_text: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_file_byte_range_reads_only_that_region() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("app.log");
+        // The binary check looks at the bytes read, not the start of the file
+        let mut content = vec![0u8; 16];
+        content.extend_from_slice(b"first\nsecond\nthird\n");
+        std::fs::write(&file, &content).unwrap();
+        let service = make_service_with_max(vec![canon], 64);
+
+        let output = service
+            .read_file(Parameters(byte_params(&file, Some(22), Some(6))))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "File: {} (Bytes 22-27 of 35 total, 6 B)\n\nsecond",
+                file.display()
+            )
+        );
+
+        let output = service
+            .read_file(Parameters(byte_params(&file, Some(29), None)))
+            .await
+            .unwrap();
+        assert!(
+            output.contains("(Bytes 29-34 of 35 total, 6 B)"),
+            "{output}"
+        );
+        assert!(output.ends_with("third\n"));
+
+        let err = service
+            .read_file(Parameters(byte_params(&file, Some(0), Some(8))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Binary file"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn read_file_byte_range_errors() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("big.log");
+        std::fs::write(&file, "x".repeat(100)).unwrap();
+        let service = make_service_with_max(vec![canon], 10);
+
+        let err = service
+            .read_file(Parameters(ReadFileParams {
+                limit: Some(5),
+                ..byte_params(&file, Some(0), Some(5))
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("not both"), "{err}");
+
+        let err = service
+            .read_file(Parameters(byte_params(&file, Some(100), None)))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("beyond end of file (100 bytes)"),
+            "{err}"
+        );
+
+        let err = service
+            .read_file(Parameters(byte_params(&file, Some(0), Some(0))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("at least 1"), "{err}");
+
+        let err = service
+            .read_file(Parameters(byte_params(&file, Some(50), None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("more than the read limit"), "{err}");
+
+        // A file over the read limit can still be read a region at a time
+        let output = service
+            .read_file(Parameters(byte_params(&file, Some(90), Some(10))))
+            .await
+            .unwrap();
+        assert!(
+            output.contains("(Bytes 90-99 of 100 total, 10 B)"),
+            "{output}"
+        );
+    }
+
+    fn hex_params(
+        path: &std::path::Path,
+        offset: Option<u64>,
+        length: Option<u64>,
+    ) -> ReadFileAsHexParams {
+        ReadFileAsHexParams {
+            path: path.to_string_lossy().to_string(),
+            offset,
+            length,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_file_as_hex_dumps_binary_bytes() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("prog");
+        let mut content = b"\x7fELF\x02\x01\x01\0".to_vec();
+        content.extend_from_slice(b"\0\0\0\0\0\0\0\0Hi there!");
+        std::fs::write(&file, &content).unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .read_file_as_hex(Parameters(hex_params(&file, None, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "File: {} (Bytes 0-24 of 25 total, 25 B)\n\n\
+                 00000000: 7f45 4c46 0201 0100 0000 0000 0000 0000  .ELF............\n\
+                 00000010: 4869 2074 6865 7265 21                   Hi there!\n",
+                file.display()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn read_file_as_hex_dumps_a_byte_range() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("data.bin");
+        let content: Vec<u8> = (0..64u8).collect();
+        std::fs::write(&file, &content).unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .read_file_as_hex(Parameters(hex_params(&file, Some(20), Some(4))))
+            .await
+            .unwrap();
+        assert!(
+            output.contains("(Bytes 20-23 of 64 total, 4 B)"),
+            "{output}"
+        );
+        assert!(output.ends_with("00000014: 1415 1617                                ....\n"));
+
+        let err = service
+            .read_file_as_hex(Parameters(hex_params(&file, Some(64), None)))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("beyond end of file (64 bytes)"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_file_as_hex_stops_at_read_limit() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("big.bin");
+        std::fs::write(&file, vec![0xffu8; 100]).unwrap();
+        let service = make_service_with_max(vec![canon], 32);
+
+        let output = service
+            .read_file_as_hex(Parameters(hex_params(&file, Some(10), Some(50))))
+            .await
+            .unwrap();
+        assert!(
+            output.contains("(Bytes 10-41 of 100 total, 32 B)"),
+            "{output}"
+        );
+        // Offsets count from the start of the file, not the slice
+        assert!(output.contains("\n0000000a: ffff"), "{output}");
+        assert!(output.contains("\n0000001a: ffff"), "{output}");
+        assert!(!output.contains("0000002a"), "{output}");
+        assert!(
+            output.ends_with("(Stopped at the read limit of 32 B; pass offset: 42 to continue)"),
+            "{output}"
+        );
+    }
+
+    fn base64_params(path: &std::path::Path) -> ReadFileBase64Params {
+        ReadFileBase64Params {
+            path: path.to_string_lossy().to_string(),
+            offset: None,
+            length: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_file_base64_round_trips_binary() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("image.bin");
+        let content: Vec<u8> = (0..=255u8).rev().collect();
+        std::fs::write(&file, &content).unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .read_file_base64(Parameters(base64_params(&file)))
+            .await
+            .unwrap();
+        let (header, encoded) = output.split_once("\n\n").unwrap();
+        assert_eq!(header, format!("File: {} (256 B, base64)", file.display()));
+        assert_eq!(STANDARD.decode(encoded).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn read_file_base64_pages_within_output_limit() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("blob.bin");
+        let content: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        std::fs::write(&file, &content).unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            max_read_size: 10_485_760,
+            max_output_bytes: 1024,
+            ..Default::default()
+        });
+
+        let mut decoded = Vec::new();
+        let mut offset = None;
+        let mut pages = 0;
+        loop {
+            let output = service
+                .read_file_base64(Parameters(ReadFileBase64Params {
+                    offset,
+                    ..base64_params(&file)
+                }))
+                .await
+                .unwrap();
+            assert!(output.len() <= 1024, "{}", output.len());
+            let (header, encoded) = output.split_once("\n\n").unwrap();
+            decoded.extend(STANDARD.decode(encoded).unwrap());
+            pages += 1;
+            match header.split_once("pass offset: ") {
+                Some((_, rest)) => {
+                    offset = Some(rest.split(' ').next().unwrap().parse().unwrap());
+                }
+                None => break,
+            }
+        }
+        assert!(pages > 1);
+        assert_eq!(decoded, content);
+
+        let output = service
+            .read_file_base64(Parameters(ReadFileBase64Params {
+                offset: Some(10),
+                length: Some(4),
+                ..base64_params(&file)
+            }))
+            .await
+            .unwrap();
+        let (header, encoded) = output.split_once("\n\n").unwrap();
+        assert!(
+            header.ends_with("(Bytes 10-13 of 5000 total, 4 B, base64)"),
+            "{header}"
+        );
+        assert_eq!(STANDARD.decode(encoded).unwrap(), &content[10..14]);
+    }
+
+    #[tokio::test]
+    async fn read_file_base64_refuses_large_and_denied_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("big.bin");
+        std::fs::write(&file, vec![0u8; 100]).unwrap();
+        let service = make_service_with_max(vec![canon], 10);
+
+        let err = service
+            .read_file_base64(Parameters(base64_params(&file)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("File too large"), "{err}");
+
+        let other = TempDir::new().unwrap();
+        let outside = other.path().join("secret.bin");
+        std::fs::write(&outside, b"secret").unwrap();
+        let err = service
+            .read_file_base64(Parameters(base64_params(&outside)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn read_file_counts_toward_usage_stats() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hello").unwrap();
+
+        let service = make_service(vec![canon]);
+        for name in ["a.txt", "missing.txt"] {
+            let params = ReadFileParams {
+                path: dir.path().join(name).to_string_lossy().to_string(),
+                offset: None,
+                limit: None,
+                byte_offset: None,
+                byte_length: None,
+                force_text: None,
+            };
+            let _ = service
+                .stats
+                .instrument(
+                    "read_file",
+                    service.read_file(Parameters(params)),
+                    Result::is_ok,
+                )
+                .await;
+        }
+
+        let usage = service.stats.usage("read_file").unwrap();
+        assert_eq!(
+            (usage.invocations, usage.successes, usage.errors),
+            (2, 1, 1)
+        );
+        assert_eq!((usage.bytes_read, usage.bytes_written), (5, 0));
+    }
 }
@@ -1,11 +1,38 @@
 use crate::FilesystemService;
-use crate::error::FsError;
-use globset::Glob;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::RegexBuilder;
 use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::LoggingLevel;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
-use super::util::format_size;
+use super::checksum::manifest_path;
+use super::count::{Visit, walk_sync};
+use super::gitignore::{Gitignore, is_ignored};
+use super::structured::{OutputFormat, Structured, output_schema};
+use super::util::{
+    SkippedEntries, SkippedPath, display_path, format_size, is_binary, matches_extension_filter,
+    resolve_extension_filter, spawn_blocking,
+};
+
+/// Matching lines search_file_contents returns when max_results is not given.
+const DEFAULT_CONTENT_RESULTS: usize = 50;
+
+/// Ceiling on search_file_contents' max_results.
+const MAX_CONTENT_RESULTS: usize = 500;
+
+/// Ceiling on search_file_contents' and grep_file's context_lines.
+const MAX_CONTEXT_LINES: usize = 10;
+
+/// Characters of a line shown in search_file_contents output; longer lines,
+/// such as minified code, are cut short.
+const MAX_LINE_CHARS: usize = 300;
 
 /// Parameters for the search_files tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -13,10 +40,103 @@ struct SearchFilesParams {
     /// Absolute path to the directory to search in
     path: String,
     /// Glob pattern to match file paths against (e.g., "*.rs", "**/*.txt")
+    #[serde(default)]
     pattern: String,
+    /// Additional glob patterns; a file matches if it matches any pattern
+    #[schemars(description = "Additional glob patterns; a file matches if it matches any pattern")]
+    patterns: Option<Vec<String>>,
     /// Maximum number of results to return (default: 50, max: 200)
     #[schemars(description = "Maximum number of results to return (default: 50, max: 200)")]
     max_results: Option<u32>,
+    /// Only match files with these extensions (overrides the server default; empty list matches all)
+    #[schemars(
+        description = "Only match files with these extensions (overrides the server default; empty list matches all)"
+    )]
+    extensions: Option<Vec<String>>,
+    /// Skip files and directories ignored by .gitignore files in the searched tree (default: true)
+    #[schemars(
+        description = "Skip files and directories ignored by .gitignore files in the searched tree (default: true)"
+    )]
+    ignore_gitignored: Option<bool>,
+    /// Glob patterns for paths to leave out, relative to the search root
+    /// (e.g. "target/**", "**/vendor"); matching directories are not descended into
+    #[schemars(
+        description = "Glob patterns for paths to leave out, relative to the search root (e.g. \"target/**\", \"**/vendor\"); matching directories are not descended into"
+    )]
+    exclude: Option<Vec<String>>,
+    /// Output format: "text" (default) for readable text, or "json" for the structured result as JSON
+    #[schemars(
+        description = "Output format: \"text\" (default) for readable text, or \"json\" for the structured result as JSON"
+    )]
+    format: Option<OutputFormat>,
+}
+
+/// Parameters for the search_file_contents tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct SearchFileContentsParams {
+    /// Absolute path to the directory to search in
+    path: String,
+    /// Text to look for, matched literally within each line
+    query: String,
+    /// Match case exactly (default: true)
+    #[schemars(description = "Match case exactly (default: true)")]
+    case_sensitive: Option<bool>,
+    /// Maximum number of matching lines to return (default: 50, max: 500)
+    #[schemars(description = "Maximum number of matching lines to return (default: 50, max: 500)")]
+    max_results: Option<u32>,
+    /// Lines to show before and after each match (default: 0, max: 10)
+    #[schemars(description = "Lines to show before and after each match (default: 0, max: 10)")]
+    context_lines: Option<u32>,
+}
+
+/// Parameters for the grep_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct GrepFileParams {
+    /// Absolute path to the file to search
+    path: String,
+    /// Regular expression matched against each line (Rust regex syntax)
+    pattern: String,
+    /// Ignore case when matching (default: false)
+    #[schemars(description = "Ignore case when matching (default: false)")]
+    case_insensitive: Option<bool>,
+    /// Lines to show before and after each match (default: 0, max: 10)
+    #[schemars(description = "Lines to show before and after each match (default: 0, max: 10)")]
+    context_lines: Option<u32>,
+}
+
+/// Structured result of search_files.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SearchResults {
+    /// Canonical path of the searched directory
+    pub root: String,
+    /// Glob patterns searched for
+    pub patterns: Vec<String>,
+    /// Patterns that matched at least one file
+    pub matched_patterns: Vec<String>,
+    /// Extension filter in effect; empty when unfiltered
+    pub extensions: Vec<String>,
+    /// Matching files in traversal order
+    pub files: Vec<SearchMatch>,
+    /// Whether the search stopped at max_results, or matches were dropped to
+    /// fit `--max-output-bytes`
+    pub truncated: bool,
+    /// Directories and entries that could not be read and were not searched
+    pub skipped: Vec<SkippedPath>,
+    /// Files and directories left out because a .gitignore ignores them
+    pub gitignored: usize,
+    /// Files and directories left out because an exclude pattern matches them
+    pub excluded: usize,
+    /// Files and directories left out because a deny pattern matches them
+    pub denied: usize,
+}
+
+/// One file found by search_files.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SearchMatch {
+    /// Absolute path
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
 }
 
 #[rmcp::tool_router(router = "search_tools_router", vis = "pub(crate)")]
@@ -24,105 +144,770 @@ impl FilesystemService {
     /// Searches for files matching a glob pattern within a directory tree.
     #[rmcp::tool(
         name = "search_files",
-        description = "Searches for files matching a glob pattern within a directory tree. Returns matched file paths with sizes. Use '*.ext' for files in the root directory, '**/*.ext' for recursive matching.",
-        annotations(read_only_hint = true, destructive_hint = false)
+        description = "Searches for files matching a glob pattern within a directory tree. Returns matched file paths with sizes. Use '*.ext' for files in the root directory, '**/*.ext' for recursive matching. Pass several globs in 'patterns' to match any of them. Paths ignored by .gitignore files in the searched tree, and .git directories, are skipped unless ignore_gitignored is false. Paths matching an 'exclude' glob are skipped, and excluded directories are not searched. With format \"json\" the text is the structured result as JSON.",
+        output_schema = output_schema::<SearchResults>(),
+        annotations(
+            title = "Search Files",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn search_files(
         &self,
         Parameters(params): Parameters<SearchFilesParams>,
-    ) -> Result<String, String> {
-        let path = std::path::Path::new(&params.path);
-        let canonical = self
+        ct: CancellationToken,
+    ) -> Structured<SearchResults> {
+        let mut patterns: Vec<String> = Vec::new();
+        if !params.pattern.is_empty() {
+            patterns.push(params.pattern);
+        }
+        patterns.extend(params.patterns.into_iter().flatten());
+        Structured::from(
+            self.search_files_report(
+                &params.path,
+                patterns,
+                params.max_results,
+                params.extensions.as_deref(),
+                SearchExclusions {
+                    gitignored: params.ignore_gitignored.unwrap_or(true),
+                    globs: params.exclude.unwrap_or_default(),
+                },
+                ct,
+            )
+            .await,
+        )
+        .formatted(params.format)
+    }
+
+    /// Finds lines containing a literal string in the text files below a directory.
+    #[rmcp::tool(
+        name = "search_file_contents",
+        description = "Searches the text files in a directory tree for lines containing query, matched literally (case-sensitive unless case_sensitive: false). Returns matches as 'file:line: text' with paths relative to the directory, in sorted file order, optionally with context_lines of surrounding lines marked 'file:line- text'. Binary files, files over the read limit, and denied paths are skipped and counted.",
+        annotations(
+            title = "Search File Contents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn search_file_contents(
+        &self,
+        Parameters(params): Parameters<SearchFileContentsParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self
             .security
-            .validate_directory(path)
-            .map_err(|e| e.to_string())?;
+            .validate_directory(std::path::Path::new(&params.path))?;
+        if params.query.is_empty() {
+            return Err("query must not be empty".into());
+        }
+        let case_sensitive = params.case_sensitive.unwrap_or(true);
+        let query = ContentQuery {
+            needle: if case_sensitive {
+                params.query.clone()
+            } else {
+                params.query.to_lowercase()
+            },
+            case_sensitive,
+            context_lines: params
+                .context_lines
+                .map_or(0, |c| c as usize)
+                .min(MAX_CONTEXT_LINES),
+            max_results: params
+                .max_results
+                .map_or(DEFAULT_CONTENT_RESULTS, |m| m as usize)
+                .clamp(1, MAX_CONTENT_RESULTS),
+        };
+        let extensions = resolve_extension_filter(&self.config.only_extensions, None);
 
-        let matcher = Glob::new(&params.pattern)
-            .map_err(|e| FsError::PatternError(e.to_string()).to_string())?
-            .compile_matcher();
+        let security = self.security.clone();
+        let (max_depth, max_size) = (self.config.max_depth, self.config.max_read_size as u64);
+        let walk_root = root.clone();
+        let found = spawn_blocking(move || {
+            search_contents_sync(
+                &walk_root,
+                &security,
+                max_depth,
+                max_size,
+                &extensions,
+                &query,
+                &ct,
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        stats::record_read(found.bytes_read);
+
+        Ok(format_content_matches(
+            &root,
+            &params.query,
+            case_sensitive,
+            &found,
+        ))
+    }
+
+    /// Finds the lines of one text file matching a regular expression.
+    #[rmcp::tool(
+        name = "grep_file",
+        description = "Searches one text file for lines matching a regular expression (Rust regex syntax; case-sensitive unless case_insensitive: true). Returns matches as 'line: text', optionally with context_lines of surrounding lines marked '+line: text', and stops after 500 matches. Binary files and files over the read limit are refused.",
+        annotations(
+            title = "Grep File",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn grep_file(
+        &self,
+        Parameters(params): Parameters<GrepFileParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let case_insensitive = params.case_insensitive.unwrap_or(false);
+        let regex = RegexBuilder::new(&params.pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
 
-        let max_results = params.max_results.unwrap_or(50).min(200) as usize;
-        let max_depth = self.config.max_depth;
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max_size = self.config.max_read_size as u64;
+        if size > max_size {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max: max_size,
+            }
+            .into());
+        }
+        let content = tokio::fs::read(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+        if is_binary(&content) {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        }
 
-        let mut results: Vec<(std::path::PathBuf, u64)> = Vec::new();
-        let mut stack: Vec<(std::path::PathBuf, usize)> = vec![(canonical.clone(), 0)];
+        let text = String::from_utf8_lossy(&content);
+        let lines: Vec<&str> = text.lines().collect();
+        let mut hits: Vec<usize> = (0..lines.len())
+            .filter(|&i| regex.is_match(lines[i]))
+            .collect();
+        let case_note = if case_insensitive {
+            " (case-insensitive)"
+        } else {
+            ""
+        };
+        if hits.is_empty() {
+            return Ok(format!(
+                "No matches for /{}/ in {}{case_note}",
+                params.pattern,
+                canonical.display()
+            ));
+        }
+        let truncated = hits.len() > MAX_CONTENT_RESULTS;
+        hits.truncate(MAX_CONTENT_RESULTS);
 
-        while let Some((dir, depth)) = stack.pop() {
-            let mut entries = match tokio::fs::read_dir(&dir).await {
-                Ok(e) => e,
-                Err(_) => continue,
+        let context = params
+            .context_lines
+            .map_or(0, |c| c as usize)
+            .min(MAX_CONTEXT_LINES);
+        let mut out = Vec::new();
+        for (start, end) in context_groups(&hits, context, lines.len()) {
+            if context > 0 && !out.is_empty() {
+                out.push("--".to_string());
+            }
+            for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+                let marker = if hits.binary_search(&i).is_ok() {
+                    ""
+                } else {
+                    "+"
+                };
+                out.push(format!("{marker}{}: {}", i + 1, clip_line(line)));
+            }
+        }
+        Ok(format!(
+            "Found {} match{} for /{}/ in {}{case_note}{}:\n\n{}",
+            hits.len(),
+            if hits.len() == 1 { "" } else { "es" },
+            params.pattern,
+            canonical.display(),
+            if truncated {
+                " (results truncated)"
+            } else {
+                ""
+            },
+            out.join("\n")
+        ))
+    }
+}
+
+/// What search_file_contents looks for.
+struct ContentQuery {
+    /// The query, lowercased when matching ignores case
+    needle: String,
+    case_sensitive: bool,
+    context_lines: usize,
+    max_results: usize,
+}
+
+impl ContentQuery {
+    fn matches(&self, line: &str) -> bool {
+        if self.case_sensitive {
+            line.contains(&self.needle)
+        } else {
+            line.to_lowercase().contains(&self.needle)
+        }
+    }
+}
+
+/// Output and counts gathered by [`search_contents_sync`].
+#[derive(Debug, Default)]
+struct ContentMatches {
+    /// Formatted match and context lines, with "--" between separate groups
+    lines: Vec<String>,
+    matches: usize,
+    files_matched: usize,
+    files_searched: usize,
+    bytes_read: u64,
+    binary: u64,
+    too_large: u64,
+    denied: u64,
+    unreadable: u64,
+    /// Whether the search stopped at max_results
+    truncated: bool,
+}
+
+/// Walks `root` up to `max_depth` levels and searches each regular file passing
+/// the extension filter, in sorted path order, until `query.max_results`
+/// matching lines are found. Binary files and files over `max_size` are
+/// skipped and counted.
+fn search_contents_sync(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    max_size: u64,
+    extensions: &[String],
+    query: &ContentQuery,
+    ct: &CancellationToken,
+) -> Result<ContentMatches, FsError> {
+    let mut found = ContentMatches::default();
+    let mut denied = 0;
+    let mut files = Vec::new();
+    found.unreadable = walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if file_type.is_file() && matches_extension_filter(path, extensions) {
+                files.push(path.to_path_buf());
+            }
+            Visit::Continue
+        },
+    )?;
+    found.denied = denied;
+    files.sort();
+
+    for path in files {
+        if found.truncated {
+            break;
+        }
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            found.unreadable += 1;
+            continue;
+        };
+        if metadata.len() > max_size {
+            found.too_large += 1;
+            continue;
+        }
+        let Ok(content) = std::fs::read(&path) else {
+            found.unreadable += 1;
+            continue;
+        };
+        found.bytes_read += content.len() as u64;
+        if is_binary(&content) {
+            found.binary += 1;
+            continue;
+        }
+        found.files_searched += 1;
+        let text = String::from_utf8_lossy(&content);
+        search_text(&manifest_path(root, &path), &text, query, &mut found);
+    }
+    Ok(found)
+}
+
+/// Adds the lines of `text` matching `query`, with their context, to `found`.
+/// Overlapping context around nearby matches is merged into one group.
+fn search_text(relative: &str, text: &str, query: &ContentQuery, found: &mut ContentMatches) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut hits: Vec<usize> = (0..lines.len())
+        .filter(|&i| query.matches(lines[i]))
+        .collect();
+    if hits.is_empty() {
+        return;
+    }
+    let room = query.max_results - found.matches;
+    if hits.len() > room {
+        hits.truncate(room);
+        found.truncated = true;
+    }
+    found.matches += hits.len();
+    found.files_matched += 1;
+
+    let context = query.context_lines;
+    for (start, end) in context_groups(&hits, context, lines.len()) {
+        if context > 0 && !found.lines.is_empty() {
+            found.lines.push("--".to_string());
+        }
+        for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+            let marker = if hits.binary_search(&i).is_ok() {
+                ':'
+            } else {
+                '-'
             };
+            found
+                .lines
+                .push(format!("{relative}:{}{marker} {}", i + 1, clip_line(line)));
+        }
+    }
+}
+
+/// The line ranges to show for the sorted matching lines `hits` with
+/// `context` lines around each, merging ranges that touch or overlap.
+fn context_groups(hits: &[usize], context: usize, line_count: usize) -> Vec<(usize, usize)> {
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &hit in hits {
+        let (start, end) = (
+            hit.saturating_sub(context),
+            (hit + context + 1).min(line_count),
+        );
+        match groups.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => groups.push((start, end)),
+        }
+    }
+    groups
+}
+
+/// `line` cut to [`MAX_LINE_CHARS`] characters, marked with "…" if shortened.
+fn clip_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+fn format_content_matches(
+    root: &Path,
+    query: &str,
+    case_sensitive: bool,
+    found: &ContentMatches,
+) -> String {
+    let case_note = if case_sensitive {
+        ""
+    } else {
+        " (case-insensitive)"
+    };
+    let mut out = if found.matches == 0 {
+        format!(
+            "No matches for \"{query}\" under {}{case_note}\n",
+            root.display()
+        )
+    } else {
+        format!(
+            "Found {} match{} for \"{query}\" in {} file{} under {}{case_note}{}:\n\n{}\n",
+            found.matches,
+            if found.matches == 1 { "" } else { "es" },
+            found.files_matched,
+            if found.files_matched == 1 { "" } else { "s" },
+            root.display(),
+            if found.truncated {
+                " (results truncated)"
+            } else {
+                ""
+            },
+            found.lines.join("\n")
+        )
+    };
+
+    out.push_str(&format!(
+        "\nSearched {} text file{}.",
+        found.files_searched,
+        if found.files_searched == 1 { "" } else { "s" }
+    ));
+    let mut skipped = Vec::new();
+    for (count, label) in [
+        (found.binary, "binary"),
+        (found.too_large, "over the read limit"),
+        (found.denied, "denied"),
+        (found.unreadable, "unreadable"),
+    ] {
+        if count > 0 {
+            skipped.push(format!("{count} {label}"));
+        }
+    }
+    if skipped.is_empty() {
+        out.push_str(" Skipped: none");
+    } else {
+        out.push_str(&format!(" Skipped: {}", skipped.join(", ")));
+    }
+    out
+}
 
-            let mut subdirs = Vec::new();
-
-            loop {
-                match entries.next_entry().await {
-                    Ok(Some(entry)) => {
-                        let metadata = match entry.metadata().await {
-                            Ok(m) => m,
-                            Err(_) => continue,
-                        };
-
-                        let entry_path = entry.path();
-
-                        if metadata.is_dir() && depth < max_depth {
-                            subdirs.push(entry_path);
-                        } else if metadata.is_file() {
-                            let relative =
-                                entry_path.strip_prefix(&canonical).unwrap_or(&entry_path);
-                            if matcher.is_match(relative) {
-                                results.push((entry_path, metadata.len()));
-                                if results.len() >= max_results {
-                                    return Ok(format_search_results(
-                                        &canonical,
-                                        &params.pattern,
-                                        &results,
-                                        true,
-                                    ));
-                                }
-                            }
-                        }
+/// Paths a search_files walk leaves out, besides denied ones.
+pub(crate) struct SearchExclusions {
+    /// Skip paths ignored by .gitignore files in the tree, and .git directories
+    pub gitignored: bool,
+    /// Globs matched against the path relative to the search root
+    pub globs: Vec<String>,
+}
+
+impl FilesystemService {
+    pub(crate) async fn search_files_report(
+        &self,
+        requested: &str,
+        patterns: Vec<String>,
+        max_results: Option<u32>,
+        extensions: Option<&[String]>,
+        exclusions: SearchExclusions,
+        ct: CancellationToken,
+    ) -> Result<(String, SearchResults), ToolError> {
+        let path = std::path::Path::new(requested);
+        let canonical = self.security.validate_directory(path)?;
+
+        if patterns.is_empty() {
+            return Err(FsError::PatternError(
+                "provide 'pattern' or at least one entry in 'patterns'".into(),
+            )
+            .into());
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &patterns {
+            builder.add(Glob::new(pattern).map_err(|e| FsError::PatternError(e.to_string()))?);
+        }
+        let matcher = builder
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &exclusions.globs {
+            builder.add(
+                Glob::new(pattern).map_err(|e| {
+                    FsError::PatternError(format!("exclude pattern '{pattern}': {e}"))
+                })?,
+            );
+        }
+        let exclude = builder
+            .build()
+            .map_err(|e| FsError::PatternError(e.to_string()))?;
+
+        let extensions = resolve_extension_filter(&self.config.only_extensions, extensions);
+        let max_results = max_results.unwrap_or(50).min(200) as usize;
+
+        let found = find_matching_files(
+            &canonical,
+            &matcher,
+            &extensions,
+            Traversal {
+                max_depth: self.config.max_depth,
+                gitignore: exclusions.gitignored,
+                exclude,
+                security: self.security.clone(),
+            },
+            max_results,
+            |_| true,
+            &ct,
+        )
+        .await?;
+        self.log_unreadable(&canonical, &found.skipped);
+
+        let mut text = format_search_results(
+            &canonical,
+            &patterns,
+            &found.pattern_hits,
+            &extensions,
+            &found.files,
+            found.truncated,
+        );
+        if let Some(summary) = found.skipped.summary(&canonical) {
+            text.push_str(&format!("\n({summary})"));
+        }
+        if found.gitignored > 0 {
+            text.push_str(&format!(
+                "\n({} path{} skipped by .gitignore; pass ignore_gitignored: false to include them)",
+                found.gitignored,
+                if found.gitignored == 1 { "" } else { "s" }
+            ));
+        }
+        if found.excluded > 0 {
+            text.push_str(&format!(
+                "\n({} path{} skipped by exclude patterns)",
+                found.excluded,
+                if found.excluded == 1 { "" } else { "s" }
+            ));
+        }
+        if found.denied > 0 {
+            text.push_str(&format!(
+                "\n({} path{} skipped by deny patterns)",
+                found.denied,
+                if found.denied == 1 { "" } else { "s" }
+            ));
+        }
+        let results = SearchResults {
+            root: canonical.display().to_string(),
+            matched_patterns: patterns
+                .iter()
+                .zip(&found.pattern_hits)
+                .filter(|(_, hit)| **hit)
+                .map(|(p, _)| p.clone())
+                .collect(),
+            patterns,
+            extensions,
+            files: found
+                .files
+                .into_iter()
+                .map(|(path, size)| SearchMatch {
+                    path: display_path(&path),
+                    size,
+                })
+                .collect(),
+            truncated: found.truncated,
+            skipped: found.skipped.report(&canonical),
+            gitignored: found.gitignored,
+            excluded: found.excluded,
+            denied: found.denied,
+        };
+        Ok((text, results))
+    }
+
+    /// Tells the client which directories and entries a walk of `root` had to skip.
+    pub(crate) fn log_unreadable(&self, root: &Path, skipped: &SkippedEntries) {
+        if let Some(summary) = skipped.summary(root) {
+            self.client_log.log(
+                LoggingLevel::Warning,
+                "walk",
+                &format!("{}: {summary}", root.display()),
+            );
+        }
+    }
+}
+
+/// Files found by [`find_matching_files`].
+pub(crate) struct FileMatches {
+    /// Matching regular files and their sizes, in traversal order
+    pub files: Vec<(PathBuf, u64)>,
+    /// Which of the matcher's patterns matched at least one file
+    pub pattern_hits: Vec<bool>,
+    /// Whether the walk stopped at `limit` before visiting everything
+    pub truncated: bool,
+    /// Directories and entries that could not be read and were skipped
+    pub skipped: SkippedEntries,
+    /// Files and directories left out because a .gitignore ignores them
+    pub gitignored: usize,
+    /// Files and directories left out because `Traversal::exclude` matches them
+    pub excluded: usize,
+    /// Files and directories left out because a deny pattern matches them
+    pub denied: usize,
+}
+
+/// How far [`find_matching_files`] descends.
+pub(crate) struct Traversal {
+    /// Levels below the root to visit
+    pub max_depth: usize,
+    /// Honor `.gitignore` files found in the walked tree and skip `.git`
+    /// directories
+    pub gitignore: bool,
+    /// Paths relative to the root to skip; a matching directory is not entered
+    pub exclude: GlobSet,
+    /// Context whose deny patterns apply to every entry; a denied directory
+    /// is not entered
+    pub security: SecurityContext,
+}
+
+/// Walks `root` depth-first in sorted order, up to `traversal.max_depth` levels,
+/// collecting regular files whose path relative to `root` matches `matcher` and
+/// the extension filter. Symlinks are not followed or returned. Denied paths,
+/// files rejected by `accept`, with `traversal.gitignore` paths a `.gitignore`
+/// in the tree ignores, and paths matching `traversal.exclude` are skipped.
+/// Stops once `limit` files are found, and fails with
+/// [`FsError::Cancelled`] if `ct` fires between directories. Each directory is
+/// read on the blocking pool so large trees do not stall the runtime.
+pub(crate) async fn find_matching_files(
+    root: &Path,
+    matcher: &GlobSet,
+    extensions: &[String],
+    traversal: Traversal,
+    limit: usize,
+    accept: impl Fn(&Path) -> bool,
+    ct: &CancellationToken,
+) -> Result<FileMatches, FsError> {
+    let mut found = FileMatches {
+        files: Vec::new(),
+        pattern_hits: vec![false; matcher.len()],
+        truncated: false,
+        skipped: SkippedEntries::default(),
+        gitignored: 0,
+        excluded: 0,
+        denied: 0,
+    };
+    // Each directory carries the .gitignore files of the directories above it
+    let mut stack: Vec<(PathBuf, usize, Vec<Arc<Gitignore>>)> =
+        vec![(root.to_path_buf(), 0, Vec::new())];
+
+    while let Some((dir, depth, mut ignores)) = stack.pop() {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled);
+        }
+        let (entries, gitignore) = {
+            let dir = dir.clone();
+            let load_gitignore = traversal.gitignore;
+            spawn_blocking(move || {
+                let gitignore = load_gitignore.then(|| Gitignore::from_dir(&dir)).flatten();
+                (read_dir_sync(&dir), gitignore)
+            })
+            .await
+            .map_err(|e| FsError::IoError(std::io::Error::other(e)))?
+        };
+        ignores.extend(gitignore.map(Arc::new));
+        let (entries, failed) = match entries {
+            Ok(e) => e,
+            Err(e) => {
+                found.skipped.record(dir, &e, true);
+                continue;
+            }
+        };
+        for (entry_path, err, directory) in failed {
+            found.skipped.record(entry_path, &err, directory);
+        }
+
+        let mut subdirs = Vec::new();
+
+        for (entry_path, metadata) in entries {
+            if traversal.security.is_denied(&entry_path) {
+                found.denied += 1;
+                continue;
+            }
+            if traversal.gitignore
+                && (metadata.is_dir() && entry_path.file_name() == Some(".git".as_ref())
+                    || is_ignored(&ignores, &entry_path, metadata.is_dir()))
+            {
+                found.gitignored += 1;
+                continue;
+            }
+            let relative = entry_path.strip_prefix(root).unwrap_or(&entry_path);
+            if traversal.exclude.is_match(relative) {
+                found.excluded += 1;
+                continue;
+            }
+            if metadata.is_dir() && depth < traversal.max_depth {
+                subdirs.push(entry_path);
+            } else if metadata.is_file() {
+                let hits = matcher.matches(relative);
+                if !hits.is_empty()
+                    && matches_extension_filter(&entry_path, extensions)
+                    && accept(&entry_path)
+                {
+                    for i in hits {
+                        found.pattern_hits[i] = true;
+                    }
+                    found.files.push((entry_path, metadata.len()));
+                    if found.files.len() >= limit {
+                        found.truncated = true;
+                        return Ok(found);
                     }
-                    Ok(None) => break,
-                    Err(_) => break,
                 }
             }
+        }
 
-            subdirs.sort();
-            for subdir in subdirs.into_iter().rev() {
-                stack.push((subdir, depth + 1));
-            }
+        subdirs.sort();
+        for subdir in subdirs.into_iter().rev() {
+            stack.push((subdir, depth + 1, ignores.clone()));
         }
+    }
 
-        Ok(format_search_results(
-            &canonical,
-            &params.pattern,
-            &results,
-            false,
-        ))
+    Ok(found)
+}
+
+/// One directory's entries with their metadata.
+type DirEntries = Vec<(PathBuf, std::fs::Metadata)>;
+
+/// Entries whose metadata could not be read, with the error and whether the
+/// entry is known to be a directory.
+type FailedEntries = Vec<(PathBuf, std::io::Error, bool)>;
+
+/// Lists one directory with each entry's metadata (not following symlinks),
+/// in one blocking pass. Entries whose metadata cannot be read are returned
+/// separately, and an error partway through ends the listing early.
+fn read_dir_sync(dir: &Path) -> std::io::Result<(DirEntries, FailedEntries)> {
+    let mut entries = Vec::new();
+    let mut failed = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let Ok(entry) = entry else {
+            break;
+        };
+        match entry.metadata() {
+            Ok(metadata) => entries.push((entry.path(), metadata)),
+            Err(e) => {
+                let directory = entry.file_type().is_ok_and(|t| t.is_dir());
+                failed.push((entry.path(), e, directory));
+            }
+        }
     }
+    Ok((entries, failed))
 }
 
 fn format_search_results(
-    root: &std::path::Path,
-    pattern: &str,
-    results: &[(std::path::PathBuf, u64)],
+    root: &Path,
+    patterns: &[String],
+    pattern_hits: &[bool],
+    extensions: &[String],
+    results: &[(PathBuf, u64)],
     truncated: bool,
 ) -> String {
+    let mut filter_note = if extensions.is_empty() {
+        String::new()
+    } else {
+        format!(" (extensions: {})", extensions.join(", "))
+    };
+    let pattern_label = if patterns.len() == 1 {
+        format!("pattern \"{}\"", patterns[0])
+    } else {
+        let quoted: Vec<String> = patterns.iter().map(|p| format!("\"{p}\"")).collect();
+        filter_note.push_str(&format!(
+            " (Matched {} of {} patterns)",
+            pattern_hits.iter().filter(|hit| **hit).count(),
+            patterns.len()
+        ));
+        format!("patterns {}", quoted.join(", "))
+    };
+
     if results.is_empty() {
         return format!(
-            "No matches found for pattern \"{}\" in {}",
-            pattern,
-            root.display()
+            "No matches found for {} in {}{}",
+            pattern_label,
+            root.display(),
+            filter_note,
         );
     }
 
     let mut output = format!(
-        "Found {} match{} for pattern \"{}\" in {}{}:\n\n",
+        "Found {} match{} for {} in {}{}{}:\n\n",
         results.len(),
         if results.len() == 1 { "" } else { "es" },
-        pattern,
+        pattern_label,
         root.display(),
+        filter_note,
         if truncated {
             " (results truncated)"
         } else {
@@ -132,7 +917,7 @@ fn format_search_results(
 
     for (path, size) in results {
         let size_str = format_size(*size);
-        output.push_str(&format!("{} ({})\n", path.display(), size_str));
+        output.push_str(&format!("{} ({})\n", display_path(path), size_str));
     }
 
     output
@@ -141,6 +926,7 @@ fn format_search_results(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::structured::client_view;
     use crate::{Config, FilesystemService};
     use rmcp::handler::server::wrapper::Parameters;
     use std::path::PathBuf;
@@ -153,6 +939,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -164,6 +951,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -172,8 +960,313 @@ mod tests {
     fn search_tools_router_contains_search_files() {
         let router = FilesystemService::search_tools_router();
         let tools = router.list_all();
-        assert_eq!(tools.len(), 1);
-        assert_eq!(tools[0].name.as_ref(), "search_files");
+        assert_eq!(tools.len(), 3);
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+        assert!(names.contains(&"search_files"));
+        assert!(names.contains(&"search_file_contents"));
+        assert!(names.contains(&"grep_file"));
+    }
+
+    fn contents_params(
+        dir: &TempDir,
+        query: &str,
+        case_sensitive: Option<bool>,
+        context_lines: Option<u32>,
+    ) -> SearchFileContentsParams {
+        SearchFileContentsParams {
+            path: dir.path().to_string_lossy().to_string(),
+            query: query.to_string(),
+            case_sensitive,
+            max_results: None,
+            context_lines,
+        }
+    }
+
+    #[tokio::test]
+    async fn search_file_contents_finds_hits_across_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("README.md"),
+            "# Demo\nCall connect() first\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/net.rs"),
+            "fn connect() {}\nfn close() {}\nfn reconnect() { connect() }\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/other.rs"), "fn main() {}\n").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let output = service
+            .search_file_contents(
+                Parameters(contents_params(&dir, "connect()", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Found 3 matches for \"connect()\" in 2 files under {}:\n\n\
+                 README.md:2: Call connect() first\n\
+                 src/net.rs:1: fn connect() {{}}\n\
+                 src/net.rs:3: fn reconnect() {{ connect() }}\n\
+                 \nSearched 3 text files. Skipped: none",
+                canon.display()
+            )
+        );
+
+        let output = service
+            .search_file_contents(
+                Parameters(SearchFileContentsParams {
+                    max_results: Some(2),
+                    ..contents_params(&dir, "connect()", None, None)
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("Found 2 matches"), "{output}");
+        assert!(output.contains("(results truncated)"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn search_file_contents_case_insensitive() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(
+            dir.path().join("log.txt"),
+            "ERROR disk full\nerror retry\nok\n",
+        )
+        .unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .search_file_contents(
+                Parameters(contents_params(&dir, "Error", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.starts_with("No matches for \"Error\""), "{output}");
+
+        let output = service
+            .search_file_contents(
+                Parameters(contents_params(&dir, "Error", Some(false), None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("Found 2 matches"), "{output}");
+        assert!(output.contains("(case-insensitive)"), "{output}");
+        assert!(output.contains("log.txt:1: ERROR disk full"), "{output}");
+        assert!(output.contains("log.txt:2: error retry"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn search_file_contents_skips_binary_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("blob.bin"), b"needle\0\x01\x02").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "needle here\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .search_file_contents(
+                Parameters(contents_params(&dir, "needle", None, None)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(output.contains("notes.txt:1: needle here"), "{output}");
+        assert!(!output.contains("blob.bin"), "{output}");
+        assert!(
+            output.ends_with("Searched 1 text file. Skipped: 1 binary"),
+            "{output}"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_file_contents_shows_context_lines() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let lines: Vec<String> = (1..=12).map(|i| format!("line {i}")).collect();
+        let mut text = lines.join("\n");
+        text = text
+            .replace("line 3", "line 3 TODO")
+            .replace("line 5", "line 5 TODO");
+        text = text.replace("line 11", "line 11 TODO");
+        std::fs::write(dir.path().join("a.txt"), text).unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .search_file_contents(
+                Parameters(contents_params(&dir, "TODO", None, Some(1))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let body = output.split("\n\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            "a.txt:2- line 2\n\
+             a.txt:3: line 3 TODO\n\
+             a.txt:4- line 4\n\
+             a.txt:5: line 5 TODO\n\
+             a.txt:6- line 6\n\
+             --\n\
+             a.txt:10- line 10\n\
+             a.txt:11: line 11 TODO\n\
+             a.txt:12- line 12"
+        );
+    }
+
+    fn grep_params(
+        path: &Path,
+        pattern: &str,
+        case_insensitive: Option<bool>,
+        context_lines: Option<u32>,
+    ) -> GrepFileParams {
+        GrepFileParams {
+            path: path.to_string_lossy().to_string(),
+            pattern: pattern.to_string(),
+            case_insensitive,
+            context_lines,
+        }
+    }
+
+    #[tokio::test]
+    async fn grep_file_lists_matching_lines() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("main.rs");
+        std::fs::write(&file, "use std::io;\nfn main() {}\nfn helper_2() {}\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .grep_file(Parameters(grep_params(&file, r"^fn \w+\(", None, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Found 2 matches for /^fn \\w+\\(/ in {}:\n\n2: fn main() {{}}\n3: fn helper_2() {{}}",
+                file.display()
+            )
+        );
+
+        let output = service
+            .grep_file(Parameters(grep_params(&file, "struct", None, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!("No matches for /struct/ in {}", file.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_file_shows_context_lines() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("a.txt");
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        std::fs::write(&file, lines.join("\n").replace("line 3", "line 3 TODO")).unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .grep_file(Parameters(grep_params(&file, "TODO|10$", None, Some(1))))
+            .await
+            .unwrap();
+        let body = output.split("\n\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            "+2: line 2\n3: line 3 TODO\n+4: line 4\n--\n+9: line 9\n10: line 10"
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_file_ignores_case_on_request() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("notes.txt");
+        std::fs::write(&file, "Error: one\nerror: two\nfine\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let output = service
+            .grep_file(Parameters(grep_params(&file, "^error", None, None)))
+            .await
+            .unwrap();
+        assert!(output.starts_with("Found 1 match "), "{output}");
+        let output = service
+            .grep_file(Parameters(grep_params(&file, "^error", Some(true), None)))
+            .await
+            .unwrap();
+        assert!(output.starts_with("Found 2 matches "), "{output}");
+        assert!(output.contains("(case-insensitive)"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn grep_file_rejects_bad_pattern_and_binary_files() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let text = canon.join("a.txt");
+        let binary = canon.join("blob.bin");
+        std::fs::write(&text, "text\n").unwrap();
+        std::fs::write(&binary, b"needle\0\x01").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .grep_file(Parameters(grep_params(&text, "(unclosed", None, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("unclosed group"), "{err}");
+        assert_eq!(err.details.unwrap()["kind"], "invalid_pattern");
+
+        let err = service
+            .grep_file(Parameters(grep_params(&binary, "needle", None, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Binary file"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn search_files_escapes_non_utf8_components() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let sub = canon.join(std::ffi::OsStr::from_bytes(b"\xff"));
+        if std::fs::create_dir(&sub).is_err() {
+            // The filesystem only accepts UTF-8 names
+            return;
+        }
+        std::fs::write(sub.join("notes.txt"), "x").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, results) = service
+            .search_files_report(
+                &canon.to_string_lossy(),
+                vec!["**/*.txt".to_string()],
+                None,
+                None,
+                SearchExclusions {
+                    gitignored: true,
+                    globs: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let escaped = canon.join("{hex:ff}").join("notes.txt");
+        assert!(
+            text.contains(&format!("{} (1 B)", escaped.display())),
+            "{text}"
+        );
+        assert_eq!(results.files[0].path, escaped.display().to_string());
     }
 
     #[tokio::test]
@@ -186,12 +1279,21 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: dir.path().to_string_lossy().to_string(),
-                pattern: "*.rs".to_string(),
-                max_results: None,
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*.rs".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("main.rs"));
@@ -200,6 +1302,82 @@ mod tests {
         assert!(output.contains("2 matches"));
     }
 
+    #[tokio::test]
+    async fn search_files_json_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(canon.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(canon.join("readme.md"), "# Readme").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let text = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: canon.to_string_lossy().to_string(),
+                    pattern: "*.rs".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: Some(OutputFormat::Json),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        let results: SearchResults = serde_json::from_str(&text).unwrap();
+        assert_eq!(results.files.len(), 1);
+        assert_eq!(
+            results.files[0].path,
+            canon.join("main.rs").display().to_string()
+        );
+        assert_eq!(results.files[0].size, 12);
+        assert!(!results.truncated);
+    }
+
+    #[tokio::test]
+    async fn search_files_leaves_out_denied_paths() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(canon.join("sub")).unwrap();
+        std::fs::write(canon.join("key.pem"), "secret").unwrap();
+        std::fs::write(canon.join("sub/other.pem"), "secret").unwrap();
+        std::fs::write(canon.join("notes.txt"), "hello").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon.clone()],
+            deny: vec!["*.pem".into()],
+            ..Default::default()
+        });
+
+        let (text, results) = client_view(
+            service
+                .search_files(
+                    Parameters(SearchFilesParams {
+                        path: canon.to_string_lossy().to_string(),
+                        pattern: "**/*".to_string(),
+                        patterns: None,
+                        max_results: None,
+                        extensions: None,
+                        ignore_gitignored: None,
+                        exclude: None,
+                        format: None,
+                    }),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+        assert!(!text.contains(".pem"), "{text}");
+        assert!(
+            text.ends_with("(2 paths skipped by deny patterns)"),
+            "{text}"
+        );
+        assert_eq!(results.files.len(), 1);
+        assert_eq!(results.denied, 2);
+    }
+
     #[tokio::test]
     async fn search_files_invalid_glob() {
         let dir = TempDir::new().unwrap();
@@ -207,15 +1385,103 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: dir.path().to_string_lossy().to_string(),
-                pattern: "[invalid".to_string(),
-                max_results: None,
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "[invalid".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid pattern"));
+        assert!(result.unwrap_err().message.contains("Invalid pattern"));
+    }
+
+    #[tokio::test]
+    async fn search_files_exclude_prunes_directories() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(canon.join("src/bin")).unwrap();
+        std::fs::create_dir_all(canon.join("target/debug/build")).unwrap();
+        std::fs::create_dir_all(canon.join("crates/a/vendor/dep")).unwrap();
+        std::fs::write(canon.join("src/lib.rs"), "").unwrap();
+        std::fs::write(canon.join("src/bin/main.rs"), "").unwrap();
+        std::fs::write(canon.join("target/debug/build/out.rs"), "").unwrap();
+        std::fs::write(canon.join("target/gen.rs"), "").unwrap();
+        std::fs::write(canon.join("crates/a/vendor/dep/lib.rs"), "").unwrap();
+        std::fs::write(canon.join("crates/a/lib.rs"), "").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, results) = client_view(
+            service
+                .search_files(
+                    Parameters(SearchFilesParams {
+                        path: canon.to_string_lossy().to_string(),
+                        pattern: "**/*.rs".to_string(),
+                        patterns: None,
+                        max_results: None,
+                        extensions: None,
+                        ignore_gitignored: None,
+                        exclude: Some(vec!["target".to_string(), "**/vendor".to_string()]),
+                        format: None,
+                    }),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+
+        let found: Vec<&str> = results.files.iter().map(|f| f.path.as_str()).collect();
+        let expected: Vec<String> = ["crates/a/lib.rs", "src/lib.rs", "src/bin/main.rs"]
+            .iter()
+            .map(|p| canon.join(p).display().to_string())
+            .collect();
+        assert_eq!(found, expected);
+        // Only the two directories are counted: nothing below them is visited
+        assert_eq!(results.excluded, 2);
+        assert!(
+            text.contains("(2 paths skipped by exclude patterns)"),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn search_files_invalid_exclude_names_pattern() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+
+        let service = make_service(vec![canon]);
+        let err = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "**/*.rs".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: Some(vec!["target/**".to_string(), "[bad".to_string()]),
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap_err();
+
+        assert!(
+            err.message.contains("exclude pattern '[bad'"),
+            "{}",
+            err.message
+        );
+        assert_eq!(err.details.unwrap()["kind"], "invalid_pattern");
     }
 
     #[tokio::test]
@@ -228,12 +1494,21 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: dir.path().to_string_lossy().to_string(),
-                pattern: "*.txt".to_string(),
-                max_results: Some(3),
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*.txt".to_string(),
+                    patterns: None,
+                    max_results: Some(3),
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("3 matches"));
@@ -254,12 +1529,21 @@ mod tests {
 
         let service = make_service_with_depth(vec![canon], 1);
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: dir.path().to_string_lossy().to_string(),
-                pattern: "**/*.txt".to_string(),
-                max_results: None,
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "**/*.txt".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("root.txt"));
@@ -267,6 +1551,141 @@ mod tests {
         assert!(!output.contains("deep.txt"));
     }
 
+    #[tokio::test]
+    async fn search_files_honors_nested_gitignore() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(canon.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(canon.join("app.txt"), "app").unwrap();
+        std::fs::write(canon.join("app.log"), "log").unwrap();
+        let web = canon.join("web");
+        std::fs::create_dir_all(web.join("node_modules/pkg")).unwrap();
+        std::fs::write(web.join(".gitignore"), "node_modules/\n").unwrap();
+        std::fs::write(web.join("index.txt"), "index").unwrap();
+        std::fs::write(web.join("node_modules/pkg/readme.txt"), "pkg").unwrap();
+        std::fs::create_dir(canon.join(".git")).unwrap();
+        std::fs::write(canon.join(".git/HEAD.txt"), "ref").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let root = canon.to_string_lossy().to_string();
+        let search = |ignore_gitignored| {
+            service.search_files_report(
+                &root,
+                vec!["**/*.txt".to_string(), "**/*.log".to_string()],
+                None,
+                None,
+                SearchExclusions {
+                    gitignored: ignore_gitignored,
+                    globs: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+        };
+
+        let (text, results) = search(true).await.unwrap();
+        let found: Vec<&str> = results.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            found,
+            [
+                canon.join("app.txt").display().to_string(),
+                web.join("index.txt").display().to_string(),
+            ]
+        );
+        assert_eq!(results.gitignored, 3);
+        assert!(
+            text.ends_with(
+                "(3 paths skipped by .gitignore; pass ignore_gitignored: false to include them)"
+            ),
+            "{text}"
+        );
+
+        // Disabled, the walk is the same as without any .gitignore
+        let (text, results) = search(false).await.unwrap();
+        assert_eq!(results.files.len(), 5);
+        assert_eq!(results.gitignored, 0);
+        assert!(!text.contains(".gitignore"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn search_files_gitignore_negation_reincludes() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(canon.join(".gitignore"), "*.txt\n!keep.txt\n").unwrap();
+        std::fs::write(canon.join("drop.txt"), "x").unwrap();
+        std::fs::write(canon.join("keep.txt"), "x").unwrap();
+        let docs = canon.join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        // A deeper .gitignore overrides the one above it
+        std::fs::write(docs.join(".gitignore"), "!guide.txt\n").unwrap();
+        std::fs::write(docs.join("guide.txt"), "x").unwrap();
+        std::fs::write(docs.join("notes.txt"), "x").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (_, results) = service
+            .search_files_report(
+                &canon.to_string_lossy(),
+                vec!["**/*.txt".to_string()],
+                None,
+                None,
+                SearchExclusions {
+                    gitignored: true,
+                    globs: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let found: Vec<&str> = results.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            found,
+            [
+                canon.join("keep.txt").display().to_string(),
+                docs.join("guide.txt").display().to_string(),
+            ]
+        );
+        assert_eq!(results.gitignored, 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn search_files_reports_unreadable_subdirectories() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let locked = dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::write(locked.join("hidden.rs"), "fn x() {}").unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        let readable_anyway = std::fs::read_dir(&locked).is_ok();
+
+        let service = make_service(vec![canon.clone()]);
+        let result = service
+            .search_files_report(
+                &dir.path().to_string_lossy(),
+                vec!["**/*.rs".to_string()],
+                None,
+                None,
+                SearchExclusions {
+                    gitignored: true,
+                    globs: Vec::new(),
+                },
+                CancellationToken::new(),
+            )
+            .await;
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        if readable_anyway {
+            // Running as root, so permissions are not enforced
+            return;
+        }
+
+        let (output, results) = result.unwrap();
+        assert!(output.contains("main.rs"));
+        assert!(!output.contains("hidden.rs"));
+        assert!(output.ends_with("(1 directory skipped: permission denied: ./locked)"));
+        assert_eq!(results.skipped[0].path, "./locked");
+    }
+
     #[tokio::test]
     async fn search_files_no_matches() {
         let dir = TempDir::new().unwrap();
@@ -275,17 +1694,67 @@ mod tests {
 
         let service = make_service(vec![canon]);
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: dir.path().to_string_lossy().to_string(),
-                pattern: "*.rs".to_string(),
-                max_results: None,
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*.rs".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         let output = result.unwrap();
         assert!(output.contains("No matches"));
     }
 
+    #[tokio::test]
+    async fn find_matching_files_stops_when_cancelled_mid_walk() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for d in 0..100 {
+            let sub = dir.path().join(format!("d{d:03}"));
+            std::fs::create_dir(&sub).unwrap();
+            std::fs::write(sub.join("f.txt"), "x").unwrap();
+        }
+        let matcher = {
+            let mut builder = GlobSetBuilder::new();
+            builder.add(Glob::new("**/*.txt").unwrap());
+            builder.build().unwrap()
+        };
+
+        // Cancel as soon as the first file is seen; the walk must not visit the rest
+        let ct = CancellationToken::new();
+        let seen = std::sync::atomic::AtomicUsize::new(0);
+        let result = find_matching_files(
+            &canon,
+            &matcher,
+            &[],
+            Traversal {
+                max_depth: 10,
+                gitignore: false,
+                exclude: GlobSet::empty(),
+                security: SecurityContext::new(vec![canon.clone()]),
+            },
+            usize::MAX,
+            |_| {
+                seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                ct.cancel();
+                true
+            },
+            &ct,
+        )
+        .await;
+        assert!(matches!(result, Err(FsError::Cancelled)));
+        assert_eq!(seen.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn search_files_denied_outside() {
         let dir = TempDir::new().unwrap();
@@ -294,14 +1763,309 @@ mod tests {
 
         let other = TempDir::new().unwrap();
         let result = service
-            .search_files(Parameters(SearchFilesParams {
-                path: other.path().to_string_lossy().to_string(),
-                pattern: "*.txt".to_string(),
-                max_results: None,
-            }))
-            .await;
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: other.path().to_string_lossy().to_string(),
+                    pattern: "*.txt".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
+    }
+
+    #[tokio::test]
+    async fn search_files_applies_extension_filter() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+
+        let config = Config {
+            allowed_directories: vec![canon],
+            only_extensions: vec!["rs".to_string(), "toml".to_string()],
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+        let output = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("(extensions: rs, toml)"));
+        assert!(output.contains("main.rs"));
+        assert!(output.contains("Cargo.toml"));
+        assert!(!output.contains("notes.txt"));
+
+        let overridden = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*".to_string(),
+                    patterns: None,
+                    max_results: None,
+                    extensions: Some(vec![".TXT".to_string()]),
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+        assert!(overridden.contains("notes.txt"));
+        assert!(!overridden.contains("main.rs"));
+    }
+
+    #[tokio::test]
+    async fn search_files_multiple_patterns_or_semantics() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(dir.path().join("readme.md"), "# Readme").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: String::new(),
+                    patterns: Some(vec!["*.rs".to_string(), "*.toml".to_string()]),
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("main.rs"));
+        assert!(output.contains("Cargo.toml"));
+        assert!(!output.contains("readme.md"));
+        assert!(output.contains("Matched 2 of 2 patterns"));
+    }
+
+    #[tokio::test]
+    async fn search_files_combines_pattern_and_patterns() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("readme.md"), "# Readme").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: "*.md".to_string(),
+                    patterns: Some(vec!["*.rs".to_string(), "*.py".to_string()]),
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("main.rs"));
+        assert!(output.contains("readme.md"));
+        assert!(output.contains("Matched 2 of 3 patterns"));
+    }
+
+    #[tokio::test]
+    async fn search_files_single_entry_patterns_behaves_like_pattern() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("readme.md"), "# Readme").unwrap();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: String::new(),
+                    patterns: Some(vec!["*.rs".to_string()]),
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text()
+            .unwrap();
+
+        assert!(output.contains("1 match for pattern \"*.rs\""));
+        assert!(!output.contains("readme.md"));
+    }
+
+    #[tokio::test]
+    async fn search_files_requires_a_pattern() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .search_files(
+                Parameters(SearchFilesParams {
+                    path: dir.path().to_string_lossy().to_string(),
+                    pattern: String::new(),
+                    patterns: Some(vec![]),
+                    max_results: None,
+                    extensions: None,
+                    ignore_gitignored: None,
+                    exclude: None,
+                    format: None,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .text();
+
+        assert!(result.unwrap_err().message.contains("Invalid pattern"));
+    }
+
+    #[tokio::test]
+    async fn search_files_structured_content_matches_text() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src").join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "# Notes").unwrap();
+
+        let service = make_service(vec![canon.clone()]);
+        let (text, results) = client_view(
+            service
+                .search_files(
+                    Parameters(SearchFilesParams {
+                        path: dir.path().to_string_lossy().to_string(),
+                        pattern: "**/*.rs".to_string(),
+                        patterns: Some(vec!["*.toml".to_string()]),
+                        max_results: None,
+                        extensions: None,
+                        ignore_gitignored: None,
+                        exclude: None,
+                        format: None,
+                    }),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+
+        assert_eq!(results.root, canon.display().to_string());
+        assert_eq!(results.patterns, ["**/*.rs", "*.toml"]);
+        assert_eq!(results.matched_patterns, ["**/*.rs"]);
+        assert!(!results.truncated);
+        assert_eq!(results.files.len(), 1);
+        let file = &results.files[0];
+        assert_eq!(file.size, 12);
+        assert!(text.contains(&format!("{} (12 B)", file.path)), "{text}");
+        assert!(text.contains("Matched 1 of 2 patterns"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn search_files_over_10k_file_tree_is_deterministic() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        for d in 0..100 {
+            let sub = canon.join(format!("d{d:03}"));
+            std::fs::create_dir(&sub).unwrap();
+            for f in 0..100 {
+                std::fs::write(sub.join(format!("f{f:03}.txt")), "x").unwrap();
+            }
+        }
+
+        let service = make_service(vec![canon.clone()]);
+        let search = |pattern: &str, max_results: u32| SearchFilesParams {
+            path: canon.to_string_lossy().to_string(),
+            pattern: pattern.to_string(),
+            patterns: None,
+            max_results: Some(max_results),
+            extensions: None,
+            ignore_gitignored: None,
+            exclude: None,
+            format: None,
+        };
+
+        let (text, results) = client_view(
+            service
+                .search_files(
+                    Parameters(search("**/*.txt", 200)),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+        assert!(results.truncated);
+        assert!(text.contains("Found 200 matches"), "{text}");
+        // Subdirectories are walked in sorted order: all of d000, then all of
+        // d001. Files within a directory keep the order the OS returns them in.
+        for (chunk, d) in results.files.chunks(100).zip(0..) {
+            let mut paths: Vec<&str> = chunk.iter().map(|f| f.path.as_str()).collect();
+            paths.sort_unstable();
+            let expected: Vec<String> = (0..100)
+                .map(|f| {
+                    canon
+                        .join(format!("d{d:03}"))
+                        .join(format!("f{f:03}.txt"))
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            assert_eq!(paths, expected);
+        }
+
+        let (_, results) = client_view(
+            service
+                .search_files(
+                    Parameters(search("d042/f09*", 200)),
+                    CancellationToken::new(),
+                )
+                .await,
+        );
+        assert!(!results.truncated);
+        let mut names: Vec<&str> = results
+            .files
+            .iter()
+            .map(|f| &f.path[f.path.len() - 8..])
+            .collect();
+        names.sort_unstable();
+        let expected: Vec<String> = (90..100).map(|f| format!("f{f:03}.txt")).collect();
+        assert_eq!(names, expected);
     }
 }
@@ -0,0 +1,73 @@
+use crate::FilesystemService;
+use crate::error::ToolError;
+
+/// Formats an uptime as e.g. `2h 05m 09s`.
+pub(crate) fn format_uptime(uptime: std::time::Duration) -> String {
+    let secs = uptime.as_secs();
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[rmcp::tool_router(router = "stats_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Reports per-tool usage counters since the server started.
+    #[rmcp::tool(
+        name = "server_stats",
+        description = "Reports what this server has done since it started: calls, successes, errors, bytes read and written, and total time per tool, plus uptime and concurrency. Counters reset only when the server restarts.",
+        annotations(
+            title = "Server Stats",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn server_stats(&self) -> Result<String, ToolError> {
+        let (in_flight, peak) = self.concurrency();
+        Ok(format!(
+            "Server stats (uptime {}, {} running now, peak {} concurrent):\n\n{}",
+            format_uptime(self.stats.uptime()),
+            in_flight,
+            peak,
+            self.stats.report()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn format_uptime_pads_minutes_and_seconds() {
+        assert_eq!(format_uptime(Duration::from_secs(7509)), "2h 05m 09s");
+        assert_eq!(format_uptime(Duration::ZERO), "0h 00m 00s");
+    }
+
+    #[tokio::test]
+    async fn server_stats_reports_called_tools() {
+        let dir = TempDir::new().unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+        let report = service.server_stats().await.unwrap();
+        assert!(
+            report.starts_with("Server stats (uptime 0h 00m"),
+            "{report}"
+        );
+        assert!(report.ends_with("(no tool calls yet)"));
+
+        service
+            .stats
+            .instrument("read_file", async { crate::stats::record_read(5) }, |_| {
+                true
+            })
+            .await;
+        let report = service.server_stats().await.unwrap();
+        assert!(report.contains("read_file"), "{report}");
+        assert!(!report.contains("list_directory"));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::error::ToolError;
+use rmcp::ErrorData;
+use rmcp::handler::server::tool::IntoCallToolResult;
+use rmcp::model::{CallToolResult, Content, IntoContents, JsonObject};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Text block of a tool returning [`Structured<T>`]: the usual human-readable
+/// text, or the structured payload serialized as JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Result of a tool that returns structured content alongside its text.
+///
+/// On success the client gets the usual human-readable text block plus `data`
+/// as `structuredContent`; errors carry text only, like a `Result<String, ToolError>` tool.
+pub struct Structured<T>(pub Result<(String, T), ToolError>);
+
+impl<T> Structured<T> {
+    /// The text block, exactly as clients without structured content support see it.
+    pub fn text(self) -> Result<String, ToolError> {
+        self.0.map(|(text, _)| text)
+    }
+
+    /// The structured payload, if the call succeeded.
+    pub fn data(self) -> Option<T> {
+        self.0.ok().map(|(_, data)| data)
+    }
+}
+
+impl<T: Serialize> Structured<T> {
+    /// With [`OutputFormat::Json`], replaces the text block with the payload
+    /// as JSON, so clients that only read text can parse it directly.
+    pub fn formatted(self, format: Option<OutputFormat>) -> Self {
+        if format.unwrap_or_default() == OutputFormat::Text {
+            return self;
+        }
+        Self(self.0.and_then(|(_, data)| {
+            let json = serde_json::to_string(&data).map_err(|e| e.to_string())?;
+            Ok((json, data))
+        }))
+    }
+}
+
+impl<T> From<Result<(String, T), ToolError>> for Structured<T> {
+    fn from(result: Result<(String, T), ToolError>) -> Self {
+        Self(result)
+    }
+}
+
+impl<T: Serialize> IntoCallToolResult for Structured<T> {
+    fn into_call_tool_result(self) -> Result<CallToolResult, ErrorData> {
+        match self.0 {
+            Ok((text, data)) => {
+                let value = serde_json::to_value(data)
+                    .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+                let mut result = CallToolResult::success(vec![Content::text(text)]);
+                result.structured_content = Some(value);
+                Ok(result)
+            }
+            Err(error) => Ok(CallToolResult::error(error.into_contents())),
+        }
+    }
+}
+
+/// Output schema for a tool returning [`Structured<T>`].
+pub fn output_schema<T: JsonSchema + 'static>() -> Arc<JsonObject> {
+    rmcp::handler::server::common::schema_for_output::<T>()
+        .expect("structured tool results are JSON objects")
+}
+
+/// Converts `result` the way the server does and reads it back as a client
+/// would: the text block and the deserialized structured content.
+#[cfg(test)]
+pub(crate) fn client_view<T>(result: Structured<T>) -> (String, T)
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let result = result.into_call_tool_result().unwrap();
+    assert_ne!(result.is_error, Some(true));
+    let text = match &result.content[0].raw {
+        rmcp::model::RawContent::Text(t) => t.text.clone(),
+        _ => panic!("expected text content"),
+    };
+    let data = serde_json::from_value(result.structured_content.unwrap()).unwrap();
+    (text, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Sample {
+        count: u32,
+    }
+
+    #[test]
+    fn success_carries_text_and_structured_content() {
+        let (text, data) = client_view(Structured(Ok((
+            "3 things".to_string(),
+            Sample { count: 3 },
+        ))));
+        assert_eq!(text, "3 things");
+        assert_eq!(data.count, 3);
+    }
+
+    #[test]
+    fn json_format_replaces_text_with_payload() {
+        let structured = Structured(Ok(("3 things".to_string(), Sample { count: 3 })));
+        let (text, _) = client_view(structured.formatted(Some(OutputFormat::Json)));
+        let parsed: Sample = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed.count, 3);
+
+        let structured = Structured(Ok(("3 things".to_string(), Sample { count: 3 })));
+        assert_eq!(structured.formatted(None).text().unwrap(), "3 things");
+    }
+
+    #[test]
+    fn error_is_text_only() {
+        let result = Structured::<Sample>(Err("Not found".into()))
+            .into_call_tool_result()
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.structured_content.is_none());
+    }
+
+    #[test]
+    fn output_schema_describes_fields() {
+        let schema = output_schema::<Sample>();
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"].get("count").is_some());
+    }
+}
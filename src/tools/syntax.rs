@@ -0,0 +1,358 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::util::{is_binary, spawn_blocking};
+
+/// Parameters for the validate_syntax tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct ValidateSyntaxParams {
+    /// Absolute path to the file to check
+    path: String,
+    /// Parser to use: "json", "yaml", or "toml". Chosen from the file
+    /// extension when omitted
+    format: Option<String>,
+}
+
+/// A structured format `validate_syntax` can parse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SyntaxFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl SyntaxFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(SyntaxFormat::Json),
+            "yaml" | "yml" => Some(SyntaxFormat::Yaml),
+            "toml" => Some(SyntaxFormat::Toml),
+            _ => None,
+        }
+    }
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::parse)
+    }
+}
+
+impl std::fmt::Display for SyntaxFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SyntaxFormat::Json => "JSON",
+            SyntaxFormat::Yaml => "YAML",
+            SyntaxFormat::Toml => "TOML",
+        })
+    }
+}
+
+/// Where and why parsing failed. Lines and columns are 1-based.
+#[derive(Debug, PartialEq)]
+struct SyntaxError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+/// Strips the " at line X column Y" suffix serde_json and serde_yaml append,
+/// since the position is reported separately.
+fn strip_position(message: String) -> String {
+    match message.find(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+/// 1-based line and column of byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Describes the top level of a parsed document.
+fn describe_top_level(entries: Option<usize>, items: Option<usize>) -> String {
+    match (entries, items) {
+        (Some(1), _) => "1 key at top level".to_string(),
+        (Some(n), _) => format!("{n} keys at top level"),
+        (None, Some(1)) => "array of 1 item at top level".to_string(),
+        (None, Some(n)) => format!("array of {n} items at top level"),
+        (None, None) => "a single value at top level".to_string(),
+    }
+}
+
+/// Parses `text` as `format`, returning a summary of the top level on
+/// success.
+fn check_syntax(text: &str, format: SyntaxFormat) -> Result<String, SyntaxError> {
+    match format {
+        SyntaxFormat::Json => {
+            let value: serde_json::Value = serde_json::from_str(text).map_err(|e| SyntaxError {
+                line: e.line(),
+                column: e.column(),
+                message: strip_position(e.to_string()),
+            })?;
+            Ok(describe_top_level(
+                value.as_object().map(|m| m.len()),
+                value.as_array().map(|a| a.len()),
+            ))
+        }
+        SyntaxFormat::Yaml => {
+            let mut documents = Vec::new();
+            for document in serde_yaml::Deserializer::from_str(text) {
+                let value = serde_yaml::Value::deserialize(document).map_err(|e| {
+                    let (line, column) = e
+                        .location()
+                        .map_or((1, 1), |location| (location.line(), location.column()));
+                    SyntaxError {
+                        line,
+                        column,
+                        message: strip_position(e.to_string()),
+                    }
+                })?;
+                documents.push(value);
+            }
+            if documents.len() > 1 {
+                return Ok(format!("{} documents", documents.len()));
+            }
+            Ok(match documents.first() {
+                None | Some(serde_yaml::Value::Null) => "empty document".to_string(),
+                Some(value) => describe_top_level(
+                    value.as_mapping().map(|m| m.len()),
+                    value.as_sequence().map(|s| s.len()),
+                ),
+            })
+        }
+        SyntaxFormat::Toml => {
+            let table: toml::Table = toml::from_str(text).map_err(|e| {
+                let (line, column) = e
+                    .span()
+                    .map_or((1, 1), |span| line_column(text, span.start));
+                SyntaxError {
+                    line,
+                    column,
+                    message: e.message().trim_end().to_string(),
+                }
+            })?;
+            Ok(describe_top_level(Some(table.len()), None))
+        }
+    }
+}
+
+/// The failing line and the one before it, numbered, with a caret under the
+/// reported column.
+fn excerpt(text: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let line = line.clamp(1, lines.len().max(1));
+    let width = line.to_string().len();
+    let mut out = String::new();
+    for number in line.saturating_sub(1).max(1)..=line {
+        let content = lines.get(number - 1).copied().unwrap_or("");
+        out.push_str(&format!("{number:>width$} | {content}\n"));
+    }
+    out.push_str(&format!(
+        "{:>width$} | {}^",
+        "",
+        " ".repeat(column.saturating_sub(1))
+    ));
+    out
+}
+
+#[rmcp::tool_router(router = "syntax_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Parses a JSON, YAML, or TOML file and reports whether it is valid.
+    #[rmcp::tool(
+        name = "validate_syntax",
+        description = "Checks whether a JSON, YAML, or TOML file parses, picking the parser from the extension (.json, .yaml/.yml, .toml) or the format parameter. Reports the number of top-level keys when valid, or the parser's error with line, column, and the surrounding lines when not. Prefer this to reading the file and judging by eye.",
+        annotations(
+            title = "Validate Syntax",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn validate_syntax(
+        &self,
+        Parameters(params): Parameters<ValidateSyntaxParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let format = match &params.format {
+            Some(name) => SyntaxFormat::parse(name).ok_or_else(|| {
+                format!("Unsupported format '{name}': use json, yaml, or toml")
+            })?,
+            None => SyntaxFormat::from_extension(&canonical).ok_or_else(|| {
+                format!(
+                    "Cannot tell the format of {} from its extension; pass format as json, yaml, or toml",
+                    params.path
+                )
+            })?,
+        };
+
+        let size = tokio::fs::metadata(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        let max = self.config.max_read_size as u64;
+        if size > max {
+            return Err(FsError::FileTooLarge {
+                path: params.path,
+                size,
+                max,
+            }
+            .into());
+        }
+        let content = tokio::fs::read(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(content.len() as u64);
+        if is_binary(&content) {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        }
+        let text =
+            String::from_utf8(content).map_err(|_| format!("Not valid UTF-8: {}", params.path))?;
+
+        let result = spawn_blocking(move || {
+            let result = check_syntax(&text, format);
+            (text, result)
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        Ok(match result {
+            (_, Ok(summary)) => format!("{}: valid {format} ({summary})", params.path),
+            (text, Err(err)) => format!(
+                "{}: invalid {format} at line {}, column {}: {}\n\n{}",
+                params.path,
+                err.line,
+                err.column,
+                err.message,
+                excerpt(&text, err.line, err.column)
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reports_valid_documents() {
+        let cases = [
+            (
+                r#"{"a": 1, "b": [2]}"#,
+                SyntaxFormat::Json,
+                "2 keys at top level",
+            ),
+            (
+                "[1, 2, 3]",
+                SyntaxFormat::Json,
+                "array of 3 items at top level",
+            ),
+            (
+                "a: 1\nb: 2\nc: 3\n",
+                SyntaxFormat::Yaml,
+                "3 keys at top level",
+            ),
+            ("a: 1\n---\nb: 2\n", SyntaxFormat::Yaml, "2 documents"),
+            ("", SyntaxFormat::Yaml, "empty document"),
+            (
+                "name = \"x\"\n[server]\nport = 80\n",
+                SyntaxFormat::Toml,
+                "2 keys at top level",
+            ),
+        ];
+        for (text, format, expected) in cases {
+            assert_eq!(
+                check_syntax(text, format).as_deref(),
+                Ok(expected),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn locates_errors_in_each_format() {
+        let cases = [
+            ("{\n  \"a\": 1,\n  \"b\" 2\n}\n", SyntaxFormat::Json, (3, 7)),
+            ("a: 1\nb: [1, 2\nc: 3\n", SyntaxFormat::Yaml, (3, 2)),
+            ("[server]\nport = = 80\n", SyntaxFormat::Toml, (2, 8)),
+        ];
+        for (text, format, (line, column)) in cases {
+            let err = check_syntax(text, format).unwrap_err();
+            assert_eq!((err.line, err.column), (line, column), "{format}: {err:?}");
+            assert!(!err.message.is_empty());
+            assert!(!err.message.contains(" at line "), "{}", err.message);
+        }
+    }
+
+    #[test]
+    fn excerpt_shows_previous_line_and_caret() {
+        let text = "one\ntwo\nthree\n";
+        assert_eq!(excerpt(text, 3, 2), "2 | two\n3 | three\n  |  ^");
+        assert_eq!(excerpt(text, 1, 1), "1 | one\n  | ^");
+    }
+
+    fn service(dir: &TempDir) -> FilesystemService {
+        FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            max_read_size: 64,
+            ..Default::default()
+        })
+    }
+
+    async fn validate(
+        service: &FilesystemService,
+        path: &Path,
+        format: Option<&str>,
+    ) -> Result<String, ToolError> {
+        service
+            .validate_syntax(Parameters(ValidateSyntaxParams {
+                path: path.to_string_lossy().to_string(),
+                format: format.map(str::to_string),
+            }))
+            .await
+    }
+
+    #[tokio::test]
+    async fn validate_syntax_reports_result() {
+        let dir = TempDir::new().unwrap();
+        let service = service(&dir);
+        let broken = dir.path().join("config.toml");
+        std::fs::write(&broken, "[server]\nport = = 80\n").unwrap();
+        let output = validate(&service, &broken, None).await.unwrap();
+        let expected = ": invalid TOML at line 2, column 8: extra `=`, expected nothing\n\n\
+             1 | [server]\n2 | port = = 80\n  |        ^";
+        assert!(output.ends_with(expected), "{output}");
+
+        let json = dir.path().join("data.txt");
+        std::fs::write(&json, "{\"ok\": true}").unwrap();
+        let output = validate(&service, &json, Some("json")).await.unwrap();
+        assert!(
+            output.ends_with(": valid JSON (1 key at top level)"),
+            "{output}"
+        );
+
+        let err = validate(&service, &json, None).await.unwrap_err();
+        assert!(err.message.starts_with("Cannot tell the format"), "{err}");
+
+        let big = dir.path().join("big.json");
+        std::fs::write(&big, format!("[{}]", "1,".repeat(40) + "1")).unwrap();
+        let err = validate(&service, &big, None).await.unwrap_err();
+        assert_eq!(err.details.unwrap()["kind"], "file_too_large");
+
+        let binary = dir.path().join("blob.yaml");
+        std::fs::write(&binary, b"a: \0\x01").unwrap();
+        let err = validate(&service, &binary, None).await.unwrap_err();
+        assert_eq!(err.details.unwrap()["kind"], "binary_file");
+    }
+}
@@ -0,0 +1,546 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::stats;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use super::util::{BINARY_CHECK_SIZE, format_size, is_binary, spawn_blocking};
+
+/// Sample rows shown when the caller does not ask for a number.
+const DEFAULT_SAMPLE_ROWS: usize = 10;
+
+/// Most sample rows shown, whatever the caller asks for.
+const MAX_SAMPLE_ROWS: usize = 100;
+
+/// Longest cell shown in the sample, in characters; longer cells are cut.
+const MAX_CELL_WIDTH: usize = 30;
+
+/// Lines of the file's head inspected to detect the delimiter.
+const DETECT_LINES: usize = 20;
+
+/// Delimiters recognized by detection, in order of preference on a tie.
+const CANDIDATE_DELIMITERS: [u8; 3] = [b',', b'\t', b';'];
+
+/// Parameters for the preview_table tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct PreviewTableParams {
+    /// Absolute path to the CSV or TSV file
+    path: String,
+    /// Field delimiter: "comma", "tab", "semicolon", or a single character.
+    /// Detected from the first lines when omitted
+    delimiter: Option<String>,
+    /// Number of data rows to show in the sample (default: 10, max: 100)
+    rows: Option<usize>,
+}
+
+/// Type inferred for a column from every scanned value, widening from
+/// `Empty` (no non-blank value seen) through `Int` and `Float` to `Text`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ColumnType {
+    Empty,
+    Int,
+    Float,
+    Text,
+}
+
+impl ColumnType {
+    fn of(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() {
+            ColumnType::Empty
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Int
+        } else if value.parse::<f64>().is_ok() && value.bytes().any(|b| b.is_ascii_digit()) {
+            // The digit check keeps words such as "inf" and "NaN" as text
+            ColumnType::Float
+        } else {
+            ColumnType::Text
+        }
+    }
+}
+
+impl std::fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ColumnType::Empty => "empty",
+            ColumnType::Int => "int",
+            ColumnType::Float => "float",
+            ColumnType::Text => "text",
+        })
+    }
+}
+
+/// Why the scan ended before the end of the file.
+#[derive(Debug, PartialEq)]
+enum ScanStop {
+    /// `--max-table-rows` data rows were scanned
+    RowLimit,
+    /// The file is larger than the max read size
+    ReadLimit,
+}
+
+/// What [`preview_table_sync`] learned about a file.
+#[derive(Debug)]
+struct TablePreview {
+    header: Vec<String>,
+    types: Vec<ColumnType>,
+    /// Well-formed data rows scanned
+    rows: u64,
+    sample: Vec<Vec<String>>,
+    /// Rows whose field count differs from the header's
+    malformed: u64,
+    /// Line and field count of the first malformed row
+    first_malformed: Option<(u64, usize)>,
+    stop: Option<ScanStop>,
+    bytes_read: u64,
+}
+
+/// Parses a delimiter name or single ASCII character.
+fn parse_delimiter(value: &str) -> Result<u8, String> {
+    match value {
+        "comma" | "," => Ok(b','),
+        "tab" | "\t" | "\\t" => Ok(b'\t'),
+        "semicolon" | ";" => Ok(b';'),
+        other if other.len() == 1 && other.is_ascii() => Ok(other.as_bytes()[0]),
+        other => Err(format!(
+            "Invalid delimiter '{other}': use comma, tab, semicolon, or a single ASCII character"
+        )),
+    }
+}
+
+fn delimiter_name(delimiter: u8) -> String {
+    match delimiter {
+        b',' => "comma".to_string(),
+        b'\t' => "tab".to_string(),
+        b';' => "semicolon".to_string(),
+        other => format!("'{}'", other as char),
+    }
+}
+
+/// Picks the candidate delimiter that splits the first lines of `head` most
+/// consistently: the same non-zero count on every line wins, then the highest
+/// total count. Falls back to comma when none appears.
+fn detect_delimiter(head: &[u8]) -> u8 {
+    let mut lines: Vec<&[u8]> = head.split(|&b| b == b'\n').collect();
+    // A full head likely ends mid-line; an unterminated last line is dropped
+    if lines.len() > 1 {
+        lines.pop();
+    }
+    lines.retain(|line| !line.trim_ascii().is_empty());
+    lines.truncate(DETECT_LINES);
+
+    let mut best = (b',', (false, 0));
+    for delimiter in CANDIDATE_DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_unquoted(line, delimiter))
+            .collect();
+        let consistent = counts.first().is_some_and(|&first| first > 0)
+            && counts.iter().all(|&count| count == counts[0]);
+        let score = (consistent, counts.iter().sum());
+        if score > best.1 {
+            best = (delimiter, score);
+        }
+    }
+    best.0
+}
+
+/// Occurrences of `delimiter` in `line` outside double-quoted fields.
+fn count_unquoted(line: &[u8], delimiter: u8) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for &b in line {
+        if b == b'"' {
+            quoted = !quoted;
+        } else if b == delimiter && !quoted {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Streams the table from `reader`: the first record is the header, and data
+/// rows are scanned until the end, `max_rows`, or `max_bytes`. A record cut
+/// off by the byte limit is dropped rather than reported as malformed.
+fn preview_table_sync(
+    reader: impl Read,
+    delimiter: u8,
+    sample_rows: usize,
+    max_rows: u64,
+    max_bytes: u64,
+    file_size: u64,
+) -> Result<TablePreview, std::io::Error> {
+    let mut csv = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader.take(max_bytes));
+    let csv_err = |e: csv::Error| std::io::Error::other(e.to_string());
+
+    let mut record = csv::ByteRecord::new();
+    if !csv.read_byte_record(&mut record).map_err(csv_err)? {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "File is empty",
+        ));
+    }
+    let header: Vec<String> = record
+        .iter()
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect();
+
+    let mut preview = TablePreview {
+        types: vec![ColumnType::Empty; header.len()],
+        header,
+        rows: 0,
+        sample: Vec::new(),
+        malformed: 0,
+        first_malformed: None,
+        stop: None,
+        bytes_read: 0,
+    };
+
+    // Each record is handled one read late so that the last one can be
+    // dropped if the byte limit cut it short
+    let mut pending: Option<csv::ByteRecord> = None;
+    let mut next = csv::ByteRecord::new();
+    loop {
+        let more = csv.read_byte_record(&mut next).map_err(csv_err)?;
+        if let Some(row) = pending.take() {
+            if !more && file_size > max_bytes {
+                preview.stop = Some(ScanStop::ReadLimit);
+                break;
+            }
+            if preview.rows + preview.malformed >= max_rows {
+                preview.stop = Some(ScanStop::RowLimit);
+                break;
+            }
+            scan_row(&mut preview, &row, sample_rows);
+        }
+        if !more {
+            break;
+        }
+        pending = Some(next.clone());
+    }
+    preview.bytes_read = csv.position().byte();
+    Ok(preview)
+}
+
+fn scan_row(preview: &mut TablePreview, row: &csv::ByteRecord, sample_rows: usize) {
+    if row.len() != preview.header.len() {
+        preview.malformed += 1;
+        if preview.first_malformed.is_none() {
+            let line = row.position().map_or(0, |p| p.line());
+            preview.first_malformed = Some((line, row.len()));
+        }
+        return;
+    }
+    let fields: Vec<String> = row
+        .iter()
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect();
+    for (column, value) in preview.types.iter_mut().zip(&fields) {
+        *column = (*column).max(ColumnType::of(value));
+    }
+    preview.rows += 1;
+    if preview.sample.len() < sample_rows {
+        preview.sample.push(fields);
+    }
+}
+
+/// Cuts `cell` to [`MAX_CELL_WIDTH`] characters and flattens line breaks.
+fn display_cell(cell: &str) -> String {
+    let cell = cell.replace(['\r', '\n'], " ");
+    if cell.chars().count() > MAX_CELL_WIDTH {
+        let cut: String = cell.chars().take(MAX_CELL_WIDTH - 1).collect();
+        format!("{cut}…")
+    } else {
+        cell
+    }
+}
+
+/// Lays out the header and sample rows in space-padded columns with a rule
+/// under the header.
+fn format_sample(header: &[String], sample: &[Vec<String>]) -> String {
+    let header: Vec<String> = header.iter().map(|c| display_cell(c)).collect();
+    let rows: Vec<Vec<String>> = sample
+        .iter()
+        .map(|row| row.iter().map(|c| display_cell(c)).collect())
+        .collect();
+    let mut widths: Vec<usize> = header.iter().map(|c| c.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_line = |cells: &[String]| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        line.trim_end().to_string()
+    };
+    let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w.max(1))).collect();
+
+    let mut lines = vec![format_line(&header), format_line(&rule)];
+    lines.extend(rows.iter().map(|row| format_line(row)));
+    lines.join("\n")
+}
+
+#[rmcp::tool_router(router = "table_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Summarizes a CSV or TSV file without reading it whole.
+    #[rmcp::tool(
+        name = "preview_table",
+        description = "Previews a CSV, TSV, or other delimited file: the delimiter (detected or given), header row, column count, inferred column types (int, float, text, empty), data row count, and an aligned sample of the first rows. Rows with the wrong number of fields are counted and reported, not fatal. Scanning stops at --max-table-rows rows or the max read size, so large files are cheap to preview.",
+        annotations(
+            title = "Preview Table",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn preview_table(
+        &self,
+        Parameters(params): Parameters<PreviewTableParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self.security.validate_file(Path::new(&params.path))?;
+        let delimiter = params
+            .delimiter
+            .as_deref()
+            .map(parse_delimiter)
+            .transpose()?;
+        let sample_rows = params
+            .rows
+            .unwrap_or(DEFAULT_SAMPLE_ROWS)
+            .min(MAX_SAMPLE_ROWS);
+        let max_rows = self.config.max_table_rows;
+        let max_bytes = self.config.max_read_size as u64;
+
+        let (result, file_size, detected) = spawn_blocking(move || {
+            let mut file = std::fs::File::open(&canonical)?;
+            let file_size = file.metadata()?.len();
+            let mut head = Vec::with_capacity(BINARY_CHECK_SIZE);
+            (&mut file)
+                .take(BINARY_CHECK_SIZE as u64)
+                .read_to_end(&mut head)?;
+            if is_binary(&head) {
+                return Ok((None, file_size, b','));
+            }
+            let detected = delimiter.unwrap_or_else(|| detect_delimiter(&head));
+            let reader = Cursor::new(head).chain(file);
+            let preview = preview_table_sync(
+                reader,
+                detected,
+                sample_rows,
+                max_rows,
+                max_bytes,
+                file_size,
+            );
+            Ok::<_, std::io::Error>((Some(preview), file_size, detected))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| ToolError::io(e, &params.path))?;
+
+        let Some(preview) = result else {
+            return Err(FsError::BinaryFile { path: params.path }.into());
+        };
+        let preview = preview.map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(preview.bytes_read);
+
+        let mut out = format!(
+            "Path: {}\nDelimiter: {} ({})\nColumns: {}\n",
+            params.path,
+            delimiter_name(detected),
+            if delimiter.is_some() {
+                "given"
+            } else {
+                "detected"
+            },
+            preview.header.len()
+        );
+        match preview.stop {
+            None => out.push_str(&format!("Rows: {} (excluding header)\n", preview.rows)),
+            Some(ScanStop::RowLimit) => out.push_str(&format!(
+                "Rows: ≥{} (stopped after {max_rows} rows; raise --max-table-rows to scan further)\n",
+                preview.rows
+            )),
+            Some(ScanStop::ReadLimit) => out.push_str(&format!(
+                "Rows: ≥{} (stopped at the max read size of {}; the file is {})\n",
+                preview.rows,
+                format_size(max_bytes),
+                format_size(file_size)
+            )),
+        }
+        match preview.first_malformed {
+            None => out.push_str("Malformed rows: none\n"),
+            Some((line, fields)) => out.push_str(&format!(
+                "Malformed rows: {} (first on line {line}: {fields} fields, expected {})\n",
+                preview.malformed,
+                preview.header.len()
+            )),
+        }
+        out.push_str("Column types:\n");
+        for (name, column_type) in preview.header.iter().zip(&preview.types) {
+            out.push_str(&format!("  {}: {column_type}\n", display_cell(name)));
+        }
+        if preview.sample.is_empty() {
+            out.push_str("Sample: none (no well-formed data rows)");
+        } else {
+            out.push_str(&format!(
+                "Sample (first {} rows):\n{}",
+                preview.sample.len(),
+                format_sample(&preview.header, &preview.sample)
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn preview(data: &str, max_rows: u64, max_bytes: u64) -> TablePreview {
+        let delimiter = detect_delimiter(data.as_bytes());
+        preview_table_sync(
+            data.as_bytes(),
+            delimiter,
+            DEFAULT_SAMPLE_ROWS,
+            max_rows,
+            max_bytes,
+            data.len() as u64,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_delimiters() {
+        let cases = [
+            ("a,b,c\n1,2,3\n", b','),
+            ("a\tb\tc\n1\t2\t3\n", b'\t'),
+            ("a;b;c\n1,5;2,5;3\n", b';'),
+            ("\"x,y\";b\n\"1,2\";3\n", b';'),
+            ("single\ncolumn\n", b','),
+            ("", b','),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(detect_delimiter(data.as_bytes()), expected, "{data:?}");
+        }
+    }
+
+    #[test]
+    fn infers_column_types() {
+        let cases = [
+            ("42", ColumnType::Int),
+            ("-7", ColumnType::Int),
+            ("3.5", ColumnType::Float),
+            ("1e3", ColumnType::Float),
+            ("", ColumnType::Empty),
+            ("  ", ColumnType::Empty),
+            ("inf", ColumnType::Text),
+            ("NaN", ColumnType::Text),
+            ("abc", ColumnType::Text),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(ColumnType::of(value), expected, "{value:?}");
+        }
+
+        let table = preview("id,score,name,note\n1,2,a,\n2,2.5,b,\n", 100, 1024);
+        assert_eq!(
+            table.types,
+            [
+                ColumnType::Int,
+                ColumnType::Float,
+                ColumnType::Text,
+                ColumnType::Empty
+            ]
+        );
+        assert_eq!(table.rows, 2);
+    }
+
+    #[test]
+    fn counts_malformed_rows_without_aborting() {
+        let table = preview("a,b\n1,2\n3\n4,5,6\n7,8\n", 100, 1024);
+        assert_eq!(table.rows, 2);
+        assert_eq!(table.malformed, 2);
+        assert_eq!(table.first_malformed, Some((3, 1)));
+        assert_eq!(table.sample, [["1", "2"], ["7", "8"]]);
+    }
+
+    #[test]
+    fn stops_at_row_and_byte_limits() {
+        let table = preview("n\n1\n2\n3\n4\n", 2, 1024);
+        assert_eq!(table.rows, 2);
+        assert_eq!(table.stop, Some(ScanStop::RowLimit));
+
+        let table = preview("n\n1\n2\n3\n4\n", 100, 1024);
+        assert_eq!(table.rows, 4);
+        assert_eq!(table.stop, None);
+
+        // The byte limit falls inside "333,3": that row is dropped, not malformed
+        let table = preview("a,b\n1,1\n22,2\n333,3\n", 100, 16);
+        assert_eq!(table.rows, 2);
+        assert_eq!(table.malformed, 0);
+        assert_eq!(table.stop, Some(ScanStop::ReadLimit));
+    }
+
+    #[test]
+    fn aligns_sample_columns() {
+        let header = vec!["id".to_string(), "name".to_string()];
+        let sample = vec![
+            vec!["1".to_string(), "alice".to_string()],
+            vec!["100".to_string(), "x".repeat(40)],
+        ];
+        let expected = format!(
+            "id   name\n---  {}\n1    alice\n100  {}…",
+            "-".repeat(MAX_CELL_WIDTH),
+            "x".repeat(MAX_CELL_WIDTH - 1)
+        );
+        assert_eq!(format_sample(&header, &sample), expected);
+    }
+
+    #[tokio::test]
+    async fn preview_table_reports_summary() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("scores.tsv");
+        std::fs::write(&file, "name\tscore\nalice\t3.5\nbob\t4\ncarol\n").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![dir.path().canonicalize().unwrap()],
+            ..Default::default()
+        });
+
+        let output = service
+            .preview_table(Parameters(PreviewTableParams {
+                path: file.to_string_lossy().to_string(),
+                delimiter: None,
+                rows: Some(1),
+            }))
+            .await
+            .unwrap();
+        let expected = "\nDelimiter: tab (detected)\nColumns: 2\nRows: 2 (excluding header)\n\
+             Malformed rows: 1 (first on line 4: 1 fields, expected 2)\n\
+             Column types:\n  name: text\n  score: float\n\
+             Sample (first 1 rows):\nname   score\n-----  -----\nalice  3.5";
+        assert!(output.ends_with(expected), "{output}");
+
+        let err = service
+            .preview_table(Parameters(PreviewTableParams {
+                path: file.to_string_lossy().to_string(),
+                delimiter: Some("pipes".to_string()),
+                rows: None,
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.message.starts_with("Invalid delimiter"), "{err}");
+    }
+}
@@ -0,0 +1,762 @@
+use crate::FilesystemService;
+use crate::error::ToolError;
+use crate::security::strip_verbatim_prefix;
+use crate::tools::util::{format_size, format_timestamp, spawn_blocking, to_hex};
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct RestoreFromTrashParams {
+    /// Trash entry identifier as shown by list_trash
+    #[schemars(description = "Trash entry identifier as shown by list_trash")]
+    entry: Option<String>,
+    /// Original absolute path; restores the newest trashed copy of it
+    #[schemars(description = "Original absolute path; restores the newest trashed copy of it")]
+    path: Option<String>,
+    /// Replace an existing file or empty directory at the original location (default: false)
+    #[schemars(
+        description = "Replace an existing file or empty directory at the original location (default: false)"
+    )]
+    overwrite: Option<bool>,
+}
+
+/// One trashed item, found by walking the trash directory.
+struct TrashEntry {
+    /// Path of the item relative to the trash directory: `<timestamp>/<root key>/<relative>`
+    id: PathBuf,
+    /// Root key folder, naming the allowed directory the item came from
+    key: String,
+    /// Path of the item relative to its allowed root
+    relative: PathBuf,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Names the trash folder for an allowed root: its path components joined with `_`,
+/// then a SHA-256 prefix of the full path, e.g. `/home/me/project` becomes
+/// `home_me_project-` and 12 hex digits. The joined name is only for people
+/// browsing the trash; the hash keeps roots such as `/a_b/c` and `/a/b_c` apart.
+pub(crate) fn trash_root_key(root: &Path) -> String {
+    let root = strip_verbatim_prefix(root);
+    let digest = Sha256::digest(root.as_os_str().as_encoded_bytes());
+    let parts: Vec<String> = root
+        .components()
+        .filter_map(|component| match component {
+            Component::Prefix(prefix) => Some(
+                prefix
+                    .as_os_str()
+                    .to_string_lossy()
+                    .replace([':', '\\', '?'], ""),
+            ),
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .filter(|part| !part.is_empty())
+        .collect();
+    let name = if parts.is_empty() {
+        "_".to_string()
+    } else {
+        parts.join("_")
+    };
+    format!("{name}-{}", to_hex(&digest[..6]))
+}
+
+/// Lists everything in `trash`, newest first.
+///
+/// Each timestamp folder holds exactly one trashed item. The item is taken to be
+/// the deepest path below the root key folder that isn't a directory with a single
+/// child, so restoring it recreates the same tree without clobbering siblings
+/// that were never deleted.
+fn list_trash_sync(trash: &Path) -> std::io::Result<Vec<TrashEntry>> {
+    let mut stamps: Vec<_> = std::fs::read_dir(trash)?
+        .filter_map(Result::ok)
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name())
+        .collect();
+    stamps.sort();
+    stamps.reverse();
+
+    let mut entries = Vec::new();
+    for stamp in stamps {
+        for key_entry in std::fs::read_dir(trash.join(&stamp))?.filter_map(Result::ok) {
+            let key = key_entry.file_name().to_string_lossy().into_owned();
+            let mut item = key_entry.path();
+            loop {
+                let meta = std::fs::symlink_metadata(&item)?;
+                if !meta.is_dir() {
+                    break;
+                }
+                let children: Vec<_> = std::fs::read_dir(&item)?
+                    .filter_map(Result::ok)
+                    .take(2)
+                    .collect();
+                match children.as_slice() {
+                    [only] => item = only.path(),
+                    _ => break,
+                }
+            }
+            let meta = std::fs::symlink_metadata(&item)?;
+            let relative = item
+                .strip_prefix(key_entry.path())
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+            entries.push(TrashEntry {
+                id: item.strip_prefix(trash).unwrap_or(&item).to_path_buf(),
+                key,
+                relative,
+                is_dir: meta.is_dir(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Removes now-empty directories from `dir` upwards, stopping at `trash`.
+fn prune_empty_dirs_sync(mut dir: &Path, trash: &Path) {
+    while dir != trash && dir.starts_with(trash) && std::fs::remove_dir(dir).is_ok() {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => break,
+        }
+    }
+}
+
+/// Moves `path` into a new timestamped folder under `trash`, at `relative`
+/// inside it, and returns its new location. Falls back to copy and remove when the trash is on another device.
+pub(crate) fn move_to_trash_sync(
+    path: &Path,
+    relative: &Path,
+    trash: &Path,
+) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(trash)?;
+    let stamp = format_timestamp(std::time::SystemTime::now());
+    let mut folder = trash.join(&stamp);
+    let mut attempt = 1;
+    loop {
+        match std::fs::create_dir(&folder) {
+            Ok(()) => break,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                folder = trash.join(format!("{stamp}-{attempt}"));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    let dest = folder.join(relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::rename(path, &dest) {
+        Ok(()) => Ok(dest),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            copy_then_remove_sync(path, &dest)?;
+            Ok(dest)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Cross-device replacement for `rename`: copies `src` to `dest`, then removes `src`.
+pub(crate) fn copy_then_remove_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    copy_tree_sync(src, dest)?;
+    if std::fs::symlink_metadata(src)?.is_dir() {
+        std::fs::remove_dir_all(src)
+    } else {
+        std::fs::remove_file(src)
+    }
+}
+
+/// Copies a file or directory tree. Symlinks are recreated, never followed.
+fn copy_tree_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(src)?;
+    if meta.file_type().is_symlink() {
+        copy_symlink_sync(src, dest)
+    } else if meta.is_dir() {
+        std::fs::create_dir(dest)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree_sync(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(src, dest).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+}
+
+#[cfg(windows)]
+fn copy_symlink_sync(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let target = std::fs::read_link(src)?;
+    if std::fs::metadata(src).is_ok_and(|m| m.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
+
+impl FilesystemService {
+    /// Fails when `--require-trash` is set but no trash directory is configured.
+    pub(crate) fn check_delete_allowed(&self) -> Result<(), ToolError> {
+        if self.config.require_trash && self.config.trash_dir.is_none() {
+            return Err(
+                "Deletes are disabled: --require-trash is set but no --trash-dir is configured"
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Moves `canonical` into the configured trash directory under
+    /// `<timestamp>/<root key>/<path relative to its root>`. Returns `None` when
+    /// no trash is configured.
+    pub(crate) async fn move_to_trash(
+        &self,
+        canonical: &Path,
+    ) -> Result<Option<PathBuf>, ToolError> {
+        let Some(trash) = self.config.trash_dir.clone() else {
+            return Ok(None);
+        };
+        let (root, relative) = self
+            .security
+            .split_allowed(canonical)
+            .ok_or_else(|| format!("Access denied: {}", canonical.display()))?;
+        let in_stamp = Path::new(&trash_root_key(&root)).join(relative);
+        let source = canonical.to_path_buf();
+        spawn_blocking(move || move_to_trash_sync(&source, &in_stamp, &trash))
+            .await
+            .map_err(|e| e.to_string())?
+            .map(Some)
+            .map_err(|e| ToolError::io(e, &canonical.display().to_string()))
+    }
+
+    /// Maps a trash root key back to the allowed directory it was made from.
+    fn root_for_trash_key(&self, key: &str) -> Option<PathBuf> {
+        self.security
+            .allowed_dirs()
+            .into_iter()
+            .find(|root| trash_root_key(root) == key)
+    }
+}
+
+#[rmcp::tool_router(router = "trash_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Lists items in the trash directory, newest first.
+    #[rmcp::tool(
+        name = "list_trash",
+        description = "Lists items moved to the trash directory by delete operations, newest first. Each line shows the entry identifier to pass to restore_from_trash and the original path.",
+        annotations(
+            title = "List Trash",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn list_trash(&self) -> Result<String, ToolError> {
+        let Some(trash) = self.config.trash_dir.clone() else {
+            return Err("No trash directory is configured".to_string().into());
+        };
+        let listing = trash.clone();
+        let entries = spawn_blocking(move || list_trash_sync(&listing))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| ToolError::io(e, &trash.display().to_string()))?;
+        if entries.is_empty() {
+            return Ok(format!("Trash: {} (empty)", trash.display()));
+        }
+
+        let mut output = format!(
+            "Trash: {} ({} entr{}, newest first)\n",
+            trash.display(),
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" }
+        );
+        for entry in &entries {
+            let original = match self.root_for_trash_key(&entry.key) {
+                Some(root) => root.join(&entry.relative).display().to_string(),
+                None => format!("(unknown root '{}')", entry.key),
+            };
+            let kind = if entry.is_dir {
+                "[DIR] ".to_string()
+            } else {
+                format!("[FILE] ({}) ", format_size(entry.size))
+            };
+            output.push_str(&format!("\n{kind}{} -> {original}", entry.id.display()));
+        }
+        Ok(output)
+    }
+
+    /// Moves a trashed item back to where it was deleted from.
+    #[rmcp::tool(
+        name = "restore_from_trash",
+        description = "Restores an item from the trash directory to its original location. Pass either 'entry' (an identifier from list_trash) or 'path' (the original absolute path, restoring the newest trashed copy). Refuses to replace an existing file unless overwrite: true; a non-empty directory is never replaced.",
+        annotations(
+            title = "Restore from Trash",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn restore_from_trash(
+        &self,
+        Parameters(params): Parameters<RestoreFromTrashParams>,
+    ) -> Result<String, ToolError> {
+        let Some(trash) = self.config.trash_dir.clone() else {
+            return Err("No trash directory is configured".to_string().into());
+        };
+
+        let (trashed, original) = match (&params.entry, &params.path) {
+            (Some(entry), None) => {
+                let id = Path::new(entry);
+                if !id.components().all(|c| matches!(c, Component::Normal(_))) {
+                    return Err(format!("Invalid trash entry: {entry}").into());
+                }
+                let mut parts = id.components();
+                let (Some(_stamp), Some(key)) = (parts.next(), parts.next()) else {
+                    return Err(format!("Invalid trash entry: {entry}").into());
+                };
+                let key = key.as_os_str().to_string_lossy();
+                let root = self.root_for_trash_key(&key).ok_or_else(|| {
+                    format!("Trash entry {entry} is not from an allowed directory")
+                })?;
+                let trashed = trash.join(id);
+                if tokio::fs::symlink_metadata(&trashed).await.is_err() {
+                    return Err(format!("Trash entry not found: {entry}").into());
+                }
+                (trashed, root.join(parts.as_path()))
+            }
+            (None, Some(path)) => {
+                let original = self.security.validate_creatable_path(Path::new(path))?;
+                let (root, relative) = self
+                    .security
+                    .split_allowed(&original)
+                    .ok_or_else(|| format!("Access denied: {path}"))?;
+                let in_stamp = Path::new(&trash_root_key(&root)).join(relative);
+                let listing = trash.clone();
+                let mut stamps: Vec<PathBuf> = spawn_blocking(move || {
+                    std::fs::read_dir(&listing)
+                        .map(|dir| dir.filter_map(|e| e.ok().map(|e| e.path())).collect())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| ToolError::io(e, &trash.display().to_string()))?;
+                stamps.sort();
+                let trashed = stamps
+                    .iter()
+                    .rev()
+                    .map(|stamp| stamp.join(&in_stamp))
+                    .find(|candidate| std::fs::symlink_metadata(candidate).is_ok())
+                    .ok_or_else(|| format!("No trashed copy of {path} found"))?;
+                (trashed, original)
+            }
+            _ => {
+                return Err("Pass exactly one of 'entry' or 'path'".to_string().into());
+            }
+        };
+
+        // Re-check the destination so deny patterns and read-only directories
+        // apply to restores too
+        let original = self.security.validate_creatable_path(&original)?;
+        self.security.ensure_writable(&original, &original)?;
+
+        let mut replaced = None;
+        if let Ok(meta) = tokio::fs::symlink_metadata(&original).await {
+            if !params.overwrite.unwrap_or(false) {
+                return Err(format!(
+                    "{} already exists. Pass overwrite: true to replace it.",
+                    original.display()
+                )
+                .into());
+            }
+            if meta.is_dir() && std::fs::read_dir(&original).is_ok_and(|mut d| d.next().is_some()) {
+                return Err(format!(
+                    "{} is a non-empty directory. Refusing to overwrite it.",
+                    original.display()
+                )
+                .into());
+            }
+            self.check_delete_allowed()?;
+            replaced = self.move_to_trash(&original).await?;
+        }
+
+        let (from, to, trash_root) = (trashed.clone(), original.clone(), trash.clone());
+        spawn_blocking(move || -> std::io::Result<()> {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match std::fs::rename(&from, &to) {
+                Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                    copy_then_remove_sync(&from, &to)?
+                }
+                other => other?,
+            }
+            if let Some(parent) = from.parent() {
+                prune_empty_dirs_sync(parent, &trash_root);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| ToolError::io(e, &original.display().to_string()))?;
+
+        let id = trashed
+            .strip_prefix(&trash)
+            .unwrap_or(&trashed)
+            .display()
+            .to_string();
+        Ok(match replaced {
+            Some(moved) => format!(
+                "Restored {} from trash entry {id} (previous item moved to trash at {})",
+                original.display(),
+                moved.display()
+            ),
+            None => format!("Restored {} from trash entry {id}", original.display()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use tempfile::TempDir;
+
+    fn make_service(root: &Path, trash: &Path) -> FilesystemService {
+        let config = Config::builder()
+            .allowed_directory(root)
+            .allow_destructive(true)
+            .trash_dir(trash)
+            .build()
+            .unwrap();
+        FilesystemService::new(config)
+    }
+
+    fn restore_params(
+        entry: Option<&str>,
+        path: Option<&Path>,
+        overwrite: Option<bool>,
+    ) -> RestoreFromTrashParams {
+        RestoreFromTrashParams {
+            entry: entry.map(str::to_string),
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            overwrite,
+        }
+    }
+
+    /// Pulls the first entry identifier out of list_trash output.
+    fn first_entry_id(listing: &str) -> String {
+        let line = listing.lines().nth(2).unwrap();
+        let start = line.rfind(") ").map(|i| i + 2).unwrap_or("[DIR] ".len());
+        line[start..line.find(" -> ").unwrap()].to_string()
+    }
+
+    #[test]
+    fn trash_tools_registered_only_with_write_and_trash_dir() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let names = |service: FilesystemService| -> Vec<String> {
+            service
+                .tool_router
+                .list_all()
+                .into_iter()
+                .map(|t| t.name.to_string())
+                .collect()
+        };
+        let with_trash = names(make_service(dir.path(), trash.path()));
+        assert!(with_trash.contains(&"list_trash".to_string()));
+        assert!(with_trash.contains(&"restore_from_trash".to_string()));
+
+        let read_only = Config::builder()
+            .allowed_directory(dir.path())
+            .trash_dir(trash.path())
+            .build()
+            .unwrap();
+        assert!(!names(FilesystemService::new(read_only)).contains(&"list_trash".to_string()));
+
+        let no_trash = Config::builder()
+            .allowed_directory(dir.path())
+            .allow_write(true)
+            .build()
+            .unwrap();
+        assert!(!names(FilesystemService::new(no_trash)).contains(&"list_trash".to_string()));
+    }
+
+    #[test]
+    fn trash_root_key_joins_components_and_hashes_path() {
+        let key = trash_root_key(Path::new("/home/me/project"));
+        let (name, hash) = key.rsplit_once('-').unwrap();
+        assert_eq!(name, "home_me_project");
+        assert_eq!(hash.len(), 12);
+        assert!(trash_root_key(Path::new("/")).starts_with("_-"));
+        assert_ne!(
+            trash_root_key(Path::new("/a_b/c")),
+            trash_root_key(Path::new("/a/b_c"))
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_by_entry_keeps_colliding_roots_apart() {
+        let base = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let first = base.path().join("a_b").join("c");
+        let second = base.path().join("a").join("b_c");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        let config = Config::builder()
+            .allowed_directory(&first)
+            .allowed_directory(&second)
+            .allow_destructive(true)
+            .trash_dir(trash.path())
+            .build()
+            .unwrap();
+        let service = FilesystemService::new(config);
+        let (first, second) = (
+            first.canonicalize().unwrap(),
+            second.canonicalize().unwrap(),
+        );
+
+        for (root, content) in [(&first, "first"), (&second, "second")] {
+            let file = root.join("notes.txt");
+            std::fs::write(&file, content).unwrap();
+            service.move_to_trash(&file).await.unwrap().unwrap();
+        }
+        for _ in 0..2 {
+            let listing = service.list_trash().await.unwrap();
+            let id = first_entry_id(&listing);
+            service
+                .restore_from_trash(Parameters(restore_params(Some(&id), None, None)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(first.join("notes.txt")).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            std::fs::read_to_string(second.join("notes.txt")).unwrap(),
+            "second"
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_then_restore_by_path_round_trips_content() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        let file = root.join("src").join("lib.rs");
+        std::fs::write(&file, "pub fn answer() -> u32 { 42 }").unwrap();
+
+        service.move_to_trash(&file).await.unwrap().unwrap();
+        assert!(!file.exists());
+
+        let result = service
+            .restore_from_trash(Parameters(restore_params(None, Some(&file), None)))
+            .await
+            .unwrap();
+        assert!(result.starts_with(&format!("Restored {}", file.display())));
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "pub fn answer() -> u32 { 42 }"
+        );
+        // Emptied timestamp folders are cleaned up
+        assert_eq!(std::fs::read_dir(trash.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn list_then_restore_by_entry_recreates_missing_parents() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        let root = dir.path().canonicalize().unwrap();
+        let build = root.join("build");
+        std::fs::create_dir_all(build.join("obj")).unwrap();
+        std::fs::write(build.join("obj").join("a.o"), "object").unwrap();
+        std::fs::write(build.join("log.txt"), "log").unwrap();
+
+        service.move_to_trash(&build).await.unwrap().unwrap();
+        let listing = service.list_trash().await.unwrap();
+        assert!(listing.contains("(1 entry, newest first)"), "{listing}");
+        assert!(
+            listing.contains(&format!("-> {}", build.display())),
+            "{listing}"
+        );
+
+        let id = first_entry_id(&listing);
+        service
+            .restore_from_trash(Parameters(restore_params(Some(&id), None, None)))
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(build.join("obj").join("a.o")).unwrap(),
+            "object"
+        );
+        assert_eq!(
+            std::fs::read_to_string(build.join("log.txt")).unwrap(),
+            "log"
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_picks_newest_copy() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        let file = dir.path().canonicalize().unwrap().join("notes.txt");
+        for version in ["v1", "v2"] {
+            std::fs::write(&file, version).unwrap();
+            service.move_to_trash(&file).await.unwrap().unwrap();
+        }
+        service
+            .restore_from_trash(Parameters(restore_params(None, Some(&file), None)))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "v2");
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_existing_file_unless_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        let file = dir.path().canonicalize().unwrap().join("notes.txt");
+        std::fs::write(&file, "old").unwrap();
+        service.move_to_trash(&file).await.unwrap().unwrap();
+        std::fs::write(&file, "new").unwrap();
+
+        let err = service
+            .restore_from_trash(Parameters(restore_params(None, Some(&file), None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("already exists"), "{err}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+
+        let result = service
+            .restore_from_trash(Parameters(restore_params(None, Some(&file), Some(true))))
+            .await
+            .unwrap();
+        assert!(result.contains("previous item moved to trash"), "{result}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_bad_arguments() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        let file = dir.path().join("x.txt");
+
+        let err = service
+            .restore_from_trash(Parameters(restore_params(None, None, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("exactly one"));
+        let err = service
+            .restore_from_trash(Parameters(restore_params(Some("a"), Some(&file), None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("exactly one"));
+        let err = service
+            .restore_from_trash(Parameters(restore_params(
+                Some("../../etc/passwd"),
+                None,
+                None,
+            )))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid trash entry"));
+        let err = service
+            .restore_from_trash(Parameters(restore_params(None, Some(&file), None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("No trashed copy"));
+    }
+
+    #[tokio::test]
+    async fn list_trash_empty() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        let service = make_service(dir.path(), trash.path());
+        assert!(service.list_trash().await.unwrap().ends_with("(empty)"));
+    }
+
+    #[test]
+    fn move_to_trash_uses_a_fresh_folder_per_call() {
+        let dir = TempDir::new().unwrap();
+        let trash = TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt"] {
+            std::fs::write(dir.path().join(name), name).unwrap();
+        }
+        let first = move_to_trash_sync(&dir.path().join("a.txt"), Path::new("a.txt"), trash.path())
+            .unwrap();
+        let second =
+            move_to_trash_sync(&dir.path().join("b.txt"), Path::new("b.txt"), trash.path())
+                .unwrap();
+        assert_ne!(first.parent(), second.parent());
+        assert!(first.exists() && second.exists());
+    }
+
+    #[test]
+    fn copy_then_remove_moves_file() {
+        let src_dir = TempDir::new().unwrap();
+        let dest_dir = TempDir::new().unwrap();
+        let src = src_dir.path().join("f.txt");
+        std::fs::write(&src, "payload").unwrap();
+        let dest = dest_dir.path().join("f.txt");
+        copy_then_remove_sync(&src, &dest).unwrap();
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(dest).unwrap(), "payload");
+    }
+
+    #[test]
+    fn copy_then_remove_moves_tree() {
+        let src_dir = TempDir::new().unwrap();
+        let dest_dir = TempDir::new().unwrap();
+        let src = src_dir.path().join("tree");
+        std::fs::create_dir_all(src.join("a").join("b")).unwrap();
+        std::fs::write(src.join("top.txt"), "1").unwrap();
+        std::fs::write(src.join("a").join("b").join("deep.txt"), "2").unwrap();
+        let dest = dest_dir.path().join("tree");
+        copy_then_remove_sync(&src, &dest).unwrap();
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(dest.join("top.txt")).unwrap(), "1");
+        assert_eq!(
+            std::fs::read_to_string(dest.join("a").join("b").join("deep.txt")).unwrap(),
+            "2"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_then_remove_recreates_symlinks_without_following() {
+        let src_dir = TempDir::new().unwrap();
+        let dest_dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("big.bin"), "external").unwrap();
+        let src = src_dir.path().join("tree");
+        std::fs::create_dir(&src).unwrap();
+        std::os::unix::fs::symlink(outside.path(), src.join("link")).unwrap();
+
+        let dest = dest_dir.path().join("tree");
+        copy_then_remove_sync(&src, &dest).unwrap();
+        let link = dest.join("link");
+        assert!(
+            std::fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(std::fs::read_link(&link).unwrap(), outside.path());
+        assert!(outside.path().join("big.bin").exists());
+    }
+}
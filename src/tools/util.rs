@@ -1,5 +1,34 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
 use std::time::SystemTime;
 
+/// Number of leading bytes checked for null bytes when detecting binary files.
+pub const BINARY_CHECK_SIZE: usize = 8192;
+
+/// Whether a file whose contents start with `head` should be treated as
+/// binary: a null byte within the first [`BINARY_CHECK_SIZE`] bytes.
+pub fn is_binary(head: &[u8]) -> bool {
+    head[..head.len().min(BINARY_CHECK_SIZE)].contains(&0)
+}
+
+/// Chunk size used when streaming file contents through a hasher.
+pub const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Runs `f` on the blocking pool inside the caller's tracing span, so events
+/// from blocking sections are attributed to the tool call that started them.
+pub fn spawn_blocking<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
+
 /// Format a byte count as a human-readable size string.
 pub fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
@@ -24,6 +53,31 @@ pub fn format_date(time: SystemTime) -> String {
     format!("{y:04}-{m:02}-{d:02}")
 }
 
+/// Seconds from the Unix epoch to `time`, or `None` for earlier times.
+pub fn unix_seconds(time: SystemTime) -> Option<u64> {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// Format a SystemTime as a filename-safe UTC timestamp,
+/// e.g. `2026-10-17T09-30-00.123456789`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (y, m, d) = civil_from_days((secs / 86400) as i32);
+    let rem = secs % 86400;
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}-{:02}-{:02}.{:09}",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        since_epoch.subsec_nanos()
+    )
+}
+
 /// Howard Hinnant's civil_from_days algorithm.
 /// Converts days since 1970-01-01 to (year, month, day).
 fn civil_from_days(days: i32) -> (i32, u32, u32) {
@@ -40,6 +94,44 @@ fn civil_from_days(days: i32) -> (i32, u32, u32) {
     (y, m, d)
 }
 
+/// Smallest shortfall of allocated against apparent size reported as sparse.
+const SPARSE_MIN_GAP: u64 = 64 * 1024;
+
+/// Bytes a file occupies on disk, from its count of 512-byte blocks. `None`
+/// where the platform does not report blocks.
+pub fn allocated_size(metadata: &std::fs::Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.blocks() * 512)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Whether a file of `apparent` bytes occupying `allocated` bytes is sparse:
+/// under 90% allocated and short by at least 64 KB, so block rounding and
+/// small files never qualify.
+pub fn is_sparse(apparent: u64, allocated: u64) -> bool {
+    allocated < apparent / 10 * 9 && apparent - allocated >= SPARSE_MIN_GAP
+}
+
+/// Format a file size, adding the allocated size when the file is sparse,
+/// e.g. "10.0 GB (allocated: 1.2 GB, sparse)".
+pub fn format_size_allocated(apparent: u64, allocated: Option<u64>) -> String {
+    match allocated {
+        Some(allocated) if is_sparse(apparent, allocated) => format!(
+            "{} (allocated: {}, sparse)",
+            format_size(apparent),
+            format_size(allocated)
+        ),
+        _ => format_size(apparent),
+    }
+}
+
 /// Format filesystem permissions as a string.
 pub fn format_permissions(metadata: &std::fs::Metadata) -> String {
     #[cfg(unix)]
@@ -57,10 +149,373 @@ pub fn format_permissions(metadata: &std::fs::Metadata) -> String {
     }
 }
 
+/// Compute the SHA-256 of a file as a lowercase hex string.
+/// Streams the file in fixed-size chunks so large files are never held in memory.
+pub fn sha256_file(path: &std::path::Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    stream_file(path, HASH_CHUNK_SIZE, |chunk| hasher.update(chunk))?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Feed a file to `update` in chunks of at most `chunk_size` bytes, returning
+/// the number of bytes read.
+pub fn stream_file(
+    path: &std::path::Path,
+    chunk_size: usize,
+    mut update: impl FnMut(&[u8]),
+) -> std::io::Result<u64> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buf = vec![0u8; chunk_size.max(1)];
+    let mut total = 0;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        update(&buf[..n]);
+        total += n as u64;
+    }
+    Ok(total)
+}
+
+/// Sibling of `path` that [`write_atomic_sync`] writes before renaming it into
+/// place: `.{name}.ironbeard-tmp-{pid}`.
+pub fn atomic_temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.ironbeard-tmp-{}", std::process::id()))
+}
+
+/// Replaces `path` with `data` by writing [`atomic_temp_path`], syncing it to
+/// disk, and renaming it into place, so an interrupted write never leaves a
+/// truncated file. An existing file's permissions are kept, and the temporary
+/// file is removed if any step fails.
+pub fn write_atomic_sync(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    // Windows refuses to rename onto a file that is open elsewhere
+    write_atomic_with(
+        path,
+        data,
+        |from, to| std::fs::rename(from, to),
+        cfg!(windows),
+    )
+}
+
+/// [`write_atomic_sync`] with the rename step supplied by the caller. With
+/// `copy_fallback`, a failed rename onto an existing file is retried by
+/// copying the synced temporary file over it; the replacement is then no
+/// longer atomic, but the target never goes missing.
+fn write_atomic_with(
+    path: &Path,
+    data: &[u8],
+    rename: impl Fn(&Path, &Path) -> std::io::Result<()>,
+    copy_fallback: bool,
+) -> std::io::Result<()> {
+    let temp = atomic_temp_path(path);
+    let result = (|| {
+        let permissions = std::fs::metadata(path).ok().map(|m| m.permissions());
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)?;
+        file.write_all(data)?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()?;
+        drop(file);
+        match rename(&temp, path) {
+            Err(e) if copy_fallback && path.is_file() => {
+                std::fs::copy(&temp, path).map_err(|_| e)?;
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(path)?
+                    .sync_all()?;
+                std::fs::remove_file(&temp)
+            }
+            renamed => renamed,
+        }
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Cut `text` to at most `max_bytes` on a UTF-8 boundary and append a note with
+/// the cap and `hint` on how to get the rest. Returns true if it was truncated.
+pub fn truncate_output(text: &mut String, max_bytes: usize, hint: &str) -> bool {
+    if text.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(&format!(
+        "\n\u{2026} [output truncated at {}, {hint}]",
+        format_size(max_bytes as u64)
+    ));
+    true
+}
+
+/// Normalize a list of file extensions: trims whitespace, strips leading dots,
+/// lowercases, and drops empty and duplicate entries (first occurrence wins).
+pub fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for ext in extensions {
+        let ext = ext.trim().trim_start_matches('.').to_lowercase();
+        if !ext.is_empty() && !normalized.contains(&ext) {
+            normalized.push(ext);
+        }
+    }
+    normalized
+}
+
+/// Resolve the extension filter for a tool call. A per-call list overrides the
+/// configured default; an empty per-call list disables filtering.
+pub fn resolve_extension_filter(
+    configured: &[String],
+    requested: Option<&[String]>,
+) -> Vec<String> {
+    match requested {
+        Some(exts) => normalize_extensions(exts),
+        None => configured.to_vec(),
+    }
+}
+
+/// Returns true if the path passes the extension filter. An empty filter matches everything.
+pub fn matches_extension_filter(path: &std::path::Path, extensions: &[String]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| extensions.contains(&e))
+}
+
+/// The line ending convention used by a text file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix-style `\n` (also used for files with no line breaks).
+    Lf,
+    /// Windows-style `\r\n`.
+    CrLf,
+    /// Both styles are present.
+    Mixed,
+}
+
+impl LineEnding {
+    /// The canonical separator for this style. Mixed files use `\n`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Lf | LineEnding::Mixed => "\n",
+        }
+    }
+}
+
+/// Detect the line ending style of a text.
+pub fn detect_line_endings(text: &str) -> LineEnding {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    match (crlf, lf) {
+        (0, _) => LineEnding::Lf,
+        (_, 0) => LineEnding::CrLf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// Rewrite every line break in `text` to the given style.
+pub fn normalize_line_endings(text: &str, style: LineEnding) -> String {
+    let unix = text.replace("\r\n", "\n");
+    match style {
+        LineEnding::CrLf => unix.replace('\n', "\r\n"),
+        LineEnding::Lf | LineEnding::Mixed => unix,
+    }
+}
+
+/// Encode bytes as a lowercase hex string.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Format an entry name for display. A name that is not valid UTF-8 is shown
+/// lossily and followed by its raw bytes, "(non-UTF-8 name, hex: …)", since
+/// the lossy form cannot be passed back to a tool.
+pub fn display_name(name: &OsStr) -> String {
+    match name.to_str() {
+        Some(name) => name.to_string(),
+        None => format!(
+            "{} (non-UTF-8 name, hex: {})",
+            name.to_string_lossy(),
+            to_hex(name.as_encoded_bytes())
+        ),
+    }
+}
+
+/// Format a path for display so it can be passed back to a tool: components
+/// that are not valid UTF-8 are written as `{hex:…}` with their raw bytes.
+pub fn display_path(path: &Path) -> String {
+    let mut escaped = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) if name.to_str().is_none() => {
+                escaped.push(format!("{{hex:{}}}", to_hex(name.as_encoded_bytes())));
+            }
+            other => escaped.push(other),
+        }
+    }
+    escaped.display().to_string()
+}
+
+/// Most paths named per reason when summarizing skipped entries.
+const MAX_SKIPPED_SHOWN: usize = 10;
+
+/// Why a listing or walk left an entry out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The process is not allowed to read it
+    PermissionDenied,
+    /// It disappeared between being listed and being read
+    Vanished,
+    /// Any other I/O error
+    Unreadable,
+}
+
+impl SkipReason {
+    pub fn of(err: &std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::PermissionDenied => SkipReason::PermissionDenied,
+            std::io::ErrorKind::NotFound => SkipReason::Vanished,
+            _ => SkipReason::Unreadable,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::PermissionDenied => "permission denied",
+            SkipReason::Vanished => "removed during the walk",
+            SkipReason::Unreadable => "unreadable",
+        }
+    }
+}
+
+/// An entry left out of a listing, as reported in structured output.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SkippedPath {
+    /// Path relative to the listed directory, starting with "./"
+    pub path: String,
+    /// "permission denied", "removed during the walk", or "unreadable"
+    pub reason: String,
+}
+
+/// Entries a listing or walk could not read. They are still left out, but
+/// reported so a missing entry is not mistaken for one that does not exist.
+#[derive(Debug, Default)]
+pub struct SkippedEntries {
+    entries: Vec<(PathBuf, SkipReason, bool)>,
+}
+
+impl SkippedEntries {
+    /// Records `path`, skipped because of `err`; `directory` tells whether it
+    /// is known to be a directory.
+    pub fn record(&mut self, path: PathBuf, err: &std::io::Error, directory: bool) {
+        self.entries.push((path, SkipReason::of(err), directory));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Skipped entries with paths relative to `root`, for structured output.
+    pub fn report(&self, root: &Path) -> Vec<SkippedPath> {
+        self.entries
+            .iter()
+            .map(|(path, reason, _)| SkippedPath {
+                path: relative_display(root, path),
+                reason: reason.label().to_string(),
+            })
+            .collect()
+    }
+
+    /// One-line summary grouped by reason, e.g. `2 directories skipped:
+    /// permission denied: ./secrets, ./locked`, or `None` if nothing was skipped.
+    pub fn summary(&self, root: &Path) -> Option<String> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let count = self.entries.len();
+        let noun = match (self.entries.iter().all(|(_, _, dir)| *dir), count == 1) {
+            (true, true) => "directory",
+            (true, false) => "directories",
+            (false, true) => "entry",
+            (false, false) => "entries",
+        };
+        let mut groups = Vec::new();
+        for reason in [
+            SkipReason::PermissionDenied,
+            SkipReason::Vanished,
+            SkipReason::Unreadable,
+        ] {
+            let paths: Vec<String> = self
+                .entries
+                .iter()
+                .filter(|(_, r, _)| *r == reason)
+                .map(|(path, _, _)| relative_display(root, path))
+                .collect();
+            if paths.is_empty() {
+                continue;
+            }
+            let mut shown = paths[..paths.len().min(MAX_SKIPPED_SHOWN)].join(", ");
+            if paths.len() > MAX_SKIPPED_SHOWN {
+                shown.push_str(&format!(" and {} more", paths.len() - MAX_SKIPPED_SHOWN));
+            }
+            groups.push(format!("{}: {shown}", reason.label()));
+        }
+        Some(format!("{count} {noun} skipped: {}", groups.join("; ")))
+    }
+}
+
+/// `path` below `root` as `./relative`, or in full if it is not below `root`.
+fn relative_display(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(relative) => format!("./{}", display_path(relative)),
+        Err(_) => display_path(path),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn skipped_entries_summary_groups_by_reason() {
+        let root = Path::new("/data");
+        let mut skipped = SkippedEntries::default();
+        assert_eq!(skipped.summary(root), None);
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        skipped.record(root.join("secrets"), &denied, true);
+        skipped.record(root.join("locked"), &denied, true);
+        assert_eq!(
+            skipped.summary(root).unwrap(),
+            "2 directories skipped: permission denied: ./secrets, ./locked"
+        );
+
+        let gone = std::io::Error::from(std::io::ErrorKind::NotFound);
+        skipped.record(root.join("tmp/x.log"), &gone, false);
+        assert_eq!(
+            skipped.summary(root).unwrap(),
+            "3 entries skipped: permission denied: ./secrets, ./locked; removed during the walk: ./tmp/x.log"
+        );
+        let report = skipped.report(root);
+        assert_eq!(report[2].path, "./tmp/x.log");
+        assert_eq!(report[2].reason, "removed during the walk");
+    }
+
     #[test]
     fn format_size_bytes() {
         assert_eq!(format_size(0), "0 B");
@@ -79,6 +534,37 @@ mod tests {
         assert_eq!(format_size(1_048_576), "1.0 MB");
     }
 
+    #[test]
+    fn format_size_allocated_flags_only_material_gaps() {
+        let gb = 1024 * 1024 * 1024;
+        assert_eq!(
+            format_size_allocated(10 * gb, Some(gb + gb / 5)),
+            "10.0 GB (allocated: 1.2 GB, sparse)"
+        );
+        // Block rounding, small files, and unknown allocation show one size
+        assert_eq!(format_size_allocated(100, Some(4096)), "100 B");
+        assert_eq!(format_size_allocated(70_000, Some(8192)), "68.4 KB");
+        assert_eq!(format_size_allocated(1_048_576, Some(1_000_000)), "1.0 MB");
+        assert_eq!(format_size_allocated(10 * gb, None), "10.0 GB");
+        assert!(!is_sparse(0, 0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_show_their_bytes() {
+        use std::os::unix::ffi::OsStrExt;
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        assert_eq!(
+            display_name(name),
+            "caf\u{FFFD}.txt (non-UTF-8 name, hex: 636166e92e747874)"
+        );
+        assert_eq!(display_name(OsStr::new("café.txt")), "café.txt");
+        assert_eq!(
+            display_path(&Path::new("/data").join(name).join("a.txt")),
+            "/data/{hex:636166e92e747874}/a.txt"
+        );
+    }
+
     #[test]
     fn format_date_epoch() {
         let epoch = std::time::UNIX_EPOCH;
@@ -92,4 +578,146 @@ mod tests {
         let time = std::time::UNIX_EPOCH + Duration::from_secs(946684800);
         assert_eq!(format_date(time), "2000-01-01");
     }
+
+    #[test]
+    fn format_timestamp_known() {
+        use std::time::Duration;
+        // 2000-01-01 12:34:56.000000007 UTC
+        let time = std::time::UNIX_EPOCH + Duration::new(946730096, 7);
+        assert_eq!(format_timestamp(time), "2000-01-01T12-34-56.000000007");
+    }
+
+    #[test]
+    fn truncate_output_leaves_short_text() {
+        let mut text = "short".to_string();
+        assert!(!truncate_output(&mut text, 100, "hint"));
+        assert_eq!(text, "short");
+    }
+
+    #[test]
+    fn truncate_output_cuts_on_char_boundary() {
+        // Each 'é' is two bytes, so a 5-byte cap falls inside the third one
+        let mut text = "ééééé".to_string();
+        assert!(truncate_output(&mut text, 5, "use offset/limit"));
+        assert!(text.starts_with("éé\n"));
+        assert!(text.ends_with("[output truncated at 5 B, use offset/limit]"));
+    }
+
+    #[test]
+    fn normalize_extensions_strips_dots_and_dedups() {
+        let input = vec![
+            ".Rs".to_string(),
+            "TOML".to_string(),
+            "rs".to_string(),
+            " ".to_string(),
+        ];
+        assert_eq!(normalize_extensions(&input), vec!["rs", "toml"]);
+    }
+
+    #[test]
+    fn extension_filter_matching() {
+        let exts = vec!["rs".to_string()];
+        assert!(matches_extension_filter(
+            std::path::Path::new("main.RS"),
+            &exts
+        ));
+        assert!(!matches_extension_filter(
+            std::path::Path::new("README"),
+            &exts
+        ));
+        assert!(matches_extension_filter(
+            std::path::Path::new("README"),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn detect_line_endings_styles() {
+        assert_eq!(detect_line_endings("a\nb\n"), LineEnding::Lf);
+        assert_eq!(detect_line_endings("a\r\nb\r\n"), LineEnding::CrLf);
+        assert_eq!(detect_line_endings("a\r\nb\n"), LineEnding::Mixed);
+        assert_eq!(detect_line_endings("no breaks"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn normalize_line_endings_round_trip() {
+        assert_eq!(normalize_line_endings("a\r\nb\n", LineEnding::Lf), "a\nb\n");
+        assert_eq!(
+            normalize_line_endings("a\r\nb\n", LineEnding::CrLf),
+            "a\r\nb\r\n"
+        );
+    }
+
+    #[test]
+    fn sha256_file_known_digest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("abc.txt");
+        std::fs::write(&file, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&file).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn write_atomic_sync_replaces_and_keeps_permissions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("config.toml");
+        std::fs::write(&file, "old = true\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        write_atomic_sync(&file, b"new = true\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new = true\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        write_atomic_sync(&dir.path().join("new.txt"), b"fresh").unwrap();
+
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names.len(), 2, "temporary file left behind: {names:?}");
+    }
+
+    #[test]
+    fn write_atomic_failed_rename_removes_temp_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "original").unwrap();
+        let refuse = |_: &Path, _: &Path| Err(std::io::Error::other("rename refused"));
+
+        let err = write_atomic_with(&file, b"replacement", refuse, false).unwrap_err();
+        assert_eq!(err.to_string(), "rename refused");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "original");
+        assert!(!atomic_temp_path(&file).exists());
+    }
+
+    #[test]
+    fn write_atomic_copy_fallback_when_rename_over_existing_fails() {
+        // What Windows does when the target is open in another process
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "original").unwrap();
+        let refuse =
+            |_: &Path, _: &Path| Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+
+        write_atomic_with(&file, b"replacement", refuse, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "replacement");
+        assert!(!atomic_temp_path(&file).exists());
+
+        // A new file has nothing to copy over, so the rename error stands
+        let missing = dir.path().join("missing.txt");
+        let err = write_atomic_with(&missing, b"x", refuse, true).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(!missing.exists());
+        assert!(!atomic_temp_path(&missing).exists());
+    }
 }
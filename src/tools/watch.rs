@@ -0,0 +1,546 @@
+use crate::FilesystemService;
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rmcp::handler::server::wrapper::Parameters;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+use super::compare::push_group;
+use super::count::{Visit, walk_sync};
+use super::util::{spawn_blocking, to_hex};
+
+/// Longest file-list token handed out; larger trees get a timestamp token.
+const MAX_TOKEN_LEN: usize = 32 * 1024;
+
+/// Most bytes a file-list token may inflate to, so a crafted token cannot
+/// exhaust memory.
+const MAX_STATE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Prefix of a token carrying the full file list.
+const LIST_PREFIX: &str = "f1.";
+
+/// Prefix of a token carrying only the scan time.
+const SINCE_PREFIX: &str = "t1.";
+
+/// Parameters for the watch_changes tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct WatchChangesParams {
+    /// Absolute path to the directory to watch
+    path: String,
+    /// Token returned by the previous watch_changes call on this directory;
+    /// omit to record a baseline
+    token: Option<String>,
+}
+
+/// Size and modification time (nanoseconds since the epoch) per relative path.
+type FileState = BTreeMap<String, (u64, u64)>;
+
+/// The state a token carries from one call to the next.
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// Every file's size and mtime at `scanned`
+    List {
+        root: String,
+        scanned: u64,
+        files: FileState,
+    },
+    /// Only the time of the scan
+    Since { root: String, scanned: u64 },
+}
+
+/// Short fingerprint of the watched root, so a token is not used on another
+/// directory.
+fn root_id(root: &Path) -> String {
+    to_hex(&Sha256::digest(root.to_string_lossy().as_bytes()))[..16].to_string()
+}
+
+fn nanos_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Escapes backslashes and line breaks so each path fits on one line.
+fn escape_path(path: &str) -> String {
+    path.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape_path(path: &str) -> Option<String> {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+impl Token {
+    fn root(&self) -> &str {
+        match self {
+            Token::List { root, .. } | Token::Since { root, .. } => root,
+        }
+    }
+
+    fn scanned(&self) -> u64 {
+        match self {
+            Token::List { scanned, .. } | Token::Since { scanned, .. } => *scanned,
+        }
+    }
+
+    /// A list token is a deflated, URL-safe base64 payload of a header line
+    /// and one "size\tmtime\tpath" line per file; a since token is plain text.
+    fn encode(&self) -> String {
+        match self {
+            Token::Since { root, scanned } => format!("{SINCE_PREFIX}{root}.{scanned}"),
+            Token::List {
+                root,
+                scanned,
+                files,
+            } => {
+                let mut payload = format!("{root}\t{scanned}\n");
+                for (path, (size, modified)) in files {
+                    payload.push_str(&format!("{size}\t{modified}\t{}\n", escape_path(path)));
+                }
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+                // Writing to a Vec cannot fail
+                let _ = encoder.write_all(payload.as_bytes());
+                let compressed = encoder.finish().unwrap_or_default();
+                format!("{LIST_PREFIX}{}", URL_SAFE_NO_PAD.encode(compressed))
+            }
+        }
+    }
+
+    fn decode(token: &str) -> Option<Token> {
+        if let Some(rest) = token.strip_prefix(SINCE_PREFIX) {
+            let (root, scanned) = rest.split_once('.')?;
+            return Some(Token::Since {
+                root: root.to_string(),
+                scanned: scanned.parse().ok()?,
+            });
+        }
+        let compressed = URL_SAFE_NO_PAD
+            .decode(token.strip_prefix(LIST_PREFIX)?)
+            .ok()?;
+        let mut payload = String::new();
+        flate2::read::DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_STATE_BYTES)
+            .read_to_string(&mut payload)
+            .ok()?;
+        let mut lines = payload.lines();
+        let (root, scanned) = lines.next()?.split_once('\t')?;
+        let mut files = FileState::new();
+        for line in lines {
+            let mut fields = line.splitn(3, '\t');
+            let size = fields.next()?.parse().ok()?;
+            let modified = fields.next()?.parse().ok()?;
+            files.insert(unescape_path(fields.next()?)?, (size, modified));
+        }
+        Some(Token::List {
+            root: root.to_string(),
+            scanned: scanned.parse().ok()?,
+            files,
+        })
+    }
+}
+
+/// Files under `root` and whether the scan stopped at the entry budget.
+struct Scan {
+    files: FileState,
+    truncated: bool,
+}
+
+/// Records the size and mtime of every regular file under `root`, up to
+/// `max_depth` levels and `limit` visited entries. Symlinks are skipped.
+fn scan_files(
+    root: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    limit: u64,
+    ct: &CancellationToken,
+) -> Result<Scan, FsError> {
+    let mut scan = Scan {
+        files: FileState::new(),
+        truncated: false,
+    };
+    let mut visited = 0;
+    walk_sync(
+        root,
+        security,
+        max_depth,
+        ct,
+        |_| {},
+        |path, file_type| {
+            visited += 1;
+            if visited > limit {
+                scan.truncated = true;
+                return Visit::Stop;
+            }
+            if !file_type.is_file() {
+                return Visit::Continue;
+            }
+            let Ok(metadata) = std::fs::symlink_metadata(path) else {
+                return Visit::Continue;
+            };
+            let modified = metadata.modified().map_or(0, nanos_since_epoch);
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let key = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            scan.files.insert(key, (metadata.len(), modified));
+            Visit::Continue
+        },
+    )?;
+    Ok(scan)
+}
+
+/// Files added, removed, and modified between two scans. With only a scan
+/// time to go on, every file modified since is listed under `modified` and
+/// nothing is known about removals.
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    added: Vec<String>,
+    removed: Vec<String>,
+    modified: Vec<String>,
+}
+
+fn diff_against_list(previous: &FileState, current: &FileState) -> Changes {
+    let mut changes = Changes::default();
+    for (path, state) in current {
+        match previous.get(path) {
+            None => changes.added.push(path.clone()),
+            Some(old) if old != state => changes.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = previous
+        .keys()
+        .filter(|path| !current.contains_key(*path))
+        .cloned()
+        .collect();
+    changes
+}
+
+fn modified_since(scanned: u64, current: &FileState) -> Changes {
+    Changes {
+        modified: current
+            .iter()
+            .filter(|(_, (_, modified))| *modified >= scanned)
+            .map(|(path, _)| path.clone())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[rmcp::tool_router(router = "watch_tools_router", vis = "pub(crate)")]
+impl FilesystemService {
+    /// Reports files changed since the previous call's token.
+    #[rmcp::tool(
+        name = "watch_changes",
+        description = "Answers \"what changed since I last looked\" for a directory without server-side state. Call without a token to get a baseline token; call again with it to list files added, removed, or modified (by size and modification time) since then, plus a new token for the next call. For trees too large to fit in a token, or scans stopped at --max-count-entries entries, the token holds only the scan time and the report lists files modified since then (removals cannot be seen), which the output says. Walks up to --max-depth levels; symlinks are skipped.",
+        annotations(
+            title = "Watch Changes",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn watch_changes(
+        &self,
+        Parameters(params): Parameters<WatchChangesParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let root = self.security.validate_directory(Path::new(&params.path))?;
+        let id = root_id(&root);
+        let previous = match &params.token {
+            None => None,
+            Some(token) => {
+                let token = Token::decode(token.trim()).ok_or(
+                    "Invalid watch token; call watch_changes without a token to record a new baseline",
+                )?;
+                if token.root() != id {
+                    return Err(format!(
+                        "This token was issued for a different directory than {}",
+                        params.path
+                    )
+                    .into());
+                }
+                Some(token)
+            }
+        };
+
+        let scanned = nanos_since_epoch(SystemTime::now());
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let limit = self.config.max_count_entries;
+        let walk_root = root.clone();
+        let scan = spawn_blocking(move || scan_files(&walk_root, &security, max_depth, limit, &ct))
+            .await
+            .map_err(|e| e.to_string())??;
+
+        let mut out = String::new();
+        match &previous {
+            None => out.push_str(&format!(
+                "Baseline recorded for {}: {} files.\n",
+                params.path,
+                scan.files.len()
+            )),
+            Some(token) => {
+                let changes = match token {
+                    Token::List { files, .. } if !scan.truncated => {
+                        out.push_str(&format!(
+                            "Changes in {} since the last token:\n",
+                            params.path
+                        ));
+                        diff_against_list(files, &scan.files)
+                    }
+                    _ => {
+                        out.push_str(&format!(
+                            "Files in {} modified or added since the last token (timestamp-based; removed files are not reported):\n",
+                            params.path
+                        ));
+                        modified_since(token.scanned(), &scan.files)
+                    }
+                };
+                push_group(&mut out, "Added", &changes.added);
+                push_group(&mut out, "Removed", &changes.removed);
+                push_group(&mut out, "Modified", &changes.modified);
+                if changes == Changes::default() {
+                    out.push_str("No changes.\n");
+                }
+                out.push_str(&format!("\nScanned {} files.\n", scan.files.len()));
+            }
+        }
+
+        let list_token = (!scan.truncated)
+            .then(|| {
+                Token::List {
+                    root: id.clone(),
+                    scanned,
+                    files: scan.files,
+                }
+                .encode()
+            })
+            .filter(|token| token.len() <= MAX_TOKEN_LEN);
+        match list_token {
+            Some(token) => out.push_str(&format!("Next token:\n{token}")),
+            None => {
+                let reason = if scan.truncated {
+                    format!("the scan stopped at --max-count-entries ({limit})")
+                } else {
+                    "the file list is too large for a token".to_string()
+                };
+                let token = Token::Since { root: id, scanned }.encode();
+                out.push_str(&format!(
+                    "Next token (timestamp-based because {reason}; the next call will not report removed files):\n{token}"
+                ));
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn tokens_round_trip() {
+        let mut files = FileState::new();
+        files.insert("src/a.rs".to_string(), (10, 1_700_000_000_000_000_000));
+        files.insert("odd\\name\nwith break\tand tab".to_string(), (0, 5));
+        let tokens = [
+            Token::List {
+                root: "0123456789abcdef".to_string(),
+                scanned: 42,
+                files,
+            },
+            Token::Since {
+                root: "0123456789abcdef".to_string(),
+                scanned: 42,
+            },
+        ];
+        for token in tokens {
+            assert_eq!(Token::decode(&token.encode()), Some(token));
+        }
+        for bad in [
+            "",
+            "f1.!!!",
+            "f1.AAAA",
+            "t1.abc",
+            "t1.abc.notanumber",
+            "x1.abc",
+        ] {
+            assert_eq!(Token::decode(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn diffs_file_lists() {
+        let previous = FileState::from([
+            ("kept".to_string(), (1, 1)),
+            ("gone".to_string(), (1, 1)),
+            ("grown".to_string(), (1, 1)),
+            ("touched".to_string(), (1, 1)),
+        ]);
+        let current = FileState::from([
+            ("kept".to_string(), (1, 1)),
+            ("grown".to_string(), (2, 1)),
+            ("touched".to_string(), (1, 2)),
+            ("new".to_string(), (1, 1)),
+        ]);
+        assert_eq!(
+            diff_against_list(&previous, &current),
+            Changes {
+                added: vec!["new".to_string()],
+                removed: vec!["gone".to_string()],
+                modified: vec!["grown".to_string(), "touched".to_string()],
+            }
+        );
+        assert_eq!(
+            modified_since(2, &current).modified,
+            ["touched".to_string()]
+        );
+    }
+
+    fn last_line(output: &str) -> String {
+        output.lines().last().unwrap().to_string()
+    }
+
+    async fn watch(service: &FilesystemService, root: &Path, token: Option<String>) -> String {
+        service
+            .watch_changes(
+                Parameters(WatchChangesParams {
+                    path: root.to_string_lossy().to_string(),
+                    token,
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap()
+    }
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn watch_changes_reports_since_token() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("keep.txt"), "keep").unwrap();
+        std::fs::write(root.join("edit.txt"), "before").unwrap();
+        std::fs::write(root.join("drop.txt"), "drop").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![root.clone()],
+            ..Default::default()
+        });
+
+        let baseline = watch(&service, &root, None).await;
+        assert!(baseline.contains(": 3 files.\n"), "{baseline}");
+        let token = last_line(&baseline);
+        assert!(token.starts_with(LIST_PREFIX), "{token}");
+
+        let unchanged = watch(&service, &root, Some(token.clone())).await;
+        assert!(unchanged.contains("No changes.\n"), "{unchanged}");
+
+        std::fs::write(root.join("edit.txt"), "after!").unwrap();
+        std::fs::remove_file(root.join("drop.txt")).unwrap();
+        std::fs::write(root.join("new.txt"), "new").unwrap();
+        let output = watch(&service, &root, Some(token)).await;
+        assert!(
+            output.contains(
+                "Added (1):\n  new.txt\n\nRemoved (1):\n  drop.txt\n\nModified (1):\n  edit.txt\n\nScanned 3 files."
+            ),
+            "{output}"
+        );
+
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let err = service
+            .watch_changes(
+                Parameters(WatchChangesParams {
+                    path: root.join("sub").to_string_lossy().to_string(),
+                    token: Some(last_line(&output)),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("different directory"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn watch_changes_falls_back_to_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        for name in ["a", "b", "c"] {
+            std::fs::write(root.join(name), name).unwrap();
+            set_modified(&root.join(name), old);
+        }
+
+        // A budget of 2 entries cannot hold the tree, so the token is timestamp-based
+        let small = FilesystemService::new(Config {
+            allowed_directories: vec![root.clone()],
+            max_count_entries: 2,
+            ..Default::default()
+        });
+        let baseline = watch(&small, &root, None).await;
+        assert!(
+            baseline
+                .contains("timestamp-based because the scan stopped at --max-count-entries (2)"),
+            "{baseline}"
+        );
+        assert!(last_line(&baseline).starts_with(SINCE_PREFIX));
+
+        // A timestamp token reports files modified since, whatever the budget
+        let token = Token::Since {
+            root: root_id(&root),
+            scanned: nanos_since_epoch(old + Duration::from_secs(60)),
+        }
+        .encode();
+        std::fs::write(root.join("a"), "changed").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![root.clone()],
+            ..Default::default()
+        });
+        let output = watch(&service, &root, Some(token)).await;
+        assert!(
+            output.contains(
+                "(timestamp-based; removed files are not reported):\n\nModified (1):\n  a\n"
+            ),
+            "{output}"
+        );
+        // The tree fits, so the next token carries the file list again
+        assert!(last_line(&output).starts_with(LIST_PREFIX), "{output}");
+    }
+}
@@ -1,11 +1,36 @@
 use crate::FilesystemService;
-use crate::error::{FsError, io_error_message};
+use crate::error::{FsError, ToolError};
+use crate::security::SecurityContext;
+use crate::stats;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use regex::Regex;
 use rmcp::handler::server::wrapper::Parameters;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use similar::TextDiff;
-
-use super::util::format_size;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+use super::count::{Visit, walk_sync};
+use super::util::{
+    atomic_temp_path, detect_line_endings, format_size, normalize_line_endings, spawn_blocking,
+    write_atomic_sync,
+};
+
+/// How edit_file locates old_text in the file.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum MatchMode {
+    /// Byte-for-byte
+    #[default]
+    Exact,
+    /// Runs of spaces and tabs compare equal to a single space, and trailing
+    /// whitespace on a line is ignored
+    WhitespaceInsensitive,
+}
 
 /// A single text replacement within a file.
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -14,6 +39,25 @@ struct EditOperation {
     old_text: String,
     /// The text to replace it with
     new_text: String,
+    /// Convert line breaks in old_text and new_text to the file's line ending style before matching (default: true)
+    #[schemars(
+        description = "Convert line breaks in old_text and new_text to the file's line ending style before matching (default: true)"
+    )]
+    normalize_line_endings_for_match: Option<bool>,
+    /// How to locate old_text: "exact" (default) or "whitespace_insensitive",
+    /// which treats runs of spaces and tabs as one space and ignores trailing
+    /// whitespace on a line; the matched range of the file is replaced as is
+    #[schemars(
+        description = "How to locate old_text: \"exact\" (default) or \"whitespace_insensitive\", which treats runs of spaces and tabs as one space and ignores trailing whitespace on a line; the matched range of the file is replaced as is"
+    )]
+    match_mode: Option<MatchMode>,
+    /// Treat old_text as a regular expression; new_text may refer to its
+    /// capture groups as $1 or ${name}. Cannot be combined with
+    /// match_mode "whitespace_insensitive" (default: false)
+    #[schemars(
+        description = "Treat old_text as a regular expression; new_text may refer to its capture groups as $1 or ${name}. Cannot be combined with match_mode \"whitespace_insensitive\" (default: false)"
+    )]
+    use_regex: Option<bool>,
 }
 
 /// Parameters for the edit_file tool.
@@ -23,6 +67,11 @@ struct EditFileParams {
     path: String,
     /// List of edit operations to apply sequentially
     edits: Vec<EditOperation>,
+    /// Check the edits and return the diff without writing the file (default: false)
+    #[schemars(
+        description = "Check the edits and return the diff without writing the file (default: false)"
+    )]
+    dry_run: Option<bool>,
 }
 
 /// Parameters for the write_file tool.
@@ -34,6 +83,31 @@ struct WriteFileParams {
     content: String,
 }
 
+/// Parameters for the write_file_base64 tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct WriteFileBase64Params {
+    /// Absolute path to the file to create or overwrite
+    path: String,
+    /// The file's bytes as standard base64; whitespace and line breaks are ignored
+    content_base64: String,
+}
+
+/// Parameters for the touch_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct TouchFileParams {
+    /// Absolute path to the file to create or mark as modified now
+    path: String,
+}
+
+/// Parameters for the append_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct AppendFileParams {
+    /// Absolute path to the existing file to append to
+    path: String,
+    /// The content to add at the end of the file
+    content: String,
+}
+
 /// Parameters for the create_directory tool.
 #[derive(Deserialize, Serialize, JsonSchema)]
 struct CreateDirectoryParams {
@@ -41,32 +115,262 @@ struct CreateDirectoryParams {
     path: String,
 }
 
+/// Parameters for the create_hardlink tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CreateHardlinkParams {
+    /// Absolute path to the existing file
+    source: String,
+    /// Absolute path of the new link; must not exist yet
+    destination: String,
+}
+
+/// Parameters for the copy_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CopyFileParams {
+    /// Absolute path to the file to copy
+    source: String,
+    /// Absolute path of the copy; must not exist yet
+    destination: String,
+}
+
+/// Parameters for the copy_directory tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct CopyDirectoryParams {
+    /// Absolute path to the directory to copy
+    source: String,
+    /// Absolute path of the copy; must not exist yet and must not be inside
+    /// source
+    destination: String,
+}
+
+/// Parameters for the truncate_file tool.
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct TruncateFileParams {
+    /// Absolute path to the file to truncate
+    path: String,
+    /// New length in bytes (default: 0)
+    length: Option<u64>,
+    /// Allow a length larger than the file, zero-filling the extension
+    /// (default: false)
+    #[schemars(
+        description = "Allow a length larger than the file, zero-filling the extension (default: false)"
+    )]
+    allow_extend: Option<bool>,
+    /// Abort unless the file is exactly this many bytes
+    #[schemars(description = "Abort unless the file is exactly this many bytes")]
+    expected_size: Option<u64>,
+}
+
+/// `text` with each run of spaces and tabs collapsed to one space and runs
+/// ending a line dropped, along with the byte range of `text` that each byte
+/// of the result stands for.
+fn collapse_whitespace(text: &str) -> (String, Vec<Range<usize>>) {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut spans = Vec::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == ' ' || c == '\t' {
+            let mut end = start + 1;
+            while let Some(&(i, ' ' | '\t')) = chars.peek() {
+                end = i + 1;
+                chars.next();
+            }
+            if !matches!(chars.peek(), None | Some((_, '\n' | '\r'))) {
+                collapsed.push(' ');
+                spans.push(start..end);
+            }
+        } else {
+            collapsed.push(c);
+            let end = start + c.len_utf8();
+            spans.extend(std::iter::repeat_n(start..end, c.len_utf8()));
+        }
+    }
+    (collapsed, spans)
+}
+
+/// Byte ranges of `content` where `needle` occurs, found as [`MatchMode`]
+/// describes. Ranges do not overlap.
+fn find_matches(content: &str, needle: &str, mode: MatchMode) -> Vec<Range<usize>> {
+    match mode {
+        MatchMode::Exact => content
+            .match_indices(needle)
+            .map(|(start, m)| start..start + m.len())
+            .collect(),
+        MatchMode::WhitespaceInsensitive => {
+            let (haystack, spans) = collapse_whitespace(content);
+            let (needle, _) = collapse_whitespace(needle);
+            if needle.is_empty() {
+                return Vec::new();
+            }
+            haystack
+                .match_indices(&needle)
+                .map(|(start, m)| spans[start].start..spans[start + m.len() - 1].end)
+                .collect()
+        }
+    }
+}
+
+/// What [`copy_tree_sync`] copied and left out.
+#[derive(Debug, Default, PartialEq)]
+struct TreeCopy {
+    files: u64,
+    /// Directories created below the destination root
+    dirs: u64,
+    bytes: u64,
+    /// Symlinks, which are not copied or followed
+    symlinks: u64,
+    /// Entries denied on either side
+    denied: u64,
+    /// Directories deeper than `max_depth`, left out with their contents
+    too_deep: u64,
+}
+
+/// Copies the tree under `source` into the new directory `destination`, up
+/// to `max_depth` levels. The source is walked in full before anything is
+/// created. Fails with [`FsError::Cancelled`] if `ct` fires; what was copied
+/// by then is left in place.
+fn copy_tree_sync(
+    source: &Path,
+    destination: &Path,
+    security: &SecurityContext,
+    max_depth: usize,
+    ct: &CancellationToken,
+) -> Result<TreeCopy, ToolError> {
+    let mut copy = TreeCopy::default();
+    let mut denied = 0;
+    let mut entries: Vec<(PathBuf, bool)> = Vec::new();
+    walk_sync(
+        source,
+        security,
+        max_depth,
+        ct,
+        |_| denied += 1,
+        |path, file_type| {
+            if file_type.is_symlink() {
+                copy.symlinks += 1;
+                return Visit::Skip;
+            }
+            let depth = path
+                .strip_prefix(source)
+                .map_or(0, |r| r.components().count());
+            if file_type.is_dir() && depth > max_depth {
+                copy.too_deep += 1;
+                return Visit::Skip;
+            }
+            entries.push((path.to_path_buf(), file_type.is_dir()));
+            Visit::Continue
+        },
+    )?;
+    copy.denied = denied;
+
+    let io_error = |e, path: &Path| ToolError::io(e, &path.display().to_string());
+    std::fs::create_dir_all(destination).map_err(|e| io_error(e, destination))?;
+    // Directories denied on the destination side, left out with their contents
+    let mut denied_dirs: Vec<PathBuf> = Vec::new();
+    for (path, is_dir) in entries {
+        if ct.is_cancelled() {
+            return Err(FsError::Cancelled.into());
+        }
+        let Ok(relative) = path.strip_prefix(source) else {
+            continue;
+        };
+        let target = destination.join(relative);
+        if denied_dirs.iter().any(|d| target.starts_with(d)) {
+            continue;
+        }
+        if security.is_denied(&target) {
+            copy.denied += 1;
+            if is_dir {
+                denied_dirs.push(target);
+            }
+            continue;
+        }
+        if is_dir {
+            std::fs::create_dir(&target).map_err(|e| io_error(e, &target))?;
+            copy.dirs += 1;
+        } else {
+            copy.bytes += std::fs::copy(&path, &target).map_err(|e| io_error(e, &path))?;
+            copy.files += 1;
+        }
+    }
+    Ok(copy)
+}
+
+/// Identifies the inode behind a path, e.g. "inode 1234 on device 66305, 2 links".
+#[cfg(unix)]
+fn describe_inode(metadata: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    format!(
+        "inode {} on device {}, {} links",
+        metadata.ino(),
+        metadata.dev(),
+        metadata.nlink()
+    )
+}
+
+#[cfg(not(unix))]
+fn describe_inode(_metadata: &std::fs::Metadata) -> String {
+    "inode not available on this platform".to_string()
+}
+
 #[rmcp::tool_router(router = "write_tools_router", vis = "pub(crate)")]
 impl FilesystemService {
     /// Applies a sequence of exact-text replacements to a file and returns a unified diff.
     #[rmcp::tool(
         name = "edit_file",
-        description = "Applies a sequence of exact-text replacements to a file. Each edit must match exactly one location. Returns a unified diff of all changes.",
-        annotations(read_only_hint = false, destructive_hint = false)
+        description = "Applies a sequence of exact-text replacements to a file. Each edit must match exactly one location; with match_mode \"whitespace_insensitive\", runs of spaces and tabs and trailing whitespace are ignored when locating it. With use_regex, old_text is a regular expression that must likewise match exactly once, and new_text may use $1-style capture group references. Line breaks in old_text and new_text are converted to the file's line ending style unless normalize_line_endings_for_match is false. Returns a unified diff of all changes. With dry_run: true, runs the same checks and returns the diff, prefixed with \"[DRY RUN] \", without writing the file.",
+        annotations(
+            title = "Edit File",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
     )]
     async fn edit_file(
         &self,
         Parameters(params): Parameters<EditFileParams>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ToolError> {
         let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_file(path)
-            .map_err(|e| e.to_string())?;
+        let canonical = self.security.validate_file(path)?;
+        self.security.ensure_writable(&canonical, path)?;
 
         let original = tokio::fs::read_to_string(&canonical)
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        stats::record_read(original.len() as u64);
 
         let mut content = original.clone();
+        let line_ending = detect_line_endings(&original);
 
         for edit in &params.edits {
-            let count = content.matches(&edit.old_text).count();
+            let (old_text, new_text) = if edit.normalize_line_endings_for_match.unwrap_or(true) {
+                (
+                    normalize_line_endings(&edit.old_text, line_ending),
+                    normalize_line_endings(&edit.new_text, line_ending),
+                )
+            } else {
+                (edit.old_text.clone(), edit.new_text.clone())
+            };
+            let regex = if edit.use_regex.unwrap_or(false) {
+                if edit.match_mode.unwrap_or_default() != MatchMode::Exact {
+                    return Err(
+                        "use_regex cannot be combined with match_mode \"whitespace_insensitive\""
+                            .into(),
+                    );
+                }
+                Some(
+                    Regex::new(&old_text)
+                        .map_err(|e| FsError::PatternError(format!("old_text regex: {e}")))?,
+                )
+            } else {
+                None
+            };
+            let matches: Vec<Range<usize>> = match &regex {
+                Some(regex) => regex.find_iter(&content).map(|m| m.range()).collect(),
+                None => find_matches(&content, &old_text, edit.match_mode.unwrap_or_default()),
+            };
+            let count = matches.len();
             if count == 0 {
                 return Err(FsError::EditFailed {
                     path: params.path.clone(),
@@ -74,32 +378,58 @@ impl FilesystemService {
                         "old_text not found: {:?}",
                         edit.old_text.chars().take(80).collect::<String>()
                     ),
+                    match_count: 0,
                 }
-                .to_string());
+                .into());
             }
             if count > 1 {
+                let lines: Vec<String> = matches
+                    .iter()
+                    .map(|m| (content[..m.start].matches('\n').count() + 1).to_string())
+                    .collect();
                 return Err(FsError::EditFailed {
                     path: params.path.clone(),
                     reason: format!(
-                        "old_text matches {} locations (must be unique): {:?}",
+                        "old_text matches {} locations (must be unique), starting on lines {}: {:?}",
                         count,
+                        lines.join(", "),
                         edit.old_text.chars().take(80).collect::<String>()
                     ),
+                    match_count: count,
                 }
-                .to_string());
+                .into());
             }
-            content = content.replacen(&edit.old_text, &edit.new_text, 1);
+            // The only match is also the first, so its captures are the
+            // leftmost ones
+            let replacement = match regex.as_ref().and_then(|r| r.captures(&content)) {
+                Some(captures) => {
+                    let mut expanded = String::new();
+                    captures.expand(&new_text, &mut expanded);
+                    expanded
+                }
+                None => new_text,
+            };
+            content.replace_range(matches[0].clone(), &replacement);
         }
 
-        tokio::fs::write(&canonical, &content)
-            .await
-            .map_err(|e| io_error_message(e, &params.path))?;
-
         let diff = TextDiff::from_lines(&original, &content);
         let unified = diff
             .unified_diff()
             .header(&params.path, &params.path)
             .to_string();
+        if params.dry_run.unwrap_or(false) {
+            return Ok(format!(
+                "[DRY RUN] would apply {} edit(s) to {} (no changes were made)\n\n{}",
+                params.edits.len(),
+                canonical.display(),
+                unified,
+            ));
+        }
+
+        let written = content.len() as u64;
+        self.write_atomic(&canonical, &params.path, content.into_bytes())
+            .await?;
+        stats::record_written(written);
 
         Ok(format!(
             "Applied {} edit(s) to {}\n\n{}",
@@ -112,53 +442,462 @@ impl FilesystemService {
     /// Creates or overwrites a file with the given content.
     #[rmcp::tool(
         name = "write_file",
-        description = "Creates a new file or overwrites an existing file with the provided content. Parent directory must already exist.",
-        annotations(read_only_hint = false, destructive_hint = true)
+        description = "Creates a new file or overwrites an existing file with the provided content. Parent directory must already exist. The content is written to a temporary file and renamed into place, so an interrupted write never leaves a truncated file.",
+        annotations(
+            title = "Write File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn write_file(
         &self,
         Parameters(params): Parameters<WriteFileParams>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ToolError> {
+        let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_writable_path(path)?;
+
+        let size = params.content.len() as u64;
+        self.write_atomic(&canonical, &params.path, params.content.into_bytes())
+            .await?;
+
+        stats::record_written(size);
+        Ok(format!(
+            "Wrote {} to {}",
+            format_size(size),
+            canonical.display(),
+        ))
+    }
+
+    /// Creates or overwrites a file with bytes decoded from base64.
+    #[rmcp::tool(
+        name = "write_file_base64",
+        description = "Creates a new file or overwrites an existing file with raw bytes given as standard base64 in content_base64, for binary content that write_file cannot carry. Whitespace and line breaks in the base64 are ignored; invalid base64 is rejected without touching the file. Parent directory must already exist.",
+        annotations(
+            title = "Write File from Base64",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn write_file_base64(
+        &self,
+        Parameters(params): Parameters<WriteFileBase64Params>,
+    ) -> Result<String, ToolError> {
         let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_writable_path(path)?;
+
+        let encoded: String = params
+            .content_base64
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        let content = STANDARD
+            .decode(&encoded)
+            .map_err(|e| format!("Invalid base64 in content_base64: {e}"))?;
+
+        let size = content.len() as u64;
+        self.write_atomic(&canonical, &params.path, content).await?;
+
+        stats::record_written(size);
+        Ok(format!(
+            "Wrote {} to {}",
+            format_size(size),
+            canonical.display(),
+        ))
+    }
+
+    /// Adds content to the end of an existing file.
+    #[rmcp::tool(
+        name = "append_file",
+        description = "Appends content to the end of a file without reading or rewriting it, e.g. to add log entries or notes. Creates the file if it does not exist. Reports the bytes appended and the file's new size. No newline is added, so include one in content where needed.",
+        annotations(
+            title = "Append to File",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn append_file(
+        &self,
+        Parameters(params): Parameters<AppendFileParams>,
+    ) -> Result<String, ToolError> {
         let canonical = self
             .security
-            .validate_path(path)
-            .map_err(|e| e.to_string())?;
+            .validate_writable_path(std::path::Path::new(&params.path))?;
 
-        tokio::fs::write(&canonical, &params.content)
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        file.write_all(params.content.as_bytes())
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        file.flush()
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        let total = file
+            .metadata()
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
 
         let size = params.content.len() as u64;
+        stats::record_written(size);
         Ok(format!(
-            "Wrote {} to {}",
-            format_size(size),
+            "Appended {size} bytes to {} (now {})",
             canonical.display(),
+            format_size(total)
         ))
     }
 
+    /// Creates an empty file, or sets an existing file's modification time to now.
+    #[rmcp::tool(
+        name = "touch_file",
+        description = "Like touch: creates an empty file if none exists, or sets an existing file's modification time to now without changing its contents. Useful for placeholder files and freshness markers. Parent directory must already exist.",
+        annotations(
+            title = "Touch File",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn touch_file(
+        &self,
+        Parameters(params): Parameters<TouchFileParams>,
+    ) -> Result<String, ToolError> {
+        let canonical = self
+            .security
+            .validate_writable_path(std::path::Path::new(&params.path))?;
+
+        let target = canonical.clone();
+        let created = spawn_blocking(move || {
+            match std::fs::metadata(&target) {
+                Ok(metadata) if metadata.is_dir() => Err(FsError::NotAFile {
+                    path: target.display().to_string(),
+                }),
+                Ok(_) => {
+                    std::fs::OpenOptions::new()
+                        .write(true)
+                        .open(&target)?
+                        .set_modified(std::time::SystemTime::now())?;
+                    Ok(false)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    // create_new so a file appearing meanwhile is not truncated
+                    std::fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&target)?;
+                    Ok(true)
+                }
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| match e {
+            FsError::IoError(e) => ToolError::io(e, &params.path),
+            e => e.into(),
+        })?;
+
+        Ok(if created {
+            format!("Created {}", canonical.display())
+        } else {
+            format!("Updated mtime of {}", canonical.display())
+        })
+    }
+
     /// Creates a directory (and any necessary parent directories).
     #[rmcp::tool(
         name = "create_directory",
         description = "Creates a directory and any necessary parent directories (like mkdir -p). Succeeds silently if the directory already exists.",
-        annotations(read_only_hint = false, destructive_hint = false)
+        annotations(
+            title = "Create Directory",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
     )]
     async fn create_directory(
         &self,
         Parameters(params): Parameters<CreateDirectoryParams>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ToolError> {
         let path = std::path::Path::new(&params.path);
-        let canonical = self
-            .security
-            .validate_creatable_path(path)
-            .map_err(|e| e.to_string())?;
+        let canonical = self.security.validate_creatable_path(path)?;
+        self.security.ensure_writable(&canonical, path)?;
 
         tokio::fs::create_dir_all(&canonical)
             .await
-            .map_err(|e| io_error_message(e, &params.path))?;
+            .map_err(|e| ToolError::io(e, &params.path))?;
 
         Ok(format!("Created directory {}", canonical.display()))
     }
+
+    /// Shrinks (or, when allowed, extends) a file to a given length in place.
+    #[rmcp::tool(
+        name = "truncate_file",
+        description = "Sets a file's length in place without rewriting it: shrinks it to length bytes (default 0, emptying it), which works for files of any size, such as logs. A length beyond the current size zero-fills the extension and requires allow_extend: true, up to --max-write-size. Pass expected_size to abort if the file changed since it was checked. Reports the old and new sizes.",
+        annotations(
+            title = "Truncate File",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn truncate_file(
+        &self,
+        Parameters(params): Parameters<TruncateFileParams>,
+    ) -> Result<String, ToolError> {
+        let path = std::path::Path::new(&params.path);
+        let canonical = self.security.validate_file(path)?;
+        self.security.ensure_writable(&canonical, path)?;
+        let length = params.length.unwrap_or(0);
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(&canonical)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        // Sized through the open handle so the checks and set_len see the same file
+        let size = file
+            .metadata()
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?
+            .len();
+        if let Some(expected) = params.expected_size
+            && expected != size
+        {
+            return Err(format!(
+                "Precondition failed: {} is {size} bytes, expected {expected}",
+                canonical.display()
+            )
+            .into());
+        }
+        if length > size {
+            if !params.allow_extend.unwrap_or(false) {
+                return Err(format!(
+                    "{} is {size} bytes; a length of {length} would extend it with zeros. Pass allow_extend: true to do that.",
+                    canonical.display()
+                )
+                .into());
+            }
+            if length > self.config.max_write_size {
+                return Err(format!(
+                    "Refusing to extend {} to {length} bytes: over --max-write-size ({})",
+                    canonical.display(),
+                    format_size(self.config.max_write_size)
+                )
+                .into());
+            }
+        }
+
+        file.set_len(length)
+            .await
+            .map_err(|e| ToolError::io(e, &params.path))?;
+        let verb = match length.cmp(&size) {
+            std::cmp::Ordering::Less => "Truncated",
+            std::cmp::Ordering::Equal => "Left unchanged",
+            std::cmp::Ordering::Greater => {
+                stats::record_written(length - size);
+                "Extended (zero-filled)"
+            }
+        };
+        Ok(format!(
+            "{verb} {}: {size} bytes ({}) -> {length} bytes ({})",
+            canonical.display(),
+            format_size(size),
+            format_size(length)
+        ))
+    }
+
+    /// Copies the file `source` to the new path `destination`.
+    #[rmcp::tool(
+        name = "copy_file",
+        description = "Copies a file to a new path without sending its contents through the conversation, e.g. to keep a backup before editing. Both paths must be within allowed directories. The source must be a regular file and the destination must not exist; its parent directory must. Permissions are copied along with the contents.",
+        annotations(
+            title = "Copy File",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn copy_file(
+        &self,
+        Parameters(params): Parameters<CopyFileParams>,
+    ) -> Result<String, ToolError> {
+        let source = self
+            .security
+            .validate_file(std::path::Path::new(&params.source))?;
+        let destination = self
+            .security
+            .validate_writable_path(std::path::Path::new(&params.destination))?;
+        if tokio::fs::symlink_metadata(&destination).await.is_ok() {
+            return Err(format!("Destination exists: {}", destination.display()).into());
+        }
+
+        let size = tokio::fs::copy(&source, &destination)
+            .await
+            .map_err(|e| ToolError::io(e, &params.source))?;
+        stats::record_read(size);
+        stats::record_written(size);
+        Ok(format!(
+            "Copied {} to {} ({})",
+            source.display(),
+            destination.display(),
+            format_size(size)
+        ))
+    }
+
+    /// Copies the directory tree `source` to the new path `destination`.
+    #[rmcp::tool(
+        name = "copy_directory",
+        description = "Copies a directory tree to a new path, recreating its subdirectories and copying each file with its permissions, up to --max-depth levels; deeper directories are left out and reported. Both paths must be within allowed directories. The destination must not exist and must not be inside the source; missing parents are created. Symlinks are not copied and denied paths are skipped. Reports the files and subdirectories copied.",
+        annotations(
+            title = "Copy Directory",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn copy_directory(
+        &self,
+        Parameters(params): Parameters<CopyDirectoryParams>,
+        ct: CancellationToken,
+    ) -> Result<String, ToolError> {
+        let source = self
+            .security
+            .validate_directory(Path::new(&params.source))?;
+        let destination = self
+            .security
+            .validate_creatable_path(Path::new(&params.destination))?;
+        self.security
+            .ensure_writable(&destination, Path::new(&params.destination))?;
+        if destination.starts_with(&source) {
+            return Err(format!(
+                "Destination {} is inside the source directory {}; choose a path outside it.",
+                params.destination, params.source
+            )
+            .into());
+        }
+        if tokio::fs::symlink_metadata(&destination).await.is_ok() {
+            return Err(format!("Destination exists: {}", destination.display()).into());
+        }
+
+        let security = self.security.clone();
+        let max_depth = self.config.max_depth;
+        let (from, to) = (source.clone(), destination.clone());
+        let copy = spawn_blocking(move || copy_tree_sync(&from, &to, &security, max_depth, &ct))
+            .await
+            .map_err(|e| e.to_string())??;
+        stats::record_read(copy.bytes);
+        stats::record_written(copy.bytes);
+
+        let mut out = format!(
+            "Copied {} files, {} directories from {} to {} ({})",
+            copy.files,
+            copy.dirs,
+            source.display(),
+            destination.display(),
+            format_size(copy.bytes)
+        );
+        let mut skipped = Vec::new();
+        if copy.denied > 0 {
+            skipped.push(format!("{} denied", copy.denied));
+        }
+        if copy.symlinks > 0 {
+            skipped.push(format!("{} symlinks", copy.symlinks));
+        }
+        if copy.too_deep > 0 {
+            skipped.push(format!(
+                "{} directories below --max-depth ({max_depth})",
+                copy.too_deep
+            ));
+        }
+        if !skipped.is_empty() {
+            out.push_str(&format!("\nSkipped: {}", skipped.join(", ")));
+        }
+        Ok(out)
+    }
+
+    /// Makes `destination` a hard link to the existing file `source`.
+    #[rmcp::tool(
+        name = "create_hardlink",
+        description = "Creates a hard link: destination becomes a second name for the existing file at source, sharing its contents. Both paths must be within allowed directories and on the same filesystem. The source must be a regular file (not a directory) and the destination must not exist. Reports the shared inode so identity can be verified.",
+        annotations(
+            title = "Create Hard Link",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn create_hardlink(
+        &self,
+        Parameters(params): Parameters<CreateHardlinkParams>,
+    ) -> Result<String, ToolError> {
+        let source_path = std::path::Path::new(&params.source);
+        let source = self.security.validate_file(source_path)?;
+        // The link shares the source's contents, so writes through it would
+        // change a read-only source
+        self.security.ensure_writable(&source, source_path)?;
+        let destination = self
+            .security
+            .validate_writable_path(std::path::Path::new(&params.destination))?;
+
+        tokio::fs::hard_link(&source, &destination)
+            .await
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::CrossesDevices => format!(
+                    "Cannot hard link across filesystems (EXDEV): {} and {} are on different devices. Copy the file instead.",
+                    source.display(),
+                    destination.display()
+                )
+                .into(),
+                std::io::ErrorKind::AlreadyExists => {
+                    format!("Destination exists: {}", destination.display()).into()
+                }
+                _ => ToolError::io(e, &params.destination),
+            })?;
+
+        let metadata = tokio::fs::metadata(&destination)
+            .await
+            .map_err(|e| ToolError::io(e, &params.destination))?;
+        Ok(format!(
+            "Linked {} to {} ({})",
+            destination.display(),
+            source.display(),
+            describe_inode(&metadata)
+        ))
+    }
+}
+
+impl FilesystemService {
+    /// Replaces the validated `canonical` path with `data` through a temporary
+    /// sibling renamed into place. The temporary path must pass the same
+    /// checks, so a deny pattern matching it refuses the write.
+    async fn write_atomic(
+        &self,
+        canonical: &std::path::Path,
+        requested: &str,
+        data: Vec<u8>,
+    ) -> Result<(), ToolError> {
+        self.security.validate_path(&atomic_temp_path(canonical))?;
+        let target = canonical.to_path_buf();
+        spawn_blocking(move || write_atomic_sync(&target, &data))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| ToolError::io(e, requested))
+    }
 }
 
 #[cfg(test)]
@@ -176,6 +915,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         FilesystemService::new(config)
     }
@@ -183,14 +923,20 @@ mod tests {
     // --- Router tests ---
 
     #[test]
-    fn write_tools_router_contains_all_three() {
+    fn write_tools_router_contains_all_ten() {
         let router = FilesystemService::write_tools_router();
         let tools = router.list_all();
-        assert_eq!(tools.len(), 3);
+        assert_eq!(tools.len(), 10);
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
         assert!(names.contains(&"edit_file"));
         assert!(names.contains(&"write_file"));
         assert!(names.contains(&"create_directory"));
+        assert!(names.contains(&"truncate_file"));
+        assert!(names.contains(&"copy_file"));
+        assert!(names.contains(&"append_file"));
+        assert!(names.contains(&"write_file_base64"));
+        assert!(names.contains(&"touch_file"));
+        assert!(names.contains(&"copy_directory"));
     }
 
     #[test]
@@ -200,6 +946,8 @@ mod tests {
         let ann = tool.annotations.as_ref().unwrap();
         assert_eq!(ann.read_only_hint, Some(false));
         assert_eq!(ann.destructive_hint, Some(false));
+        // Repeating an edit fails or applies it again, so it is not idempotent
+        assert_eq!(ann.idempotent_hint, Some(false));
     }
 
     #[test]
@@ -208,7 +956,11 @@ mod tests {
         let tool = router.get("write_file").unwrap();
         let ann = tool.annotations.as_ref().unwrap();
         assert_eq!(ann.read_only_hint, Some(false));
+        // Kept destructive: write_file silently replaces an existing file's
+        // contents, and clients use this hint to decide when to ask the user.
         assert_eq!(ann.destructive_hint, Some(true));
+        // Writing the same content twice leaves the same file
+        assert_eq!(ann.idempotent_hint, Some(true));
     }
 
     #[test]
@@ -218,6 +970,7 @@ mod tests {
         let ann = tool.annotations.as_ref().unwrap();
         assert_eq!(ann.read_only_hint, Some(false));
         assert_eq!(ann.destructive_hint, Some(false));
+        assert_eq!(ann.idempotent_hint, Some(true));
     }
 
     // --- Conditional visibility tests ---
@@ -232,6 +985,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let service = FilesystemService::new(config);
         let tools = service.tool_router.list_all();
@@ -239,7 +993,7 @@ mod tests {
         assert!(!names.contains(&"edit_file"));
         assert!(!names.contains(&"write_file"));
         assert!(!names.contains(&"create_directory"));
-        assert_eq!(tools.len(), 7);
+        assert_eq!(tools.len(), 30);
     }
 
     #[test]
@@ -252,6 +1006,7 @@ mod tests {
             allow_destructive: false,
             max_read_size: 10_485_760,
             max_depth: 10,
+            ..Default::default()
         };
         let service = FilesystemService::new(config);
         let tools = service.tool_router.list_all();
@@ -259,7 +1014,10 @@ mod tests {
         assert!(names.contains(&"edit_file"));
         assert!(names.contains(&"write_file"));
         assert!(names.contains(&"create_directory"));
-        assert_eq!(tools.len(), 10);
+        assert!(names.contains(&"create_hardlink"));
+        assert!(names.contains(&"copy_file"));
+        assert!(names.contains(&"append_file"));
+        assert_eq!(tools.len(), 45);
     }
 
     // --- edit_file tests ---
@@ -278,7 +1036,11 @@ mod tests {
                 edits: vec![EditOperation {
                     old_text: "Hello".to_string(),
                     new_text: "Hi".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
                 }],
+                dry_run: None,
             }))
             .await;
 
@@ -307,7 +1069,11 @@ mod tests {
                 edits: vec![EditOperation {
                     old_text: "x".to_string(),
                     new_text: "y".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
                 }],
+                dry_run: None,
             }))
             .await;
 
@@ -328,12 +1094,16 @@ mod tests {
                 edits: vec![EditOperation {
                     old_text: "NONEXISTENT".to_string(),
                     new_text: "y".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
                 }],
+                dry_run: None,
             }))
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("old_text not found"));
+        assert!(result.unwrap_err().message.contains("old_text not found"));
     }
 
     #[tokio::test]
@@ -350,12 +1120,240 @@ mod tests {
                 edits: vec![EditOperation {
                     old_text: "foo".to_string(),
                     new_text: "baz".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
                 }],
+                dry_run: None,
             }))
             .await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("matches 2 locations"));
+        let err = result.unwrap_err();
+        assert!(err.message.contains("matches 2 locations"));
+        let details = err.details.unwrap();
+        assert_eq!(details["kind"], "edit_failed");
+        assert_eq!(details["match_count"], 2);
+    }
+
+    #[test]
+    fn whitespace_insensitive_matches_cover_the_original_bytes() {
+        let content = "fn main() {\n\tlet x =  1;   \n    let y = 2;\n}\n";
+        let ranges = find_matches(
+            content,
+            "    let x = 1;\n  let y = 2;",
+            MatchMode::WhitespaceInsensitive,
+        );
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(
+            &content[ranges[0].clone()],
+            "\tlet x =  1;   \n    let y = 2;"
+        );
+        assert!(find_matches(content, "let x = 1;", MatchMode::Exact).is_empty());
+        assert!(find_matches(content, "  \t ", MatchMode::WhitespaceInsensitive).is_empty());
+    }
+
+    #[tokio::test]
+    async fn edit_file_whitespace_insensitive_keeps_surrounding_indentation() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(
+            &file,
+            "fn main() {\n\tlet x = 1;  \n\tprintln!(\"{x}\");\n}\n",
+        )
+        .unwrap();
+
+        let service = make_service(vec![canon]);
+        let mut params = edit_params(&file, "    let x = 1;\n", "\tlet x = 2;\n", false);
+        params.edits[0].match_mode = Some(MatchMode::WhitespaceInsensitive);
+        service.edit_file(Parameters(params)).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "fn main() {\n\tlet x = 2;\n\tprintln!(\"{x}\");\n}\n"
+        );
+
+        // The default exact mode still rejects the same old_text
+        let err = service
+            .edit_file(Parameters(edit_params(&file, "    let x = 2;", "", false)))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("old_text not found"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[tokio::test]
+    async fn edit_file_whitespace_insensitive_reports_candidate_lines() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("test.txt");
+        std::fs::write(&file, "a  = 1\nb = 2\na\t= 1\n").unwrap();
+
+        let service = make_service(vec![canon]);
+        let mut params = edit_params(&file, "a = 1", "a = 3", false);
+        params.edits[0].match_mode = Some(MatchMode::WhitespaceInsensitive);
+        let err = service.edit_file(Parameters(params)).await.unwrap_err();
+
+        assert!(
+            err.message
+                .contains("matches 2 locations (must be unique), starting on lines 1, 3"),
+            "{}",
+            err.message
+        );
+        assert_eq!(err.details.unwrap()["match_count"], 2);
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "a  = 1\nb = 2\na\t= 1\n"
+        );
+    }
+
+    fn edit_params(file: &std::path::Path, old: &str, new: &str, dry_run: bool) -> EditFileParams {
+        EditFileParams {
+            path: file.to_string_lossy().to_string(),
+            edits: vec![EditOperation {
+                old_text: old.to_string(),
+                new_text: new.to_string(),
+                normalize_line_endings_for_match: None,
+                match_mode: None,
+                use_regex: None,
+            }],
+            dry_run: Some(dry_run),
+        }
+    }
+
+    #[tokio::test]
+    async fn edit_file_dry_run_returns_diff_without_writing() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("config.toml");
+        std::fs::write(&file, "port = 80\nhost = \"a\"\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let preview = service
+            .edit_file(Parameters(edit_params(&file, "80", "8080", true)))
+            .await
+            .unwrap();
+        assert!(
+            preview.starts_with(&format!(
+                "[DRY RUN] would apply 1 edit(s) to {} (no changes were made)\n\n",
+                file.display()
+            )),
+            "{preview}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "port = 80\nhost = \"a\"\n"
+        );
+
+        let applied = service
+            .edit_file(Parameters(edit_params(&file, "80", "8080", false)))
+            .await
+            .unwrap();
+        let diff_of = |text: &str| text.split_once("\n\n").unwrap().1.to_string();
+        assert_eq!(diff_of(&preview), diff_of(&applied));
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "port = 8080\nhost = \"a\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn edit_file_dry_run_fails_like_real_run() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("test.txt");
+        std::fs::write(&file, "foo bar foo\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        for (old, expected) in [("foo", "matches 2 locations"), ("qux", "not found")] {
+            let dry = service
+                .edit_file(Parameters(edit_params(&file, old, "x", true)))
+                .await
+                .unwrap_err();
+            let real = service
+                .edit_file(Parameters(edit_params(&file, old, "x", false)))
+                .await
+                .unwrap_err();
+            assert!(dry.message.contains(expected), "{dry}");
+            assert_eq!(dry.message, real.message);
+            assert_eq!(dry.details, real.details);
+        }
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "foo bar foo\n");
+    }
+
+    fn regex_params(file: &std::path::Path, old: &str, new: &str, dry_run: bool) -> EditFileParams {
+        let mut params = edit_params(file, old, new, dry_run);
+        params.edits[0].use_regex = Some(true);
+        params
+    }
+
+    #[tokio::test]
+    async fn edit_file_regex_expands_capture_groups() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("Cargo.toml");
+        std::fs::write(&file, "name = \"app\"\nversion = \"0.3.1\"\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let params = regex_params(
+            &file,
+            r#"version = "(\d+)\.(\d+)\.\d+""#,
+            r#"version = "$1.${2}.9""#,
+            true,
+        );
+        let preview = service.edit_file(Parameters(params)).await.unwrap();
+        assert!(preview.starts_with("[DRY RUN] "), "{preview}");
+        assert!(preview.contains("+version = \"0.3.9\""), "{preview}");
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "name = \"app\"\nversion = \"0.3.1\"\n"
+        );
+
+        let params = regex_params(
+            &file,
+            r#"version = "(\d+)\.(\d+)\.\d+""#,
+            r#"version = "$1.${2}.9""#,
+            false,
+        );
+        service.edit_file(Parameters(params)).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "name = \"app\"\nversion = \"0.3.9\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn edit_file_regex_rejects_multiple_matches_and_bad_patterns() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("test.txt");
+        std::fs::write(&file, "a1\nb2\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .edit_file(Parameters(regex_params(&file, r"[a-z]\d", "x", false)))
+            .await
+            .unwrap_err();
+        assert!(
+            err.message
+                .contains("matches 2 locations (must be unique), starting on lines 1, 2"),
+            "{err}"
+        );
+        let err = service
+            .edit_file(Parameters(regex_params(&file, "(unclosed", "x", false)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("old_text regex"), "{err}");
+        assert_eq!(err.details.unwrap()["kind"], "invalid_pattern");
+
+        let mut params = regex_params(&file, "a1", "x", false);
+        params.edits[0].match_mode = Some(MatchMode::WhitespaceInsensitive);
+        let err = service.edit_file(Parameters(params)).await.unwrap_err();
+        assert!(err.message.contains("cannot be combined"), "{err}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a1\nb2\n");
     }
 
     #[tokio::test]
@@ -372,7 +1370,11 @@ mod tests {
                 edits: vec![EditOperation {
                     old_text: "\"old\"".to_string(),
                     new_text: "\"new\"".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
                 }],
+                dry_run: None,
             }))
             .await;
 
@@ -382,28 +1384,108 @@ mod tests {
         assert!(output.contains("@@"));
     }
 
-    // --- write_file tests ---
-
     #[tokio::test]
-    async fn write_file_create_new() {
+    async fn edit_file_crlf_old_text_matches_lf_file() {
         let dir = TempDir::new().unwrap();
         let canon = dir.path().canonicalize().unwrap();
-        let file = dir.path().join("new.txt");
+        let file = dir.path().join("unix.txt");
+        std::fs::write(&file, "first\nsecond\nthird\n").unwrap();
 
         let service = make_service(vec![canon]);
         let result = service
-            .write_file(Parameters(WriteFileParams {
+            .edit_file(Parameters(EditFileParams {
                 path: file.to_string_lossy().to_string(),
-                content: "Hello, new file!\n".to_string(),
+                edits: vec![EditOperation {
+                    old_text: "first\r\nsecond".to_string(),
+                    new_text: "one\r\ntwo".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
+                }],
+                dry_run: None,
             }))
             .await;
 
-        let output = result.unwrap();
-        assert!(output.contains("Wrote"));
-        assert!(output.contains("17 B"));
-
+        assert!(result.is_ok());
         let on_disk = std::fs::read_to_string(&file).unwrap();
-        assert_eq!(on_disk, "Hello, new file!\n");
+        assert_eq!(on_disk, "one\ntwo\nthird\n");
+    }
+
+    #[tokio::test]
+    async fn edit_file_lf_old_text_matches_crlf_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("windows.txt");
+        std::fs::write(&file, "first\r\nsecond\r\n").unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .edit_file(Parameters(EditFileParams {
+                path: file.to_string_lossy().to_string(),
+                edits: vec![EditOperation {
+                    old_text: "first\nsecond".to_string(),
+                    new_text: "one\ntwo".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
+                }],
+                dry_run: None,
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let on_disk = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(on_disk, "one\r\ntwo\r\n");
+    }
+
+    #[tokio::test]
+    async fn edit_file_crlf_old_text_fails_without_normalization() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("unix.txt");
+        std::fs::write(&file, "first\nsecond\n").unwrap();
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .edit_file(Parameters(EditFileParams {
+                path: file.to_string_lossy().to_string(),
+                edits: vec![EditOperation {
+                    old_text: "first\r\nsecond".to_string(),
+                    new_text: "one".to_string(),
+                    normalize_line_endings_for_match: Some(false),
+                    match_mode: None,
+                    use_regex: None,
+                }],
+                dry_run: None,
+            }))
+            .await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("old_text not found"));
+    }
+
+    // --- write_file tests ---
+
+    #[tokio::test]
+    async fn write_file_create_new() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("new.txt");
+
+        let service = make_service(vec![canon]);
+        let result = service
+            .write_file(Parameters(WriteFileParams {
+                path: file.to_string_lossy().to_string(),
+                content: "Hello, new file!\n".to_string(),
+            }))
+            .await;
+
+        let output = result.unwrap();
+        assert!(output.contains("Wrote"));
+        assert!(output.contains("17 B"));
+
+        let on_disk = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(on_disk, "Hello, new file!\n");
     }
 
     #[tokio::test]
@@ -426,6 +1508,114 @@ mod tests {
         assert_eq!(on_disk, "new content");
     }
 
+    #[tokio::test]
+    async fn read_only_directories_refuse_writes() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let vendor = canon.join("vendor");
+        std::fs::create_dir(&vendor).unwrap();
+        std::fs::write(vendor.join("lib.rs"), "fn main() {}").unwrap();
+        let config = Config {
+            allowed_directories: vec![canon.clone()],
+            read_only_directories: vec![vendor.clone()],
+            allow_write: true,
+            ..Default::default()
+        };
+        let service = FilesystemService::new(config);
+
+        let err = service
+            .write_file(Parameters(WriteFileParams {
+                path: vendor.join("lib.rs").to_string_lossy().to_string(),
+                content: "changed".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.details.unwrap()["kind"], "read_only");
+        assert_eq!(
+            std::fs::read_to_string(vendor.join("lib.rs")).unwrap(),
+            "fn main() {}"
+        );
+        let err = service
+            .create_directory(Parameters(CreateDirectoryParams {
+                path: vendor.join("new/nested").to_string_lossy().to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.details.unwrap()["kind"], "read_only");
+
+        service
+            .write_file(Parameters(WriteFileParams {
+                path: canon.join("notes.txt").to_string_lossy().to_string(),
+                content: "fine".to_string(),
+            }))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn write_file_and_edit_file_leave_no_temp_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("notes.txt");
+        std::fs::write(&file, "old").unwrap();
+        let service = make_service(vec![canon.clone()]);
+
+        service
+            .write_file(Parameters(WriteFileParams {
+                path: file.to_string_lossy().to_string(),
+                content: "Hello world".to_string(),
+            }))
+            .await
+            .unwrap();
+        service
+            .edit_file(Parameters(EditFileParams {
+                path: file.to_string_lossy().to_string(),
+                edits: vec![EditOperation {
+                    old_text: "Hello".to_string(),
+                    new_text: "Hi".to_string(),
+                    normalize_line_endings_for_match: None,
+                    match_mode: None,
+                    use_regex: None,
+                }],
+                dry_run: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "Hi world");
+        let names: Vec<_> = std::fs::read_dir(&canon)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["notes.txt"]);
+    }
+
+    #[tokio::test]
+    async fn write_file_refuses_denied_temp_path() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("notes.txt");
+        std::fs::write(&file, "keep").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            allow_write: true,
+            deny: vec!["*.ironbeard-tmp-*".into()],
+            ..Default::default()
+        });
+
+        let err = service
+            .write_file(Parameters(WriteFileParams {
+                path: file.to_string_lossy().to_string(),
+                content: "replaced".to_string(),
+            }))
+            .await
+            .unwrap_err();
+
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(err.message.contains(".notes.txt.ironbeard-tmp-"), "{err}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep");
+    }
+
     #[tokio::test]
     async fn write_file_denied_outside() {
         let dir = TempDir::new().unwrap();
@@ -441,7 +1631,80 @@ mod tests {
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
+    }
+
+    // --- write_file_base64 tests ---
+
+    #[tokio::test]
+    async fn write_file_base64_round_trips_binary() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("blob.bin");
+        let content: Vec<u8> = (0..=255u8).collect();
+        let encoded = STANDARD.encode(&content);
+        // Line-wrapped base64, as tools like `base64` print it
+        let wrapped: Vec<&str> = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect();
+
+        let service = make_service(vec![canon]);
+        let output = service
+            .write_file_base64(Parameters(WriteFileBase64Params {
+                path: file.to_string_lossy().to_string(),
+                content_base64: wrapped.join("\n"),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(output, format!("Wrote 256 B to {}", file.display()));
+        assert_eq!(std::fs::read(&file).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn write_file_base64_rejects_invalid_input() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("keep.bin");
+        std::fs::write(&file, b"original").unwrap();
+
+        let service = make_service(vec![canon]);
+        let err = service
+            .write_file_base64(Parameters(WriteFileBase64Params {
+                path: file.to_string_lossy().to_string(),
+                content_base64: "not*base64".to_string(),
+            }))
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.message
+                .starts_with("Invalid base64 in content_base64: "),
+            "{err}"
+        );
+        assert_eq!(std::fs::read(&file).unwrap(), b"original");
+    }
+
+    #[tokio::test]
+    async fn write_file_base64_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+
+        let other = TempDir::new().unwrap();
+        let target = other.path().join("hack.bin");
+        let err = service
+            .write_file_base64(Parameters(WriteFileBase64Params {
+                path: target.to_string_lossy().to_string(),
+                content_base64: STANDARD.encode(b"pwned"),
+            }))
+            .await
+            .unwrap_err();
+
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(!target.exists());
     }
 
     // --- create_directory tests ---
@@ -512,6 +1775,608 @@ mod tests {
             .await;
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Access denied"));
+        assert!(result.unwrap_err().message.contains("Access denied"));
+    }
+
+    // --- create_hardlink tests ---
+
+    fn hardlink_params(
+        source: &std::path::Path,
+        destination: &std::path::Path,
+    ) -> CreateHardlinkParams {
+        CreateHardlinkParams {
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_hardlink_shares_contents() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let original = dir.path().join("asset.bin");
+        let link = dir.path().join("asset-copy.bin");
+        std::fs::write(&original, "v1").unwrap();
+        let service = make_service(vec![canon]);
+        let result = service
+            .create_hardlink(Parameters(hardlink_params(&original, &link)))
+            .await
+            .unwrap();
+        assert!(result.starts_with("Linked"), "{result}");
+        #[cfg(unix)]
+        assert!(result.contains("2 links"), "{result}");
+
+        std::fs::write(&link, "v2").unwrap();
+        assert_eq!(std::fs::read_to_string(&original).unwrap(), "v2");
+    }
+
+    #[tokio::test]
+    async fn create_hardlink_refuses_directory_and_existing_destination() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        let file = dir.path().join("a.txt");
+        let other = dir.path().join("b.txt");
+        std::fs::write(&file, "a").unwrap();
+        std::fs::write(&other, "b").unwrap();
+        let service = make_service(vec![canon]);
+
+        assert!(
+            service
+                .create_hardlink(Parameters(hardlink_params(&sub, &dir.path().join("l"))))
+                .await
+                .is_err()
+        );
+        let err = service
+            .create_hardlink(Parameters(hardlink_params(&file, &other)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+        assert_eq!(std::fs::read_to_string(&other).unwrap(), "b");
+    }
+
+    // --- append_file tests ---
+
+    fn append_params(file: &std::path::Path, content: &str) -> AppendFileParams {
+        AppendFileParams {
+            path: file.to_string_lossy().to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn append_file_adds_to_existing_content() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("app.log");
+        std::fs::write(&file, "first\n").unwrap();
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .append_file(Parameters(append_params(&file, "second\n")))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!("Appended 7 bytes to {} (now 13 B)", file.display())
+        );
+        service
+            .append_file(Parameters(append_params(&file, "third\n")))
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "first\nsecond\nthird\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn append_file_to_empty_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("empty.txt");
+        std::fs::write(&file, "").unwrap();
+        let service = make_service(vec![canon]);
+
+        service
+            .append_file(Parameters(append_params(&file, "key = 1")))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "key = 1");
+    }
+
+    #[tokio::test]
+    async fn append_file_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = outside.path().join("other.log");
+        std::fs::write(&file, "keep").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .append_file(Parameters(append_params(&file, "x")))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep");
+    }
+
+    #[tokio::test]
+    async fn append_file_creates_missing_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("notes.md");
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .append_file(Parameters(append_params(&file, "- first\n")))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!("Appended 8 bytes to {} (now 8 B)", file.display())
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "- first\n");
+    }
+
+    #[tokio::test]
+    async fn append_file_needs_existing_parent() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("missing").join("notes.md");
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .append_file(Parameters(append_params(&file, "x")))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Not found"), "{err}");
+        assert!(!file.exists());
+    }
+
+    // --- touch_file tests ---
+
+    fn touch_params(file: &std::path::Path) -> TouchFileParams {
+        TouchFileParams {
+            path: file.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn touch_file_creates_empty_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join(".done");
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .touch_file(Parameters(touch_params(&file)))
+            .await
+            .unwrap();
+        assert_eq!(result, format!("Created {}", file.display()));
+        assert_eq!(std::fs::read(&file).unwrap(), b"");
+    }
+
+    #[tokio::test]
+    async fn touch_file_updates_mtime_of_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("stamp.txt");
+        std::fs::write(&file, "keep").unwrap();
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .touch_file(Parameters(touch_params(&file)))
+            .await
+            .unwrap();
+        assert_eq!(result, format!("Updated mtime of {}", file.display()));
+        let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+        assert!(modified > old + std::time::Duration::from_secs(3000));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep");
+    }
+
+    #[tokio::test]
+    async fn touch_file_denied_outside() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = outside.path().join("marker");
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .touch_file(Parameters(touch_params(&file)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn touch_file_needs_existing_parent() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("missing").join("marker");
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .touch_file(Parameters(touch_params(&file)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Not found"), "{err}");
+        assert!(!file.exists());
+    }
+
+    // --- copy_file tests ---
+
+    fn copy_params(source: &std::path::Path, destination: &std::path::Path) -> CopyFileParams {
+        CopyFileParams {
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn copy_file_copies_contents() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let original = canon.join("notes.txt");
+        let backup = canon.join("notes.bak");
+        std::fs::write(&original, "draft").unwrap();
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .copy_file(Parameters(copy_params(&original, &backup)))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "Copied {} to {} (5 B)",
+                original.display(),
+                backup.display()
+            )
+        );
+        std::fs::write(&original, "final").unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "draft");
+
+        let err = service
+            .copy_file(Parameters(copy_params(&original, &backup)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "draft");
+    }
+
+    #[tokio::test]
+    async fn copy_file_denied_source_outside() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let secret = outside.path().join("secret.txt");
+        std::fs::write(&secret, "secret").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .copy_file(Parameters(copy_params(&secret, &dir.path().join("x.txt"))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(!dir.path().join("x.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_file_denied_destination_outside() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .copy_file(Parameters(copy_params(
+                &file,
+                &outside.path().join("a.txt"),
+            )))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(!outside.path().join("a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_file_source_not_found() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+
+        let err = service
+            .copy_file(Parameters(copy_params(
+                &dir.path().join("missing.txt"),
+                &dir.path().join("copy.txt"),
+            )))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Not found"), "{err}");
+    }
+
+    fn copy_dir_params(
+        source: &std::path::Path,
+        destination: &std::path::Path,
+    ) -> CopyDirectoryParams {
+        CopyDirectoryParams {
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn copy_directory_copies_tree() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let source = canon.join("src");
+        std::fs::create_dir_all(source.join("a/b")).unwrap();
+        std::fs::create_dir(source.join("empty")).unwrap();
+        std::fs::write(source.join("top.txt"), "top").unwrap();
+        std::fs::write(source.join("a/mid.txt"), "middle").unwrap();
+        std::fs::write(source.join("a/b/deep.txt"), "deep").unwrap();
+        let backup = canon.join("backups/src");
+        let service = make_service(vec![canon.clone()]);
+
+        let result = service
+            .copy_directory(
+                Parameters(copy_dir_params(&source, &backup)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "Copied 3 files, 3 directories from {} to {} (13 B)",
+                source.display(),
+                backup.display()
+            )
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup.join("top.txt")).unwrap(),
+            "top"
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup.join("a/mid.txt")).unwrap(),
+            "middle"
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup.join("a/b/deep.txt")).unwrap(),
+            "deep"
+        );
+        assert!(backup.join("empty").is_dir());
+
+        let err = service
+            .copy_directory(
+                Parameters(copy_dir_params(&source, &backup)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Destination exists"), "{err}");
+
+        let err = service
+            .copy_directory(
+                Parameters(copy_dir_params(&source, &source.join("a/copy"))),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("inside the source"), "{err}");
+        assert!(!source.join("a/copy").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_directory_denied_destination_outside() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        std::fs::write(canon.join("a.txt"), "a").unwrap();
+        let target = outside.path().join("copy");
+        let service = make_service(vec![canon.clone()]);
+
+        let err = service
+            .copy_directory(
+                Parameters(copy_dir_params(&canon, &target)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Access denied"), "{err}");
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn copy_directory_stops_at_max_depth() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let source = canon.join("src");
+        std::fs::create_dir_all(source.join("one/two/three")).unwrap();
+        std::fs::write(source.join("one/two/kept.txt"), "kept").unwrap();
+        std::fs::write(source.join("one/two/three/lost.txt"), "lost").unwrap();
+        let copy = canon.join("copy");
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            allow_write: true,
+            max_depth: 2,
+            ..Default::default()
+        });
+
+        let result = service
+            .copy_directory(
+                Parameters(copy_dir_params(&source, &copy)),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert!(
+            result.starts_with("Copied 1 files, 2 directories"),
+            "{result}"
+        );
+        assert!(
+            result.ends_with("\nSkipped: 1 directories below --max-depth (2)"),
+            "{result}"
+        );
+        assert!(copy.join("one/two/kept.txt").is_file());
+        assert!(!copy.join("one/two/three").exists());
+    }
+
+    fn truncate_params(
+        file: &std::path::Path,
+        length: Option<u64>,
+        allow_extend: Option<bool>,
+        expected_size: Option<u64>,
+    ) -> TruncateFileParams {
+        TruncateFileParams {
+            path: file.to_string_lossy().to_string(),
+            length,
+            allow_extend,
+            expected_size,
+        }
+    }
+
+    #[tokio::test]
+    async fn truncate_file_shrinks_and_empties() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("app.log");
+        std::fs::write(&file, "0123456789").unwrap();
+        let service = make_service(vec![canon]);
+
+        let result = service
+            .truncate_file(Parameters(truncate_params(&file, Some(4), None, Some(10))))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "Truncated {}: 10 bytes (10 B) -> 4 bytes (4 B)",
+                file.display()
+            )
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "0123");
+
+        let err = service
+            .truncate_file(Parameters(truncate_params(&file, None, None, Some(10))))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("is 4 bytes, expected 10"), "{err}");
+        assert_eq!(std::fs::metadata(&file).unwrap().len(), 4);
+
+        service
+            .truncate_file(Parameters(truncate_params(&file, None, None, None)))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::metadata(&file).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn truncate_file_extends_only_when_allowed() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let file = canon.join("placeholder.bin");
+        std::fs::write(&file, "ab").unwrap();
+        let service = FilesystemService::new(Config {
+            allowed_directories: vec![canon],
+            allow_write: true,
+            max_write_size: 1024,
+            ..Default::default()
+        });
+
+        let err = service
+            .truncate_file(Parameters(truncate_params(&file, Some(8), None, None)))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("allow_extend: true"), "{err}");
+        assert_eq!(std::fs::read(&file).unwrap(), b"ab");
+
+        let err = service
+            .truncate_file(Parameters(truncate_params(
+                &file,
+                Some(2048),
+                Some(true),
+                None,
+            )))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("--max-write-size"), "{err}");
+
+        let result = service
+            .truncate_file(Parameters(truncate_params(
+                &file,
+                Some(8),
+                Some(true),
+                None,
+            )))
+            .await
+            .unwrap();
+        assert!(result.starts_with("Extended (zero-filled) "), "{result}");
+        assert_eq!(std::fs::read(&file).unwrap(), b"ab\0\0\0\0\0\0");
+
+        let dir_err = service
+            .truncate_file(Parameters(truncate_params(dir.path(), None, None, None)))
+            .await;
+        assert!(dir_err.is_err());
+    }
+
+    #[tokio::test]
+    async fn write_tools_count_toward_usage_stats() {
+        let dir = TempDir::new().unwrap();
+        let canon = dir.path().canonicalize().unwrap();
+        let service = make_service(vec![canon]);
+        let path = dir.path().join("a.txt").to_string_lossy().to_string();
+
+        service
+            .stats
+            .instrument(
+                "write_file",
+                service.write_file(Parameters(WriteFileParams {
+                    path: path.clone(),
+                    content: "hello".to_string(),
+                })),
+                Result::is_ok,
+            )
+            .await
+            .unwrap();
+        service
+            .stats
+            .instrument(
+                "edit_file",
+                service.edit_file(Parameters(EditFileParams {
+                    path,
+                    edits: vec![EditOperation {
+                        old_text: "hello".to_string(),
+                        new_text: "hello world".to_string(),
+                        normalize_line_endings_for_match: None,
+                        match_mode: None,
+                        use_regex: None,
+                    }],
+                    dry_run: None,
+                })),
+                Result::is_ok,
+            )
+            .await
+            .unwrap();
+
+        let write = service.stats.usage("write_file").unwrap();
+        assert_eq!((write.invocations, write.successes), (1, 1));
+        assert_eq!(write.bytes_written, 5);
+        let edit = service.stats.usage("edit_file").unwrap();
+        assert_eq!((edit.bytes_read, edit.bytes_written), (5, 11));
     }
 }
@@ -0,0 +1,83 @@
+//! Runs the server against an rmcp client over an in-memory pipe, so tests go
+//! through initialization, routing and JSON serialization like a real client.
+
+// Each test binary uses its own subset of these helpers
+#![allow(dead_code)]
+
+use ironbeard_mcp_filesystem::server::LATEST_PROTOCOL_VERSION;
+use ironbeard_mcp_filesystem::{Config, FilesystemService};
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, ClientInfo, Implementation, ProtocolVersion, RawContent,
+};
+use rmcp::service::{RunningService, ServiceError};
+use rmcp::{ClientHandler, RoleClient, ServiceExt};
+use std::sync::Arc;
+
+pub type Client = RunningService<RoleClient, ClientInfo>;
+
+/// Serves `config` over a duplex pipe and returns a connected client that
+/// negotiated the newest protocol version, plus the service for inspecting or
+/// draining it.
+pub async fn connect(config: Config) -> (Client, Arc<FilesystemService>) {
+    connect_with_version(config, LATEST_PROTOCOL_VERSION).await
+}
+
+/// Like [`connect`], with the client requesting `version`.
+pub async fn connect_with_version(
+    config: Config,
+    version: ProtocolVersion,
+) -> (Client, Arc<FilesystemService>) {
+    let client_info = ClientInfo {
+        meta: None,
+        protocol_version: version,
+        capabilities: Default::default(),
+        client_info: Implementation {
+            name: "protocol-tests".to_string(),
+            version: "0".to_string(),
+            ..Default::default()
+        },
+    };
+    connect_client(config, client_info).await
+}
+
+/// Like [`connect`], with `handler` answering the server's requests to the
+/// client, such as `roots/list`.
+pub async fn connect_client<C: ClientHandler>(
+    config: Config,
+    handler: C,
+) -> (RunningService<RoleClient, C>, Arc<FilesystemService>) {
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    let service = Arc::new(FilesystemService::new(config));
+    let server = service.clone();
+    tokio::spawn(async move {
+        if let Ok(running) = server.serve(server_io).await {
+            let _ = running.waiting().await;
+        }
+    });
+    let client = handler.serve(client_io).await.expect("client initializes");
+    (client, service)
+}
+
+/// Calls `tool` with `arguments`, which must be a JSON object.
+pub async fn call<C: ClientHandler>(
+    client: &RunningService<RoleClient, C>,
+    tool: &str,
+    arguments: serde_json::Value,
+) -> Result<CallToolResult, ServiceError> {
+    client
+        .call_tool(CallToolRequestParams {
+            meta: None,
+            name: tool.to_string().into(),
+            arguments: Some(arguments.as_object().expect("arguments object").clone()),
+            task: None,
+        })
+        .await
+}
+
+/// Text of the first content block.
+pub fn text(result: &CallToolResult) -> &str {
+    match &result.content[0].raw {
+        RawContent::Text(t) => &t.text,
+        _ => panic!("expected text content"),
+    }
+}
@@ -0,0 +1,344 @@
+//! End-to-end protocol tests: initialize, tools/list and tools/call through an
+//! rmcp client.
+
+mod common;
+
+use common::{call, connect, connect_client, connect_with_version, text};
+use ironbeard_mcp_filesystem::Config;
+use rmcp::model::{
+    ClientCapabilities, ClientInfo, ErrorCode, ListRootsResult, ProtocolVersion, Root,
+};
+use rmcp::service::{RequestContext, RunningService, ServiceError};
+use rmcp::{ClientHandler, ErrorData, RoleClient};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn config(dir: &TempDir, allow_write: bool) -> Config {
+    Config::builder()
+        .allowed_directory(dir.path())
+        .allow_write(allow_write)
+        .build()
+        .unwrap()
+}
+
+async fn tool_names(allow_write: bool) -> Vec<String> {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, allow_write)).await;
+    let mut names: Vec<String> = client
+        .list_all_tools()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name.into())
+        .collect();
+    names.sort();
+    client.cancel().await.unwrap();
+    names
+}
+
+#[tokio::test]
+async fn initialize_reports_server_info_and_instructions() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let info = client.peer_info().unwrap();
+    assert_eq!(info.server_info.name, "ironbeard-mcp-filesystem");
+    assert!(info.capabilities.tools.is_some());
+    let instructions = info.instructions.as_deref().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    assert!(instructions.contains(&root.display().to_string()));
+}
+
+#[tokio::test]
+async fn tools_list_follows_allow_write() {
+    let read_only = tool_names(false).await;
+    assert_eq!(read_only.len(), 30);
+    assert!(read_only.contains(&"read_file".to_string()));
+    assert!(!read_only.contains(&"write_file".to_string()));
+
+    let writable = tool_names(true).await;
+    assert_eq!(writable.len(), 45);
+    assert!(writable.contains(&"write_file".to_string()));
+    assert!(!writable.contains(&"delete_file".to_string()));
+}
+
+#[tokio::test]
+async fn listed_tools_carry_schemas_and_annotations() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let tools = client.list_all_tools().await.unwrap();
+    let list = tools.iter().find(|t| t.name == "list_directory").unwrap();
+    assert_eq!(list.input_schema["type"], "object");
+    assert!(list.input_schema["properties"].get("path").is_some());
+    assert!(list.output_schema.is_some());
+    let annotations = list.annotations.as_ref().unwrap();
+    assert_eq!(annotations.read_only_hint, Some(true));
+}
+
+#[tokio::test]
+async fn read_file_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("a.txt");
+    std::fs::write(&path, "first\nsecond\n").unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let result = call(&client, "read_file", json!({ "path": path, "offset": 1 }))
+        .await
+        .unwrap();
+    assert_ne!(result.is_error, Some(true));
+    assert!(text(&result).ends_with("(Lines 2-2 of 2 total, 13 B)\n\nsecond"));
+}
+
+#[tokio::test]
+async fn read_file_with_bad_params_is_invalid_params() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    for arguments in [json!({}), json!({ "path": 42 })] {
+        let err = call(&client, "read_file", arguments).await.unwrap_err();
+        let ServiceError::McpError(err) = err else {
+            panic!("expected an MCP error, got {err:?}");
+        };
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+    }
+}
+
+#[tokio::test]
+async fn unknown_tool_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let err = call(&client, "write_file", json!({ "path": "x", "content": "" }))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ServiceError::McpError(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn tool_failure_is_an_error_result() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let result = call(&client, "read_file", json!({ "path": "/etc/passwd" }))
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(
+        text(&result).starts_with("Access denied"),
+        "{}",
+        text(&result)
+    );
+    let meta = result.content[0].as_text().unwrap().meta.as_ref().unwrap();
+    assert_eq!(meta.0["error"]["kind"], "path_denied");
+    assert_eq!(meta.0["error"]["path"], "/etc/passwd");
+}
+
+#[tokio::test]
+async fn structured_content_reaches_client() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+    let (client, _service) = connect(config(&dir, false)).await;
+    let result = call(&client, "list_directory", json!({ "path": dir.path() }))
+        .await
+        .unwrap();
+    let data = result.structured_content.unwrap();
+    assert_eq!(data["entries"][0]["name"], "a.rs");
+    assert_eq!(data["summary"]["files"], 1);
+}
+
+#[tokio::test]
+async fn json_format_listing_is_shortened_by_entries() {
+    let dir = TempDir::new().unwrap();
+    for i in 0..50 {
+        std::fs::write(dir.path().join(format!("file_{i:03}.txt")), "x").unwrap();
+    }
+    let config = Config::builder()
+        .allowed_directory(dir.path())
+        .max_output_bytes(512)
+        .build()
+        .unwrap();
+    let (client, _service) = connect(config).await;
+    let args = json!({ "path": dir.path(), "format": "json" });
+    let result = call(&client, "list_directory", args).await.unwrap();
+    let body = text(&result);
+    assert!(body.len() <= 512, "{}", body.len());
+    let parsed: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(parsed["truncated"], true);
+    let entries = parsed["entries"].as_array().unwrap();
+    assert!(!entries.is_empty() && entries.len() < 50);
+
+    let args = json!({ "path": dir.path() });
+    let result = call(&client, "list_directory", args).await.unwrap();
+    assert!(text(&result).contains("output truncated"));
+}
+
+#[tokio::test]
+async fn validate_syntax_json_format_is_still_capped() {
+    let dir = TempDir::new().unwrap();
+    let minified = format!("[{}", "1,".repeat(2000));
+    std::fs::write(dir.path().join("data.txt"), minified).unwrap();
+    let config = Config::builder()
+        .allowed_directory(dir.path())
+        .max_output_bytes(512)
+        .build()
+        .unwrap();
+    let (client, _service) = connect(config).await;
+    let args = json!({ "path": dir.path().join("data.txt"), "format": "json" });
+    let result = call(&client, "validate_syntax", args).await.unwrap();
+    let body = text(&result);
+    assert!(body.contains("output truncated"), "{body}");
+    assert!(body.len() < 1024, "{}", body.len());
+}
+
+#[tokio::test]
+async fn older_client_gets_no_output_schema_or_structured_content() {
+    let dir = TempDir::new().unwrap();
+    let (client, _service) =
+        connect_with_version(config(&dir, false), ProtocolVersion::V_2025_03_26).await;
+    assert_eq!(
+        client.peer_info().unwrap().protocol_version,
+        ProtocolVersion::V_2025_03_26
+    );
+    let tools = client.list_all_tools().await.unwrap();
+    assert!(tools.iter().all(|t| t.output_schema.is_none()));
+    let result = call(&client, "list_directory", json!({ "path": dir.path() }))
+        .await
+        .unwrap();
+    assert!(result.structured_content.is_none());
+    assert!(text(&result).contains("(empty directory)"));
+}
+
+#[tokio::test]
+async fn shutting_down_error_code_reaches_client() {
+    let dir = TempDir::new().unwrap();
+    let (client, service) = connect(config(&dir, false)).await;
+    assert!(service.drain(Duration::from_secs(1)).await);
+    let err = call(&client, "list_allowed_directories", json!({}))
+        .await
+        .unwrap_err();
+    let ServiceError::McpError(err) = err else {
+        panic!("expected an MCP error, got {err:?}");
+    };
+    assert_eq!(err.code, ErrorCode::INTERNAL_ERROR);
+    assert!(err.message.contains("shutting down"), "{}", err.message);
+    assert_eq!(err.data.unwrap()["kind"], "shutting_down");
+}
+
+/// A client that reports `roots` as its workspace roots.
+#[derive(Clone, Default)]
+struct RootsClient {
+    roots: Arc<Mutex<Vec<Root>>>,
+}
+
+impl RootsClient {
+    fn set_roots(&self, dirs: &[&std::path::Path]) {
+        *self.roots.lock().unwrap() = dirs
+            .iter()
+            .map(|dir| Root {
+                uri: format!("file://{}", dir.display()),
+                name: None,
+            })
+            .collect();
+    }
+}
+
+impl ClientHandler for RootsClient {
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        Ok(ListRootsResult {
+            roots: self.roots.lock().unwrap().clone(),
+        })
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_roots()
+                .enable_roots_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Polls list_allowed_directories until it lists exactly `expected`.
+async fn wait_for_allowed(
+    client: &RunningService<RoleClient, RootsClient>,
+    expected: &[&std::path::Path],
+) {
+    let expected: Vec<String> = expected
+        .iter()
+        .map(|dir| dir.canonicalize().unwrap().display().to_string())
+        .collect();
+    for _ in 0..100 {
+        let result = call(client, "list_allowed_directories", json!({}))
+            .await
+            .unwrap();
+        let listed: Vec<&str> = text(&result).lines().collect();
+        if listed == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("allowed directories never became {expected:?}");
+}
+
+#[tokio::test]
+async fn client_roots_extend_allowed_directories() {
+    let cli_dir = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let other = TempDir::new().unwrap();
+    std::fs::write(workspace.path().join("a.txt"), "from the workspace").unwrap();
+    let handler = RootsClient::default();
+    handler.set_roots(&[workspace.path()]);
+    let config = Config::builder()
+        .allowed_directory(cli_dir.path())
+        .accept_client_roots(true)
+        .build()
+        .unwrap();
+    let (client, _service) = connect_client(config, handler.clone()).await;
+
+    wait_for_allowed(&client, &[cli_dir.path(), workspace.path()]).await;
+    let path = workspace.path().join("a.txt");
+    let result = call(&client, "read_file", json!({ "path": path }))
+        .await
+        .unwrap();
+    assert!(text(&result).contains("from the workspace"));
+
+    // A changed root list replaces the client roots; the CLI directory stays
+    handler.set_roots(&[other.path()]);
+    client.notify_roots_list_changed().await.unwrap();
+    wait_for_allowed(&client, &[cli_dir.path(), other.path()]).await;
+    let result = call(&client, "read_file", json!({ "path": path }))
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(true));
+}
+
+#[tokio::test]
+async fn client_roots_are_ignored_unless_accepted_or_allowlisted() {
+    let cli_dir = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let handler = RootsClient::default();
+    handler.set_roots(&[workspace.path()]);
+
+    let (client, service) = connect_client(config(&cli_dir, false), handler.clone()).await;
+    client.notify_roots_list_changed().await.unwrap();
+    wait_for_allowed(&client, &[cli_dir.path()]).await;
+    assert_eq!(service.security.allowed_dirs().len(), 1);
+
+    // The workspace is outside the prefix; a nested directory is inside it
+    let nested = cli_dir.path().join("nested");
+    std::fs::create_dir(&nested).unwrap();
+    let prefix = TempDir::new().unwrap();
+    let inside = prefix.path().join("project");
+    std::fs::create_dir(&inside).unwrap();
+    handler.set_roots(&[workspace.path(), &inside]);
+    let config = Config::builder()
+        .allowed_directory(cli_dir.path())
+        .accept_client_roots(true)
+        .root_allowlist_prefix(prefix.path())
+        .build()
+        .unwrap();
+    let (client, _service) = connect_client(config, handler).await;
+    wait_for_allowed(&client, &[cli_dir.path(), &inside]).await;
+}
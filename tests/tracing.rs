@@ -0,0 +1,111 @@
+//! Tool call spans and closing events, checked against a captured log.
+//!
+//! Installs a global subscriber, so this binary holds a single test.
+
+mod common;
+
+use common::{call, connect};
+use ironbeard_mcp_filesystem::Config;
+use ironbeard_mcp_filesystem::tools::util::spawn_blocking;
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Captured {
+    /// Takes the log lines written so far.
+    fn take(&self) -> Vec<String> {
+        let bytes = std::mem::take(&mut *self.0.lock().unwrap());
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn find<'a>(lines: &'a [String], needle: &str) -> &'a str {
+    lines
+        .iter()
+        .find(|l| l.contains(needle))
+        .unwrap_or_else(|| panic!("no log line with {needle:?} in {lines:#?}"))
+}
+
+#[tokio::test]
+async fn tool_calls_are_logged_under_request_spans() {
+    let log = Captured::default();
+    let writer = log.clone();
+    tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    // Blocking sections inherit the caller's span
+    let span = tracing::info_span!("tool_call", id = 0_u64);
+    let _entered = span.enter();
+    spawn_blocking(|| tracing::info!("inside blocking section"))
+        .await
+        .unwrap();
+    drop(_entered);
+    let lines = log.take();
+    assert!(find(&lines, "inside blocking section").contains("tool_call{id=0}"));
+
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("a.txt");
+    std::fs::write(&file, "hello\n").unwrap();
+    let config = Config::builder()
+        .allowed_directory(dir.path())
+        .build()
+        .unwrap();
+    let (client, _service) = connect(config).await;
+    call(&client, "read_file", json!({ "path": file }))
+        .await
+        .unwrap();
+    call(&client, "read_file", json!({ "path": "/etc/passwd" }))
+        .await
+        .unwrap();
+    let lines = log.take();
+    let ok = find(&lines, "tool=read_file path=");
+    assert!(ok.contains(" INFO "), "{ok}");
+    assert!(ok.contains("bytes_read=6"), "{ok}");
+    assert!(ok.contains("tool call finished"), "{ok}");
+    assert!(ok.contains("outcome=\"ok\""), "{ok}");
+    let denied = find(&lines, "path=\"/etc/passwd\"");
+    assert!(denied.contains("outcome=\"tool_error\""), "{denied}");
+    let ids: Vec<&str> = lines
+        .iter()
+        .filter(|l| l.contains("tool call finished"))
+        .map(|l| l.split_once("{id=").unwrap().1.split_once(' ').unwrap().0)
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+
+    let config = Config::builder()
+        .allowed_directory(dir.path())
+        .log_slow_ops_ms(0)
+        .build()
+        .unwrap();
+    let (client, _service) = connect(config).await;
+    call(&client, "list_allowed_directories", json!({}))
+        .await
+        .unwrap();
+    let lines = log.take();
+    let slow = find(&lines, "slow tool call finished");
+    assert!(slow.contains(" WARN "), "{slow}");
+    assert!(slow.contains("tool=list_allowed_directories"), "{slow}");
+}